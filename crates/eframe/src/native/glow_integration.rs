@@ -627,6 +627,7 @@ impl GlowWinitRunning {
             platform_output,
             textures_delta,
             shapes,
+            damage,
             pixels_per_point,
             viewport_output,
         } = full_output;
@@ -661,16 +662,31 @@ impl GlowWinitRunning {
 
         let screen_size_in_pixels: [u32; 2] = window.inner_size().into();
 
-        if !clear_before_update {
-            painter.clear(screen_size_in_pixels, clear_color);
-        }
+        // The painter keeps a single copy of the previous frame, so with several viewports we repaint everything:
+        let damage = damage.filter(|_| !has_many_viewports);
+
+        if let Some(damage) = damage {
+            // This covers anything painted before `update`, see `egui::Options::track_damage`:
+            painter.paint_damaged_and_update_textures(
+                screen_size_in_pixels,
+                pixels_per_point,
+                clear_color,
+                &clipped_primitives,
+                &textures_delta,
+                &damage,
+            );
+        } else {
+            if !clear_before_update {
+                painter.clear(screen_size_in_pixels, clear_color);
+            }
 
-        painter.paint_and_update_textures(
-            screen_size_in_pixels,
-            pixels_per_point,
-            &clipped_primitives,
-            &textures_delta,
-        );
+            painter.paint_and_update_textures(
+                screen_size_in_pixels,
+                pixels_per_point,
+                &clipped_primitives,
+                &textures_delta,
+            );
+        }
 
         {
             for action in viewport.actions_requested.drain() {
@@ -1438,6 +1454,7 @@ fn render_immediate_viewport(
         platform_output,
         textures_delta,
         shapes,
+        damage: _, // immediate viewports are always repainted fully
         pixels_per_point,
         viewport_output,
     } = egui_ctx.run(input, |ctx| {
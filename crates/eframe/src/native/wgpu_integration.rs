@@ -638,6 +638,7 @@ impl WgpuWinitRunning {
             platform_output,
             textures_delta,
             shapes,
+            damage: _,
             pixels_per_point,
             viewport_output,
         } = full_output;
@@ -979,6 +980,7 @@ fn render_immediate_viewport(
         platform_output,
         textures_delta,
         shapes,
+        damage: _,
         pixels_per_point,
         viewport_output,
     } = egui_ctx.run(input, |ctx| {
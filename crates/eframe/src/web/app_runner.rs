@@ -195,6 +195,7 @@ impl AppRunner {
             platform_output,
             textures_delta,
             shapes,
+            damage: _,
             pixels_per_point,
             viewport_output,
         } = full_output;
@@ -89,3 +89,22 @@ fn fs_main_gamma_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
     let out_color_gamma = in.color * tex_gamma;
     return out_color_gamma;
 }
+
+// The alpha of the texture is a signed distance field, where 0.5 is the edge of the glyph.
+// This gives a sharp, anti-aliased edge at any scale.
+fn sdf_coverage(tex_coord: vec2<f32>) -> f32 {
+    let dist = textureSample(r_tex_color, r_tex_sampler, tex_coord).a;
+    let width = max(0.7 * fwidth(dist), 0.0001);
+    return smoothstep(0.5 - width, 0.5 + width, dist);
+}
+
+@fragment
+fn fs_main_linear_framebuffer_sdf(in: VertexOutput) -> @location(0) vec4<f32> {
+    let out_color_gamma = in.color * sdf_coverage(in.tex_coord);
+    return vec4<f32>(linear_from_gamma_rgb(out_color_gamma.rgb), out_color_gamma.a);
+}
+
+@fragment
+fn fs_main_gamma_framebuffer_sdf(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color * sdf_coverage(in.tex_coord);
+}
@@ -2,7 +2,11 @@
 
 use std::{borrow::Cow, num::NonZeroU64, ops::Range};
 
-use epaint::{ahash::HashMap, emath::NumExt, PaintCallbackInfo, Primitive, Vertex};
+use epaint::{
+    ahash::{HashMap, HashSet},
+    emath::NumExt,
+    PaintCallbackInfo, Primitive, Vertex,
+};
 
 use wgpu::util::DeviceExt as _;
 
@@ -153,6 +157,9 @@ struct SlicedBuffer {
 pub struct Renderer {
     pipeline: wgpu::RenderPipeline,
 
+    /// Used for textures with [`epaint::textures::TextureOptions::signed_distance_field`].
+    sdf_pipeline: wgpu::RenderPipeline,
+
     index_buffer: SlicedBuffer,
     vertex_buffer: SlicedBuffer,
 
@@ -168,6 +175,13 @@ pub struct Renderer {
     next_user_texture_id: u64,
     samplers: HashMap<epaint::textures::TextureOptions, wgpu::Sampler>,
 
+    /// The textures that are signed distance fields, painted with [`Self::sdf_pipeline`].
+    sdf_textures: HashSet<epaint::TextureId>,
+
+    /// The mip chains of the textures with [`epaint::textures::TextureOptions::mipmap_mode`],
+    /// so that partial updates can regenerate the parts of the mipmaps they cover.
+    mip_levels: HashMap<epaint::TextureId, Vec<epaint::ColorImage>>,
+
     /// Storage for resources shared with all invocations of [`CallbackTrait`]'s methods.
     ///
     /// See also [`CallbackTrait`].
@@ -277,10 +291,15 @@ impl Renderer {
             bias: wgpu::DepthBiasState::default(),
         });
 
-        let pipeline = {
+        let linear_framebuffer = output_color_format.is_srgb();
+        if linear_framebuffer {
+            log::warn!("Detected a linear (sRGBA aware) framebuffer {:?}. egui prefers Rgba8Unorm or Bgra8Unorm", output_color_format);
+        }
+
+        let create_pipeline = |label: &str, fragment_entry_point: &str| {
             crate::profile_scope!("create_render_pipeline");
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("egui_pipeline"),
+                label: Some(label),
                 layout: Some(&pipeline_layout),
                 vertex: wgpu::VertexState {
                     entry_point: "vs_main",
@@ -304,7 +323,7 @@ impl Renderer {
                     polygon_mode: wgpu::PolygonMode::default(),
                     strip_index_format: None,
                 },
-                depth_stencil,
+                depth_stencil: depth_stencil.clone(),
                 multisample: wgpu::MultisampleState {
                     alpha_to_coverage_enabled: false,
                     count: msaa_samples,
@@ -313,12 +332,7 @@ impl Renderer {
 
                 fragment: Some(wgpu::FragmentState {
                     module: &module,
-                    entry_point: if output_color_format.is_srgb() {
-                        log::warn!("Detected a linear (sRGBA aware) framebuffer {:?}. egui prefers Rgba8Unorm or Bgra8Unorm", output_color_format);
-                        "fs_main_linear_framebuffer"
-                    } else {
-                        "fs_main_gamma_framebuffer" // this is what we prefer
-                    },
+                    entry_point: fragment_entry_point,
                     targets: &[Some(wgpu::ColorTargetState {
                         format: output_color_format,
                         blend: Some(wgpu::BlendState {
@@ -342,6 +356,19 @@ impl Renderer {
         )
         };
 
+        let (pipeline, sdf_pipeline) = if linear_framebuffer {
+            (
+                create_pipeline("egui_pipeline", "fs_main_linear_framebuffer"),
+                create_pipeline("egui_sdf_pipeline", "fs_main_linear_framebuffer_sdf"),
+            )
+        } else {
+            // this is what we prefer
+            (
+                create_pipeline("egui_pipeline", "fs_main_gamma_framebuffer"),
+                create_pipeline("egui_sdf_pipeline", "fs_main_gamma_framebuffer_sdf"),
+            )
+        };
+
         const VERTEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
             (std::mem::size_of::<Vertex>() * 1024) as _;
         const INDEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
@@ -349,6 +376,7 @@ impl Renderer {
 
         Self {
             pipeline,
+            sdf_pipeline,
             vertex_buffer: SlicedBuffer {
                 buffer: create_vertex_buffer(device, VERTEX_BUFFER_START_CAPACITY),
                 slices: Vec::with_capacity(64),
@@ -370,6 +398,8 @@ impl Renderer {
             textures: HashMap::default(),
             next_user_texture_id: 0,
             samplers: HashMap::default(),
+            sdf_textures: HashSet::default(),
+            mip_levels: HashMap::default(),
             callback_resources: CallbackResources::default(),
         }
     }
@@ -390,6 +420,9 @@ impl Renderer {
         // run.
         let mut needs_reset = true;
 
+        // Whether the current pipeline is the one for signed distance fields.
+        let mut sdf_pipeline_is_set = false;
+
         let mut index_buffer_slices = self.index_buffer.slices.iter();
         let mut vertex_buffer_slices = self.vertex_buffer.slices.iter();
 
@@ -409,6 +442,7 @@ impl Renderer {
                 );
                 render_pass.set_pipeline(&self.pipeline);
                 render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                sdf_pipeline_is_set = false;
                 needs_reset = false;
             }
 
@@ -434,6 +468,15 @@ impl Renderer {
                     let vertex_buffer_slice = vertex_buffer_slices.next().unwrap();
 
                     if let Some((_texture, bind_group)) = self.textures.get(&mesh.texture_id) {
+                        let is_sdf = self.sdf_textures.contains(&mesh.texture_id);
+                        if is_sdf != sdf_pipeline_is_set {
+                            render_pass.set_pipeline(if is_sdf {
+                                &self.sdf_pipeline
+                            } else {
+                                &self.pipeline
+                            });
+                            sdf_pipeline_is_set = is_sdf;
+                        }
                         render_pass.set_bind_group(1, bind_group, &[]);
                         render_pass.set_index_buffer(
                             self.index_buffer.buffer.slice(
@@ -535,53 +578,93 @@ impl Renderer {
                 Cow::Owned(image.srgba_pixels(None).collect::<Vec<egui::Color32>>())
             }
         };
-        let data_bytes: &[u8] = bytemuck::cast_slice(data_color32.as_slice());
 
-        let queue_write_data_to_texture = |texture, origin| {
+        let write_level = |texture: &wgpu::Texture,
+                           mip_level: u32,
+                           origin: wgpu::Origin3d,
+                           [width, height]: [usize; 2],
+                           pixels: &[epaint::Color32]| {
             crate::profile_scope!("write_texture");
             queue.write_texture(
                 wgpu::ImageCopyTexture {
                     texture,
-                    mip_level: 0,
+                    mip_level,
                     origin,
                     aspect: wgpu::TextureAspect::All,
                 },
-                data_bytes,
+                bytemuck::cast_slice(pixels),
                 wgpu::ImageDataLayout {
                     offset: 0,
-                    bytes_per_row: Some(4 * width),
-                    rows_per_image: Some(height),
+                    bytes_per_row: Some(4 * width as u32),
+                    rows_per_image: Some(height as u32),
+                },
+                wgpu::Extent3d {
+                    width: width as u32,
+                    height: height as u32,
+                    depth_or_array_layers: 1,
                 },
-                size,
             );
         };
 
+        // wgpu can't generate mipmaps, so we do it on the CPU.
+
         if let Some(pos) = image_delta.pos {
             // update the existing texture
             let (texture, _bind_group) = self
                 .textures
                 .get(&id)
                 .expect("Tried to update a texture that has not been allocated yet.");
+            let texture = texture.as_ref().expect("Tried to update user texture.");
             let origin = wgpu::Origin3d {
                 x: pos[0] as u32,
                 y: pos[1] as u32,
                 z: 0,
             };
-            queue_write_data_to_texture(
-                texture.as_ref().expect("Tried to update user texture."),
-                origin,
-            );
+            write_level(texture, 0, origin, image_delta.image.size(), &data_color32);
+
+            if let Some(mip_levels) = self.mip_levels.get_mut(&id) {
+                let patch = epaint::ColorImage {
+                    size: image_delta.image.size(),
+                    pixels: data_color32.into_owned(),
+                };
+                let blit_pos = [pos[0] as isize, pos[1] as isize];
+                mip_levels[0].blit(&patch, blit_pos, epaint::BlendMode::Replace);
+                let changed = epaint::ColorImage::update_mipmaps(mip_levels, pos, patch.size);
+                for (mip_level, (level, ([x, y], size))) in
+                    (1..).zip(mip_levels[1..].iter().zip(changed))
+                {
+                    if size[0] == 0 || size[1] == 0 {
+                        break;
+                    }
+                    let region = epaint::Rect::from_min_size(
+                        epaint::pos2(x as f32, y as f32),
+                        epaint::vec2(size[0] as f32, size[1] as f32),
+                    );
+                    let origin = wgpu::Origin3d {
+                        x: x as u32,
+                        y: y as u32,
+                        z: 0,
+                    };
+                    let pixels = level.region(&region, None).pixels;
+                    write_level(texture, mip_level, origin, size, &pixels);
+                }
+            }
         } else {
             // allocate a new texture
             // Use same label for all resources associated with this texture id (no point in retyping the type)
             let label_str = format!("egui_texid_{id:?}");
             let label = Some(label_str.as_str());
+            let mipmapped = image_delta.options.mipmap_mode.is_some();
             let texture = {
                 crate::profile_scope!("create_texture");
                 device.create_texture(&wgpu::TextureDescriptor {
                     label,
                     size,
-                    mip_level_count: 1,
+                    mip_level_count: if mipmapped {
+                        epaint::textures::mip_level_count(image_delta.image.size())
+                    } else {
+                        1
+                    },
                     sample_count: 1,
                     dimension: wgpu::TextureDimension::D2,
                     format: wgpu::TextureFormat::Rgba8UnormSrgb, // Minspec for wgpu WebGL emulation is WebGL2, so this should always be supported.
@@ -610,13 +693,39 @@ impl Renderer {
                 ],
             });
             let origin = wgpu::Origin3d::ZERO;
-            queue_write_data_to_texture(&texture, origin);
+            write_level(&texture, 0, origin, image_delta.image.size(), &data_color32);
+            if mipmapped {
+                let image = epaint::ColorImage {
+                    size: image_delta.image.size(),
+                    pixels: data_color32.into_owned(),
+                };
+                let mip_levels = image.mipmaps();
+                for (mip_level, level) in mip_levels.iter().enumerate().skip(1) {
+                    write_level(
+                        &texture,
+                        mip_level as u32,
+                        origin,
+                        level.size,
+                        &level.pixels,
+                    );
+                }
+                self.mip_levels.insert(id, mip_levels);
+            } else {
+                self.mip_levels.remove(&id);
+            }
             self.textures.insert(id, (Some(texture), bind_group));
+            if image_delta.options.signed_distance_field {
+                self.sdf_textures.insert(id);
+            } else {
+                self.sdf_textures.remove(&id);
+            }
         };
     }
 
     pub fn free_texture(&mut self, id: &epaint::TextureId) {
         self.textures.remove(id);
+        self.sdf_textures.remove(id);
+        self.mip_levels.remove(id);
     }
 
     /// Get the WGPU texture and bind group associated to a texture that has been allocated by egui.
@@ -934,6 +1043,10 @@ fn create_sampler(
         epaint::textures::TextureFilter::Nearest => wgpu::FilterMode::Nearest,
         epaint::textures::TextureFilter::Linear => wgpu::FilterMode::Linear,
     };
+    let mipmap_filter = match options.mipmap_mode {
+        None | Some(epaint::textures::TextureFilter::Nearest) => wgpu::FilterMode::Nearest,
+        Some(epaint::textures::TextureFilter::Linear) => wgpu::FilterMode::Linear,
+    };
     let address_mode = match options.wrap_mode {
         epaint::textures::TextureWrapMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
         epaint::textures::TextureWrapMode::Repeat => wgpu::AddressMode::Repeat,
@@ -947,6 +1060,8 @@ fn create_sampler(
         min_filter,
         address_mode_u: address_mode,
         address_mode_v: address_mode,
+        mipmap_filter,
+        anisotropy_clamp: options.effective_anisotropy() as u16,
         ..Default::default()
     })
 }
@@ -417,12 +417,12 @@ fn button_frame(
 
         ui.painter().set(
             where_to_put_background,
-            epaint::RectShape::new(
+            visuals.shadow.around(epaint::RectShape::new(
                 outer_rect.expand(visuals.expansion),
                 visuals.rounding,
                 visuals.weak_bg_fill,
                 visuals.bg_stroke,
-            ),
+            )),
         );
     }
 
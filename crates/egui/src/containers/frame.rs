@@ -1,6 +1,6 @@
 //! Frame container
 
-use crate::{layers::ShapeIdx, *};
+use crate::{layers::ShapeIdx, load::SizedTexture, *};
 use epaint::*;
 
 /// Add a background, frame and/or margin to a rectangular background of a [`Ui`].
@@ -67,7 +67,18 @@ pub struct Frame {
 
     pub fill: Color32,
 
+    /// Paint this texture as a [`NineSlice`] instead of a solid fill.
+    ///
+    /// The texture is tinted by [`Self::fill`], so use [`Color32::WHITE`] to show it as is.
+    /// The size of the texture is in texels, and the [`Self::rounding`] only affects the stroke.
+    pub fill_texture: Option<(SizedTexture, NineSlice)>,
+
     pub stroke: Stroke,
+
+    /// Clip the contents to the inside of the painted frame, including its rounded corners.
+    ///
+    /// This way images and lists won't poke out of the corners.
+    pub clip_content: bool,
 }
 
 impl Frame {
@@ -164,6 +175,26 @@ impl Frame {
         self
     }
 
+    /// Fill the frame with a texture painted as a [`NineSlice`], tinted by [`Self::fill`].
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let texture_id = egui::TextureId::default();
+    /// egui::Frame::none()
+    ///     .fill(egui::Color32::WHITE)
+    ///     .fill_texture((texture_id, egui::vec2(48.0, 48.0)), egui::NineSlice::new(16.0))
+    ///     .inner_margin(16.0)
+    ///     .show(ui, |ui| {
+    ///         ui.label("Label on a skinned background");
+    ///     });
+    /// # });
+    /// ```
+    #[inline]
+    pub fn fill_texture(mut self, texture: impl Into<SizedTexture>, nine_slice: NineSlice) -> Self {
+        self.fill_texture = Some((texture.into(), nine_slice));
+        self
+    }
+
     #[inline]
     pub fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
         self.stroke = stroke.into();
@@ -196,6 +227,15 @@ impl Frame {
         self
     }
 
+    /// Clip the contents to the inside of the painted frame, including its rounded corners.
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn clip_content(mut self, clip_content: bool) -> Self {
+        self.clip_content = clip_content;
+        self
+    }
+
     /// Opacity multiplier in gamma space.
     ///
     /// For instance, multiplying with `0.5`
@@ -287,16 +327,33 @@ impl Frame {
             rounding,
             shadow,
             fill,
+            fill_texture,
             stroke,
+            clip_content: _,
         } = *self;
 
+        if let Some((texture, nine_slice)) = fill_texture {
+            let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+            let fill_shape =
+                Shape::mesh(nine_slice.mesh(outer_rect, texture.id, texture.size, uv, fill));
+            let stroke_shape = Shape::rect_stroke(outer_rect, rounding, stroke);
+            return if shadow == Default::default() {
+                Shape::Vec(vec![fill_shape, stroke_shape])
+            } else if shadow.inset {
+                let shadow = shadow.cast_by(&Shape::rect_filled(outer_rect, rounding, fill));
+                Shape::Vec(vec![fill_shape, shadow, stroke_shape])
+            } else {
+                let shadow = shadow.as_shape(outer_rect, rounding);
+                Shape::Vec(vec![Shape::from(shadow), fill_shape, stroke_shape])
+            };
+        }
+
         let frame_shape = Shape::Rect(epaint::RectShape::new(outer_rect, rounding, fill, stroke));
 
         if shadow == Default::default() {
             frame_shape
         } else {
-            let shadow = shadow.as_shape(outer_rect, rounding);
-            Shape::Vec(vec![Shape::from(shadow), frame_shape])
+            shadow.around(frame_shape)
         }
     }
 }
@@ -317,13 +374,25 @@ impl Prepared {
 
     /// Paint the frame.
     ///
-    /// This can be called before or after [`Self::allocate_space`].
+    /// This can be called before or after [`Self::allocate_space`],
+    /// but should be called after adding the contents if [`Frame::clip_content`] is set.
     pub fn paint(&self, ui: &Ui) {
         let paint_rect = self.content_ui.min_rect() + self.frame.inner_margin;
 
         if ui.is_rect_visible(paint_rect) {
             let shape = self.frame.paint(paint_rect);
             ui.painter().set(self.where_to_put_background, shape);
+
+            if self.frame.clip_content {
+                // Keep the contents within the stroke:
+                let inset = 0.5 * self.frame.stroke.width;
+                let clip_rect = paint_rect.shrink(inset);
+                let mut rounding = self.frame.rounding;
+                rounding -= inset;
+                let clip_shape = ClipShape::rounded_rect(clip_rect, rounding.at_least(0.0));
+                ui.painter()
+                    .clip_shapes_after(self.where_to_put_background, &clip_shape);
+            }
         }
     }
 
@@ -249,6 +249,18 @@ struct ViewportState {
     // Most of the things in `PlatformOutput` are not actually viewport dependent.
     output: PlatformOutput,
     commands: Vec<ViewportCommand>,
+
+    /// Which layer painted the shapes of the last [`FullOutput::shapes`], in order,
+    /// and how many shapes each layer painted.
+    ///
+    /// Used by the tessellation cache.
+    layer_shape_counts: Vec<(LayerId, usize)>,
+
+    /// See [`crate::Options::cache_tessellation`].
+    tessellation_cache: epaint::TessellationCache<LayerId>,
+
+    /// See [`crate::Options::track_damage`].
+    damage_tracker: crate::damage::DamageTracker,
 }
 
 /// What called [`Context::request_repaint`]?
@@ -1054,6 +1066,13 @@ impl Context {
         res
     }
 
+    /// Use the painted area of the shape as the hit area of the given widget this frame.
+    ///
+    /// See [`Ui::interact_shape`].
+    pub(crate) fn set_widget_hit_shape(&self, id: Id, shape: Shape) {
+        self.write(|ctx| ctx.viewport().widgets_this_frame.set_hit_shape(id, shape));
+    }
+
     /// Read the response of some widget, which may be called _before_ creating the widget (!).
     ///
     /// This is because widget interaction happens at the start of the frame, using the previous frame's widgets.
@@ -1955,9 +1974,17 @@ impl ContextImpl {
 
         if let Some(fonts) = self.fonts.get(&pixels_per_point.into()) {
             let tex_mngr = &mut self.tex_manager.0.write();
-            if let Some(font_image_delta) = fonts.font_image_delta() {
+            let font_page_deltas = fonts.font_page_deltas(|| {
+                // The font atlas needed another page:
+                tex_mngr.alloc(
+                    "egui_font_page".to_owned(),
+                    epaint::FontImage::new([1, 1]).into(),
+                    TextureAtlas::texture_options(),
+                )
+            });
+            for (texture_id, delta) in font_page_deltas {
                 // A partial font atlas update, e.g. a new glyph has been entered.
-                tex_mngr.set(TextureId::default(), font_image_delta);
+                tex_mngr.set(texture_id, delta);
             }
 
             if 1 < self.fonts.len() {
@@ -2015,9 +2042,40 @@ impl ContextImpl {
             }
         }
 
-        let shapes = viewport
+        let layers = viewport
             .graphics
-            .drain(self.memory.areas().order(), &self.memory.layer_transforms);
+            .drain_layers(self.memory.areas().order(), &self.memory.layer_transforms);
+        viewport.layer_shape_counts = layers
+            .iter()
+            .map(|(layer_id, shapes)| (*layer_id, shapes.len()))
+            .collect();
+        let damage = if self.memory.options.track_damage {
+            let font_page_textures: Vec<Option<TextureId>> = self
+                .fonts
+                .get(&pixels_per_point.into())
+                .map(|fonts| {
+                    let font_pages = fonts.texture_atlas().lock().font_pages();
+                    std::iter::once(Some(TextureId::default()))
+                        .chain(
+                            font_pages
+                                .iter()
+                                .map(|page| Some(page.as_ref()?.texture_id)),
+                        )
+                        .collect()
+                })
+                .unwrap_or_default();
+            viewport.damage_tracker.update(
+                viewport.input.screen_rect(),
+                pixels_per_point,
+                &layers,
+                &textures_delta,
+                &font_page_textures,
+            )
+        } else {
+            viewport.damage_tracker.clear();
+            None
+        };
+        let shapes: Vec<ClippedShape> = layers.into_iter().flat_map(|(_, shapes)| shapes).collect();
 
         let mut repaint_needed = false;
 
@@ -2146,6 +2204,7 @@ impl ContextImpl {
             platform_output,
             textures_delta,
             shapes,
+            damage,
             pixels_per_point,
             viewport_output,
         }
@@ -2165,9 +2224,9 @@ impl Context {
     ) -> Vec<ClippedPrimitive> {
         crate::profile_function!();
 
-        // A tempting optimization is to reuse the tessellation from last frame if the
-        // shapes are the same, but just comparing the shapes takes about 50% of the time
-        // it takes to tessellate them, so it is not a worth optimization.
+        // Reusing the tessellation from last frame if the shapes are the same
+        // is not always a win: just comparing the shapes takes about 50% of the time
+        // it takes to tessellate them. So this is opt-in, see `Options::cache_tessellation`.
 
         self.write(|ctx| {
             let tessellation_options = ctx.memory.options.tessellation_options;
@@ -2177,23 +2236,47 @@ impl Context {
                 .expect("tessellate called with a different pixels_per_point than the font atlas was created with. \
                          You should use egui::FullOutput::pixels_per_point when tessellating.")
                 .texture_atlas();
-            let (font_tex_size, prepared_discs) = {
+            let (font_tex_size, prepared_discs, font_pages) = {
                 let atlas = texture_atlas.lock();
-                (atlas.size(), atlas.prepared_discs())
+                (atlas.size(), atlas.prepared_discs(), atlas.font_pages())
             };
 
             let paint_stats = PaintStats::from_shapes(&shapes);
-            let clipped_primitives = {
-                crate::profile_scope!("tessellator::tessellate_shapes");
-                tessellator::Tessellator::new(
-                    pixels_per_point,
-                    tessellation_options,
-                    font_tex_size,
-                    prepared_discs,
-                )
-                .tessellate_shapes(shapes)
-            };
+            let mut tessellator = tessellator::Tessellator::new(
+                pixels_per_point,
+                tessellation_options,
+                font_tex_size,
+                prepared_discs,
+            )
+            .with_font_pages(font_pages);
+
+            let cache_tessellation = ctx.memory.options.cache_tessellation;
+            let viewport = ctx.viewports.entry(ctx.last_viewport).or_default();
+            let layer_shape_counts = std::mem::take(&mut viewport.layer_shape_counts);
+            let num_layer_shapes: usize = layer_shape_counts.iter().map(|(_, count)| count).sum();
+
+            // We can only use the cache if we get back the shapes we output last,
+            // otherwise we don't know what layers they belong to.
+            let (clipped_primitives, cache_stats) =
+                if cache_tessellation && num_layer_shapes == shapes.len() {
+                    crate::profile_scope!("tessellation_cache");
+                    let mut shapes = shapes.into_iter();
+                    let layers = layer_shape_counts
+                        .into_iter()
+                        .map(|(layer_id, count)| (layer_id, shapes.by_ref().take(count).collect()));
+                    viewport
+                        .tessellation_cache
+                        .tessellate_layers(&mut tessellator, layers)
+                } else {
+                    crate::profile_scope!("tessellator::tessellate_shapes");
+                    if !cache_tessellation {
+                        viewport.tessellation_cache.clear();
+                    }
+                    (tessellator.tessellate_shapes(shapes), Default::default())
+                };
+
             ctx.paint_stats = paint_stats.with_clipped_primitives(&clipped_primitives);
+            ctx.paint_stats.tessellation_cache = cache_stats;
             clipped_primitives
         })
     }
@@ -2687,6 +2770,32 @@ impl Context {
             "{num_state} widget states stored (of which {num_serialized} are serialized)."
         ));
 
+        ui.collapsing("Widget states by type", |ui| {
+            let type_stats = self.data(|d| d.type_stats());
+            crate::Grid::new("type_stats")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Type");
+                    ui.strong("Count");
+                    ui.strong("Serialized");
+                    ui.strong("Bytes");
+                    ui.end_row();
+
+                    for stats in type_stats {
+                        let type_name = stats.type_name.unwrap_or("<not yet deserialized>");
+                        let response = ui.monospace(type_name);
+                        if !stats.evictable {
+                            response.on_hover_text("Never evicted");
+                        }
+                        ui.label(stats.num_values.to_string());
+                        ui.label(stats.num_serialized.to_string());
+                        ui.label(stats.num_bytes.to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+
         ui.horizontal(|ui| {
             ui.label(format!(
                 "{} areas (panels, windows, popups, …)",
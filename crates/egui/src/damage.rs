@@ -0,0 +1,363 @@
+//! Figure out what parts of the screen changed since the previous frame.
+//!
+//! See [`crate::FullOutput::damage`].
+
+use epaint::{textures::TexturesDelta, ClippedShape, ImageDelta, Rect, Shape, TextureId};
+
+use crate::LayerId;
+
+/// If we end up with more damage rectangles than this, we merge them all into one.
+const MAX_DAMAGE_RECTS: usize = 16;
+
+/// Remembers what was painted last frame, so we can diff it with what is painted this frame.
+#[derive(Default)]
+pub(crate) struct DamageTracker {
+    /// The layers painted in the previous frame, back to front.
+    prev_layers: Vec<(LayerId, Vec<ClippedShape>)>,
+
+    /// The screen rectangle and `pixels_per_point` of the previous frame.
+    ///
+    /// `None` if there was no previous frame.
+    prev_screen: Option<(Rect, f32)>,
+}
+
+impl DamageTracker {
+    /// Forget the previous frame, so that the next frame is repainted in full.
+    pub fn clear(&mut self) {
+        self.prev_layers.clear();
+        self.prev_screen = None;
+    }
+
+    /// Compare the new layers with the previous frame, and remember them for next frame.
+    ///
+    /// `font_page_textures` are the textures of the pages of the font atlas,
+    /// starting with [`TextureId::default`] for the first page.
+    ///
+    /// Returns the regions (in points) that need to be repainted,
+    /// or `None` if the whole screen needs to be repainted.
+    pub fn update(
+        &mut self,
+        screen_rect: Rect,
+        pixels_per_point: f32,
+        layers: &[(LayerId, Vec<ClippedShape>)],
+        textures_delta: &TexturesDelta,
+        font_page_textures: &[Option<TextureId>],
+    ) -> Option<Vec<Rect>> {
+        crate::profile_function!();
+
+        let prev_screen = self.prev_screen.replace((screen_rect, pixels_per_point));
+        let prev_layers = std::mem::replace(&mut self.prev_layers, layers.to_vec());
+
+        if prev_screen != Some((screen_rect, pixels_per_point)) {
+            return None;
+        }
+
+        // Widen each damaged rectangle by a couple of physical pixels, to cover feathering and rounding:
+        let margin = 2.0 / pixels_per_point;
+        let mut damage = Damage {
+            rects: Vec::new(),
+            margin,
+        };
+
+        let prev_order: ahash::HashMap<LayerId, usize> = prev_layers
+            .iter()
+            .enumerate()
+            .map(|(index, (layer_id, _))| (*layer_id, index))
+            .collect();
+
+        for (index, (layer_id, shapes)) in layers.iter().enumerate() {
+            match prev_order.get(layer_id) {
+                Some(&prev_index) if prev_index == index => {
+                    damage.add_changed_shapes(&prev_layers[prev_index].1, shapes);
+                }
+                Some(&prev_index) => {
+                    // The layer moved relative to other layers (e.g. a window was brought to the front),
+                    // so whatever it overlaps may look different now:
+                    damage.add_shapes(&prev_layers[prev_index].1);
+                    damage.add_shapes(shapes);
+                }
+                None => {
+                    damage.add_shapes(shapes);
+                }
+            }
+        }
+
+        let new_layers: ahash::HashSet<LayerId> =
+            layers.iter().map(|(layer_id, _)| *layer_id).collect();
+        for (layer_id, shapes) in &prev_layers {
+            if !new_layers.contains(layer_id) {
+                damage.add_shapes(shapes);
+            }
+        }
+
+        // Some things can change without their shapes changing:
+        let updates = TextureUpdates {
+            regions: textures_delta
+                .set
+                .iter()
+                .map(|(id, delta)| (*id, updated_region(delta)))
+                .collect(),
+            font_page_textures,
+        };
+        if !updates.regions.is_empty() {
+            for (_, shapes) in layers {
+                for clipped_shape in shapes {
+                    if updates.needs_repaint(&clipped_shape.shape) {
+                        damage.add_shape(clipped_shape);
+                    }
+                }
+            }
+        }
+
+        Some(damage.finish(screen_rect))
+    }
+}
+
+/// The texels of a texture that were changed, as `[min, max)`.
+///
+/// `None` means the whole texture.
+type TexelRegion = Option<[[usize; 2]; 2]>;
+
+fn updated_region(delta: &ImageDelta) -> TexelRegion {
+    let [x, y] = delta.pos?;
+    let [w, h] = delta.image.size();
+    Some([[x, y], [x + w, y + h]])
+}
+
+/// The textures that changed this frame.
+struct TextureUpdates<'a> {
+    regions: Vec<(TextureId, TexelRegion)>,
+
+    /// See [`DamageTracker::update`].
+    font_page_textures: &'a [Option<TextureId>],
+}
+
+impl TextureUpdates<'_> {
+    /// Shapes that may look different even though they compare equal to last frame.
+    fn needs_repaint(&self, shape: &Shape) -> bool {
+        match shape {
+            Shape::Vec(shapes) => shapes.iter().any(|shape| self.needs_repaint(shape)),
+            Shape::Gradient(gradient_shape) => self.needs_repaint(&gradient_shape.shape),
+            Shape::Clipped(clipped) => self.needs_repaint(&clipped.shape),
+            Shape::Callback(_) => true, // We have no idea what a callback paints
+            Shape::Mesh(_) | Shape::Rect(_) => {
+                let texture_id = shape.texture_id();
+                // Nothing on the first page of the font atlas but the glyphs ever moves,
+                // so adding new glyphs doesn't affect e.g. the white texel:
+                texture_id != TextureId::default()
+                    && self.regions.iter().any(|(id, _)| *id == texture_id)
+            }
+            Shape::Text(text_shape) => {
+                // Glyphs that were evicted from the font atlas may have been replaced by others:
+                text_shape.galley.rows.iter().any(|row| {
+                    row.glyphs_with_hyphen()
+                        .filter(|glyph| !glyph.uv_rect.is_nothing())
+                        .any(|glyph| {
+                            let uv_rect = &glyph.uv_rect;
+                            let page = uv_rect.page as usize;
+                            let min = uv_rect.min.map(usize::from);
+                            let max = uv_rect.max.map(usize::from);
+                            self.is_updated(
+                                self.font_page_textures.get(page).copied().flatten(),
+                                min,
+                                max,
+                            )
+                        })
+                })
+            }
+            Shape::Noop
+            | Shape::Circle(_)
+            | Shape::Ellipse(_)
+            | Shape::LineSegment { .. }
+            | Shape::Path(_)
+            | Shape::QuadraticBezier(_)
+            | Shape::CubicBezier(_)
+            | Shape::BezPath(_) => false,
+        }
+    }
+
+    /// Were any of the texels in `[min, max)` of the texture updated?
+    fn is_updated(&self, texture_id: Option<TextureId>, min: [usize; 2], max: [usize; 2]) -> bool {
+        let Some(texture_id) = texture_id else {
+            return false;
+        };
+        self.regions.iter().any(|(id, region)| {
+            *id == texture_id
+                && region.map_or(true, |[region_min, region_max]| {
+                    (0..2).all(|i| region_min[i] < max[i] && min[i] < region_max[i])
+                })
+        })
+    }
+}
+
+struct Damage {
+    rects: Vec<Rect>,
+    margin: f32,
+}
+
+impl Damage {
+    fn add_shape(&mut self, clipped_shape: &ClippedShape) {
+        let ClippedShape { clip_rect, shape } = clipped_shape;
+        let rect = shape
+            .visual_bounding_rect()
+            .expand(self.margin)
+            .intersect(*clip_rect);
+        if rect.is_positive() {
+            self.rects.push(rect);
+        }
+    }
+
+    fn add_shapes(&mut self, shapes: &[ClippedShape]) {
+        for clipped_shape in shapes {
+            self.add_shape(clipped_shape);
+        }
+    }
+
+    /// Add the shapes that differ between the two lists.
+    fn add_changed_shapes(&mut self, old: &[ClippedShape], new: &[ClippedShape]) {
+        for i in 0..old.len().max(new.len()) {
+            let (old, new) = (old.get(i), new.get(i));
+            if old != new {
+                if let Some(old) = old {
+                    self.add_shape(old);
+                }
+                if let Some(new) = new {
+                    self.add_shape(new);
+                }
+            }
+        }
+    }
+
+    /// Merge overlapping rectangles and clamp to the screen.
+    fn finish(self, screen_rect: Rect) -> Vec<Rect> {
+        let mut merged: Vec<Rect> = Vec::with_capacity(self.rects.len());
+
+        for rect in self.rects {
+            let mut rect = rect.intersect(screen_rect);
+            if !rect.is_positive() {
+                continue;
+            }
+
+            // Keep merging until the new rectangle doesn't overlap anything we have:
+            while let Some(index) = merged.iter().position(|other| other.intersects(rect)) {
+                rect = rect.union(merged.swap_remove(index));
+            }
+            merged.push(rect);
+        }
+
+        if MAX_DAMAGE_RECTS < merged.len() {
+            let union = merged.iter().fold(Rect::NOTHING, |a, b| a.union(*b));
+            merged = vec![union];
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pos2, vec2, Color32, Id, Order};
+
+    fn circle(x: f32, color: Color32) -> ClippedShape {
+        ClippedShape {
+            clip_rect: Rect::EVERYTHING,
+            shape: Shape::circle_filled(pos2(x, 50.0), 5.0, color),
+        }
+    }
+
+    #[test]
+    fn test_damage() {
+        let screen_rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(200.0, 100.0));
+        let layer = LayerId::new(Order::Background, Id::new("bg"));
+        let no_textures = TexturesDelta::default();
+
+        let mut tracker = DamageTracker::default();
+        let frame = vec![(
+            layer,
+            vec![circle(10.0, Color32::RED), circle(100.0, Color32::RED)],
+        )];
+        assert_eq!(
+            tracker.update(screen_rect, 1.0, &frame, &no_textures, &[]),
+            None,
+            "First frame should be repainted in full"
+        );
+        assert_eq!(
+            tracker.update(screen_rect, 1.0, &frame, &no_textures, &[]),
+            Some(vec![]),
+            "Nothing changed"
+        );
+
+        let frame = vec![(
+            layer,
+            vec![circle(10.0, Color32::RED), circle(100.0, Color32::BLUE)],
+        )];
+        let damage = tracker
+            .update(screen_rect, 1.0, &frame, &no_textures, &[])
+            .unwrap();
+        assert_eq!(damage.len(), 1);
+        assert!(damage[0].contains(pos2(100.0, 50.0)));
+        assert!(!damage[0].contains(pos2(10.0, 50.0)));
+
+        assert_eq!(
+            tracker.update(screen_rect, 2.0, &frame, &no_textures, &[]),
+            None,
+            "Changing the scale should repaint everything"
+        );
+    }
+
+    #[test]
+    fn test_font_atlas_updates() {
+        let screen_rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(200.0, 100.0));
+        let layer = LayerId::new(Order::Background, Id::new("bg"));
+
+        let fonts = epaint::text::Fonts::new(1.0, 1024, Default::default());
+        let galley = fonts.layout_no_wrap("Hi".to_owned(), Default::default(), Color32::WHITE);
+        let glyph = galley.rows[0].glyphs[0].uv_rect;
+        let frame = vec![(
+            layer,
+            vec![ClippedShape {
+                clip_rect: Rect::EVERYTHING,
+                shape: Shape::galley(pos2(10.0, 10.0), galley, Color32::WHITE),
+            }],
+        )];
+
+        let mut tracker = DamageTracker::default();
+        let font_pages = [Some(TextureId::default())];
+        tracker.update(
+            screen_rect,
+            1.0,
+            &frame,
+            &TexturesDelta::default(),
+            &font_pages,
+        );
+
+        let font_update = |pos: [u16; 2]| {
+            let mut textures_delta = TexturesDelta::default();
+            textures_delta.set.push((
+                TextureId::default(),
+                ImageDelta::partial(
+                    pos.map(usize::from),
+                    epaint::FontImage::new([1, 1]),
+                    Default::default(),
+                ),
+            ));
+            textures_delta
+        };
+
+        // A new glyph somewhere else in the atlas doesn't affect the text:
+        let elsewhere = font_update([1000, 1000]);
+        assert_eq!(
+            tracker.update(screen_rect, 1.0, &frame, &elsewhere, &font_pages),
+            Some(vec![])
+        );
+
+        // …but one that replaces an evicted glyph of the text does:
+        let replaced = font_update(glyph.min);
+        let damage = tracker
+            .update(screen_rect, 1.0, &frame, &replaced, &font_pages)
+            .unwrap();
+        assert_eq!(damage.len(), 1);
+        assert!(damage[0].contains(pos2(12.0, 15.0)));
+    }
+}
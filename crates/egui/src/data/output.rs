@@ -23,6 +23,17 @@ pub struct FullOutput {
     /// You can use [`crate::Context::tessellate`] to turn this into triangles.
     pub shapes: Vec<epaint::ClippedShape>,
 
+    /// The regions of the screen (in points) that changed since the previous frame of this viewport.
+    ///
+    /// `None` means the whole screen should be repainted,
+    /// which is always the case unless [`crate::Options::track_damage`] is turned on.
+    ///
+    /// A backend that preserves the contents of the framebuffer between frames
+    /// can use this to only repaint the parts of the screen that changed,
+    /// which saves power and bandwidth.
+    /// Note that you still need to paint all the [`Self::shapes`] that overlap these regions.
+    pub damage: Option<Vec<crate::Rect>>,
+
     /// The number of physical pixels per logical ui point, for the viewport that was updated.
     ///
     /// You can pass this to [`crate::Context::tessellate`] together with [`Self::shapes`].
@@ -42,6 +53,7 @@ impl FullOutput {
             platform_output,
             textures_delta,
             shapes,
+            damage,
             pixels_per_point,
             viewport_output: viewports,
         } = newer;
@@ -49,6 +61,15 @@ impl FullOutput {
         self.platform_output.append(platform_output);
         self.textures_delta.append(textures_delta);
         self.shapes = shapes; // Only paint the latest
+
+        // We never painted the older output, so its damage still needs repainting:
+        self.damage = match (self.damage.take(), damage) {
+            (Some(mut older), Some(newer)) => {
+                older.extend(newer);
+                Some(older)
+            }
+            _ => None,
+        };
         self.pixels_per_point = pixels_per_point; // Use latest
 
         for (id, new_viewport) in viewports {
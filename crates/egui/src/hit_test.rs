@@ -59,7 +59,7 @@ pub fn hit_test(
         .flat_map(|&layer_id| widgets.get_layer(layer_id))
         .filter(|&w| {
             let pos_in_layer = pos_in_layers.get(&w.layer_id).copied().unwrap_or(pos);
-            let dist_sq = w.interact_rect.distance_sq_to_pos(pos_in_layer);
+            let dist_sq = widgets.distance_sq_to_pos(w, pos_in_layer);
 
             // In tie, pick last = topmost.
             if dist_sq <= closest_dist_sq {
@@ -89,7 +89,7 @@ pub fn hit_test(
         }
 
         let pos_in_layer = pos_in_layers.get(&top_layer).copied().unwrap_or(pos);
-        let hits = hit_test_on_close(&close, pos_in_layer);
+        let hits = hit_test_on_close(&close, widgets, pos_in_layer);
 
         if let Some(drag) = hits.drag {
             debug_assert!(drag.sense.drag);
@@ -105,13 +105,13 @@ pub fn hit_test(
     }
 }
 
-fn hit_test_on_close(close: &[WidgetRect], pos: Pos2) -> WidgetHits {
+fn hit_test_on_close(close: &[WidgetRect], widgets: &WidgetRects, pos: Pos2) -> WidgetHits {
     #![allow(clippy::collapsible_else_if)]
 
     // Only those widgets directly under the `pos`.
     let hits: Vec<WidgetRect> = close
         .iter()
-        .filter(|widget| widget.interact_rect.contains(pos))
+        .filter(|widget| widgets.hit_area_contains(widget, pos))
         .copied()
         .collect();
 
@@ -127,6 +127,7 @@ fn hit_test_on_close(close: &[WidgetRect], pos: Pos2) -> WidgetHits {
                     .iter()
                     .copied()
                     .filter(|w| w.sense.click || w.sense.drag),
+                widgets,
                 pos,
             );
 
@@ -154,7 +155,11 @@ fn hit_test_on_close(close: &[WidgetRect], pos: Pos2) -> WidgetHits {
             // or a moveable window.
             // It could also be something small, like a slider, or panel resize handle.
 
-            let closest_click = find_closest(close.iter().copied().filter(|w| w.sense.click), pos);
+            let closest_click = find_closest(
+                close.iter().copied().filter(|w| w.sense.click),
+                widgets,
+                pos,
+            );
             if let Some(closest_click) = closest_click {
                 if closest_click.sense.drag {
                     // We have something close that sense both clicks and drag.
@@ -214,6 +219,7 @@ fn hit_test_on_close(close: &[WidgetRect], pos: Pos2) -> WidgetHits {
                         .iter()
                         .copied()
                         .filter(|w| w.sense.drag && w.id != hit_drag.id),
+                    widgets,
                     pos,
                 );
 
@@ -307,11 +313,15 @@ fn hit_test_on_close(close: &[WidgetRect], pos: Pos2) -> WidgetHits {
     }
 }
 
-fn find_closest(widgets: impl Iterator<Item = WidgetRect>, pos: Pos2) -> Option<WidgetRect> {
+fn find_closest(
+    candidates: impl Iterator<Item = WidgetRect>,
+    widgets: &WidgetRects,
+    pos: Pos2,
+) -> Option<WidgetRect> {
     let mut closest = None;
     let mut closest_dist_sq = f32::INFINITY;
-    for widget in widgets {
-        let dist_sq = widget.interact_rect.distance_sq_to_pos(pos);
+    for widget in candidates {
+        let dist_sq = widgets.distance_sq_to_pos(&widget, pos);
 
         // In case of a tie, take the last one = the one on top.
         if dist_sq <= closest_dist_sq {
@@ -359,22 +369,22 @@ mod tests {
         ];
 
         // Perfect hit:
-        let hits = hit_test_on_close(&widgets, pos2(15.0, 15.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(15.0, 15.0));
         assert_eq!(hits.click.unwrap().id, Id::new("click"));
         assert_eq!(hits.drag.unwrap().id, Id::new("bg-area"));
 
         // Close hit:
-        let hits = hit_test_on_close(&widgets, pos2(5.0, 5.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(5.0, 5.0));
         assert_eq!(hits.click.unwrap().id, Id::new("click"));
         assert_eq!(hits.drag.unwrap().id, Id::new("bg-area"));
 
         // Perfect hit:
-        let hits = hit_test_on_close(&widgets, pos2(105.0, 15.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(105.0, 15.0));
         assert_eq!(hits.click.unwrap().id, Id::new("click-and-drag"));
         assert_eq!(hits.drag.unwrap().id, Id::new("click-and-drag"));
 
         // Close hit - should still ignore the drag-background so as not to confuse the userr:
-        let hits = hit_test_on_close(&widgets, pos2(105.0, 5.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(105.0, 5.0));
         assert_eq!(hits.click.unwrap().id, Id::new("click-and-drag"));
         assert_eq!(hits.drag.unwrap().id, Id::new("click-and-drag"));
     }
@@ -409,23 +419,47 @@ mod tests {
         }
 
         // In the middle of the bg-left-label:
-        let hits = hit_test_on_close(&widgets, pos2(25.0, 50.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(25.0, 50.0));
         assert_eq!(hits.click.unwrap().id, Id::new("bg-left-label"));
         assert_eq!(hits.drag.unwrap().id, Id::new("bg-left-label"));
 
         // On both the left click-and-drag and thin handle, but the thin handle is on top and should win:
-        let hits = hit_test_on_close(&widgets, pos2(35.0, 50.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(35.0, 50.0));
         assert_eq!(hits.click, None);
         assert_eq!(hits.drag.unwrap().id, Id::new("thin-drag-handle"));
 
         // Only on the thin-drag-handle:
-        let hits = hit_test_on_close(&widgets, pos2(50.0, 50.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(50.0, 50.0));
         assert_eq!(hits.click, None);
         assert_eq!(hits.drag.unwrap().id, Id::new("thin-drag-handle"));
 
         // On both the thin handle and right label. The label is on top and should win
-        let hits = hit_test_on_close(&widgets, pos2(65.0, 50.0));
+        let hits = hit_test_on_close(&widgets, &WidgetRects::default(), pos2(65.0, 50.0));
         assert_eq!(hits.click.unwrap().id, Id::new("fg-right-label"));
         assert_eq!(hits.drag.unwrap().id, Id::new("fg-right-label"));
     }
+
+    #[test]
+    fn hit_shape() {
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 100.0));
+        let widgets = vec![
+            wr(Id::new("button"), Sense::click(), rect),
+            wr(Id::new("wire"), Sense::click(), rect),
+        ];
+        let mut hit_shapes = WidgetRects::default();
+        hit_shapes.set_hit_shape(
+            Id::new("wire"),
+            Shape::line_segment([rect.left_top(), rect.right_bottom()], (4.0, Color32::RED)),
+        );
+
+        // On the wire, which is on top:
+        let hits = hit_test_on_close(&widgets, &hit_shapes, pos2(50.0, 51.0));
+        assert_eq!(hits.click.unwrap().id, Id::new("wire"));
+        assert_eq!(hits.contains_pointer.len(), 2);
+
+        // Within the rectangle of the wire, but not on it:
+        let hits = hit_test_on_close(&widgets, &hit_shapes, pos2(80.0, 20.0));
+        assert_eq!(hits.click.unwrap().id, Id::new("button"));
+        assert_eq!(hits.contains_pointer.len(), 1);
+    }
 }
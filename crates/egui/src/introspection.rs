@@ -94,6 +94,7 @@ impl Widget for &epaint::stats::PaintStats {
                 clipped_primitives,
                 vertices,
                 indices,
+                tessellation_cache,
             } = self;
 
             ui.label("Intermediate:");
@@ -121,6 +122,15 @@ impl Widget for &epaint::stats::PaintStats {
             label(ui, indices, "indices").on_hover_text("Three 32-bit indices per triangles");
             ui.add_space(10.0);
 
+            let epaint::tessellation_cache::TessellationCacheStats { hits, misses } =
+                tessellation_cache;
+            if hits + misses > 0 {
+                ui.label("Tessellation cache:");
+                ui.label(format!("{hits:6} layers reused"));
+                ui.label(format!("{misses:6} layers tessellated"));
+                ui.add_space(10.0);
+            }
+
             // ui.label("Total:");
             // ui.label(self.total().format(""));
         })
@@ -2,7 +2,7 @@
 //! are sometimes painted behind or in front of other things.
 
 use crate::{Id, *};
-use epaint::{emath::TSTransform, ClippedShape, Shape};
+use epaint::{emath::TSTransform, ClipShape, ClippedShape, ClippedToShape, Shape};
 
 /// Different layer categories
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
@@ -132,6 +132,17 @@ impl PaintList {
         idx
     }
 
+    /// Like [`Self::add`], but also clip the shape to `clip_shape`, if any.
+    #[inline]
+    pub fn add_with_clip_shape(
+        &mut self,
+        clip_rect: Rect,
+        clip_shape: Option<&ClipShape>,
+        shape: Shape,
+    ) -> ShapeIdx {
+        self.add(clip_rect, clip_to(shape, clip_shape))
+    }
+
     pub fn extend<I: IntoIterator<Item = Shape>>(&mut self, clip_rect: Rect, shapes: I) {
         self.0.extend(
             shapes
@@ -140,6 +151,19 @@ impl PaintList {
         );
     }
 
+    /// Like [`Self::extend`], but also clip the shapes to `clip_shape`, if any.
+    pub fn extend_with_clip_shape<I: IntoIterator<Item = Shape>>(
+        &mut self,
+        clip_rect: Rect,
+        clip_shape: Option<&ClipShape>,
+        shapes: I,
+    ) {
+        self.extend(
+            clip_rect,
+            shapes.into_iter().map(|shape| clip_to(shape, clip_shape)),
+        );
+    }
+
     /// Modify an existing [`Shape`].
     ///
     /// Sometimes you want to paint a frame behind some contents, but don't know how large the frame needs to be
@@ -152,6 +176,30 @@ impl PaintList {
         self.0[idx.0] = ClippedShape { clip_rect, shape };
     }
 
+    /// Like [`Self::set`], but also clip the shape to `clip_shape`, if any.
+    #[inline]
+    pub fn set_with_clip_shape(
+        &mut self,
+        idx: ShapeIdx,
+        clip_rect: Rect,
+        clip_shape: Option<&ClipShape>,
+        shape: Shape,
+    ) {
+        self.set(idx, clip_rect, clip_to(shape, clip_shape));
+    }
+
+    /// Also clip all shapes added after the given one to this region.
+    ///
+    /// This is used to clip the contents of a [`crate::Frame`] once its final size is known.
+    pub fn clip_shapes_after(&mut self, idx: ShapeIdx, clip_shape: &ClipShape) {
+        let bounding_rect = clip_shape.bounding_rect();
+        for clipped_shape in self.0.iter_mut().skip(idx.0 + 1) {
+            clipped_shape.clip_rect = clipped_shape.clip_rect.intersect(bounding_rect);
+            let shape = std::mem::replace(&mut clipped_shape.shape, Shape::Noop);
+            clipped_shape.shape = ClippedToShape::clip(shape, clip_shape);
+        }
+    }
+
     /// Set the given shape to be empty (a `Shape::Noop`).
     #[inline(always)]
     pub fn reset_shape(&mut self, idx: ShapeIdx) {
@@ -172,6 +220,13 @@ impl PaintList {
     }
 }
 
+fn clip_to(shape: Shape, clip_shape: Option<&ClipShape>) -> Shape {
+    match clip_shape {
+        Some(clip_shape) => ClippedToShape::clip(shape, clip_shape),
+        None => shape,
+    }
+}
+
 /// This is where painted [`Shape`]s end up during a frame.
 #[derive(Clone, Default)]
 pub struct GraphicLayers([IdMap<PaintList>; Order::COUNT]);
@@ -202,6 +257,23 @@ impl GraphicLayers {
         crate::profile_function!();
 
         let mut all_shapes: Vec<_> = Default::default();
+        for (_, mut shapes) in self.drain_layers(area_order, transforms) {
+            all_shapes.append(&mut shapes);
+        }
+        all_shapes
+    }
+
+    /// Like [`Self::drain`], but keeps the shapes of each layer separate.
+    ///
+    /// The layers are returned in the order they should be painted, back to front.
+    pub fn drain_layers(
+        &mut self,
+        area_order: &[LayerId],
+        transforms: &ahash::HashMap<LayerId, TSTransform>,
+    ) -> Vec<(LayerId, Vec<ClippedShape>)> {
+        crate::profile_function!();
+
+        let mut all_layers: Vec<_> = Default::default();
 
         for &order in &Order::ALL {
             let order_map = &mut self.0[order as usize];
@@ -221,7 +293,7 @@ impl GraphicLayers {
                                 clipped_shape.shape.transform(*transform);
                             }
                         }
-                        all_shapes.append(&mut list.0);
+                        all_layers.push((*layer_id, std::mem::take(&mut list.0)));
                     }
                 }
             }
@@ -237,10 +309,12 @@ impl GraphicLayers {
                     }
                 }
 
-                all_shapes.append(&mut list.0);
+                if !list.0.is_empty() {
+                    all_layers.push((layer_id, std::mem::take(&mut list.0)));
+                }
             }
         }
 
-        all_shapes
+        all_layers
     }
 }
@@ -375,6 +375,7 @@
 mod animation_manager;
 pub mod containers;
 mod context;
+mod damage;
 mod data;
 pub mod debug_text;
 mod drag_and_drop;
@@ -426,17 +427,21 @@ pub use emath::{
 };
 pub use epaint::{
     mutex,
-    text::{FontData, FontDefinitions, FontFamily, FontId, FontTweak},
+    text::{
+        FontData, FontDefinitions, FontFace, FontFamily, FontId, FontStyle, FontTweak, FontWeight,
+    },
     textures::{TextureFilter, TextureOptions, TextureWrapMode, TexturesDelta},
-    ClippedPrimitive, ColorImage, FontImage, ImageData, Margin, Mesh, PaintCallback,
-    PaintCallbackInfo, Rounding, Shadow, Shape, Stroke, TextureHandle, TextureId,
+    ClipShape, ClippedPrimitive, ClippedToShape, ColorImage, FontImage, ImageData, Margin, Mesh,
+    NineSlice, NineSliceFill, PaintCallback, PaintCallbackInfo, Rounding, Shadow, Shape, Stroke,
+    TextureHandle, TextureId,
 };
 
 pub mod text {
     pub use crate::text_selection::{CCursorRange, CursorRange};
     pub use epaint::text::{
-        cursor::CCursor, FontData, FontDefinitions, FontFamily, Fonts, Galley, LayoutJob,
-        LayoutSection, TextFormat, TextWrapping, TAB_SIZE,
+        cursor::CCursor, DecorationStyle, ElisionPosition, FontData, FontDefinitions, FontFamily,
+        FontStyle, FontVariation, FontWeight, Fonts, Galley, LayoutJob, LayoutSection, TabAlign,
+        TabStop, TextFormat, TextWrapping, TAB_SIZE,
     };
 }
 
@@ -378,6 +378,7 @@ pub trait ImageLoader {
 
 /// A texture with a known size.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct SizedTexture {
     pub id: TextureId,
     pub size: Vec2,
@@ -1,8 +1,24 @@
 use super::*;
 
+/// How many sizes of the same image we keep textures for.
+///
+/// Size hints that change every frame (e.g. while resizing a window)
+/// would otherwise leave a texture behind for every size.
+const MAX_SIZES_PER_URI: usize = 4;
+
+/// A texture, and the requests it was loaded for.
+struct CachedTexture {
+    handle: TextureHandle,
+
+    /// The size hints (and `pixels_per_point`, which the loaders may take into account)
+    /// that resulted in this texture, the most recently used last.
+    requests: Vec<(SizeHint, f32)>,
+}
+
 #[derive(Default)]
 pub struct DefaultTextureLoader {
-    cache: Mutex<HashMap<(String, TextureOptions), TextureHandle>>,
+    /// The textures of each image, one per image size, the most recently used last.
+    cache: Mutex<HashMap<(String, TextureOptions), Vec<CachedTexture>>>,
 }
 
 impl TextureLoader for DefaultTextureLoader {
@@ -17,19 +33,53 @@ impl TextureLoader for DefaultTextureLoader {
         texture_options: TextureOptions,
         size_hint: SizeHint,
     ) -> TextureLoadResult {
-        let mut cache = self.cache.lock();
-        if let Some(handle) = cache.get(&(uri.into(), texture_options)) {
-            let texture = SizedTexture::from_handle(handle);
-            Ok(TexturePoll::Ready { texture })
-        } else {
-            match ctx.try_load_image(uri, size_hint)? {
-                ImagePoll::Pending { size } => Ok(TexturePoll::Pending { size }),
-                ImagePoll::Ready { image } => {
-                    let handle = ctx.load_texture(uri, image, texture_options);
-                    let texture = SizedTexture::from_handle(&handle);
-                    cache.insert((uri.into(), texture_options), handle);
-                    Ok(TexturePoll::Ready { texture })
+        let request = (size_hint, ctx.pixels_per_point());
+        let key = (uri.to_owned(), texture_options);
+
+        if let Some(textures) = self.cache.lock().get_mut(&key) {
+            if let Some(index) = textures
+                .iter()
+                .position(|texture| texture.requests.contains(&request))
+            {
+                let texture = textures.remove(index);
+                let sized_texture = SizedTexture::from_handle(&texture.handle);
+                textures.push(texture);
+                return Ok(TexturePoll::Ready {
+                    texture: sized_texture,
+                });
+            }
+        }
+
+        // The size hint decides the size of the image, so we need to ask the image loaders
+        // (which have their own caches) which texture to use:
+        match ctx.try_load_image(uri, size_hint)? {
+            ImagePoll::Pending { size } => Ok(TexturePoll::Pending { size }),
+            ImagePoll::Ready { image } => {
+                let mut cache = self.cache.lock();
+                let textures = cache.entry(key).or_default();
+                let mut texture = if let Some(index) = textures
+                    .iter()
+                    .position(|texture| texture.handle.size() == image.size)
+                {
+                    textures.remove(index)
+                } else {
+                    if textures.len() == MAX_SIZES_PER_URI {
+                        let _ = textures.remove(0);
+                    }
+                    CachedTexture {
+                        handle: ctx.load_texture(uri, image, texture_options),
+                        requests: vec![],
+                    }
+                };
+                if texture.requests.len() == MAX_SIZES_PER_URI {
+                    texture.requests.remove(0);
                 }
+                texture.requests.push(request);
+                let sized_texture = SizedTexture::from_handle(&texture.handle);
+                textures.push(texture);
+                Ok(TexturePoll::Ready {
+                    texture: sized_texture,
+                })
             }
         }
     }
@@ -54,7 +104,81 @@ impl TextureLoader for DefaultTextureLoader {
         self.cache
             .lock()
             .values()
-            .map(|texture| texture.byte_size())
+            .flatten()
+            .map(|texture| texture.handle.byte_size())
             .sum()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color32;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Loads square images as wide as the size hint asks for.
+    #[derive(Default)]
+    struct SquareLoader {
+        num_loads: AtomicUsize,
+    }
+
+    impl ImageLoader for SquareLoader {
+        fn id(&self) -> &str {
+            "SquareLoader"
+        }
+
+        fn load(&self, _: &Context, _: &str, size_hint: SizeHint) -> ImageLoadResult {
+            let SizeHint::Width(width) = size_hint else {
+                return Err(LoadError::NotSupported);
+            };
+            self.num_loads.fetch_add(1, Ordering::Relaxed);
+            let size = [width.min(64) as usize; 2];
+            let image = ColorImage::new(size, Color32::WHITE);
+            Ok(ImagePoll::Ready {
+                image: Arc::new(image),
+            })
+        }
+
+        fn forget(&self, _: &str) {}
+
+        fn forget_all(&self) {}
+
+        fn byte_size(&self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn test_texture_per_size() {
+        let ctx = Context::default();
+        let image_loader = Arc::new(SquareLoader::default());
+        ctx.add_image_loader(image_loader.clone());
+        let num_loads = || image_loader.num_loads.load(Ordering::Relaxed);
+        let loader = DefaultTextureLoader::default();
+        let options = TextureOptions::default();
+        let load = |width| match loader.load(&ctx, "square", options, SizeHint::Width(width)) {
+            Ok(TexturePoll::Ready { texture }) => texture,
+            _ => panic!("the image should be ready"),
+        };
+
+        let small = load(16);
+        let large = load(32);
+        assert_eq!(small.size, Vec2::splat(16.0));
+        assert_eq!(large.size, Vec2::splat(32.0));
+        assert_ne!(small.id, large.id);
+        assert_eq!(num_loads(), 2);
+
+        assert_eq!(load(16).id, small.id, "The texture is reused");
+        assert_eq!(num_loads(), 2, "…without asking the image loaders");
+
+        // Different size hints for the same image size share the texture:
+        let clamped = load(100);
+        assert_eq!(load(200).id, clamped.id);
+
+        for width in 1..20 {
+            load(width);
+        }
+        let num_textures = loader.cache.lock().values().map(Vec::len).sum::<usize>();
+        assert_eq!(num_textures, MAX_SIZES_PER_URI);
+    }
+}
@@ -197,6 +197,31 @@ pub struct Options {
     /// Controls the tessellator.
     pub tessellation_options: epaint::TessellationOptions,
 
+    /// If `true`, [`crate::Context::tessellate`] will reuse the meshes of layers
+    /// whose shapes are identical to the previous frame.
+    ///
+    /// This is a win for UIs that are mostly static, but costs some CPU and memory
+    /// for UIs where everything changes every frame (e.g. during animations),
+    /// since all shapes are compared to the shapes of the previous frame.
+    ///
+    /// See [`epaint::stats::PaintStats::tessellation_cache`] for the hit rate.
+    ///
+    /// Default: `false`.
+    pub cache_tessellation: bool,
+
+    /// If `true`, egui will compare the shapes painted each frame with those of the previous frame,
+    /// and report what parts of the screen changed in [`crate::FullOutput::damage`].
+    ///
+    /// This costs some CPU and memory, and is only useful
+    /// if your backend can repaint just parts of the screen,
+    /// like `egui_glow::Painter::paint_damaged_and_update_textures` (used by `eframe` with glow).
+    ///
+    /// That copies the whole frame onto the screen, so with `eframe` and glow
+    /// it can't be combined with custom GL painting under egui (before the UI is painted).
+    ///
+    /// Default: `false`.
+    pub track_damage: bool,
+
     /// If any widget moves or changes id, repaint everything.
     ///
     /// It is recommended you keep this OFF, because
@@ -236,6 +261,8 @@ impl Default for Options {
             zoom_factor: 1.0,
             zoom_with_keyboard: true,
             tessellation_options: Default::default(),
+            cache_tessellation: false,
+            track_damage: false,
             repaint_on_widget_change: false,
             screen_reader: false,
             preload_font_glyphs: true,
@@ -252,6 +279,8 @@ impl Options {
             zoom_factor: _, // TODO(emilk)
             zoom_with_keyboard,
             tessellation_options,
+            cache_tessellation,
+            track_damage: _, // depends on the backend
             repaint_on_widget_change,
             screen_reader: _, // needs to come from the integration
             preload_font_glyphs: _,
@@ -286,6 +315,10 @@ impl Options {
         CollapsingHeader::new("✒ Painting")
             .default_open(false)
             .show(ui, |ui| {
+                ui.checkbox(
+                    cache_tessellation,
+                    "Reuse the tessellation of unchanged layers",
+                );
                 tessellation_options.ui(ui);
                 ui.vertical_centered(|ui| {
                     crate::reset_button(ui, tessellation_options, "Reset paint settings");
@@ -612,6 +645,10 @@ impl Memory {
 
     pub(crate) fn end_frame(&mut self, used_ids: &IdMap<Rect>) {
         self.caches.update();
+        if self.viewport_id == ViewportId::ROOT {
+            // `data` is shared by all viewports, so we only age it once per root frame.
+            self.data.end_frame();
+        }
         self.areas_mut().end_frame();
         self.focus_mut().end_frame(used_ids);
     }
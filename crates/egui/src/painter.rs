@@ -7,7 +7,7 @@ use crate::{
 };
 use epaint::{
     text::{Fonts, Galley, LayoutJob},
-    CircleShape, ClippedShape, PathStroke, RectShape, Rounding, Shape, Stroke,
+    CircleShape, ClipShape, ClippedShape, PathStroke, RectShape, Rounding, Shape, Stroke,
 };
 
 /// Helper to paint shapes and text to a specific region on a specific layer.
@@ -25,6 +25,10 @@ pub struct Painter {
     /// This means nothing outside of this rectangle will be visible on screen.
     clip_rect: Rect,
 
+    /// If set, everything painted in this [`Painter`] will also be clipped against this,
+    /// e.g. a rounded rectangle.
+    clip_shape: Option<ClipShape>,
+
     /// If set, all shapes will have their colors modified to be closer to this.
     /// This is used to implement grayed out interfaces.
     fade_to_color: Option<Color32>,
@@ -42,6 +46,7 @@ impl Painter {
             ctx,
             layer_id,
             clip_rect,
+            clip_shape: None,
             fade_to_color: None,
             opacity_factor: 1.0,
         }
@@ -54,6 +59,7 @@ impl Painter {
             ctx: self.ctx,
             layer_id,
             clip_rect: self.clip_rect,
+            clip_shape: self.clip_shape,
             fade_to_color: None,
             opacity_factor: 1.0,
         }
@@ -68,6 +74,27 @@ impl Painter {
             ctx: self.ctx.clone(),
             layer_id: self.layer_id,
             clip_rect: rect.intersect(self.clip_rect),
+            clip_shape: self.clip_shape.clone(),
+            fade_to_color: self.fade_to_color,
+            opacity_factor: self.opacity_factor,
+        }
+    }
+
+    /// Create a painter for a sub-region of this [`Painter`], e.g. a circle or a rounded rectangle.
+    ///
+    /// The clip shape of the returned [`Painter`] will be the intersection
+    /// of the given shape and the `clip_shape()` of the parent [`Painter`],
+    /// and the clip rectangle will shrink to fit it.
+    pub fn with_clip_shape(&self, clip_shape: ClipShape) -> Self {
+        let clip_shape = match &self.clip_shape {
+            Some(existing) => existing.intersect(&clip_shape),
+            None => clip_shape,
+        };
+        Self {
+            ctx: self.ctx.clone(),
+            layer_id: self.layer_id,
+            clip_rect: clip_shape.bounding_rect().intersect(self.clip_rect),
+            clip_shape: Some(clip_shape),
             fade_to_color: self.fade_to_color,
             opacity_factor: self.opacity_factor,
         }
@@ -135,6 +162,21 @@ impl Painter {
         self.clip_rect = clip_rect;
     }
 
+    /// If set, everything painted in this [`Painter`] will also be clipped against this,
+    /// in addition to [`Self::clip_rect`].
+    #[inline]
+    pub fn clip_shape(&self) -> Option<&ClipShape> {
+        self.clip_shape.as_ref()
+    }
+
+    /// Also clip everything painted in this [`Painter`] against this, in addition to [`Self::clip_rect`].
+    ///
+    /// This does not apply to [`epaint::PaintCallback`]s.
+    #[inline]
+    pub fn set_clip_shape(&mut self, clip_shape: Option<ClipShape>) {
+        self.clip_shape = clip_shape;
+    }
+
     /// Useful for pixel-perfect rendering.
     #[inline]
     pub fn round_to_pixel(&self, point: f32) -> f32 {
@@ -185,7 +227,9 @@ impl Painter {
         } else {
             let mut shape = shape.into();
             self.transform_shape(&mut shape);
-            self.paint_list(|l| l.add(self.clip_rect, shape))
+            self.paint_list(|l| {
+                l.add_with_clip_shape(self.clip_rect, self.clip_shape.as_ref(), shape)
+            })
         }
     }
 
@@ -201,9 +245,13 @@ impl Painter {
                 self.transform_shape(&mut shape);
                 shape
             });
-            self.paint_list(|l| l.extend(self.clip_rect, shapes));
+            self.paint_list(|l| {
+                l.extend_with_clip_shape(self.clip_rect, self.clip_shape.as_ref(), shapes);
+            });
         } else {
-            self.paint_list(|l| l.extend(self.clip_rect, shapes));
+            self.paint_list(|l| {
+                l.extend_with_clip_shape(self.clip_rect, self.clip_shape.as_ref(), shapes);
+            });
         }
     }
 
@@ -214,7 +262,16 @@ impl Painter {
         }
         let mut shape = shape.into();
         self.transform_shape(&mut shape);
-        self.paint_list(|l| l.set(idx, self.clip_rect, shape));
+        self.paint_list(|l| {
+            l.set_with_clip_shape(idx, self.clip_rect, self.clip_shape.as_ref(), shape);
+        });
+    }
+
+    /// Also clip everything added after the given shape to this region.
+    ///
+    /// Useful for clipping contents to a frame whose size is not known until the contents have been added.
+    pub fn clip_shapes_after(&self, idx: ShapeIdx, clip_shape: &ClipShape) {
+        self.paint_list(|l| l.clip_shapes_after(idx, clip_shape));
     }
 
     /// Access all shapes added this frame.
@@ -4,7 +4,7 @@
 
 use std::collections::BTreeMap;
 
-use epaint::{Rounding, Shadow, Stroke};
+use epaint::{NineSliceFill, Rounding, Shadow, Stroke};
 
 use crate::{
     ecolor::*, emath::*, ComboBox, CursorIcon, FontFamily, FontId, Grid, Margin, Response,
@@ -936,6 +936,11 @@ pub struct WidgetVisuals {
 
     /// Make the frame this much larger.
     pub expansion: f32,
+
+    /// Shadow of button frames, check boxes, radio buttons, combo boxes, slider handles and text edits.
+    ///
+    /// An [inset](Shadow::inset) shadow makes the widget look pressed in.
+    pub shadow: Shadow,
 }
 
 impl WidgetVisuals {
@@ -1106,6 +1111,7 @@ impl Visuals {
                 blur: 15.0,
                 spread: 0.0,
                 color: Color32::from_black_alpha(96),
+                inset: false,
             },
             window_fill: Color32::from_gray(27),
             window_stroke: Stroke::new(1.0, Color32::from_gray(60)),
@@ -1120,6 +1126,7 @@ impl Visuals {
                 blur: 8.0,
                 spread: 0.0,
                 color: Color32::from_black_alpha(96),
+                inset: false,
             },
 
             resize_corner_size: 12.0,
@@ -1162,6 +1169,7 @@ impl Visuals {
                 blur: 15.0,
                 spread: 0.0,
                 color: Color32::from_black_alpha(25),
+                inset: false,
             },
             window_fill: Color32::from_gray(248),
             window_stroke: Stroke::new(1.0, Color32::from_gray(190)),
@@ -1173,6 +1181,7 @@ impl Visuals {
                 blur: 8.0,
                 spread: 0.0,
                 color: Color32::from_black_alpha(25),
+                inset: false,
             },
 
             text_cursor: TextCursorStyle {
@@ -1223,6 +1232,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.0, Color32::from_gray(140)), // normal text color
                 rounding: Rounding::same(2.0),
                 expansion: 0.0,
+                shadow: Shadow::NONE,
             },
             inactive: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(60), // button background
@@ -1231,6 +1241,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.0, Color32::from_gray(180)), // button text
                 rounding: Rounding::same(2.0),
                 expansion: 0.0,
+                shadow: Shadow::NONE,
             },
             hovered: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(70),
@@ -1239,6 +1250,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.5, Color32::from_gray(240)),
                 rounding: Rounding::same(3.0),
                 expansion: 1.0,
+                shadow: Shadow::NONE,
             },
             active: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(55),
@@ -1247,6 +1259,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(2.0, Color32::WHITE),
                 rounding: Rounding::same(2.0),
                 expansion: 1.0,
+                shadow: Shadow::NONE,
             },
             open: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(45),
@@ -1255,6 +1268,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.0, Color32::from_gray(210)),
                 rounding: Rounding::same(2.0),
                 expansion: 0.0,
+                shadow: Shadow::NONE,
             },
         }
     }
@@ -1268,6 +1282,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.0, Color32::from_gray(80)),  // normal text color
                 rounding: Rounding::same(2.0),
                 expansion: 0.0,
+                shadow: Shadow::NONE,
             },
             inactive: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(230), // button background
@@ -1276,6 +1291,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.0, Color32::from_gray(60)), // button text
                 rounding: Rounding::same(2.0),
                 expansion: 0.0,
+                shadow: Shadow::NONE,
             },
             hovered: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(220),
@@ -1284,6 +1300,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.5, Color32::BLACK),
                 rounding: Rounding::same(3.0),
                 expansion: 1.0,
+                shadow: Shadow::NONE,
             },
             active: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(165),
@@ -1292,6 +1309,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(2.0, Color32::BLACK),
                 rounding: Rounding::same(2.0),
                 expansion: 1.0,
+                shadow: Shadow::NONE,
             },
             open: WidgetVisuals {
                 weak_bg_fill: Color32::from_gray(220),
@@ -1300,6 +1318,7 @@ impl Widgets {
                 fg_stroke: Stroke::new(1.0, Color32::BLACK),
                 rounding: Rounding::same(2.0),
                 expansion: 0.0,
+                shadow: Shadow::NONE,
             },
         }
     }
@@ -1673,6 +1692,7 @@ impl WidgetVisuals {
             rounding,
             fg_stroke,
             expansion,
+            shadow,
         } = self;
 
         Grid::new("widget")
@@ -1706,6 +1726,10 @@ impl WidgetVisuals {
                     .on_hover_text("make shapes this much larger");
                 ui.add(DragValue::new(expansion).speed(0.1));
                 ui.end_row();
+
+                ui.label("Shadow");
+                ui.add(shadow);
+                ui.end_row();
             });
     }
 }
@@ -2208,6 +2232,7 @@ impl Widget for &mut Shadow {
             blur,
             spread,
             color,
+            inset,
         } = self;
 
         ui.vertical(|ui| {
@@ -2240,7 +2265,11 @@ impl Widget for &mut Shadow {
                         .prefix("spread: "),
                 );
             });
-            ui.color_edit_button_srgba(color);
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(color);
+                ui.checkbox(inset, "Inset")
+                    .on_hover_text("Cast the shadow inside of the shape");
+            });
         })
         .response
     }
@@ -2277,7 +2306,9 @@ impl Widget for &mut crate::Frame {
             rounding,
             shadow,
             fill,
+            fill_texture,
             stroke,
+            clip_content,
         } = self;
 
         crate::Grid::new("frame")
@@ -2305,9 +2336,38 @@ impl Widget for &mut crate::Frame {
                 ui.color_edit_button_srgba(fill);
                 ui.end_row();
 
+                if let Some((_, nine_slice)) = fill_texture {
+                    ui.label("Fill texture insets");
+                    ui.add(&mut nine_slice.insets);
+                    ui.end_row();
+
+                    ui.label("Fill texture scale");
+                    ui.add(
+                        DragValue::new(&mut nine_slice.scale)
+                            .speed(0.01)
+                            .clamp_range(0.0..=8.0),
+                    );
+                    ui.end_row();
+
+                    ui.label("Fill texture tiling");
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(
+                            &mut nine_slice.fill,
+                            NineSliceFill::Stretch,
+                            "Stretch",
+                        );
+                        ui.selectable_value(&mut nine_slice.fill, NineSliceFill::Tile, "Tile");
+                    });
+                    ui.end_row();
+                }
+
                 ui.label("Stroke");
                 ui.add(stroke);
                 ui.end_row();
+
+                ui.label("Clip content");
+                ui.checkbox(clip_content, "");
+                ui.end_row();
             })
             .response
     }
@@ -20,11 +20,11 @@ pub fn update_accesskit_for_text_widget(
             builder.set_text_selection(accesskit::TextSelection {
                 anchor: accesskit::TextPosition {
                     node: parent_id.with(anchor.row).accesskit_id(),
-                    character_index: anchor.column,
+                    character_index: galley.rows[anchor.row].glyph_index_from_column(anchor.column),
                 },
                 focus: accesskit::TextPosition {
                     node: parent_id.with(focus.row).accesskit_id(),
-                    character_index: focus.column,
+                    character_index: galley.rows[focus.row].glyph_index_from_column(focus.column),
                 },
             });
         }
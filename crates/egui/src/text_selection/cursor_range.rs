@@ -285,13 +285,13 @@ fn ccursor_from_accesskit_text_position(
         let row_id = id.with(i);
         if row_id.accesskit_id() == position.node {
             return Some(CCursor {
-                index: total_length + position.character_index,
+                index: total_length + row.column_from_glyph_index(position.character_index),
                 prefer_next_row: !(position.character_index == row.glyphs.len()
                     && !row.ends_with_newline
                     && (i + 1) < galley.rows.len()),
             });
         }
-        total_length += row.glyphs.len() + (row.ends_with_newline as usize);
+        total_length += row.char_count_including_newline();
     }
     None
 }
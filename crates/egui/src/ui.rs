@@ -375,6 +375,19 @@ impl Ui {
         self.painter.set_clip_rect(clip_rect);
     }
 
+    /// Screen-space region for clipping what we paint in this ui, in addition to [`Self::clip_rect`].
+    #[inline]
+    pub fn clip_shape(&self) -> Option<&ClipShape> {
+        self.painter.clip_shape()
+    }
+
+    /// Also clip what we paint in this ui to this region, e.g. a rounded rectangle.
+    ///
+    /// Unlike [`Self::set_clip_rect`], this does not affect interaction and culling.
+    pub fn set_clip_shape(&mut self, clip_shape: Option<ClipShape>) {
+        self.painter.set_clip_shape(clip_shape);
+    }
+
     /// Can be used for culling: if `false`, then no part of `rect` will be visible on screen.
     pub fn is_rect_visible(&self, rect: Rect) -> bool {
         self.is_visible() && rect.intersects(self.clip_rect())
@@ -684,6 +697,33 @@ impl Ui {
         })
     }
 
+    /// Check for clicks, drags and/or hover on the painted area of a shape,
+    /// e.g. a stroked line or a circle.
+    ///
+    /// This is like [`Self::interact`], but only the fill and stroke of the shape
+    /// (see [`Shape::contains_point`]) is sensitive, instead of its whole bounding rectangle.
+    /// The [`Response::rect`] will be the visual bounding rectangle of the shape.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let wire = egui::Shape::line_segment(
+    ///     [egui::pos2(10.0, 10.0), egui::pos2(100.0, 100.0)],
+    ///     egui::Stroke::new(4.0, egui::Color32::WHITE),
+    /// );
+    /// let response = ui.interact_shape(wire.clone(), ui.id().with("wire"), egui::Sense::click());
+    /// ui.painter().add(wire);
+    /// if response.clicked() {
+    ///     // …
+    /// }
+    /// # });
+    /// ```
+    pub fn interact_shape(&self, shape: impl Into<Shape>, id: Id, sense: Sense) -> Response {
+        let shape = shape.into();
+        let rect = shape.visual_bounding_rect();
+        self.ctx().set_widget_hit_shape(id, shape);
+        self.interact(rect, id, sense)
+    }
+
     /// Deprecated: use [`Self::interact`] instead.
     #[deprecated = "The contains_pointer argument is ignored. Use `ui.interact` instead."]
     pub fn interact_with_hovered(
@@ -3,7 +3,13 @@
 // For non-serializable types, these simply return `None`.
 // This will also allow users to pick their own serialization format per type.
 
-use std::{any::Any, sync::Arc};
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 // -----------------------------------------------------------------------------------------------
 
@@ -307,6 +313,56 @@ fn from_ron_str<T: serde::de::DeserializeOwned>(ron: &str) -> Option<T> {
 
 use crate::Id;
 
+/// An [`Element`] together with the generation in which it was last accessed.
+struct Slot {
+    element: Element,
+
+    /// The [`IdTypeMap::generation`] this value was last read or written.
+    ///
+    /// Atomic so that we can mark values as used even when reading through a `&IdTypeMap`.
+    last_used: AtomicU64,
+}
+
+impl Slot {
+    #[inline]
+    fn new(element: Element, generation: u64) -> Self {
+        Self {
+            element,
+            last_used: AtomicU64::new(generation),
+        }
+    }
+
+    #[inline]
+    fn touch(&self, generation: u64) -> &Element {
+        self.last_used.store(generation, Ordering::Relaxed);
+        &self.element
+    }
+
+    #[inline]
+    fn touch_mut(&mut self, generation: u64) -> &mut Element {
+        *self.last_used.get_mut() = generation;
+        &mut self.element
+    }
+}
+
+impl Clone for Slot {
+    fn clone(&self) -> Self {
+        Self {
+            element: self.element.clone(),
+            last_used: AtomicU64::new(self.last_used.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl std::fmt::Debug for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Slot")
+            .field("element", &self.element)
+            .field("last_used", &self.last_used.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
 // TODO(emilk): make IdTypeMap generic over the key (`Id`), and make a library of IdTypeMap.
 /// Stores values identified by an [`Id`] AND a the [`std::any::TypeId`] of the value.
 ///
@@ -320,6 +376,9 @@ use crate::Id;
 ///
 /// You can store state using the key [`Id::NULL`]. The state will then only be identified by its type.
 ///
+/// Optionally, values that haven't been accessed for a number of frames can be evicted automatically.
+/// See [`Self::set_evict_after_frames`].
+///
 /// ```
 /// # use egui::{Id, util::IdTypeMap};
 /// let a = Id::new("a");
@@ -349,9 +408,21 @@ use crate::Id;
 #[derive(Clone, Debug)]
 // We use `id XOR typeid` as a key, so we don't need to hash again!
 pub struct IdTypeMap {
-    map: nohash_hasher::IntMap<u64, Element>,
+    map: nohash_hasher::IntMap<u64, Slot>,
 
     max_bytes_per_type: usize,
+
+    /// Incremented by one each call to [`Self::end_frame`].
+    generation: u64,
+
+    /// If set, values not accessed for this many frames are removed in [`Self::end_frame`].
+    evict_after_frames: Option<u64>,
+
+    /// Types that are never evicted by [`Self::end_frame`].
+    never_evict: nohash_hasher::IntSet<TypeId>,
+
+    /// The names of the types we have seen, for introspection.
+    type_names: nohash_hasher::IntMap<TypeId, &'static str>,
 }
 
 impl Default for IdTypeMap {
@@ -359,6 +430,10 @@ impl Default for IdTypeMap {
         Self {
             map: Default::default(),
             max_bytes_per_type: 256 * 1024,
+            generation: 0,
+            evict_after_frames: None,
+            never_evict: Default::default(),
+            type_names: Default::default(),
         }
     }
 }
@@ -367,15 +442,19 @@ impl IdTypeMap {
     /// Insert a value that will not be persisted.
     #[inline]
     pub fn insert_temp<T: 'static + Any + Clone + Send + Sync>(&mut self, id: Id, value: T) {
-        let hash = hash(TypeId::of::<T>(), id);
-        self.map.insert(hash, Element::new_temp(value));
+        let hash = self.register_type::<T>(id);
+        self.map
+            .insert(hash, Slot::new(Element::new_temp(value), self.generation));
     }
 
     /// Insert a value that will be persisted next time you start the app.
     #[inline]
     pub fn insert_persisted<T: SerializableAny>(&mut self, id: Id, value: T) {
-        let hash = hash(TypeId::of::<T>(), id);
-        self.map.insert(hash, Element::new_persisted(value));
+        let hash = self.register_type::<T>(id);
+        self.map.insert(
+            hash,
+            Slot::new(Element::new_persisted(value), self.generation),
+        );
     }
 
     /// Read a value without trying to deserialize a persisted value.
@@ -384,7 +463,10 @@ impl IdTypeMap {
     #[inline]
     pub fn get_temp<T: 'static + Clone>(&self, id: Id) -> Option<T> {
         let hash = hash(TypeId::of::<T>(), id);
-        self.map.get(&hash).and_then(|x| x.get_temp()).cloned()
+        self.map
+            .get(&hash)
+            .and_then(|x| x.touch(self.generation).get_temp())
+            .cloned()
     }
 
     /// Read a value, optionally deserializing it if available.
@@ -395,10 +477,11 @@ impl IdTypeMap {
     /// The call clones the value (if found), so make sure it is cheap to clone!
     #[inline]
     pub fn get_persisted<T: SerializableAny>(&mut self, id: Id) -> Option<T> {
-        let hash = hash(TypeId::of::<T>(), id);
+        let hash = self.register_type::<T>(id);
+        let generation = self.generation;
         self.map
             .get_mut(&hash)
-            .and_then(|x| x.get_mut_persisted())
+            .and_then(|x| x.touch_mut(generation).get_mut_persisted())
             .cloned()
     }
 
@@ -434,16 +517,19 @@ impl IdTypeMap {
         id: Id,
         insert_with: impl FnOnce() -> T,
     ) -> &mut T {
-        let hash = hash(TypeId::of::<T>(), id);
+        let hash = self.register_type::<T>(id);
+        let generation = self.generation;
         use std::collections::hash_map::Entry;
         match self.map.entry(hash) {
             Entry::Vacant(vacant) => vacant
-                .insert(Element::new_temp(insert_with()))
+                .insert(Slot::new(Element::new_temp(insert_with()), generation))
+                .element
                 .get_mut_temp()
                 .unwrap(), // this unwrap will never panic, because we insert correct type right now
-            Entry::Occupied(occupied) => {
-                occupied.into_mut().get_temp_mut_or_insert_with(insert_with)
-            }
+            Entry::Occupied(occupied) => occupied
+                .into_mut()
+                .touch_mut(generation)
+                .get_temp_mut_or_insert_with(insert_with),
         }
     }
 
@@ -452,15 +538,18 @@ impl IdTypeMap {
         id: Id,
         insert_with: impl FnOnce() -> T,
     ) -> &mut T {
-        let hash = hash(TypeId::of::<T>(), id);
+        let hash = self.register_type::<T>(id);
+        let generation = self.generation;
         use std::collections::hash_map::Entry;
         match self.map.entry(hash) {
             Entry::Vacant(vacant) => vacant
-                .insert(Element::new_persisted(insert_with()))
+                .insert(Slot::new(Element::new_persisted(insert_with()), generation))
+                .element
                 .get_mut_persisted()
                 .unwrap(), // this unwrap will never panic, because we insert correct type right now
             Entry::Occupied(occupied) => occupied
                 .into_mut()
+                .touch_mut(generation)
                 .get_persisted_mut_or_insert_with(insert_with),
         }
     }
@@ -469,8 +558,8 @@ impl IdTypeMap {
     #[cfg(feature = "persistence")]
     #[allow(unused)]
     fn get_generation<T: SerializableAny>(&self, id: Id) -> Option<usize> {
-        let element = self.map.get(&hash(TypeId::of::<T>(), id))?;
-        match element {
+        let slot = self.map.get(&hash(TypeId::of::<T>(), id))?;
+        match &slot.element {
             Element::Value { .. } => Some(0),
             Element::Serialized(SerializedElement { generation, .. }) => Some(*generation),
         }
@@ -487,17 +576,14 @@ impl IdTypeMap {
     #[inline]
     pub fn remove_temp<T: 'static + Default>(&mut self, id: Id) -> Option<T> {
         let hash = hash(TypeId::of::<T>(), id);
-        let mut element = self.map.remove(&hash)?;
-        Some(std::mem::take(element.get_mut_temp()?))
+        let mut slot = self.map.remove(&hash)?;
+        Some(std::mem::take(slot.element.get_mut_temp()?))
     }
 
     /// Note all state of the given type.
     pub fn remove_by_type<T: 'static>(&mut self) {
         let key = TypeId::of::<T>();
-        self.map.retain(|_, e| {
-            let e: &Element = e;
-            e.type_id() != key
-        });
+        self.map.retain(|_, slot| slot.element.type_id() != key);
     }
 
     #[inline]
@@ -520,7 +606,7 @@ impl IdTypeMap {
     pub fn count_serialized(&self) -> usize {
         self.map
             .values()
-            .filter(|e| matches!(e, Element::Serialized(_)))
+            .filter(|slot| matches!(slot.element, Element::Serialized(_)))
             .count()
     }
 
@@ -528,11 +614,8 @@ impl IdTypeMap {
     pub fn count<T: 'static>(&self) -> usize {
         let key = TypeId::of::<T>();
         self.map
-            .iter()
-            .filter(|(_, e)| {
-                let e: &Element = e;
-                e.type_id() == key
-            })
+            .values()
+            .filter(|slot| slot.element.type_id() == key)
             .count()
     }
 
@@ -557,6 +640,115 @@ impl IdTypeMap {
     pub fn set_max_bytes_per_type(&mut self, max_bytes_per_type: usize) {
         self.max_bytes_per_type = max_bytes_per_type;
     }
+
+    /// If set, values that haven't been read or written for this many frames
+    /// are removed from the map at the end of the frame.
+    ///
+    /// This is useful for apps that keep creating widgets with unique ids
+    /// (e.g. one per document or table row), whose state would otherwise accumulate forever.
+    ///
+    /// Values whose type has been excluded with [`Self::set_evictable`] are never evicted.
+    ///
+    /// The default is `None`, meaning values are only removed when explicitly asked to.
+    ///
+    /// This setting is persisted together with the values.
+    /// Restored values count as used in the frame they are loaded.
+    pub fn evict_after_frames(&self) -> Option<u64> {
+        self.evict_after_frames
+    }
+
+    /// See [`Self::evict_after_frames`].
+    pub fn set_evict_after_frames(&mut self, evict_after_frames: Option<u64>) {
+        self.evict_after_frames = evict_after_frames;
+    }
+
+    /// Should values of type `T` be evicted when unused for [`Self::evict_after_frames`]?
+    ///
+    /// Use this to opt out types that must persist even when the widget using them
+    /// isn't shown for a while. All types are evictable by default.
+    ///
+    /// This setting is persisted together with the values.
+    pub fn set_evictable<T: 'static>(&mut self, evictable: bool) {
+        let type_id = TypeId::of::<T>();
+        if evictable {
+            self.never_evict.remove(&type_id);
+        } else {
+            self.never_evict.insert(type_id);
+        }
+    }
+
+    /// See [`Self::set_evictable`].
+    pub fn is_evictable<T: 'static>(&self) -> bool {
+        !self.never_evict.contains(&TypeId::of::<T>())
+    }
+
+    /// Call once at the end of each frame.
+    ///
+    /// Evicts values that haven't been used in [`Self::evict_after_frames`] frames (if set).
+    ///
+    /// [`crate::Context`] calls this for you on [`Memory::data`](crate::Memory::data).
+    pub fn end_frame(&mut self) {
+        if let Some(evict_after_frames) = self.evict_after_frames {
+            crate::profile_function!();
+            let Self {
+                map,
+                generation,
+                never_evict,
+                ..
+            } = self;
+            map.retain(|_, slot| {
+                let age = generation.saturating_sub(*slot.last_used.get_mut());
+                age < evict_after_frames || never_evict.contains(&slot.element.type_id())
+            });
+        }
+        self.generation += 1;
+    }
+
+    /// Statistics about the stored values, grouped by type.
+    ///
+    /// Sorted by the number of bytes used, largest first.
+    ///
+    /// The byte counts are approximate: for live values we only count the size of the value itself
+    /// (not any heap allocations it owns), and for serialized values we count the length of the serialized data.
+    pub fn type_stats(&self) -> Vec<TypeStats> {
+        let mut stats: nohash_hasher::IntMap<TypeId, TypeStats> = Default::default();
+
+        for slot in self.map.values() {
+            let type_id = slot.element.type_id();
+            let stats = stats.entry(type_id).or_insert_with(|| TypeStats {
+                type_id,
+                type_name: self.type_names.get(&type_id).copied(),
+                num_values: 0,
+                num_serialized: 0,
+                num_bytes: 0,
+                evictable: !self.never_evict.contains(&type_id),
+            });
+            stats.num_values += 1;
+            match &slot.element {
+                Element::Value { value, .. } => {
+                    stats.num_bytes += std::mem::size_of_val(&**value);
+                }
+                Element::Serialized(SerializedElement { ron, .. }) => {
+                    stats.num_serialized += 1;
+                    stats.num_bytes += ron.len();
+                }
+            }
+        }
+
+        let mut stats: Vec<TypeStats> = stats.into_values().collect();
+        stats.sort_by(|a, b| b.num_bytes.cmp(&a.num_bytes));
+        stats
+    }
+
+    /// Remember the name of `T` (for [`Self::type_stats`]) and return the key for `(T, id)`.
+    #[inline]
+    fn register_type<T: 'static>(&mut self, id: Id) -> u64 {
+        let type_id = TypeId::of::<T>();
+        self.type_names
+            .entry(type_id)
+            .or_insert_with(std::any::type_name::<T>);
+        hash(type_id, id)
+    }
 }
 
 #[inline(always)]
@@ -564,12 +756,78 @@ fn hash(type_id: TypeId, id: Id) -> u64 {
     type_id.value() ^ id.value()
 }
 
+/// Statistics about all values of one type in an [`IdTypeMap`].
+///
+/// See [`IdTypeMap::type_stats`].
+#[derive(Clone, Debug)]
+pub struct TypeStats {
+    pub type_id: TypeId,
+
+    /// The Rust type name, if known.
+    ///
+    /// This is `None` for values that have been loaded from disk but not yet deserialized.
+    pub type_name: Option<&'static str>,
+
+    /// Total number of values of this type.
+    pub num_values: usize,
+
+    /// How many of the values are stored but not yet deserialized.
+    pub num_serialized: usize,
+
+    /// Approximate number of bytes used by the values.
+    pub num_bytes: usize,
+
+    /// See [`IdTypeMap::set_evictable`].
+    pub evictable: bool,
+}
+
 // ----------------------------------------------------------------------------
 
 /// How [`IdTypeMap`] is persisted.
+///
+/// Serialized as a tuple of the elements and the eviction settings.
+/// The settings are optional when deserializing, for data saved before they were persisted.
 #[cfg(feature = "persistence")]
-#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
-struct PersistedMap(Vec<(u64, SerializedElement)>);
+#[cfg_attr(feature = "persistence", derive(serde::Serialize))]
+struct PersistedMap(
+    Vec<(u64, SerializedElement)>,
+    /// See [`IdTypeMap::set_evict_after_frames`].
+    Option<u64>,
+    /// The types excluded with [`IdTypeMap::set_evictable`].
+    Vec<TypeId>,
+);
+
+#[cfg(feature = "persistence")]
+impl<'de> serde::Deserialize<'de> for PersistedMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = PersistedMap;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a persisted IdTypeMap")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let elements = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let evict_after_frames = seq.next_element()?.flatten();
+                let never_evict = seq.next_element()?.unwrap_or_default();
+                Ok(PersistedMap(elements, evict_after_frames, never_evict))
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("PersistedMap", 3, Visitor)
+    }
+}
 
 #[cfg(feature = "persistence")]
 impl PersistedMap {
@@ -594,8 +852,8 @@ impl PersistedMap {
 
         {
             crate::profile_scope!("gather");
-            for (hash, element) in &map.map {
-                if let Some(element) = element.to_serialize() {
+            for (hash, slot) in &map.map {
+                if let Some(element) = slot.element.to_serialize() {
                     let stats = types_map.entry(element.type_id).or_default();
                     stats.num_bytes += element.ron.len();
                     let generation_stats = stats.generations.entry(element.generation).or_default();
@@ -630,13 +888,26 @@ impl PersistedMap {
             }
         }
 
-        Self(persisted)
+        let mut never_evict: Vec<TypeId> = map.never_evict.iter().copied().collect();
+        never_evict.sort_by_key(TypeId::value); // Deterministic output
+
+        Self(persisted, map.evict_after_frames, never_evict)
     }
 
     fn into_map(self) -> IdTypeMap {
         crate::profile_function!();
-        let map = self
-            .0
+        let Self(elements, evict_after_frames, never_evict) = self;
+
+        let mut map = IdTypeMap {
+            evict_after_frames,
+            never_evict: never_evict.into_iter().collect(),
+            ..Default::default()
+        };
+
+        // Restored values count as used this frame, so they aren't evicted before the app gets to read them:
+        let current_frame = map.generation;
+
+        map.map = elements
             .into_iter()
             .map(
                 |(
@@ -647,21 +918,16 @@ impl PersistedMap {
                         generation,
                     },
                 )| {
-                    (
-                        hash,
-                        Element::Serialized(SerializedElement {
-                            type_id,
-                            ron,
-                            generation: generation + 1, // This is where we increment the generation!
-                        }),
-                    )
+                    let element = Element::Serialized(SerializedElement {
+                        type_id,
+                        ron,
+                        generation: generation + 1, // This is where we increment the generation!
+                    });
+                    (hash, Slot::new(element, current_frame))
                 },
             )
             .collect();
-        IdTypeMap {
-            map,
-            ..Default::default()
-        }
+        map
     }
 }
 
@@ -984,3 +1250,86 @@ fn test_serialize_gc() {
         Some(B(2_000_000))
     );
 }
+
+#[test]
+fn test_evict_unused() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Kept(i32);
+
+    let mut map: IdTypeMap = Default::default();
+    map.set_evict_after_frames(Some(2));
+    map.set_evictable::<Kept>(false);
+
+    map.insert_temp(Id::new("read"), 1_i32);
+    map.insert_temp(Id::new("unused"), 2_i32);
+    map.insert_temp(Id::new("kept"), Kept(3));
+
+    for _ in 0..5 {
+        assert_eq!(map.get_temp::<i32>(Id::new("read")), Some(1));
+        map.end_frame();
+    }
+
+    assert_eq!(map.get_temp::<i32>(Id::new("read")), Some(1));
+    assert_eq!(map.get_temp::<i32>(Id::new("unused")), None);
+    assert_eq!(map.get_temp::<Kept>(Id::new("kept")), Some(Kept(3)));
+    assert_eq!(map.len(), 2);
+}
+
+#[cfg(feature = "persistence")]
+#[test]
+fn test_evict_after_deserialize() {
+    let mut map: IdTypeMap = Default::default();
+    map.set_evict_after_frames(Some(2));
+    map.set_evictable::<String>(false);
+    for _ in 0..10 {
+        map.end_frame();
+    }
+    map.insert_persisted(Id::new("a"), 1_i32);
+
+    let serialized = ron::to_string(&map).unwrap();
+    let mut map: IdTypeMap = ron::from_str(&serialized).unwrap();
+    assert_eq!(
+        map.evict_after_frames(),
+        Some(2),
+        "Eviction settings are persisted"
+    );
+    assert!(!map.is_evictable::<String>());
+
+    // Restored values get a chance to be read before they are evicted:
+    map.end_frame();
+    assert_eq!(map.get_persisted::<i32>(Id::new("a")), Some(1));
+    for _ in 0..2 {
+        map.end_frame();
+    }
+    assert_eq!(map.get_persisted::<i32>(Id::new("a")), Some(1));
+    for _ in 0..3 {
+        map.end_frame();
+    }
+    assert_eq!(map.get_persisted::<i32>(Id::new("a")), None);
+
+    // The format from before the settings were persisted can still be read:
+    #[derive(serde::Serialize)]
+    struct LegacyPersistedMap(Vec<(u64, SerializedElement)>);
+    let mut map = IdTypeMap::default();
+    map.insert_persisted(Id::new("b"), 2_i32);
+    let legacy = ron::to_string(&LegacyPersistedMap(PersistedMap::from_map(&map).0)).unwrap();
+    let mut map: IdTypeMap = ron::from_str(&legacy).unwrap();
+    assert_eq!(map.evict_after_frames(), None);
+    assert_eq!(map.get_persisted::<i32>(Id::new("b")), Some(2));
+}
+
+#[test]
+fn test_type_stats() {
+    let mut map: IdTypeMap = Default::default();
+    map.insert_temp(Id::new("a"), 1_u64);
+    map.insert_temp(Id::new("b"), 2_u64);
+    map.insert_temp(Id::new("a"), 1_u8);
+
+    let stats = map.type_stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].type_name, Some("u64"));
+    assert_eq!(stats[0].num_values, 2);
+    assert_eq!(stats[0].num_bytes, 16);
+    assert_eq!(stats[1].type_name, Some("u8"));
+    assert_eq!(stats[1].num_values, 1);
+}
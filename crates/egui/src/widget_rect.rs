@@ -59,6 +59,11 @@ pub struct WidgetRects {
     /// Only filled in if the widget is interacted with,
     /// or if this is a debug build.
     infos: IdMap<WidgetInfo>,
+
+    /// Widgets whose hit area is a [`Shape`] rather than their [`WidgetRect::interact_rect`].
+    ///
+    /// See [`Ui::interact_shape`].
+    hit_shapes: IdMap<Shape>,
 }
 
 impl PartialEq for WidgetRects {
@@ -106,6 +111,7 @@ impl WidgetRects {
             by_layer,
             by_id,
             infos,
+            hit_shapes,
         } = self;
 
         for rects in by_layer.values_mut() {
@@ -115,6 +121,8 @@ impl WidgetRects {
         by_id.clear();
 
         infos.clear();
+
+        hit_shapes.clear();
     }
 
     /// Insert the given widget rect in the given layer.
@@ -123,6 +131,7 @@ impl WidgetRects {
             by_layer,
             by_id,
             infos: _,
+            hit_shapes: _,
         } = self;
 
         let layer_widgets = by_layer.entry(layer_id).or_default();
@@ -164,4 +173,35 @@ impl WidgetRects {
     pub fn info(&self, id: Id) -> Option<&WidgetInfo> {
         self.infos.get(&id)
     }
+
+    /// Use the painted area of this shape as the hit area of the widget,
+    /// within its [`WidgetRect::interact_rect`].
+    pub fn set_hit_shape(&mut self, id: Id, shape: Shape) {
+        self.hit_shapes.insert(id, shape);
+    }
+
+    /// See [`Self::set_hit_shape`].
+    pub fn hit_shape(&self, id: Id) -> Option<&Shape> {
+        self.hit_shapes.get(&id)
+    }
+
+    /// The squared distance from the position to the hit area of the widget.
+    pub(crate) fn distance_sq_to_pos(&self, widget: &WidgetRect, pos: Pos2) -> f32 {
+        let rect_dist_sq = widget.interact_rect.distance_sq_to_pos(pos);
+        match self.hit_shape(widget.id) {
+            Some(shape) => {
+                let shape_dist = shape.distance_to(pos);
+                rect_dist_sq.max(shape_dist * shape_dist)
+            }
+            None => rect_dist_sq,
+        }
+    }
+
+    /// Is the position within the hit area of the widget?
+    pub(crate) fn hit_area_contains(&self, widget: &WidgetRect, pos: Pos2) -> bool {
+        widget.interact_rect.contains(pos)
+            && self
+                .hit_shape(widget.id)
+                .map_or(true, |shape| shape.contains_point(pos))
+    }
 }
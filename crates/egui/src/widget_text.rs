@@ -1,8 +1,8 @@
 use std::{borrow::Cow, sync::Arc};
 
 use crate::{
-    text::LayoutJob, Align, Color32, FontFamily, FontSelection, Galley, Style, TextStyle, Ui,
-    Visuals,
+    text::{DecorationStyle, LayoutJob},
+    Align, Color32, FontFamily, FontSelection, FontWeight, Galley, Style, TextStyle, Ui, Visuals,
 };
 
 /// Text and optional style choices for it.
@@ -19,6 +19,7 @@ use crate::{
 /// RichText::new("Plain");
 /// RichText::new("colored").color(Color32::RED);
 /// RichText::new("Large and underlined").size(20.0).underline();
+/// RichText::new("misspeled").underline_style(egui::text::DecorationStyle::Wavy).underline_color(Color32::RED);
 /// ```
 #[derive(Clone, Default, PartialEq)]
 pub struct RichText {
@@ -32,9 +33,14 @@ pub struct RichText {
     text_color: Option<Color32>,
     code: bool,
     strong: bool,
+    weight: Option<FontWeight>,
     weak: bool,
     strikethrough: bool,
+    strikethrough_style: DecorationStyle,
+    strikethrough_color: Option<Color32>,
     underline: bool,
+    underline_style: DecorationStyle,
+    underline_color: Option<Color32>,
     italics: bool,
     raised: bool,
 }
@@ -181,13 +187,23 @@ impl RichText {
         self.text_style(TextStyle::Monospace)
     }
 
-    /// Extra strong text (stronger color).
+    /// Extra strong text (stronger color and bold).
     #[inline]
     pub fn strong(mut self) -> Self {
         self.strong = true;
         self
     }
 
+    /// How bold the text is.
+    ///
+    /// Uses a face of the font with this weight if there is one
+    /// (see [`crate::FontDefinitions::faces`]), otherwise bold is faked.
+    #[inline]
+    pub fn weight(mut self, weight: FontWeight) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
     /// Extra weak text (fainter color).
     #[inline]
     pub fn weak(mut self) -> Self {
@@ -197,23 +213,55 @@ impl RichText {
 
     /// Draw a line under the text.
     ///
-    /// If you want to control the line color, use [`LayoutJob`] instead.
+    /// See also [`Self::underline_style`] and [`Self::underline_color`].
     #[inline]
     pub fn underline(mut self) -> Self {
         self.underline = true;
         self
     }
 
+    /// Draw a line under the text in this style, e.g. [`DecorationStyle::Wavy`] to mark an error.
+    #[inline]
+    pub fn underline_style(mut self, style: DecorationStyle) -> Self {
+        self.underline = true;
+        self.underline_style = style;
+        self
+    }
+
+    /// Draw a line under the text in this color, instead of the text color.
+    #[inline]
+    pub fn underline_color(mut self, color: impl Into<Color32>) -> Self {
+        self.underline = true;
+        self.underline_color = Some(color.into());
+        self
+    }
+
     /// Draw a line through the text, crossing it out.
     ///
-    /// If you want to control the strikethrough line color, use [`LayoutJob`] instead.
+    /// See also [`Self::strikethrough_style`] and [`Self::strikethrough_color`].
     #[inline]
     pub fn strikethrough(mut self) -> Self {
         self.strikethrough = true;
         self
     }
 
-    /// Tilt the characters to the right.
+    /// Draw a line through the text in this style.
+    #[inline]
+    pub fn strikethrough_style(mut self, style: DecorationStyle) -> Self {
+        self.strikethrough = true;
+        self.strikethrough_style = style;
+        self
+    }
+
+    /// Draw a line through the text in this color, instead of the text color.
+    #[inline]
+    pub fn strikethrough_color(mut self, color: impl Into<Color32>) -> Self {
+        self.strikethrough = true;
+        self.strikethrough_color = Some(color.into());
+        self
+    }
+
+    /// Use the italic face of the font, or tilt the characters to the right if there is none.
     #[inline]
     pub fn italics(mut self) -> Self {
         self.italics = true;
@@ -341,10 +389,15 @@ impl RichText {
             background_color,
             text_color: _, // already used by `get_text_color`
             code,
-            strong: _, // already used by `get_text_color`
-            weak: _,   // already used by `get_text_color`
+            strong,
+            weight,
+            weak: _, // already used by `get_text_color`
             strikethrough,
+            strikethrough_style,
+            strikethrough_color,
             underline,
+            underline_style,
+            underline_color,
             italics,
             raised,
         } = self;
@@ -373,12 +426,12 @@ impl RichText {
             background_color = style.visuals.code_bg_color;
         }
         let underline = if underline {
-            crate::Stroke::new(1.0, line_color)
+            crate::Stroke::new(1.0, underline_color.unwrap_or(line_color))
         } else {
             crate::Stroke::NONE
         };
         let strikethrough = if strikethrough {
-            crate::Stroke::new(1.0, line_color)
+            crate::Stroke::new(1.0, strikethrough_color.unwrap_or(line_color))
         } else {
             crate::Stroke::NONE
         };
@@ -389,6 +442,12 @@ impl RichText {
             default_valign
         };
 
+        let weight = weight.unwrap_or(if strong {
+            FontWeight::BOLD
+        } else {
+            FontWeight::REGULAR
+        });
+
         (
             text,
             crate::text::TextFormat {
@@ -397,9 +456,12 @@ impl RichText {
                 line_height,
                 color: text_color,
                 background: background_color,
+                weight,
                 italics,
                 underline,
+                underline_style,
                 strikethrough,
+                strikethrough_style,
                 valign,
             },
         )
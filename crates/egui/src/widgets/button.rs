@@ -249,33 +249,38 @@ impl Widget for Button<'_> {
         if ui.is_rect_visible(rect) {
             let visuals = ui.style().interact(&response);
 
-            let (frame_expansion, frame_rounding, frame_fill, frame_stroke) = if selected {
-                let selection = ui.visuals().selection;
-                (
-                    Vec2::ZERO,
-                    Rounding::ZERO,
-                    selection.bg_fill,
-                    selection.stroke,
-                )
-            } else if frame {
-                let expansion = Vec2::splat(visuals.expansion);
-                (
-                    expansion,
-                    visuals.rounding,
-                    visuals.weak_bg_fill,
-                    visuals.bg_stroke,
-                )
-            } else {
-                Default::default()
-            };
+            let (frame_expansion, frame_rounding, frame_fill, frame_stroke, frame_shadow) =
+                if selected {
+                    let selection = ui.visuals().selection;
+                    (
+                        Vec2::ZERO,
+                        Rounding::ZERO,
+                        selection.bg_fill,
+                        selection.stroke,
+                        Shadow::NONE,
+                    )
+                } else if frame {
+                    let expansion = Vec2::splat(visuals.expansion);
+                    (
+                        expansion,
+                        visuals.rounding,
+                        visuals.weak_bg_fill,
+                        visuals.bg_stroke,
+                        visuals.shadow,
+                    )
+                } else {
+                    Default::default()
+                };
             let frame_rounding = rounding.unwrap_or(frame_rounding);
             let frame_fill = fill.unwrap_or(frame_fill);
             let frame_stroke = stroke.unwrap_or(frame_stroke);
-            ui.painter().rect(
-                rect.expand2(frame_expansion),
-                frame_rounding,
-                frame_fill,
-                frame_stroke,
+            ui.painter().add(
+                Frame::none()
+                    .rounding(frame_rounding)
+                    .fill(frame_fill)
+                    .stroke(frame_stroke)
+                    .shadow(frame_shadow)
+                    .paint(rect.expand2(frame_expansion)),
             );
 
             let mut cursor_x = rect.min.x + button_padding.x;
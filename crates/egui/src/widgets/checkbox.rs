@@ -97,12 +97,13 @@ impl<'a> Widget for Checkbox<'a> {
             // let visuals = ui.style().interact_selectable(&response, *checked); // too colorful
             let visuals = ui.style().interact(&response);
             let (small_icon_rect, big_icon_rect) = ui.spacing().icon_rectangles(rect);
-            ui.painter().add(epaint::RectShape::new(
-                big_icon_rect.expand(visuals.expansion),
-                visuals.rounding,
-                visuals.bg_fill,
-                visuals.bg_stroke,
-            ));
+            ui.painter()
+                .add(visuals.shadow.around(epaint::RectShape::new(
+                    big_icon_rect.expand(visuals.expansion),
+                    visuals.rounding,
+                    visuals.bg_fill,
+                    visuals.bg_stroke,
+                )));
 
             if indeterminate {
                 // Horizontal line:
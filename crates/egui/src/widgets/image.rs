@@ -245,6 +245,28 @@ impl<'a> Image<'a> {
         self
     }
 
+    /// Paint the image as a [`NineSlice`], so it can be resized without distorting its border.
+    ///
+    /// Useful for skinned buttons and panels. Usually combined with [`Self::fit_to_exact_size`].
+    /// Any [`Self::rounding`] is ignored, since the corners come from the image itself.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let texture_id = egui::TextureId::default();
+    /// let border = egui::NineSlice::new(8.0).fill(egui::NineSliceFill::Tile);
+    /// ui.add(
+    ///     egui::Image::new((texture_id, egui::vec2(32.0, 32.0)))
+    ///         .nine_slice(border)
+    ///         .fit_to_exact_size(egui::vec2(200.0, 100.0)),
+    /// );
+    /// # });
+    /// ```
+    #[inline]
+    pub fn nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.image_options.nine_slice = Some(nine_slice);
+        self
+    }
+
     /// Show a spinner when the image is loading.
     ///
     /// By default this uses the value of [`Visuals::image_loading_spinners`].
@@ -718,6 +740,11 @@ pub struct ImageOptions {
     /// Due to limitations in the current implementation,
     /// this will turn off any rotation of the image.
     pub rounding: Rounding,
+
+    /// Paint the image as a [`NineSlice`], with the border insets in texels of the texture.
+    ///
+    /// [`Self::rounding`] is ignored when this is set.
+    pub nine_slice: Option<NineSlice>,
 }
 
 impl Default for ImageOptions {
@@ -728,6 +755,7 @@ impl Default for ImageOptions {
             tint: Color32::WHITE,
             rotation: None,
             rounding: Rounding::ZERO,
+            nine_slice: None,
         }
     }
 }
@@ -742,6 +770,15 @@ pub fn paint_texture_at(
         painter.add(RectShape::filled(rect, options.rounding, options.bg_fill));
     }
 
+    if let Some(nine_slice) = &options.nine_slice {
+        let mut mesh = nine_slice.mesh(rect, texture.id, texture.size, options.uv, options.tint);
+        if let Some((rot, origin)) = options.rotation {
+            mesh.rotate(rot, rect.min + origin * rect.size());
+        }
+        painter.add(Shape::mesh(mesh));
+        return;
+    }
+
     match options.rotation {
         Some((rot, origin)) => {
             // TODO(emilk): implement this using `PathShape` (add texture support to it).
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::*;
+use crate::{text::ElisionPosition, *};
 
 use self::text_selection::LabelSelectionState;
 
@@ -24,6 +24,8 @@ pub struct Label {
     text: WidgetText,
     wrap: Option<bool>,
     truncate: bool,
+    elision_position: Option<ElisionPosition>,
+    show_tooltip_when_elided: bool,
     sense: Option<Sense>,
     selectable: Option<bool>,
 }
@@ -34,6 +36,8 @@ impl Label {
             text: text.into(),
             wrap: None,
             truncate: false,
+            elision_position: None,
+            show_tooltip_when_elided: true,
             sense: None,
             selectable: None,
         }
@@ -64,7 +68,8 @@ impl Label {
     /// If `true`, the text will stop at the max width of the [`Ui`],
     /// and what doesn't fit will be elided, replaced with `…`.
     ///
-    /// If the text is truncated, the full text will be shown on hover as a tool-tip.
+    /// If the text is truncated, the full text will be shown on hover as a tool-tip
+    /// (see [`Self::show_tooltip_when_elided`]).
     ///
     /// Default is `false`, which means the text will expand the parent [`Ui`],
     /// or wrap if [`Self::wrap`] is set.
@@ -77,6 +82,38 @@ impl Label {
         self
     }
 
+    /// Which part of the text to elide when it is truncated, see [`Self::truncate`].
+    ///
+    /// Eliding in the middle keeps both ends of e.g. a file path readable:
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// use egui::text::ElisionPosition;
+    /// ui.add(
+    ///     egui::Label::new("/home/user/project/src/main.rs")
+    ///         .truncate(true)
+    ///         .elision_position(ElisionPosition::Middle),
+    /// );
+    /// # });
+    /// ```
+    ///
+    /// By default, the end of the text is elided,
+    /// unless the text is a [`crate::text::LayoutJob`] that says otherwise.
+    #[inline]
+    pub fn elision_position(mut self, elision_position: ElisionPosition) -> Self {
+        self.elision_position = Some(elision_position);
+        self
+    }
+
+    /// Show the full text as a tool-tip when the label is hovered, if the text was elided.
+    ///
+    /// Default is `true`.
+    #[inline]
+    pub fn show_tooltip_when_elided(mut self, show: bool) -> Self {
+        self.show_tooltip_when_elided = show;
+        self
+    }
+
     /// Can the user select the text with the mouse?
     ///
     /// Overrides [`crate::style::Interaction::selectable_labels`].
@@ -196,6 +233,9 @@ impl Label {
                 layout_job.wrap.max_width = available_width;
                 layout_job.wrap.max_rows = 1;
                 layout_job.wrap.break_anywhere = true;
+                if let Some(elision_position) = self.elision_position {
+                    layout_job.wrap.elision_position = elision_position;
+                }
             } else if wrap {
                 layout_job.wrap.max_width = available_width;
             } else {
@@ -231,12 +271,13 @@ impl Widget for Label {
         let interactive = self.sense.map_or(false, |sense| sense != Sense::hover());
 
         let selectable = self.selectable;
+        let show_tooltip_when_elided = self.show_tooltip_when_elided;
 
         let (galley_pos, galley, mut response) = self.layout_in_ui(ui);
         response.widget_info(|| WidgetInfo::labeled(WidgetType::Label, galley.text()));
 
         if ui.is_rect_visible(response.rect) {
-            if galley.elided {
+            if galley.elided && show_tooltip_when_elided {
                 // Show the full (non-elided) text on hover:
                 response = response.on_hover_text(galley.text());
             }
@@ -76,12 +76,12 @@ impl Widget for RadioButton {
 
             let painter = ui.painter();
 
-            painter.add(epaint::CircleShape {
+            painter.add(visuals.shadow.around(epaint::CircleShape {
                 center: big_icon_rect.center(),
                 radius: big_icon_rect.width() / 2.0 + visuals.expansion,
                 fill: visuals.bg_fill,
                 stroke: visuals.bg_stroke,
-            });
+            }));
 
             if checked {
                 painter.add(epaint::CircleShape {
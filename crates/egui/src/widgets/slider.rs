@@ -727,12 +727,12 @@ impl<'a> Slider<'a> {
                 .unwrap_or_else(|| ui.style().visuals.handle_shape);
             match handle_shape {
                 style::HandleShape::Circle => {
-                    ui.painter().add(epaint::CircleShape {
+                    ui.painter().add(visuals.shadow.around(epaint::CircleShape {
                         center,
                         radius: radius + visuals.expansion,
                         fill: visuals.bg_fill,
                         stroke: visuals.fg_stroke,
-                    });
+                    }));
                 }
                 style::HandleShape::Rect { aspect_ratio } => {
                     let v = match self.orientation {
@@ -742,7 +742,12 @@ impl<'a> Slider<'a> {
                     let v = v + Vec2::splat(visuals.expansion);
                     let rect = Rect::from_center_size(center, 2.0 * v);
                     ui.painter()
-                        .rect(rect, visuals.rounding, visuals.bg_fill, visuals.fg_stroke);
+                        .add(visuals.shadow.around(epaint::RectShape::new(
+                            rect,
+                            visuals.rounding,
+                            visuals.bg_fill,
+                            visuals.fg_stroke,
+                        )));
                 }
             }
         }
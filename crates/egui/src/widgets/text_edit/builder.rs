@@ -402,21 +402,17 @@ impl<'t> TextEdit<'t> {
             let visuals = ui.style().interact(&output.response);
             let frame_rect = outer_rect.expand(visuals.expansion);
             let shape = if is_mutable {
-                if output.response.has_focus() {
-                    epaint::RectShape::new(
-                        frame_rect,
-                        visuals.rounding,
-                        ui.visuals().extreme_bg_color,
-                        ui.visuals().selection.stroke,
-                    )
+                let stroke = if output.response.has_focus() {
+                    ui.visuals().selection.stroke
                 } else {
-                    epaint::RectShape::new(
-                        frame_rect,
-                        visuals.rounding,
-                        ui.visuals().extreme_bg_color,
-                        visuals.bg_stroke, // TODO(emilk): we want to show something here, or a text-edit field doesn't "pop".
-                    )
-                }
+                    visuals.bg_stroke // TODO(emilk): we want to show something here, or a text-edit field doesn't "pop".
+                };
+                visuals.shadow.around(epaint::RectShape::new(
+                    frame_rect,
+                    visuals.rounding,
+                    ui.visuals().extreme_bg_color,
+                    stroke,
+                ))
             } else {
                 let visuals = &ui.style().visuals.widgets.inactive;
                 epaint::RectShape::stroke(
@@ -424,6 +420,7 @@ impl<'t> TextEdit<'t> {
                     visuals.rounding,
                     visuals.bg_stroke, // TODO(emilk): we want to show something here, or a text-edit field doesn't "pop".
                 )
+                .into()
             };
 
             ui.painter().set(where_to_put_background, shape);
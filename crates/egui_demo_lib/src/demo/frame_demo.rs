@@ -16,9 +16,12 @@ impl Default for FrameDemo {
                     blur: 16.0,
                     spread: 0.0,
                     color: egui::Color32::from_black_alpha(180),
+                    inset: false,
                 },
                 fill: egui::Color32::from_rgba_unmultiplied(97, 0, 255, 128),
+                fill_texture: None,
                 stroke: egui::Stroke::new(1.0, egui::Color32::GRAY),
+                clip_content: false,
             },
         }
     }
@@ -225,6 +225,13 @@ fn label_ui(ui: &mut egui::Ui) {
         )
         .truncate(true),
     );
+    ui.add(
+        egui::Label::new(
+            "/home/user/projects/long_project_name/src/some_module/another_module/file_with_a_long_name.rs",
+        )
+        .truncate(true)
+        .elision_position(egui::text::ElisionPosition::Middle),
+    );
 }
 
 // ----------------------------------------------------------------------------
@@ -522,7 +529,7 @@ impl Tree {
 // ----------------------------------------------------------------------------
 
 fn text_layout_demo(ui: &mut Ui) {
-    use egui::text::LayoutJob;
+    use egui::text::{DecorationStyle, LayoutJob};
 
     let mut job = LayoutJob::default();
 
@@ -628,6 +635,30 @@ fn text_layout_demo(ui: &mut Ui) {
             ..Default::default()
         },
     );
+    for (text, style) in [
+        ("wavy", DecorationStyle::Wavy),
+        ("dotted", DecorationStyle::Dotted),
+        ("double", DecorationStyle::Double),
+    ] {
+        job.append(
+            ", ",
+            0.0,
+            TextFormat {
+                color: default_color,
+                ..Default::default()
+            },
+        );
+        job.append(
+            text,
+            0.0,
+            TextFormat {
+                color: default_color,
+                underline: Stroke::new(1.0, Color32::RED),
+                underline_style: style,
+                ..Default::default()
+            },
+        );
+    }
     job.append(
         " and ",
         0.0,
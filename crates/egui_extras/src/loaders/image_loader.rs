@@ -1,5 +1,6 @@
 use egui::{
     ahash::HashMap,
+    epaint::ResizeFilter,
     load::{BytesPoll, ImageLoadResult, ImageLoader, ImagePoll, LoadError, SizeHint},
     mutex::Mutex,
     ColorImage,
@@ -8,13 +9,83 @@ use std::{mem::size_of, path::Path, sync::Arc};
 
 type Entry = Result<Arc<ColorImage>, String>;
 
+/// How many downscaled versions of the same image we keep.
+///
+/// Size hints that change every frame (e.g. while resizing a window)
+/// would otherwise leave an image behind for every size.
+const MAX_DOWNSCALED_PER_URI: usize = 4;
+
 #[derive(Default)]
 pub struct ImageCrateLoader {
     cache: Mutex<HashMap<String, Entry>>,
+
+    /// Smaller versions of the images in `cache`, for size hints that ask for them.
+    ///
+    /// The most recently used version of each image is last.
+    downscaled: Mutex<HashMap<String, Vec<Arc<ColorImage>>>>,
 }
 
 impl ImageCrateLoader {
     pub const ID: &'static str = egui::generate_loader_id!(ImageCrateLoader);
+
+    /// The image, shrunk if the size hint asks for a smaller one.
+    fn downscaled(
+        &self,
+        uri: &str,
+        image: Arc<ColorImage>,
+        size_hint: SizeHint,
+        pixels_per_point: f32,
+    ) -> Arc<ColorImage> {
+        let Some(size) = downscaled_size(&image, size_hint, pixels_per_point) else {
+            return image;
+        };
+        let mut downscaled = self.downscaled.lock();
+        let versions = downscaled.entry(uri.to_owned()).or_default();
+        let version = if let Some(index) = versions.iter().position(|v| v.size == size) {
+            versions.remove(index)
+        } else {
+            if versions.len() == MAX_DOWNSCALED_PER_URI {
+                let _ = versions.remove(0);
+            }
+            log::trace!("downscaling {uri:?} to {size:?}");
+            Arc::new(image.resized(size, ResizeFilter::default()))
+        };
+        versions.push(version.clone());
+        version
+    }
+}
+
+/// The size to shrink an image to, if the size hint asks for a smaller image.
+///
+/// The size hint is in points, so we keep enough pixels for `pixels_per_point`.
+/// The image is only ever halved (rounding up), as many times as it stays large enough,
+/// so that a size hint that changes every frame (e.g. while resizing a window)
+/// doesn't resample the image at every size.
+fn downscaled_size(
+    image: &ColorImage,
+    size_hint: SizeHint,
+    pixels_per_point: f32,
+) -> Option<[usize; 2]> {
+    let [width, height] = image.size.map(|size| size as f32);
+    let max_size = match size_hint {
+        SizeHint::Scale(scale) => [width * scale.into_inner(), height * scale.into_inner()],
+        SizeHint::Width(max_width) => [max_width as f32, f32::INFINITY],
+        SizeHint::Height(max_height) => [f32::INFINITY, max_height as f32],
+        SizeHint::Size(max_width, max_height) => [max_width as f32, max_height as f32],
+    };
+    // Casting saturates, so infinity means no limit:
+    let max_size = max_size.map(|size| (size * pixels_per_point).ceil() as usize);
+    let fit = image.size_to_fit(max_size);
+
+    let mut size = image.size;
+    loop {
+        let half = size.map(|side| (side + 1) / 2);
+        if half == size || half[0] < fit[0] || half[1] < fit[1] {
+            break;
+        }
+        size = half;
+    }
+    (size != image.size).then_some(size)
 }
 
 fn is_supported_uri(uri: &str) -> bool {
@@ -37,7 +108,7 @@ impl ImageLoader for ImageCrateLoader {
         Self::ID
     }
 
-    fn load(&self, ctx: &egui::Context, uri: &str, _: SizeHint) -> ImageLoadResult {
+    fn load(&self, ctx: &egui::Context, uri: &str, size_hint: SizeHint) -> ImageLoadResult {
         // three stages of guessing if we support loading the image:
         // 1. URI extension
         // 2. Mime from `BytesPoll::Ready`
@@ -49,11 +120,8 @@ impl ImageLoader for ImageCrateLoader {
         }
 
         let mut cache = self.cache.lock();
-        if let Some(entry) = cache.get(uri).cloned() {
-            match entry {
-                Ok(image) => Ok(ImagePoll::Ready { image }),
-                Err(err) => Err(LoadError::Loading(err)),
-            }
+        let entry = if let Some(entry) = cache.get(uri).cloned() {
+            entry
         } else {
             match ctx.try_load_bytes(uri) {
                 Ok(BytesPoll::Ready { bytes, mime, .. }) => {
@@ -68,34 +136,51 @@ impl ImageLoader for ImageCrateLoader {
                     let result = crate::image::load_image_bytes(&bytes).map(Arc::new);
                     log::trace!("finished loading {uri:?}");
                     cache.insert(uri.into(), result.clone());
-                    match result {
-                        Ok(image) => Ok(ImagePoll::Ready { image }),
-                        Err(err) => Err(LoadError::Loading(err)),
-                    }
+                    result
                 }
-                Ok(BytesPoll::Pending { size }) => Ok(ImagePoll::Pending { size }),
-                Err(err) => Err(err),
+                Ok(BytesPoll::Pending { size }) => return Ok(ImagePoll::Pending { size }),
+                Err(err) => return Err(err),
+            }
+        };
+        drop(cache);
+
+        match entry {
+            Ok(image) => {
+                let image = self.downscaled(uri, image, size_hint, ctx.pixels_per_point());
+                Ok(ImagePoll::Ready { image })
             }
+            Err(err) => Err(LoadError::Loading(err)),
         }
     }
 
     fn forget(&self, uri: &str) {
         let _ = self.cache.lock().remove(uri);
+        let _ = self.downscaled.lock().remove(uri);
     }
 
     fn forget_all(&self) {
         self.cache.lock().clear();
+        self.downscaled.lock().clear();
     }
 
     fn byte_size(&self) -> usize {
-        self.cache
+        let original_size: usize = self
+            .cache
             .lock()
             .values()
             .map(|result| match result {
                 Ok(image) => image.pixels.len() * size_of::<egui::Color32>(),
                 Err(err) => err.len(),
             })
-            .sum()
+            .sum();
+        let downscaled_size: usize = self
+            .downscaled
+            .lock()
+            .values()
+            .flatten()
+            .map(|image| image.pixels.len() * size_of::<egui::Color32>())
+            .sum();
+        original_size + downscaled_size
     }
 }
 
@@ -112,4 +197,64 @@ mod tests {
         assert!(is_supported_uri("file://test"));
         assert!(!is_supported_uri("test.svg"));
     }
+
+    #[test]
+    fn check_downscaled_size() {
+        use egui::emath::OrderedFloat;
+
+        let image = ColorImage::new([400, 200], egui::Color32::WHITE);
+        assert_eq!(
+            downscaled_size(&image, SizeHint::Width(100), 1.0),
+            Some([100, 50])
+        );
+        assert_eq!(
+            downscaled_size(&image, SizeHint::Width(100), 2.0),
+            Some([200, 100])
+        );
+        assert_eq!(
+            downscaled_size(&image, SizeHint::Height(20), 1.0),
+            Some([50, 25]),
+            "Only halved, and never smaller than asked for"
+        );
+        assert_eq!(
+            downscaled_size(&image, SizeHint::Width(199), 1.0),
+            Some([200, 100])
+        );
+        assert_eq!(downscaled_size(&image, SizeHint::Width(201), 1.0), None);
+        assert_eq!(
+            downscaled_size(&image, SizeHint::Size(100, 100), 1.0),
+            Some([100, 50])
+        );
+        let half = SizeHint::Scale(OrderedFloat(0.5));
+        assert_eq!(downscaled_size(&image, half, 1.0), Some([200, 100]));
+
+        // Never enlarge:
+        assert_eq!(downscaled_size(&image, SizeHint::Width(1000), 1.0), None);
+        assert_eq!(downscaled_size(&image, SizeHint::default(), 1.0), None);
+        assert_eq!(downscaled_size(&image, half, 2.0), None);
+    }
+
+    #[test]
+    fn check_downscaled_versions_are_bounded() {
+        let loader = ImageCrateLoader::default();
+        let image = Arc::new(ColorImage::new([400, 200], egui::Color32::WHITE));
+        let half = loader.downscaled("image", image.clone(), SizeHint::Width(120), 1.0);
+        let same = loader.downscaled("image", image.clone(), SizeHint::Width(150), 1.0);
+        assert!(Arc::ptr_eq(&half, &same), "Similar sizes share the version");
+
+        let small = loader.downscaled("image", image.clone(), SizeHint::Width(100), 1.0);
+
+        for width in 1..200 {
+            loader.downscaled("image", image.clone(), SizeHint::Width(width), 1.0);
+        }
+        assert_eq!(
+            loader.downscaled.lock()["image"].len(),
+            MAX_DOWNSCALED_PER_URI
+        );
+
+        let again = loader.downscaled("image", image.clone(), SizeHint::Width(100), 1.0);
+        assert_eq!(again.size, small.size);
+        let reused = loader.downscaled("image", image, SizeHint::Width(100), 1.0);
+        assert!(Arc::ptr_eq(&again, &reused));
+    }
 }
@@ -365,6 +365,11 @@ impl Highlighter {
                 let fg = style.foreground;
                 let text_color = egui::Color32::from_rgb(fg.r, fg.g, fg.b);
                 let italics = style.font_style.contains(FontStyle::ITALIC);
+                let weight = if style.font_style.contains(FontStyle::BOLD) {
+                    egui::FontWeight::BOLD
+                } else {
+                    egui::FontWeight::REGULAR
+                };
                 let underline = style.font_style.contains(FontStyle::ITALIC);
                 let underline = if underline {
                     egui::Stroke::new(1.0, text_color)
@@ -377,6 +382,7 @@ impl Highlighter {
                     format: TextFormat {
                         font_id: egui::FontId::monospace(12.0),
                         color: text_color,
+                        weight,
                         italics,
                         underline,
                         ..Default::default()
@@ -1,7 +1,10 @@
 #![allow(clippy::collapsible_else_if)]
 #![allow(unsafe_code)]
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use egui::{
     emath::Rect,
@@ -34,6 +37,21 @@ impl TextureFilterExt for egui::TextureFilter {
     }
 }
 
+/// The `GL_TEXTURE_MIN_FILTER` for the given minification and mipmap filters.
+fn min_filter_glow_code(
+    minification: egui::TextureFilter,
+    mipmap_mode: Option<egui::TextureFilter>,
+) -> u32 {
+    use egui::TextureFilter::{Linear, Nearest};
+    match (minification, mipmap_mode) {
+        (minification, None) => minification.glow_code(),
+        (Nearest, Some(Nearest)) => glow::NEAREST_MIPMAP_NEAREST,
+        (Nearest, Some(Linear)) => glow::NEAREST_MIPMAP_LINEAR,
+        (Linear, Some(Nearest)) => glow::LINEAR_MIPMAP_NEAREST,
+        (Linear, Some(Linear)) => glow::LINEAR_MIPMAP_LINEAR,
+    }
+}
+
 trait TextureWrapModeExt {
     fn glow_code(&self) -> u32;
 }
@@ -83,24 +101,54 @@ pub struct Painter {
     program: glow::Program,
     u_screen_size: glow::UniformLocation,
     u_sampler: glow::UniformLocation,
+    u_sdf: glow::UniformLocation,
     is_webgl_1: bool,
     vao: crate::vao::VertexArrayObject,
     srgb_textures: bool,
     supports_srgb_framebuffer: bool,
+
+    /// The maximum anisotropy, if `EXT_texture_filter_anisotropic` is supported.
+    max_anisotropy: Option<f32>,
+
     vbo: glow::Buffer,
     element_array_buffer: glow::Buffer,
 
     textures: HashMap<egui::TextureId, glow::Texture>,
 
+    /// The textures with [`egui::TextureOptions::signed_distance_field`].
+    sdf_textures: HashSet<egui::TextureId>,
+
+    /// The mip chains of the textures whose mipmaps we make on the CPU, see [`Self::upload_mipmaps`].
+    mip_levels: HashMap<egui::TextureId, Vec<egui::ColorImage>>,
+
     next_native_tex_id: u64,
 
     /// Stores outdated OpenGL textures that are yet to be deleted
     textures_to_destroy: Vec<glow::Texture>,
 
+    /// Whether we can use [`Self::paint_damaged_and_update_textures`] (needs `glBlitFramebuffer`).
+    supports_blit: bool,
+
+    /// The offscreen copy of the last frame used by [`Self::paint_damaged_and_update_textures`].
+    damage_target: Option<DamageTarget>,
+
+    /// See [`Self::intermediate_fbo`].
+    intermediate_fbo: Option<glow::Framebuffer>,
+
     /// Used to make sure we are destroyed correctly.
     destroyed: bool,
 }
 
+/// A framebuffer that keeps its contents between frames, so we only need to repaint what changed.
+struct DamageTarget {
+    fbo: glow::Framebuffer,
+    texture: glow::Texture,
+    size_px: [u32; 2],
+
+    /// Does it contain the last painted frame?
+    is_valid: bool,
+}
+
 /// A callback function that can be used to compose an [`egui::PaintCallback`] for custom rendering
 /// with [`glow`].
 ///
@@ -181,6 +229,17 @@ impl Painter {
             });
         log::debug!("SRGB framebuffer Support: {:?}", supports_srgb_framebuffer);
 
+        let max_anisotropy = supported_extensions
+            .iter()
+            .any(|extension| {
+                // GL_EXT_texture_filter_anisotropic, EXT_texture_filter_anisotropic, GL_ARB_texture_filter_anisotropic
+                extension.ends_with("texture_filter_anisotropic")
+            })
+            .then(|| unsafe { gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT) });
+        log::debug!("Max anisotropy: {:?}", max_anisotropy);
+
+        let supports_blit = !is_webgl_1 && (gl.version().major >= 3 || gl.version().is_embedded);
+
         unsafe {
             let vert = compile_shader(
                 &gl,
@@ -197,10 +256,11 @@ impl Painter {
                 &gl,
                 glow::FRAGMENT_SHADER,
                 &format!(
-                    "{}\n#define NEW_SHADER_INTERFACE {}\n#define SRGB_TEXTURES {}\n{}\n{}",
+                    "{}\n#define NEW_SHADER_INTERFACE {}\n#define SRGB_TEXTURES {}\n#define HAS_DERIVATIVES {}\n{}\n{}",
                     shader_version_declaration,
                     shader_version.is_new_shader_interface() as i32,
                     srgb_textures as i32,
+                    !is_webgl_1 as i32, // `fwidth` needs an extension in WebGL1
                     shader_prefix,
                     FRAG_SRC
                 ),
@@ -212,6 +272,7 @@ impl Painter {
             gl.delete_shader(frag);
             let u_screen_size = gl.get_uniform_location(program, "u_screen_size").unwrap();
             let u_sampler = gl.get_uniform_location(program, "u_sampler").unwrap();
+            let u_sdf = gl.get_uniform_location(program, "u_sdf").unwrap();
 
             let vbo = gl.create_buffer()?;
 
@@ -258,15 +319,22 @@ impl Painter {
                 program,
                 u_screen_size,
                 u_sampler,
+                u_sdf,
                 is_webgl_1,
                 vao,
                 srgb_textures,
                 supports_srgb_framebuffer,
+                max_anisotropy,
                 vbo,
                 element_array_buffer,
                 textures: Default::default(),
+                sdf_textures: Default::default(),
+                mip_levels: Default::default(),
                 next_native_tex_id: 1 << 32,
                 textures_to_destroy: Vec::new(),
+                supports_blit,
+                damage_target: None,
+                intermediate_fbo: None,
                 destroyed: false,
             })
         }
@@ -290,11 +358,9 @@ impl Painter {
     /// So if in a [`egui::Shape::Callback`] you need to use an offscreen FBO, you should
     /// then restore to this afterwards with
     /// `gl.bind_framebuffer(glow::FRAMEBUFFER, painter.intermediate_fbo());`
-    #[allow(clippy::unused_self)]
     pub fn intermediate_fbo(&self) -> Option<glow::Framebuffer> {
-        // We don't currently ever render to an offscreen buffer,
-        // but we may want to start to in order to do anti-aliasing on web, for instance.
-        None
+        // Only set while painting with `paint_damaged_and_update_textures`.
+        self.intermediate_fbo
     }
 
     unsafe fn prepare_painting(
@@ -352,6 +418,39 @@ impl Painter {
         clear(&self.gl, screen_size_in_pixels, clear_color);
     }
 
+    /// Clear the given regions (in points) of the screen, leaving the rest untouched.
+    ///
+    /// See [`Self::paint_primitives_in_regions`].
+    pub fn clear_regions(
+        &self,
+        screen_size_in_pixels: [u32; 2],
+        pixels_per_point: f32,
+        clear_color: [f32; 4],
+        regions: &[Rect],
+    ) {
+        crate::profile_function!();
+        unsafe {
+            self.gl.enable(glow::SCISSOR_TEST);
+            self.gl.viewport(
+                0,
+                0,
+                screen_size_in_pixels[0] as i32,
+                screen_size_in_pixels[1] as i32,
+            );
+            self.gl.clear_color(
+                clear_color[0],
+                clear_color[1],
+                clear_color[2],
+                clear_color[3],
+            );
+            for region in regions {
+                set_clip_rect(&self.gl, screen_size_in_pixels, pixels_per_point, *region);
+                self.gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+            self.gl.disable(glow::SCISSOR_TEST);
+        }
+    }
+
     /// You are expected to have cleared the color buffer before calling this.
     pub fn paint_and_update_textures(
         &mut self,
@@ -373,6 +472,171 @@ impl Painter {
         }
     }
 
+    /// Like [`Self::paint_and_update_textures`], but only repaints the `damage` regions (in points).
+    ///
+    /// The frame is painted into an offscreen framebuffer that keeps the previous frame,
+    /// and is then copied onto the screen, covering anything painted there before.
+    /// So unlike [`Self::paint_primitives_in_regions`], this doesn't need the screen contents to be preserved,
+    /// but it also can't be combined with custom GL painting under egui (e.g. between clearing the screen and painting egui).
+    ///
+    /// Use this together with [`egui::FullOutput::damage`],
+    /// and [`Self::paint_and_update_textures`] when there is no damage to go by.
+    /// If the context can't blit framebuffers (e.g. on WebGL 1), everything is repainted directly onto the screen.
+    ///
+    /// This clears the screen to `clear_color` for you.
+    pub fn paint_damaged_and_update_textures(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clear_color: [f32; 4],
+        clipped_primitives: &[egui::ClippedPrimitive],
+        textures_delta: &egui::TexturesDelta,
+        damage: &[Rect],
+    ) {
+        crate::profile_function!();
+
+        if !self.supports_blit {
+            self.clear(screen_size_px, clear_color);
+            self.paint_and_update_textures(
+                screen_size_px,
+                pixels_per_point,
+                clipped_primitives,
+                textures_delta,
+            );
+            return;
+        }
+
+        let (fbo, is_valid) = match self.damage_target(screen_size_px) {
+            Ok(target) => (target.fbo, target.is_valid),
+            Err(err) => {
+                log::warn!("Failed to create damage framebuffer: {err}");
+                self.supports_blit = false;
+                self.clear(screen_size_px, clear_color);
+                self.paint_and_update_textures(
+                    screen_size_px,
+                    pixels_per_point,
+                    clipped_primitives,
+                    textures_delta,
+                );
+                return;
+            }
+        };
+
+        let whole_screen;
+        let regions = if is_valid {
+            damage
+        } else {
+            whole_screen = [Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(screen_size_px[0] as f32, screen_size_px[1] as f32) / pixels_per_point,
+            )];
+            &whole_screen[..]
+        };
+
+        unsafe { self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo)) };
+        self.intermediate_fbo = Some(fbo);
+
+        for (id, image_delta) in &textures_delta.set {
+            self.set_texture(*id, image_delta);
+        }
+        self.clear_regions(screen_size_px, pixels_per_point, clear_color, regions);
+        self.paint_primitives_in_regions(
+            screen_size_px,
+            pixels_per_point,
+            clipped_primitives,
+            regions,
+        );
+        for &id in &textures_delta.free {
+            self.free_texture(id);
+        }
+
+        self.intermediate_fbo = None;
+
+        unsafe {
+            let [width, height] = screen_size_px.map(|side| side as i32);
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(fbo));
+            self.gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+            self.gl.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            check_for_gl_error!(&self.gl, "blit_framebuffer");
+        }
+
+        if let Some(target) = &mut self.damage_target {
+            target.is_valid = true;
+        }
+    }
+
+    /// The offscreen framebuffer for [`Self::paint_damaged_and_update_textures`], (re)created if needed.
+    fn damage_target(&mut self, size_px: [u32; 2]) -> Result<&mut DamageTarget, String> {
+        if let Some(target) = self.damage_target.take() {
+            if target.size_px == size_px {
+                return Ok(self.damage_target.insert(target));
+            }
+            unsafe {
+                self.gl.delete_framebuffer(target.fbo);
+                self.gl.delete_texture(target.texture);
+            }
+        }
+
+        crate::profile_function!();
+        unsafe {
+            let texture = self.gl.create_texture()?;
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            for filter in [glow::TEXTURE_MIN_FILTER, glow::TEXTURE_MAG_FILTER] {
+                self.gl
+                    .tex_parameter_i32(glow::TEXTURE_2D, filter, glow::NEAREST as i32);
+            }
+            self.gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                size_px[0] as i32,
+                size_px[1] as i32,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                None,
+            );
+
+            let fbo = self.gl.create_framebuffer()?;
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            self.gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(texture),
+                0,
+            );
+            let status = self.gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            check_for_gl_error!(&self.gl, "damage_target");
+
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                self.gl.delete_framebuffer(fbo);
+                self.gl.delete_texture(texture);
+                return Err(format!("framebuffer status {status:#x}"));
+            }
+
+            Ok(self.damage_target.insert(DamageTarget {
+                fbo,
+                texture,
+                size_px,
+                is_valid: false,
+            }))
+        }
+    }
+
     /// Main entry-point for painting a frame.
     ///
     /// You should call `target.clear_color(..)` before
@@ -402,6 +666,10 @@ impl Painter {
         crate::profile_function!();
         self.assert_not_destroyed();
 
+        if let Some(target) = &mut self.damage_target {
+            target.is_valid = false; // it won't contain this frame
+        }
+
         unsafe { self.prepare_painting(screen_size_px, pixels_per_point) };
 
         for egui::ClippedPrimitive {
@@ -409,48 +677,106 @@ impl Painter {
             primitive,
         } in clipped_primitives
         {
-            set_clip_rect(&self.gl, screen_size_px, pixels_per_point, *clip_rect);
+            self.paint_primitive(screen_size_px, pixels_per_point, *clip_rect, primitive);
+        }
+
+        unsafe { self.finish_painting() };
+    }
+
+    /// Like [`Self::paint_primitives`], but only touches the pixels inside the given regions (in points).
+    ///
+    /// Everything outside of the regions is left as it is, so this is only useful
+    /// if the framebuffer still contains the previous frame,
+    /// e.g. because you are painting to your own framebuffer object,
+    /// or because you have configured your surface to preserve the back buffer when swapping.
+    ///
+    /// Use this together with [`egui::FullOutput::damage`] to only repaint what changed.
+    /// Pass all the primitives of the frame: only those overlapping the regions will be painted.
+    ///
+    /// The regions must not overlap each other, or translucent shapes will be blended twice.
+    /// [`egui::FullOutput::damage`] never overlaps.
+    ///
+    /// You are expected to have cleared the regions (e.g. with [`Self::clear_regions`]) before calling this.
+    pub fn paint_primitives_in_regions(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clipped_primitives: &[egui::ClippedPrimitive],
+        regions: &[Rect],
+    ) {
+        crate::profile_function!();
+        self.assert_not_destroyed();
+
+        unsafe { self.prepare_painting(screen_size_px, pixels_per_point) };
 
-            match primitive {
-                Primitive::Mesh(mesh) => {
-                    self.paint_mesh(mesh);
+        for egui::ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in clipped_primitives
+        {
+            for region in regions {
+                let clip_rect = clip_rect.intersect(*region);
+                if clip_rect.is_positive() {
+                    self.paint_primitive(screen_size_px, pixels_per_point, clip_rect, primitive);
                 }
-                Primitive::Callback(callback) => {
-                    if callback.rect.is_positive() {
-                        crate::profile_scope!("callback");
-
-                        let info = egui::PaintCallbackInfo {
-                            viewport: callback.rect,
-                            clip_rect: *clip_rect,
-                            pixels_per_point,
-                            screen_size_px,
-                        };
-
-                        let viewport_px = info.viewport_in_pixels();
-                        unsafe {
-                            self.gl.viewport(
-                                viewport_px.left_px,
-                                viewport_px.from_bottom_px,
-                                viewport_px.width_px,
-                                viewport_px.height_px,
-                            );
-                        }
-
-                        if let Some(callback) = callback.callback.downcast_ref::<CallbackFn>() {
-                            (callback.f)(info, self);
-                        } else {
-                            log::warn!("Warning: Unsupported render callback. Expected egui_glow::CallbackFn");
-                        }
-
-                        check_for_gl_error!(&self.gl, "callback");
-
-                        // Restore state:
-                        unsafe { self.prepare_painting(screen_size_px, pixels_per_point) };
+            }
+        }
+
+        unsafe { self.finish_painting() };
+    }
+
+    fn paint_primitive(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clip_rect: Rect,
+        primitive: &Primitive,
+    ) {
+        set_clip_rect(&self.gl, screen_size_px, pixels_per_point, clip_rect);
+
+        match primitive {
+            Primitive::Mesh(mesh) => {
+                self.paint_mesh(mesh);
+            }
+            Primitive::Callback(callback) => {
+                if callback.rect.is_positive() {
+                    crate::profile_scope!("callback");
+
+                    let info = egui::PaintCallbackInfo {
+                        viewport: callback.rect,
+                        clip_rect,
+                        pixels_per_point,
+                        screen_size_px,
+                    };
+
+                    let viewport_px = info.viewport_in_pixels();
+                    unsafe {
+                        self.gl.viewport(
+                            viewport_px.left_px,
+                            viewport_px.from_bottom_px,
+                            viewport_px.width_px,
+                            viewport_px.height_px,
+                        );
+                    }
+
+                    if let Some(callback) = callback.callback.downcast_ref::<CallbackFn>() {
+                        (callback.f)(info, self);
+                    } else {
+                        log::warn!(
+                            "Warning: Unsupported render callback. Expected egui_glow::CallbackFn"
+                        );
                     }
+
+                    check_for_gl_error!(&self.gl, "callback");
+
+                    // Restore state:
+                    unsafe { self.prepare_painting(screen_size_px, pixels_per_point) };
                 }
             }
         }
+    }
 
+    unsafe fn finish_painting(&mut self) {
         unsafe {
             self.vao.unbind(&self.gl);
             self.gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
@@ -482,6 +808,10 @@ impl Painter {
                 );
 
                 self.gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                self.gl.uniform_1_i32(
+                    Some(&self.u_sdf),
+                    self.sdf_textures.contains(&mesh.texture_id) as i32,
+                );
             }
 
             unsafe {
@@ -514,6 +844,14 @@ impl Painter {
             self.gl.bind_texture(glow::TEXTURE_2D, Some(glow_texture));
         }
 
+        if delta.is_whole() {
+            if delta.options.signed_distance_field {
+                self.sdf_textures.insert(tex_id);
+            } else {
+                self.sdf_textures.remove(&tex_id);
+            }
+        }
+
         match &delta.image {
             egui::ImageData::Color(image) => {
                 assert_eq!(
@@ -524,7 +862,7 @@ impl Painter {
 
                 let data: &[u8] = bytemuck::cast_slice(image.pixels.as_ref());
 
-                self.upload_texture_srgb(delta.pos, image.size, delta.options, data);
+                self.upload_texture_srgb(tex_id, delta.pos, image.size, delta.options, data);
             }
             egui::ImageData::Font(image) => {
                 assert_eq!(
@@ -541,13 +879,14 @@ impl Painter {
                         .collect()
                 };
 
-                self.upload_texture_srgb(delta.pos, image.size, delta.options, &data);
+                self.upload_texture_srgb(tex_id, delta.pos, image.size, delta.options, &data);
             }
         };
     }
 
     fn upload_texture_srgb(
         &mut self,
+        tex_id: egui::TextureId,
         pos: Option<[usize; 2]>,
         [w, h]: [usize; 2],
         options: egui::TextureOptions,
@@ -569,11 +908,25 @@ impl Painter {
                 glow::TEXTURE_MAG_FILTER,
                 options.magnification.glow_code() as i32,
             );
+            // WebGL1 can only generate mipmaps for power-of-two textures, and not at all for sRGB ones.
+            let mipmap_mode = options.mipmap_mode.filter(|_| !self.is_webgl_1);
             self.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_MIN_FILTER,
-                options.minification.glow_code() as i32,
+                min_filter_glow_code(options.minification, mipmap_mode) as i32,
             );
+            if let Some(max_anisotropy) = self.max_anisotropy {
+                let anisotropy = if mipmap_mode.is_some() {
+                    options.effective_anisotropy() as f32
+                } else {
+                    1.0
+                };
+                self.gl.tex_parameter_f32(
+                    glow::TEXTURE_2D,
+                    glow::TEXTURE_MAX_ANISOTROPY_EXT,
+                    anisotropy.min(max_anisotropy),
+                );
+            }
 
             self.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
@@ -633,6 +986,92 @@ impl Painter {
                 );
                 check_for_gl_error!(&self.gl, "tex_image_2d");
             }
+
+            if mipmap_mode.is_none() {
+                self.mip_levels.remove(&tex_id);
+            } else if self.srgb_textures {
+                crate::profile_scope!("gl.generate_mipmap");
+                self.gl.generate_mipmap(glow::TEXTURE_2D);
+                check_for_gl_error!(&self.gl, "generate_mipmap");
+            } else {
+                // `glGenerateMipmap` would average the gamma-encoded texels,
+                // so we shrink them in linear space on the CPU, like egui-wgpu does:
+                self.upload_mipmaps(tex_id, pos, [w, h], data, internal_format, src_format);
+            }
+        }
+    }
+
+    /// Make the mipmaps of the bound texture on the CPU, and upload them.
+    ///
+    /// For partial updates, only the parts of the mipmaps covered by the update are uploaded.
+    fn upload_mipmaps(
+        &mut self,
+        tex_id: egui::TextureId,
+        pos: Option<[usize; 2]>,
+        size: [usize; 2],
+        data: &[u8],
+        internal_format: u32,
+        src_format: u32,
+    ) {
+        crate::profile_function!();
+
+        let image = egui::ColorImage {
+            size,
+            pixels: bytemuck::cast_slice(data).to_vec(),
+        };
+
+        let Some([x, y]) = pos else {
+            let mip_levels = image.mipmaps();
+            for (level, image) in (1..).zip(&mip_levels[1..]) {
+                let border = 0;
+                unsafe {
+                    self.gl.tex_image_2d(
+                        glow::TEXTURE_2D,
+                        level,
+                        internal_format as _,
+                        image.width() as _,
+                        image.height() as _,
+                        border,
+                        src_format,
+                        glow::UNSIGNED_BYTE,
+                        Some(bytemuck::cast_slice(&image.pixels)),
+                    );
+                }
+                check_for_gl_error!(&self.gl, "tex_image_2d");
+            }
+            self.mip_levels.insert(tex_id, mip_levels);
+            return;
+        };
+
+        let Some(mip_levels) = self.mip_levels.get_mut(&tex_id) else {
+            return;
+        };
+        let blit_pos = [x as isize, y as isize];
+        mip_levels[0].blit(&image, blit_pos, egui::epaint::BlendMode::Replace);
+        let changed = egui::ColorImage::update_mipmaps(mip_levels, [x, y], size);
+        for (level, (image, ([x, y], [w, h]))) in (1..).zip(mip_levels[1..].iter().zip(changed)) {
+            if w == 0 || h == 0 {
+                break;
+            }
+            let region = egui::Rect::from_min_size(
+                egui::pos2(x as f32, y as f32),
+                egui::vec2(w as f32, h as f32),
+            );
+            let pixels = image.region(&region, None).pixels;
+            unsafe {
+                self.gl.tex_sub_image_2d(
+                    glow::TEXTURE_2D,
+                    level,
+                    x as _,
+                    y as _,
+                    w as _,
+                    h as _,
+                    src_format,
+                    glow::UNSIGNED_BYTE,
+                    glow::PixelUnpackData::Slice(bytemuck::cast_slice(&pixels)),
+                );
+            }
+            check_for_gl_error!(&self.gl, "tex_sub_image_2d");
         }
     }
 
@@ -640,6 +1079,8 @@ impl Painter {
         if let Some(old_tex) = self.textures.remove(&tex_id) {
             unsafe { self.gl.delete_texture(old_tex) };
         }
+        self.sdf_textures.remove(&tex_id);
+        self.mip_levels.remove(&tex_id);
     }
 
     /// Get the [`glow::Texture`] bound to a [`egui::TextureId`].
@@ -717,6 +1158,10 @@ impl Painter {
             for t in &self.textures_to_destroy {
                 self.gl.delete_texture(*t);
             }
+            if let Some(target) = &self.damage_target {
+                self.gl.delete_framebuffer(target.fbo);
+                self.gl.delete_texture(target.texture);
+            }
         }
     }
 
@@ -4,6 +4,9 @@
 
 uniform sampler2D u_sampler;
 
+// Is the alpha of the texture a signed distance field?
+uniform bool u_sdf;
+
 #if NEW_SHADER_INTERFACE
     in vec4 v_rgba_in_gamma;
     in vec2 v_tc;
@@ -30,6 +33,18 @@ vec4 srgba_gamma_from_linear(vec4 rgba) {
 }
 
 void main() {
+    if (u_sdf) {
+        // 0.5 is the edge of the glyph. This gives a sharp, anti-aliased edge at any scale.
+        float dist = texture2D(u_sampler, v_tc).a;
+#if HAS_DERIVATIVES
+        float width = max(0.7 * fwidth(dist), 0.0001);
+#else
+        float width = 0.05;
+#endif
+        gl_FragColor = v_rgba_in_gamma * smoothstep(0.5 - width, 0.5 + width, dist);
+        return;
+    }
+
 #if SRGB_TEXTURES
     vec4 texture_in_gamma = srgba_gamma_from_linear(texture2D(u_sampler, v_tc));
 #else
@@ -0,0 +1,719 @@
+use std::sync::Arc;
+
+use emath::*;
+
+use crate::{
+    Color32, CubicBezierShape, FillRule, Gradient, GradientShape, PathStroke, QuadraticBezierShape,
+    Shape,
+};
+
+/// A command of a [`BezPathShape`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum PathCommand {
+    /// Start a new subpath at this point.
+    MoveTo(Pos2),
+
+    /// A straight line to this point.
+    ///
+    /// If there is no current subpath, this starts one at the point instead (like [`Self::MoveTo`]).
+    /// The same is true for the curves.
+    LineTo(Pos2),
+
+    /// A quadratic Bézier curve, with a control point and an end point.
+    QuadTo(Pos2, Pos2),
+
+    /// A cubic Bézier curve, with two control points and an end point.
+    CubicTo(Pos2, Pos2, Pos2),
+
+    /// Close the current subpath with a line back to where it started.
+    Close,
+}
+
+impl PathCommand {
+    /// Where the pen is after this command, if it moves it.
+    pub(crate) fn end_point(&self) -> Option<Pos2> {
+        match *self {
+            Self::MoveTo(p) | Self::LineTo(p) | Self::QuadTo(_, p) | Self::CubicTo(_, _, p) => {
+                Some(p)
+            }
+            Self::Close => None,
+        }
+    }
+}
+
+/// A path made of straight lines and curves, with any number of subpaths,
+/// like a `<path>` in SVG.
+///
+/// The curves are flattened when tessellating, using [`crate::TessellationOptions::bezier_tolerance`].
+///
+/// ```
+/// # use epaint::{pos2, vec2, BezPathShape, Color32, Stroke};
+/// let mut path = BezPathShape::new(Color32::RED, Stroke::NONE);
+/// path.move_to(pos2(0.0, 0.0))
+///     .line_to(pos2(10.0, 0.0))
+///     .arc_to(vec2(5.0, 5.0), 0.0, false, true, pos2(10.0, 10.0))
+///     .close();
+///
+/// let same = BezPathShape::from_svg("M0 0 H10 A5 5 0 0 1 10 10 Z", Color32::RED, Stroke::NONE);
+/// assert_eq!(same, Ok(path));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct BezPathShape {
+    pub commands: Vec<PathCommand>,
+
+    /// Fill for all subpaths (open ones are filled as if they were closed).
+    pub fill: Color32,
+
+    /// Stroke for all subpaths.
+    pub stroke: PathStroke,
+
+    /// Which areas of the path are inside, and should be filled.
+    ///
+    /// The default is [`FillRule::NonZero`], same as in SVG.
+    /// With [`FillRule::Convex`] each subpath is filled on its own.
+    pub fill_rule: FillRule,
+}
+
+impl BezPathShape {
+    /// An empty path. Use [`Self::move_to`] and friends to add to it.
+    pub fn new(fill: impl Into<Color32>, stroke: impl Into<PathStroke>) -> Self {
+        Self {
+            commands: Vec::new(),
+            fill: fill.into(),
+            stroke: stroke.into(),
+            fill_rule: FillRule::NonZero,
+        }
+    }
+
+    /// Parse SVG path data, i.e. the `d` attribute of a `<path>`, like `"M 0 0 L 10 0 Z"`.
+    ///
+    /// All commands are supported.
+    ///
+    /// # Errors
+    /// If the path data is malformed.
+    pub fn from_svg(
+        path_data: &str,
+        fill: impl Into<Color32>,
+        stroke: impl Into<PathStroke>,
+    ) -> Result<Self, ParseSvgPathError> {
+        let mut path = Self::new(fill, stroke);
+        parse_svg_path(path_data, &mut path)?;
+        Ok(path)
+    }
+
+    /// Fill with a gradient instead of [`Self::fill`], see [`GradientShape`].
+    #[inline]
+    pub fn with_fill_gradient(self, gradient: impl Into<Arc<Gradient>>) -> GradientShape {
+        GradientShape::new(self, gradient)
+    }
+
+    /// See [`Self::fill_rule`].
+    #[inline]
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Start a new subpath.
+    pub fn move_to(&mut self, point: Pos2) -> &mut Self {
+        self.commands.push(PathCommand::MoveTo(point));
+        self
+    }
+
+    /// Add a straight line.
+    pub fn line_to(&mut self, point: Pos2) -> &mut Self {
+        self.commands.push(PathCommand::LineTo(point));
+        self
+    }
+
+    /// Add a quadratic Bézier curve.
+    pub fn quad_to(&mut self, control: Pos2, point: Pos2) -> &mut Self {
+        self.commands.push(PathCommand::QuadTo(control, point));
+        self
+    }
+
+    /// Add a cubic Bézier curve.
+    pub fn cubic_to(&mut self, control1: Pos2, control2: Pos2, point: Pos2) -> &mut Self {
+        self.commands
+            .push(PathCommand::CubicTo(control1, control2, point));
+        self
+    }
+
+    /// Add an elliptical arc, the same way as the `A` command of SVG paths.
+    ///
+    /// * `radii`: the radii of the ellipse, which are scaled up if they are too small to reach `point`.
+    /// * `x_rotation`: rotation of the ellipse, in radians.
+    /// * `large_arc`: take the longer way around the ellipse.
+    /// * `sweep`: go clockwise (on screen, where y is down).
+    ///
+    /// The arc is added as cubic Bézier curves.
+    pub fn arc_to(
+        &mut self,
+        radii: Vec2,
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        point: Pos2,
+    ) -> &mut Self {
+        let Some(from) = self.current_point() else {
+            return self.line_to(point);
+        };
+        if from == point {
+            return self;
+        }
+        let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+        if rx == 0.0 || ry == 0.0 {
+            return self.line_to(point);
+        }
+
+        // See https://www.w3.org/TR/SVG11/implnote.html#ArcConversionEndpointToCenter
+        let (sin, cos) = x_rotation.sin_cos();
+        let rotate = |v: Vec2| vec2(cos * v.x - sin * v.y, sin * v.x + cos * v.y);
+        let half = (from - point) / 2.0;
+        let p = vec2(cos * half.x + sin * half.y, -sin * half.x + cos * half.y);
+
+        let lambda = (p.x / rx).powi(2) + (p.y / ry).powi(2);
+        if 1.0 < lambda {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let (rx2, ry2) = (rx * rx, ry * ry);
+        let numerator = rx2 * ry2 - rx2 * p.y * p.y - ry2 * p.x * p.x;
+        let denominator = rx2 * p.y * p.y + ry2 * p.x * p.x;
+        let mut factor = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            factor = -factor;
+        }
+        let center_rotated = factor * vec2(rx * p.y / ry, -ry * p.x / rx);
+        let center = from.lerp(point, 0.5) + rotate(center_rotated);
+
+        let start_angle =
+            vec2((p.x - center_rotated.x) / rx, (p.y - center_rotated.y) / ry).angle();
+        let end_angle = vec2(
+            (-p.x - center_rotated.x) / rx,
+            (-p.y - center_rotated.y) / ry,
+        )
+        .angle();
+        let mut sweep_angle = (end_angle - start_angle).rem_euclid(std::f32::consts::TAU);
+        if !sweep {
+            sweep_angle -= std::f32::consts::TAU;
+        }
+
+        // Each piece of at most a quarter of a turn is approximated with a cubic Bézier curve:
+        let num_pieces = (sweep_angle.abs() / std::f32::consts::FRAC_PI_2)
+            .ceil()
+            .max(1.0) as usize;
+        let step = sweep_angle / num_pieces as f32;
+        let handle_length = 4.0 / 3.0 * (step / 4.0).tan();
+        let on_ellipse = |angle: f32| center + rotate(vec2(rx * angle.cos(), ry * angle.sin()));
+        let tangent = |angle: f32| rotate(vec2(-rx * angle.sin(), ry * angle.cos()));
+
+        for i in 0..num_pieces {
+            let angle0 = start_angle + i as f32 * step;
+            let angle1 = angle0 + step;
+            let end = if i + 1 == num_pieces {
+                point
+            } else {
+                on_ellipse(angle1)
+            };
+            self.cubic_to(
+                on_ellipse(angle0) + handle_length * tangent(angle0),
+                on_ellipse(angle1) - handle_length * tangent(angle1),
+                end,
+            );
+        }
+        self
+    }
+
+    /// Close the current subpath.
+    pub fn close(&mut self) -> &mut Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    /// Where the next command starts, if anywhere.
+    pub fn current_point(&self) -> Option<Pos2> {
+        let last = self.commands.last()?;
+        if let Some(point) = last.end_point() {
+            return Some(point);
+        }
+
+        // After closing, we are back at the start of the subpath:
+        let mut start = None;
+        for command in self.commands.iter().rev().skip(1) {
+            match command {
+                PathCommand::MoveTo(point) => return Some(*point),
+                PathCommand::Close => break,
+                command => start = command.end_point().or(start),
+            }
+        }
+        start
+    }
+
+    /// Flatten the curves into lines, no further than `tolerance` from the curves.
+    ///
+    /// Returns the points of each subpath, and whether it is closed.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(Vec<Pos2>, bool)> {
+        fn finish(subpaths: &mut Vec<(Vec<Pos2>, bool)>, points: &mut Vec<Pos2>, closed: bool) {
+            if closed && 2 < points.len() && points.first() == points.last() {
+                points.pop();
+            }
+            if 2 <= points.len() {
+                subpaths.push((std::mem::take(points), closed));
+            }
+            points.clear();
+        }
+
+        let mut subpaths = vec![];
+        let mut points: Vec<Pos2> = vec![];
+        for command in &self.commands {
+            let start = points.last().copied();
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    finish(&mut subpaths, &mut points, false);
+                    points.push(point);
+                }
+                PathCommand::LineTo(point) => points.push(point),
+                PathCommand::QuadTo(control, point) => {
+                    if let Some(start) = start {
+                        let curve = QuadraticBezierShape::from_points_stroke(
+                            [start, control, point],
+                            false,
+                            Color32::TRANSPARENT,
+                            PathStroke::NONE,
+                        );
+                        curve.for_each_flattened_with_t(tolerance, &mut |p, _t| points.push(p));
+                    } else {
+                        points.push(point);
+                    }
+                }
+                PathCommand::CubicTo(control1, control2, point) => {
+                    if let Some(start) = start {
+                        let curve = CubicBezierShape::from_points_stroke(
+                            [start, control1, control2, point],
+                            false,
+                            Color32::TRANSPARENT,
+                            PathStroke::NONE,
+                        );
+                        curve.for_each_flattened_with_t(tolerance, &mut |p, _t| points.push(p));
+                    } else {
+                        points.push(point);
+                    }
+                }
+                PathCommand::Close => {
+                    let subpath_start = points.first().copied();
+                    finish(&mut subpaths, &mut points, true);
+                    // Anything drawn next starts where this subpath started:
+                    points.extend(subpath_start);
+                }
+            }
+        }
+        finish(&mut subpaths, &mut points, false);
+        subpaths
+    }
+
+    /// The visual bounding rectangle (includes stroke width)
+    pub fn visual_bounding_rect(&self) -> Rect {
+        if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
+            return Rect::NOTHING;
+        }
+
+        // The curves are always inside their control points:
+        let mut rect = Rect::NOTHING;
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(p) | PathCommand::LineTo(p) => rect.extend_with(p),
+                PathCommand::QuadTo(c, p) => {
+                    rect.extend_with(c);
+                    rect.extend_with(p);
+                }
+                PathCommand::CubicTo(c1, c2, p) => {
+                    rect.extend_with(c1);
+                    rect.extend_with(c2);
+                    rect.extend_with(p);
+                }
+                PathCommand::Close => {}
+            }
+        }
+        rect.expand(self.stroke.width / 2.0)
+    }
+
+    /// The bounding rectangle of the curves themselves, excluding the stroke.
+    ///
+    /// Unlike [`Self::visual_bounding_rect`] this does not include control points off the curves.
+    pub fn fill_bounding_rect(&self) -> Rect {
+        const TOLERANCE: f32 = 0.1;
+        let mut rect = Rect::NOTHING;
+        for (points, _closed) in self.flatten(TOLERANCE) {
+            rect = rect.union(Rect::from_points(&points));
+        }
+        rect
+    }
+
+    /// Move all points with the given transform.
+    pub(crate) fn transform_points(&mut self, transform: impl Fn(Pos2) -> Pos2) {
+        for command in &mut self.commands {
+            match command {
+                PathCommand::MoveTo(p) | PathCommand::LineTo(p) => *p = transform(*p),
+                PathCommand::QuadTo(c, p) => {
+                    *c = transform(*c);
+                    *p = transform(*p);
+                }
+                PathCommand::CubicTo(c1, c2, p) => {
+                    *c1 = transform(*c1);
+                    *c2 = transform(*c2);
+                    *p = transform(*p);
+                }
+                PathCommand::Close => {}
+            }
+        }
+    }
+}
+
+impl From<BezPathShape> for Shape {
+    #[inline(always)]
+    fn from(shape: BezPathShape) -> Self {
+        Self::BezPath(shape)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Error from [`BezPathShape::from_svg`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseSvgPathError {
+    /// The path data must start with a move-to command (`M` or `m`).
+    MissingMoveTo,
+
+    /// Unexpected character at the given byte offset.
+    UnexpectedChar { offset: usize, ch: char },
+
+    /// The command at the given byte offset needs more numbers (or flags).
+    MissingArguments { offset: usize, command: char },
+}
+
+impl std::fmt::Display for ParseSvgPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingMoveTo => write!(f, "SVG path data must start with a move-to command"),
+            Self::UnexpectedChar { offset, ch } => {
+                write!(
+                    f,
+                    "Unexpected character {ch:?} at offset {offset} of SVG path data"
+                )
+            }
+            Self::MissingArguments { offset, command } => write!(
+                f,
+                "Missing arguments for {command:?} command at offset {offset} of SVG path data"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseSvgPathError {}
+
+/// Reads numbers and flags from SVG path data.
+struct Parser<'a> {
+    data: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.data[self.offset..].chars().next()
+    }
+
+    /// Skip whitespace and (at most) one comma.
+    fn skip_separators(&mut self) {
+        let mut comma = false;
+        while let Some(c) = self.peek() {
+            if c == ',' && !comma {
+                comma = true;
+            } else if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.offset += c.len_utf8();
+        }
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let bytes = &self.data.as_bytes()[self.offset..];
+        let mut len = 0;
+        let digits = |mut len: usize| {
+            let start = len;
+            while bytes.get(len).map_or(false, u8::is_ascii_digit) {
+                len += 1;
+            }
+            (len, start < len)
+        };
+
+        if matches!(bytes.first(), Some(b'+' | b'-')) {
+            len += 1;
+        }
+        let (end, mut has_digits) = digits(len);
+        len = end;
+        if bytes.get(len) == Some(&b'.') {
+            let (end, has_fraction) = digits(len + 1);
+            len = end;
+            has_digits |= has_fraction;
+        }
+        if !has_digits {
+            return None;
+        }
+        if matches!(bytes.get(len), Some(b'e' | b'E')) {
+            let sign = usize::from(matches!(bytes.get(len + 1), Some(b'+' | b'-')));
+            let (end, has_exponent) = digits(len + 1 + sign);
+            if has_exponent {
+                len = end;
+            }
+        }
+
+        let number = self.data[self.offset..self.offset + len].parse().ok()?;
+        self.offset += len;
+        Some(number)
+    }
+
+    /// A flag of an arc, which doesn't need a separator after it.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.peek()? {
+            '0' => false,
+            '1' => true,
+            _ => return None,
+        };
+        self.offset += 1;
+        Some(flag)
+    }
+
+    fn point(&mut self) -> Option<Pos2> {
+        Some(pos2(self.number()?, self.number()?))
+    }
+}
+
+fn parse_svg_path(data: &str, path: &mut BezPathShape) -> Result<(), ParseSvgPathError> {
+    let mut parser = Parser { data, offset: 0 };
+    let mut command = None;
+    let mut current = Pos2::ZERO;
+    let mut subpath_start = Pos2::ZERO;
+
+    // For reflecting the control point of smooth curves:
+    let mut last_cubic_control = None;
+    let mut last_quad_control = None;
+
+    loop {
+        parser.skip_separators();
+        let Some(c) = parser.peek() else {
+            break;
+        };
+        let command_offset = parser.offset;
+
+        if c.is_ascii_alphabetic() {
+            parser.offset += 1;
+            command = Some(c);
+        } else {
+            // More arguments for the previous command, which repeats it:
+            command = match command {
+                Some('M') => Some('L'),
+                Some('m') => Some('l'),
+                Some('Z' | 'z') | None => {
+                    return Err(ParseSvgPathError::UnexpectedChar {
+                        offset: command_offset,
+                        ch: c,
+                    })
+                }
+                other => other,
+            };
+        }
+        let command = command.unwrap_or_default();
+
+        if path.commands.is_empty() && !matches!(command, 'M' | 'm') {
+            return Err(ParseSvgPathError::MissingMoveTo);
+        }
+
+        let missing = ParseSvgPathError::MissingArguments {
+            offset: command_offset,
+            command,
+        };
+        let origin = if command.is_ascii_lowercase() {
+            current.to_vec2()
+        } else {
+            Vec2::ZERO
+        };
+        let point = |parser: &mut Parser<'_>| {
+            parser
+                .point()
+                .map(|p| p + origin)
+                .ok_or_else(|| missing.clone())
+        };
+
+        let (mut cubic_control, mut quad_control) = (None, None);
+        match command.to_ascii_uppercase() {
+            'M' => {
+                current = point(&mut parser)?;
+                subpath_start = current;
+                path.move_to(current);
+            }
+            'L' => {
+                current = point(&mut parser)?;
+                path.line_to(current);
+            }
+            'H' => {
+                let x = parser.number().ok_or_else(|| missing.clone())?;
+                current.x = x + origin.x;
+                path.line_to(current);
+            }
+            'V' => {
+                let y = parser.number().ok_or_else(|| missing.clone())?;
+                current.y = y + origin.y;
+                path.line_to(current);
+            }
+            'C' | 'S' => {
+                let control1 = if command.to_ascii_uppercase() == 'C' {
+                    point(&mut parser)?
+                } else {
+                    last_cubic_control.map_or(current, |c: Pos2| current + (current - c))
+                };
+                let control2 = point(&mut parser)?;
+                current = point(&mut parser)?;
+                path.cubic_to(control1, control2, current);
+                cubic_control = Some(control2);
+            }
+            'Q' | 'T' => {
+                let control = if command.to_ascii_uppercase() == 'Q' {
+                    point(&mut parser)?
+                } else {
+                    last_quad_control.map_or(current, |c: Pos2| current + (current - c))
+                };
+                current = point(&mut parser)?;
+                path.quad_to(control, current);
+                quad_control = Some(control);
+            }
+            'A' => {
+                let mut arguments = || -> Option<(Vec2, f32, bool, bool, Pos2)> {
+                    let radii = vec2(parser.number()?, parser.number()?);
+                    let x_rotation = parser.number()?.to_radians();
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    Some((
+                        radii,
+                        x_rotation,
+                        large_arc,
+                        sweep,
+                        parser.point()? + origin,
+                    ))
+                };
+                let (radii, x_rotation, large_arc, sweep, to) =
+                    arguments().ok_or_else(|| missing.clone())?;
+                path.arc_to(radii, x_rotation, large_arc, sweep, to);
+                current = to;
+            }
+            'Z' => {
+                path.close();
+                current = subpath_start;
+            }
+            _ => {
+                return Err(ParseSvgPathError::UnexpectedChar {
+                    offset: command_offset,
+                    ch: c,
+                })
+            }
+        }
+        last_cubic_control = cubic_control;
+        last_quad_control = quad_control;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_svg_path() {
+        let path = BezPathShape::from_svg(
+            "M10,20 l5-5 H30 v10 Q 40 40 50 30 t10 0 C 1e1 2 3.5.5 -1,-1 s 2 2 3 3 z m1 1 2 2",
+            Color32::WHITE,
+            PathStroke::NONE,
+        )
+        .unwrap();
+
+        use PathCommand::*;
+        assert_eq!(
+            path.commands,
+            vec![
+                MoveTo(pos2(10.0, 20.0)),
+                LineTo(pos2(15.0, 15.0)),
+                LineTo(pos2(30.0, 15.0)),
+                LineTo(pos2(30.0, 25.0)),
+                QuadTo(pos2(40.0, 40.0), pos2(50.0, 30.0)),
+                QuadTo(pos2(60.0, 20.0), pos2(60.0, 30.0)),
+                CubicTo(pos2(10.0, 2.0), pos2(3.5, 0.5), pos2(-1.0, -1.0)),
+                CubicTo(pos2(-5.5, -2.5), pos2(1.0, 1.0), pos2(2.0, 2.0)),
+                Close,
+                MoveTo(pos2(11.0, 21.0)),
+                LineTo(pos2(13.0, 23.0)),
+            ]
+        );
+
+        let parse = |data| BezPathShape::from_svg(data, Color32::WHITE, PathStroke::NONE);
+        assert_eq!(parse("L 1 1"), Err(ParseSvgPathError::MissingMoveTo));
+        assert_eq!(
+            parse("M 1 1 L 2"),
+            Err(ParseSvgPathError::MissingArguments {
+                offset: 6,
+                command: 'L'
+            })
+        );
+        assert_eq!(
+            parse("M 1 1 X"),
+            Err(ParseSvgPathError::UnexpectedChar { offset: 6, ch: 'X' })
+        );
+    }
+
+    #[test]
+    fn test_arc_to() {
+        // A half circle, with the flags written without separators.
+        let path =
+            BezPathShape::from_svg("M0 0A1 1 0 0110 0", Color32::WHITE, PathStroke::NONE).unwrap();
+        let subpaths = path.flatten(0.01);
+        assert_eq!(subpaths.len(), 1);
+        let (points, closed) = &subpaths[0];
+        assert!(!closed);
+        assert_eq!(points.last(), Some(&pos2(10.0, 0.0)));
+
+        // The radius is scaled up to reach the end point, and the sweep goes clockwise on screen,
+        // i.e. through negative y:
+        let center = pos2(5.0, 0.0);
+        for p in points {
+            assert!((p.distance(center) - 5.0).abs() < 0.02, "{p:?}");
+            assert!(p.y <= 1e-4, "{p:?}");
+        }
+        assert!(points.iter().any(|p| p.y < -4.9));
+    }
+
+    #[test]
+    fn test_flatten_subpaths() {
+        let mut path = BezPathShape::new(Color32::WHITE, PathStroke::NONE);
+        path.move_to(pos2(0.0, 0.0))
+            .line_to(pos2(1.0, 0.0))
+            .line_to(pos2(1.0, 1.0))
+            .close()
+            .line_to(pos2(-1.0, 0.0))
+            .move_to(pos2(5.0, 5.0));
+        assert_eq!(path.current_point(), Some(pos2(5.0, 5.0)));
+
+        let subpaths = path.flatten(0.1);
+        assert_eq!(
+            subpaths,
+            vec![
+                (vec![pos2(0.0, 0.0), pos2(1.0, 0.0), pos2(1.0, 1.0)], true),
+                (vec![pos2(0.0, 0.0), pos2(-1.0, 0.0)], false),
+            ]
+        );
+    }
+}
@@ -70,6 +70,8 @@ impl CubicBezierShape {
                 closed: self.closed,
                 fill: self.fill,
                 stroke: self.stroke.clone(),
+                fill_rule: Default::default(),
+                contours: Vec::new(),
             };
             pathshapes.push(pathshape);
         }
@@ -430,6 +432,8 @@ impl QuadraticBezierShape {
             closed: self.closed,
             fill: self.fill,
             stroke: self.stroke.clone(),
+            fill_rule: Default::default(),
+            contours: Vec::new(),
         }
     }
 
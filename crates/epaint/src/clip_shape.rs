@@ -0,0 +1,397 @@
+use std::sync::Arc;
+
+use emath::{Pos2, Rect, TSTransform, Vec2};
+
+use crate::{Mesh, Rounding, Vertex};
+
+/// A region that shapes can be clipped to with [`crate::ClippedToShape`], in addition to their clip rectangle.
+///
+/// Unlike the clip rectangle, which is usually implemented as a scissor rectangle by the backend,
+/// this is applied by the [`crate::Tessellator`], by cutting the produced meshes.
+/// This means it does not apply to [`crate::PaintCallback`]s.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipShape {
+    /// A rectangle with rounded corners.
+    RoundedRect { rect: Rect, rounding: Rounding },
+
+    /// A convex polygon, with the points in either winding order.
+    Convex(Arc<[Pos2]>),
+}
+
+impl ClipShape {
+    /// A rectangle with rounded corners.
+    #[inline]
+    pub fn rounded_rect(rect: Rect, rounding: impl Into<Rounding>) -> Self {
+        Self::RoundedRect {
+            rect,
+            rounding: rounding.into(),
+        }
+    }
+
+    /// A circle, e.g. for an avatar image.
+    #[inline]
+    pub fn circle(center: Pos2, radius: f32) -> Self {
+        Self::rounded_rect(
+            Rect::from_center_size(center, Vec2::splat(2.0 * radius)),
+            radius,
+        )
+    }
+
+    /// A convex polygon, with the points in either winding order.
+    ///
+    /// If the polygon is not convex, the clipping will be wrong.
+    pub fn convex(points: impl Into<Arc<[Pos2]>>) -> Self {
+        Self::Convex(points.into())
+    }
+
+    /// The smallest rectangle that contains the whole region.
+    pub fn bounding_rect(&self) -> Rect {
+        match self {
+            Self::RoundedRect { rect, .. } => *rect,
+            Self::Convex(points) => Rect::from_points(points),
+        }
+    }
+
+    /// The outline of the region, as a convex polygon.
+    pub fn outline(&self) -> Vec<Pos2> {
+        match self {
+            Self::RoundedRect { rect, rounding } => {
+                let mut points = vec![];
+                crate::tessellator::path::rounded_rectangle(&mut points, *rect, *rounding);
+                points
+            }
+            Self::Convex(points) => points.to_vec(),
+        }
+    }
+
+    /// Is the given position inside of the region?
+    pub fn contains(&self, pos: Pos2) -> bool {
+        match self {
+            Self::RoundedRect { rect, .. } if !rect.contains(pos) => false,
+            _ => 0.0 <= ConvexClip::new(&self.outline()).distance(pos),
+        }
+    }
+
+    /// How far outside of the region is the given position?
+    ///
+    /// Returns zero if the position is inside.
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        (-ConvexClip::new(&self.outline()).distance(pos)).max(0.0)
+    }
+
+    /// The region covered by both `self` and `other`.
+    pub fn intersect(&self, other: &Self) -> Self {
+        if self == other {
+            return self.clone();
+        }
+        if let (
+            Self::RoundedRect {
+                rect: a,
+                rounding: a_rounding,
+            },
+            Self::RoundedRect {
+                rect: b,
+                rounding: b_rounding,
+            },
+        ) = (self, other)
+        {
+            if *a_rounding == Rounding::ZERO && *b_rounding == Rounding::ZERO {
+                return Self::rect(a.intersect(*b));
+            }
+        }
+
+        let clip = ConvexClip::new(&other.outline());
+        let mut points = self.outline();
+        for edge in &clip.edges {
+            points = edge.clip_polygon(&points);
+        }
+        Self::Convex(points.into())
+    }
+
+    /// Transform the region by this much, in-place.
+    pub fn transform(&mut self, transform: TSTransform) {
+        match self {
+            Self::RoundedRect { rect, rounding } => {
+                *rect = transform * *rect;
+                *rounding *= transform.scaling;
+            }
+            Self::Convex(points) => {
+                *points = points.iter().map(|&p| transform * p).collect();
+            }
+        }
+    }
+
+    fn rect(rect: Rect) -> Self {
+        Self::rounded_rect(rect, Rounding::ZERO)
+    }
+}
+
+impl From<Rect> for ClipShape {
+    #[inline]
+    fn from(rect: Rect) -> Self {
+        Self::rect(rect)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// One edge of a [`ConvexClip`].
+#[derive(Clone, Copy, Debug)]
+struct ClipEdge {
+    /// A point on the edge.
+    point: Pos2,
+
+    /// Unit normal, pointing into the region.
+    normal: Vec2,
+}
+
+impl ClipEdge {
+    /// Positive inside the region.
+    #[inline]
+    fn distance(&self, pos: Pos2) -> f32 {
+        (pos - self.point).dot(self.normal)
+    }
+
+    /// Keep the part of the convex polygon that is inside of this edge.
+    fn clip_polygon(&self, points: &[Pos2]) -> Vec<Pos2> {
+        let mut out = Vec::with_capacity(points.len() + 1);
+        for (i, &a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            let (da, db) = (self.distance(a), self.distance(b));
+            if 0.0 <= da {
+                out.push(a);
+            }
+            if (0.0 <= da) != (0.0 <= db) {
+                out.push(a.lerp(b, da / (da - db)));
+            }
+        }
+        out
+    }
+}
+
+/// A [`ClipShape`] prepared for cutting meshes.
+#[derive(Clone, Debug)]
+pub(crate) struct ConvexClip {
+    edges: Vec<ClipEdge>,
+}
+
+impl ConvexClip {
+    pub(crate) fn new(outline: &[Pos2]) -> Self {
+        let mut points = outline.to_vec();
+        points.dedup();
+        if 2 < points.len() && points.first() == points.last() {
+            points.pop();
+        }
+
+        let n = points.len();
+        let twice_area: f32 = (0..n)
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        // Clockwise on screen is positive, and then `rot90` points outwards:
+        let sign = if 0.0 <= twice_area { -1.0 } else { 1.0 };
+
+        let edges = (0..n)
+            .filter_map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % n]);
+                let dir = b - a;
+                (dir != Vec2::ZERO).then(|| ClipEdge {
+                    point: a,
+                    normal: sign * dir.normalized().rot90(),
+                })
+            })
+            .collect();
+        Self { edges }
+    }
+
+    /// Signed distance to the outline, positive inside.
+    ///
+    /// Exact near the edges, which is all that is needed for anti-aliasing.
+    pub(crate) fn distance(&self, pos: Pos2) -> f32 {
+        if self.edges.len() < 3 {
+            return f32::NEG_INFINITY; // An empty region
+        }
+        self.edges
+            .iter()
+            .map(|edge| edge.distance(pos))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Cut away everything outside of the region from the triangles of `mesh`
+    /// starting at `first_index`, which must only use vertices from `first_vertex` onwards.
+    ///
+    /// The edge is faded out over `feathering` points, for anti-aliasing.
+    pub(crate) fn clip_mesh_tail(
+        &self,
+        mesh: &mut Mesh,
+        first_vertex: usize,
+        first_index: usize,
+        feathering: f32,
+    ) {
+        let vertices = mesh.vertices.split_off(first_vertex);
+        let indices = mesh.indices.split_off(first_index);
+        if self.edges.len() < 3 {
+            return;
+        }
+
+        let half = 0.5 * feathering;
+        let distances: Vec<f32> = vertices.iter().map(|v| self.distance(v.pos)).collect();
+
+        // Vertices that are kept as they are, shared between triangles:
+        let mut new_index = vec![u32::MAX; vertices.len()];
+
+        for triangle in indices.chunks_exact(3) {
+            let local = [
+                triangle[0] as usize - first_vertex,
+                triangle[1] as usize - first_vertex,
+                triangle[2] as usize - first_vertex,
+            ];
+
+            if local.iter().all(|&i| half <= distances[i]) {
+                // Fully inside:
+                for i in local {
+                    if new_index[i] == u32::MAX {
+                        new_index[i] = mesh.vertices.len() as u32;
+                        mesh.vertices.push(vertices[i]);
+                    }
+                    mesh.indices.push(new_index[i]);
+                }
+                continue;
+            }
+
+            let polygon = local.map(|i| vertices[i]);
+            let fully_outside = self
+                .edges
+                .iter()
+                .any(|edge| polygon.iter().all(|v| edge.distance(v.pos) <= -half));
+            if fully_outside {
+                continue;
+            }
+
+            let mut pieces = vec![polygon.to_vec()];
+            for edge in &self.edges {
+                let mut next = Vec::with_capacity(pieces.len());
+                for piece in pieces {
+                    let piece = clip_vertices(&piece, |pos| edge.distance(pos) + half);
+                    if piece.len() < 3 {
+                        continue;
+                    }
+                    if 0.0 < feathering {
+                        // Split along the inner edge of the fade, so that the alpha can be linear within each piece:
+                        let inner = clip_vertices(&piece, |pos| edge.distance(pos) - half);
+                        if inner.len() == piece.len()
+                            && inner.iter().zip(&piece).all(|(a, b)| a.pos == b.pos)
+                        {
+                            next.push(piece);
+                            continue;
+                        }
+                        let outer = clip_vertices(&piece, |pos| half - edge.distance(pos));
+                        next.extend([inner, outer].into_iter().filter(|p| 3 <= p.len()));
+                    } else {
+                        next.push(piece);
+                    }
+                }
+                pieces = next;
+            }
+
+            for piece in pieces {
+                let idx = mesh.vertices.len() as u32;
+                for mut vertex in piece.iter().copied() {
+                    if 0.0 < feathering {
+                        let alpha = (self.distance(vertex.pos) / feathering + 0.5).clamp(0.0, 1.0);
+                        if alpha < 1.0 {
+                            vertex.color = vertex.color.gamma_multiply(alpha);
+                        }
+                    }
+                    mesh.vertices.push(vertex);
+                }
+                for i in 1..piece.len() as u32 - 1 {
+                    mesh.add_triangle(idx, idx + i, idx + i + 1);
+                }
+            }
+        }
+    }
+}
+
+/// Keep the part of the convex polygon where `distance` is positive.
+fn clip_vertices(vertices: &[Vertex], distance: impl Fn(Pos2) -> f32) -> Vec<Vertex> {
+    let mut out = Vec::with_capacity(vertices.len() + 1);
+    for (i, a) in vertices.iter().enumerate() {
+        let b = &vertices[(i + 1) % vertices.len()];
+        let (da, db) = (distance(a.pos), distance(b.pos));
+        if 0.0 <= da {
+            out.push(*a);
+        }
+        if (0.0 <= da) != (0.0 <= db) {
+            out.push(lerp_vertex(a, b, da / (da - db)));
+        }
+    }
+    out
+}
+
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    let lerp_u8 = |a: u8, b: u8| emath::lerp(a as f32..=b as f32, t).round() as u8;
+    let [ar, ag, ab, aa] = a.color.to_array();
+    let [br, bg, bb, ba] = b.color.to_array();
+    Vertex {
+        pos: a.pos.lerp(b.pos, t),
+        uv: a.uv.lerp(b.uv, t),
+        color: crate::Color32::from_rgba_premultiplied(
+            lerp_u8(ar, br),
+            lerp_u8(ag, bg),
+            lerp_u8(ab, bb),
+            lerp_u8(aa, ba),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pos2, vec2, Color32};
+
+    #[test]
+    fn test_clip_shape_contains() {
+        let circle = ClipShape::circle(pos2(10.0, 10.0), 10.0);
+        assert!(circle.contains(pos2(10.0, 10.0)));
+        assert!(circle.contains(pos2(1.0, 10.0)));
+        assert!(!circle.contains(pos2(1.0, 1.0)));
+        assert!(!circle.contains(pos2(21.0, 10.0)));
+
+        let rect = ClipShape::from(Rect::from_min_size(pos2(10.0, 0.0), vec2(20.0, 20.0)));
+        let both = circle.intersect(&rect);
+        assert!(both.contains(pos2(15.0, 10.0)));
+        assert!(!both.contains(pos2(5.0, 10.0)));
+        assert!(!both.contains(pos2(25.0, 10.0)));
+    }
+
+    #[test]
+    fn test_clip_mesh() {
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 100.0));
+        let mut mesh = Mesh::default();
+        mesh.add_colored_rect(rect, Color32::WHITE);
+
+        let clip = ClipShape::circle(rect.center(), 25.0);
+        ConvexClip::new(&clip.outline()).clip_mesh_tail(&mut mesh, 0, 0, 1.0);
+
+        assert!(mesh.is_valid());
+        let bounds = mesh.calc_bounds();
+        assert!(
+            clip.bounding_rect().expand(0.501).contains_rect(bounds),
+            "{bounds:?}"
+        );
+        assert!(bounds.contains_rect(clip.bounding_rect().shrink(0.5)));
+
+        // The outermost vertices are faded out, and the center is untouched:
+        for vertex in &mesh.vertices {
+            let distance = 25.0 - vertex.pos.distance(rect.center());
+            if distance < -0.4 {
+                assert_eq!(vertex.color, Color32::TRANSPARENT, "{vertex:?}");
+            } else if 1.0 < distance {
+                assert_eq!(vertex.color, Color32::WHITE, "{vertex:?}");
+            }
+        }
+    }
+}
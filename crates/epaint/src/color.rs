@@ -3,6 +3,8 @@ use std::{fmt::Debug, sync::Arc};
 use ecolor::Color32;
 use emath::{Pos2, Rect};
 
+use crate::Gradient;
+
 /// How paths will be colored.
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -16,6 +18,10 @@ pub enum ColorMode {
     /// **This cannot be serialized**
     #[cfg_attr(feature = "serde", serde(skip))]
     UV(Arc<dyn Fn(Rect, Pos2) -> Color32 + Send + Sync>),
+
+    /// A linear or radial gradient, relative to the path's bounding box
+    /// (which has the same margin as for [`Self::UV`]).
+    Gradient(Arc<Gradient>),
 }
 
 impl Default for ColorMode {
@@ -29,6 +35,7 @@ impl Debug for ColorMode {
         match self {
             Self::Solid(arg0) => f.debug_tuple("Solid").field(arg0).finish(),
             Self::UV(_arg0) => f.debug_tuple("UV").field(&"<closure>").finish(),
+            Self::Gradient(gradient) => f.debug_tuple("Gradient").field(gradient).finish(),
         }
     }
 }
@@ -38,6 +45,7 @@ impl PartialEq for ColorMode {
         match (self, other) {
             (Self::Solid(l0), Self::Solid(r0)) => l0 == r0,
             (Self::UV(_l0), Self::UV(_r0)) => false,
+            (Self::Gradient(l0), Self::Gradient(r0)) => l0 == r0,
             _ => false,
         }
     }
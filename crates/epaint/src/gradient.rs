@@ -0,0 +1,524 @@
+use ecolor::{Color32, Rgba};
+use emath::{lerp, pos2, Pos2, Rect, Vec2};
+
+use crate::{Mesh, Vertex};
+
+/// A color at some offset along a [`Gradient`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct GradientStop {
+    /// Where along the gradient this color is, usually in the `0..=1` range.
+    pub offset: f32,
+
+    pub color: Color32,
+}
+
+/// The geometry of a [`Gradient`].
+///
+/// Positions are relative to the bounding rectangle of what is painted,
+/// with `(0, 0)` in the left-top corner and `(1, 1)` in the right-bottom corner.
+/// That way the gradient follows the shape when it is moved or scaled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum GradientKind {
+    /// The colors change along the line from `start` (offset 0) to `end` (offset 1),
+    /// and are constant perpendicular to it.
+    Linear { start: Pos2, end: Pos2 },
+
+    /// The colors change with the distance to `center` (offset 0),
+    /// reaching offset 1 at `radius`.
+    ///
+    /// The circles become ellipses if the bounding rectangle isn't square.
+    Radial { center: Pos2, radius: f32 },
+}
+
+/// In which color space to blend between the stops of a [`Gradient`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum GradientInterpolation {
+    /// Blend in linear RGB, which is how light mixes.
+    #[default]
+    LinearRgb,
+
+    /// Blend in the perceptual `OKLab` space, which keeps the brightness and saturation
+    /// more even between very different colors.
+    Oklab,
+}
+
+/// A linear or radial color gradient with any number of stops.
+///
+/// Can be used to fill shapes (see [`crate::GradientShape`]),
+/// and as the color of a [`crate::PathStroke`] (see [`crate::ColorMode::Gradient`]).
+///
+/// ```
+/// # use epaint::{Color32, pos2, gradient::Gradient};
+/// let sunset = Gradient::linear(
+///     pos2(0.5, 0.0),
+///     pos2(0.5, 1.0),
+///     [(0.0, Color32::BLUE), (0.6, Color32::RED), (1.0, Color32::YELLOW)],
+/// );
+/// assert_eq!(sunset.color_at_offset(0.6), Color32::RED);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Gradient {
+    pub kind: GradientKind,
+
+    /// Sorted by [`GradientStop::offset`], without NaN offsets.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_stops"))]
+    stops: Vec<GradientStop>,
+
+    pub interpolation: GradientInterpolation,
+}
+
+impl Gradient {
+    /// A gradient along the line from `start` to `end`,
+    /// relative to the bounding rectangle (see [`GradientKind`]).
+    pub fn linear(start: Pos2, end: Pos2, stops: impl IntoIterator<Item = (f32, Color32)>) -> Self {
+        Self::new(GradientKind::Linear { start, end }, stops)
+    }
+
+    /// A gradient from the `center` outwards,
+    /// relative to the bounding rectangle (see [`GradientKind`]).
+    pub fn radial(
+        center: Pos2,
+        radius: f32,
+        stops: impl IntoIterator<Item = (f32, Color32)>,
+    ) -> Self {
+        Self::new(GradientKind::Radial { center, radius }, stops)
+    }
+
+    /// From `left` to `right`.
+    pub fn horizontal(left: Color32, right: Color32) -> Self {
+        Self::linear(pos2(0.0, 0.5), pos2(1.0, 0.5), [(0.0, left), (1.0, right)])
+    }
+
+    /// From `top` to `bottom`.
+    pub fn vertical(top: Color32, bottom: Color32) -> Self {
+        Self::linear(pos2(0.5, 0.0), pos2(0.5, 1.0), [(0.0, top), (1.0, bottom)])
+    }
+
+    fn new(kind: GradientKind, stops: impl IntoIterator<Item = (f32, Color32)>) -> Self {
+        let stops = stops
+            .into_iter()
+            .map(|(offset, color)| GradientStop { offset, color })
+            .collect();
+        Self {
+            kind,
+            stops: sorted_stops(stops),
+            interpolation: GradientInterpolation::default(),
+        }
+    }
+
+    /// The stops, sorted by [`GradientStop::offset`].
+    ///
+    /// Before the first stop and after the last, the colors of those stops are used.
+    #[inline]
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    /// In which color space to blend between the stops.
+    #[inline]
+    pub fn with_interpolation(mut self, interpolation: GradientInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Are all the stops fully transparent?
+    pub fn is_transparent(&self) -> bool {
+        self.stops
+            .iter()
+            .all(|stop| stop.color == Color32::TRANSPARENT)
+    }
+
+    /// Where along the gradient `pos` is, when painting something with the bounding rectangle `rect`.
+    pub fn offset_at(&self, rect: Rect, pos: Pos2) -> f32 {
+        let relative = relative_pos(rect, pos);
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                let dir = end - start;
+                let length_sq = dir.length_sq();
+                if length_sq <= 0.0 {
+                    0.0
+                } else {
+                    (relative - start).dot(dir) / length_sq
+                }
+            }
+            GradientKind::Radial { center, radius } => {
+                if radius <= 0.0 {
+                    1.0
+                } else {
+                    (relative - center).length() / radius
+                }
+            }
+        }
+    }
+
+    /// The color at `pos`, when painting something with the bounding rectangle `rect`.
+    pub fn color_at(&self, rect: Rect, pos: Pos2) -> Color32 {
+        self.color_at_offset(self.offset_at(rect, pos))
+    }
+
+    /// The color at the given offset along the gradient.
+    pub fn color_at_offset(&self, offset: f32) -> Color32 {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Color32::TRANSPARENT;
+        };
+        if offset.is_nan() || offset <= first.offset {
+            return first.color;
+        }
+        if last.offset <= offset {
+            return last.color;
+        }
+
+        // Now first.offset < offset < last.offset, so there are at least two stops:
+        let next = self
+            .stops
+            .partition_point(|stop| stop.offset <= offset)
+            .clamp(1, self.stops.len() - 1);
+        let (a, b) = (self.stops[next - 1], self.stops[next]);
+        let t = (offset - a.offset) / (b.offset - a.offset);
+        match self.interpolation {
+            GradientInterpolation::LinearRgb => {
+                let (a, b) = (Rgba::from(a.color), Rgba::from(b.color));
+                Color32::from(a * (1.0 - t) + b * t)
+            }
+            GradientInterpolation::Oklab => lerp_oklab(a.color, b.color, t),
+        }
+    }
+
+    /// Change the color of all stops, e.g. for [`crate::shape_transform::adjust_colors`].
+    pub fn adjust_colors(&mut self, adjust_color: impl Fn(&mut Color32)) {
+        for stop in &mut self.stops {
+            adjust_color(&mut stop.color);
+        }
+    }
+
+    /// Color the triangles added to `mesh` since it had `index_start` indices,
+    /// which cover something with the bounding rectangle `rect`.
+    ///
+    /// Their vertex colors are used as coverage (e.g. for anti-aliasing),
+    /// so they should be tessellated in white.
+    /// The triangles are split so that the colors between the vertices come out right.
+    pub(crate) fn paint_mesh(&self, rect: Rect, mesh: &mut Mesh, index_start: usize) {
+        crate::profile_function!();
+
+        let Some(&vertex_start) = mesh.indices[index_start..].iter().min() else {
+            return;
+        };
+        let vertex_start = vertex_start as usize;
+        let triangles: Vec<[Vertex; 3]> = mesh.indices[index_start..]
+            .chunks_exact(3)
+            .map(|tri| {
+                [
+                    mesh.vertices[tri[0] as usize],
+                    mesh.vertices[tri[1] as usize],
+                    mesh.vertices[tri[2] as usize],
+                ]
+            })
+            .collect();
+        mesh.vertices.truncate(vertex_start);
+        mesh.indices.truncate(index_start);
+
+        // Between stops we add extra slices, so that the colors follow the interpolation:
+        const SLICES_PER_STOP: usize = 8;
+        let mut levels: Vec<f32> = self
+            .stops
+            .windows(2)
+            .flat_map(|pair| {
+                let (a, b) = (pair[0].offset, pair[1].offset);
+                (0..SLICES_PER_STOP).map(move |i| lerp(a..=b, i as f32 / SLICES_PER_STOP as f32))
+            })
+            .chain(self.stops.last().map(|stop| stop.offset))
+            .collect();
+        levels.dedup();
+
+        let mut small_triangles = vec![];
+        for triangle in triangles {
+            self.subdivide(rect, triangle, 0, &mut small_triangles);
+        }
+
+        for triangle in small_triangles {
+            let mut polygon: Vec<(Vertex, f32)> = triangle
+                .iter()
+                .map(|v| (*v, self.offset_at(rect, v.pos)))
+                .collect();
+            let (min, max) = polygon
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), (_, t)| {
+                    (min.min(*t), max.max(*t))
+                });
+
+            for &level in &levels {
+                if level <= min || max <= level {
+                    continue;
+                }
+                let (below, above) = split_polygon(&polygon, level);
+                self.add_polygon(&below, mesh);
+                polygon = above;
+            }
+            self.add_polygon(&polygon, mesh);
+        }
+    }
+
+    /// Radial gradients aren't linear within a triangle, so large triangles need to be split up.
+    fn subdivide(
+        &self,
+        rect: Rect,
+        triangle: [Vertex; 3],
+        depth: usize,
+        out: &mut Vec<[Vertex; 3]>,
+    ) {
+        const MAX_EDGE: f32 = 0.125; // in units of the radius
+        const MAX_DEPTH: usize = 12;
+
+        let GradientKind::Radial { radius, .. } = self.kind else {
+            out.push(triangle);
+            return;
+        };
+        if radius <= 0.0 || MAX_DEPTH <= depth {
+            out.push(triangle);
+            return;
+        }
+
+        let edge_length = |i: usize| {
+            let (a, b) = (triangle[i].pos, triangle[(i + 1) % 3].pos);
+            (relative_pos(rect, a) - relative_pos(rect, b)).length() / radius
+        };
+        let longest = (0..3)
+            .max_by(|&a, &b| edge_length(a).total_cmp(&edge_length(b)))
+            .unwrap_or(0);
+        if edge_length(longest) <= MAX_EDGE {
+            out.push(triangle);
+            return;
+        }
+
+        let [a, b, c] = [
+            triangle[longest],
+            triangle[(longest + 1) % 3],
+            triangle[(longest + 2) % 3],
+        ];
+        let mid = lerp_vertex(&a, &b, 0.5);
+        self.subdivide(rect, [a, mid, c], depth + 1, out);
+        self.subdivide(rect, [mid, b, c], depth + 1, out);
+    }
+
+    /// Add a convex polygon as a triangle fan, colored by the gradient.
+    fn add_polygon(&self, polygon: &[(Vertex, f32)], mesh: &mut Mesh) {
+        if polygon.len() < 3 {
+            return;
+        }
+        let idx = mesh.vertices.len() as u32;
+        mesh.vertices.extend(polygon.iter().map(|(vertex, offset)| {
+            let coverage = vertex.color.a() as f32 / 255.0;
+            Vertex {
+                color: self.color_at_offset(*offset).gamma_multiply(coverage),
+                ..*vertex
+            }
+        }));
+        for i in 2..polygon.len() as u32 {
+            mesh.add_triangle(idx, idx + i - 1, idx + i);
+        }
+    }
+}
+
+/// Remove stops with NaN offsets, and sort the rest.
+fn sorted_stops(mut stops: Vec<GradientStop>) -> Vec<GradientStop> {
+    stops.retain(|stop| !stop.offset.is_nan());
+    stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    stops
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_stops<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<GradientStop>, D::Error> {
+    let stops: Vec<GradientStop> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(sorted_stops(stops))
+}
+
+/// Where `pos` is in `rect`, with `(0, 0)` in the left-top corner and `(1, 1)` in the right-bottom corner.
+fn relative_pos(rect: Rect, pos: Pos2) -> Pos2 {
+    let size = rect.size().max(Vec2::splat(f32::EPSILON));
+    pos2((pos.x - rect.min.x) / size.x, (pos.y - rect.min.y) / size.y)
+}
+
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    let lerp_u8 = |a: u8, b: u8| lerp(a as f32..=b as f32, t).round() as u8;
+    let (ca, cb) = (a.color, b.color);
+    Vertex {
+        pos: a.pos.lerp(b.pos, t),
+        uv: a.uv.lerp(b.uv, t),
+        color: Color32::from_rgba_premultiplied(
+            lerp_u8(ca.r(), cb.r()),
+            lerp_u8(ca.g(), cb.g()),
+            lerp_u8(ca.b(), cb.b()),
+            lerp_u8(ca.a(), cb.a()),
+        ),
+    }
+}
+
+/// Split a convex polygon along where the (linearly interpolated) offset is `level`.
+///
+/// Returns the part below and the part above the level.
+#[allow(clippy::type_complexity)]
+fn split_polygon(
+    polygon: &[(Vertex, f32)],
+    level: f32,
+) -> (Vec<(Vertex, f32)>, Vec<(Vertex, f32)>) {
+    let mut below = vec![];
+    let mut above = vec![];
+    for (i, &(a, ta)) in polygon.iter().enumerate() {
+        let (b, tb) = polygon[(i + 1) % polygon.len()];
+        if ta <= level {
+            below.push((a, ta));
+        }
+        if level <= ta {
+            above.push((a, ta));
+        }
+        if (ta < level && level < tb) || (tb < level && level < ta) {
+            let crossing = (lerp_vertex(&a, &b, (level - ta) / (tb - ta)), level);
+            below.push(crossing);
+            above.push(crossing);
+        }
+    }
+    (below, above)
+}
+
+fn lerp_oklab(a: Color32, b: Color32, t: f32) -> Color32 {
+    // Blend premultiplied, so that transparent stops don't bleed their color:
+    let premultiplied_oklab = |color: Color32| {
+        let rgba = Rgba::from(color);
+        let [r, g, b, alpha] = rgba.to_rgba_unmultiplied();
+        let [l, a, b] = oklab_from_linear_rgb([r, g, b]);
+        [l * alpha, a * alpha, b * alpha, alpha]
+    };
+    let a = premultiplied_oklab(a);
+    let b = premultiplied_oklab(b);
+    let [l, a_, b_, alpha] = std::array::from_fn(|i| lerp(a[i]..=b[i], t));
+    if alpha <= 0.0 {
+        return Color32::TRANSPARENT;
+    }
+    let [r, g, b] = linear_rgb_from_oklab([l / alpha, a_ / alpha, b_ / alpha]);
+    Color32::from(Rgba::from_rgba_unmultiplied(
+        r.clamp(0.0, 1.0),
+        g.clamp(0.0, 1.0),
+        b.clamp(0.0, 1.0),
+        alpha,
+    ))
+}
+
+/// See <https://bottosson.github.io/posts/oklab/>.
+#[allow(clippy::excessive_precision)]
+fn oklab_from_linear_rgb([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = 0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b;
+    let m = 0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b;
+    let s = 0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b;
+    let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+    [
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+    ]
+}
+
+/// See <https://bottosson.github.io/posts/oklab/>.
+#[allow(clippy::excessive_precision)]
+fn linear_rgb_from_oklab([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = l + 0.396_337_777_4 * a + 0.215_803_757_3 * b;
+    let m_ = l - 0.105_561_345_8 * a - 0.063_854_172_8 * b;
+    let s_ = l - 0.089_484_177_5 * a - 1.291_485_548_0 * b;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+    [
+        4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+        -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+        -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_at_offset() {
+        let gradient = Gradient::linear(
+            pos2(0.0, 0.0),
+            pos2(1.0, 0.0),
+            [(1.0, Color32::WHITE), (0.0, Color32::BLACK)],
+        );
+        assert_eq!(gradient.stops[0].color, Color32::BLACK, "Stops are sorted");
+        assert_eq!(gradient.color_at_offset(-1.0), Color32::BLACK);
+        assert_eq!(gradient.color_at_offset(2.0), Color32::WHITE);
+
+        // Linear RGB is brighter than the sRGB halfway point:
+        let linear_middle = gradient.color_at_offset(0.5);
+        assert!(128 < linear_middle.r());
+
+        // OKLab is perceptually even, so close to the sRGB halfway point:
+        let oklab_middle = gradient
+            .with_interpolation(GradientInterpolation::Oklab)
+            .color_at_offset(0.5);
+        assert!(oklab_middle.r().abs_diff(99) <= 2, "{oklab_middle:?}");
+    }
+
+    #[test]
+    fn test_nan_offsets() {
+        let gradient = Gradient::horizontal(Color32::BLACK, Color32::WHITE);
+        assert_eq!(gradient.color_at_offset(f32::NAN), Color32::BLACK);
+
+        let gradient = Gradient::linear(
+            pos2(0.0, 0.0),
+            pos2(1.0, 0.0),
+            [(f32::NAN, Color32::RED), (0.5, Color32::GREEN)],
+        );
+        assert_eq!(gradient.stops().len(), 1, "NaN stops are removed");
+        assert_eq!(gradient.color_at_offset(0.7), Color32::GREEN);
+        assert_eq!(gradient.color_at_offset(f32::NAN), Color32::GREEN);
+    }
+
+    #[test]
+    fn test_offset_at() {
+        let rect = Rect::from_min_max(pos2(10.0, 10.0), pos2(30.0, 50.0));
+
+        let vertical = Gradient::vertical(Color32::BLACK, Color32::WHITE);
+        assert_eq!(vertical.offset_at(rect, pos2(0.0, 10.0)), 0.0);
+        assert_eq!(vertical.offset_at(rect, pos2(20.0, 30.0)), 0.5);
+
+        let radial = Gradient::radial(pos2(0.5, 0.5), 0.5, [(0.0, Color32::WHITE)]);
+        assert_eq!(radial.offset_at(rect, rect.center()), 0.0);
+        assert_eq!(radial.offset_at(rect, rect.right_center()), 1.0);
+        assert_eq!(radial.offset_at(rect, rect.center_bottom()), 1.0);
+    }
+
+    #[test]
+    fn test_paint_mesh() {
+        let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 10.0));
+        let mut mesh = Mesh::default();
+        mesh.add_colored_rect(rect, Color32::WHITE);
+
+        let gradient = Gradient::linear(
+            pos2(0.0, 0.0),
+            pos2(1.0, 0.0),
+            [
+                (0.0, Color32::RED),
+                (0.5, Color32::GREEN),
+                (1.0, Color32::BLUE),
+            ],
+        );
+        gradient.paint_mesh(rect, &mut mesh, 0);
+        assert!(mesh.is_valid());
+
+        // The middle stop is hit exactly:
+        assert!(mesh
+            .vertices
+            .iter()
+            .any(|v| v.pos.x == 50.0 && v.color == Color32::GREEN));
+
+        // Every vertex has the color of where it is:
+        for vertex in &mesh.vertices {
+            assert_eq!(vertex.color, gradient.color_at(rect, vertex.pos));
+        }
+    }
+}
@@ -0,0 +1,469 @@
+//! Find out if a position is on a painted [`Shape`].
+
+use emath::{Pos2, Rect, Rot2, Vec2};
+
+use crate::{
+    BezPathShape, CircleShape, Color32, EllipseShape, FillRule, Mesh, PathShape, RectShape,
+    Rounding, Shape, TextShape,
+};
+
+/// How closely curves are followed when hit-testing them, in points.
+const CURVE_TOLERANCE: f32 = 0.1;
+
+/// ## Hit-testing
+impl Shape {
+    /// Is the given position on the painted parts of this shape (fill or stroke)?
+    ///
+    /// Stroke widths are taken into account, but line caps, joins and dashes are not.
+    /// Text is hit-tested against the bounding box of the laid out text.
+    #[inline]
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        self.distance_to(pos) <= 0.0
+    }
+
+    /// How far away is the given position from the painted parts of this shape (fill or stroke)?
+    ///
+    /// Returns zero if the position is on the shape,
+    /// and [`f32::INFINITY`] if the shape paints nothing.
+    ///
+    /// See also [`Self::contains_point`].
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        match self {
+            Self::Noop => f32::INFINITY,
+            Self::Vec(shapes) => shapes
+                .iter()
+                .map(|shape| shape.distance_to(pos))
+                .fold(f32::INFINITY, f32::min),
+            Self::Circle(circle_shape) => circle_shape.distance_to(pos),
+            Self::Ellipse(ellipse_shape) => ellipse_shape.distance_to(pos),
+            Self::LineSegment { points, stroke } => {
+                if stroke.is_empty() {
+                    f32::INFINITY
+                } else {
+                    (distance_to_segment(pos, points[0], points[1]) - 0.5 * stroke.width).max(0.0)
+                }
+            }
+            Self::Path(path_shape) => path_shape.distance_to(pos),
+            Self::Rect(rect_shape) => rect_shape.distance_to(pos),
+            Self::Text(text_shape) => text_shape.distance_to(pos),
+            Self::Mesh(mesh) => distance_to_mesh(mesh, pos),
+            Self::QuadraticBezier(bezier) => {
+                let mut path = bezier.to_path_shape(Some(CURVE_TOLERANCE));
+                path.fill_rule = FillRule::NonZero;
+                path.distance_to(pos)
+            }
+            Self::CubicBezier(bezier) => bezier
+                .to_path_shapes(Some(CURVE_TOLERANCE), None)
+                .into_iter()
+                .map(|mut path| {
+                    path.fill_rule = FillRule::NonZero;
+                    path.distance_to(pos)
+                })
+                .fold(f32::INFINITY, f32::min),
+            Self::BezPath(bez_path) => bez_path.distance_to(pos),
+            Self::Gradient(gradient_shape) => gradient_shape
+                .shape_with_placeholder_fill()
+                .distance_to(pos),
+            Self::Clipped(clipped) => clipped
+                .shape
+                .distance_to(pos)
+                .max(clipped.clip_shape.distance_to(pos)),
+            Self::Callback(callback) => callback.rect.distance_to_pos(pos),
+        }
+    }
+}
+
+impl CircleShape {
+    /// How far away is the given position from the fill or stroke of the circle?
+    ///
+    /// Returns zero if the position is on the circle.
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        let signed = pos.distance(self.center) - self.radius;
+        filled_or_stroked_distance(
+            signed,
+            self.fill != Color32::TRANSPARENT,
+            self.stroke.width,
+            !self.stroke.is_empty(),
+        )
+    }
+}
+
+impl EllipseShape {
+    /// How far away is the given position from the fill or stroke of the ellipse?
+    ///
+    /// Returns zero if the position is on the ellipse.
+    /// The distance is approximate far from the outline.
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        let radius = self.radius.abs();
+        let signed = if radius.min_elem() <= 0.0 {
+            // A flat ellipse is a line:
+            let half = Vec2::new(radius.x, radius.y);
+            distance_to_segment(pos, self.center - half, self.center + half)
+        } else {
+            let p = pos - self.center;
+            let k0 = (p / radius).length();
+            let k1 = (p / (radius * radius)).length();
+            if k1 == 0.0 {
+                -radius.min_elem()
+            } else {
+                k0 * (k0 - 1.0) / k1
+            }
+        };
+        filled_or_stroked_distance(
+            signed,
+            self.fill != Color32::TRANSPARENT,
+            self.stroke.width,
+            !self.stroke.is_empty(),
+        )
+    }
+}
+
+impl PathShape {
+    /// How far away is the given position from the fill or stroke of the path?
+    ///
+    /// Returns zero if the position is on the path.
+    /// The fill is tested using [`Self::fill_rule`], with [`FillRule::Convex`]
+    /// treated like [`FillRule::NonZero`] of only [`Self::points`].
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        let filled = self.closed && self.fill != Color32::TRANSPARENT;
+        let stroked = !self.stroke.is_empty();
+        if !filled && !stroked {
+            return f32::INFINITY;
+        }
+
+        let contours: &[Vec<Pos2>] = if self.closed { &self.contours } else { &[] };
+
+        let mut edge_distance = distance_to_polyline(pos, &self.points, self.closed);
+        for contour in contours {
+            edge_distance = edge_distance.min(distance_to_polyline(pos, contour, true));
+        }
+
+        if filled {
+            let filled_contours: &[Vec<Pos2>] = if self.fill_rule == FillRule::Convex {
+                &[]
+            } else {
+                contours
+            };
+            let inside = is_inside(
+                pos,
+                std::iter::once(&self.points).chain(filled_contours),
+                self.fill_rule,
+            );
+            if inside {
+                return 0.0;
+            }
+        }
+
+        let half_width = if stroked {
+            0.5 * self.stroke.width
+        } else {
+            0.0
+        };
+        (edge_distance - half_width).max(0.0)
+    }
+}
+
+impl RectShape {
+    /// How far away is the given position from the fill or stroke of the rectangle?
+    ///
+    /// Returns zero if the position is on the rectangle.
+    /// The blur is not taken into account.
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        let signed = signed_distance_to_rounded_rect(pos, self.rect, self.rounding);
+        filled_or_stroked_distance(
+            signed,
+            self.fill != Color32::TRANSPARENT,
+            self.stroke.width,
+            !self.stroke.is_empty(),
+        )
+    }
+}
+
+impl TextShape {
+    /// How far away is the given position from the bounding box of the laid out text?
+    ///
+    /// Returns zero if the position is within it.
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        let local = Rot2::from_angle(-self.angle) * (pos - self.pos);
+        self.galley.rect.distance_to_pos(Pos2::ZERO + local)
+    }
+}
+
+impl BezPathShape {
+    /// How far away is the given position from the fill or stroke of the path?
+    ///
+    /// Returns zero if the position is on the path.
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        let filled = self.fill != Color32::TRANSPARENT;
+        let stroked = !self.stroke.is_empty();
+        if !filled && !stroked {
+            return f32::INFINITY;
+        }
+
+        let subpaths = self.flatten(CURVE_TOLERANCE);
+
+        if filled {
+            // All subpaths are filled, as if they were closed:
+            let fill_rule = if self.fill_rule == FillRule::Convex {
+                FillRule::NonZero
+            } else {
+                self.fill_rule
+            };
+            if is_inside(pos, subpaths.iter().map(|(points, _)| points), fill_rule) {
+                return 0.0;
+            }
+        }
+
+        let edge_distance = subpaths
+            .iter()
+            .map(|(points, closed)| distance_to_polyline(pos, points, *closed || filled))
+            .fold(f32::INFINITY, f32::min);
+        let half_width = if stroked {
+            0.5 * self.stroke.width
+        } else {
+            0.0
+        };
+        (edge_distance - half_width).max(0.0)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// The distance to a shape with the given signed distance to its outline (negative inside),
+/// which may be filled and may have a stroke centered on its outline.
+fn filled_or_stroked_distance(
+    signed_distance: f32,
+    filled: bool,
+    stroke_width: f32,
+    stroked: bool,
+) -> f32 {
+    let half_width = if stroked { 0.5 * stroke_width } else { 0.0 };
+    if filled {
+        (signed_distance - half_width).max(0.0)
+    } else if stroked {
+        (signed_distance.abs() - half_width).max(0.0)
+    } else {
+        f32::INFINITY
+    }
+}
+
+fn distance_to_segment(pos: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let length_sq = ab.length_sq();
+    let t = if length_sq > 0.0 {
+        ((pos - a).dot(ab) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    pos.distance(a + t * ab)
+}
+
+fn distance_to_polyline(pos: Pos2, points: &[Pos2], closed: bool) -> f32 {
+    match points {
+        [] => f32::INFINITY,
+        [point] => pos.distance(*point),
+        _ => {
+            let mut distance = points
+                .windows(2)
+                .map(|w| distance_to_segment(pos, w[0], w[1]))
+                .fold(f32::INFINITY, f32::min);
+            if closed {
+                let (first, last) = (points[0], points[points.len() - 1]);
+                distance = distance.min(distance_to_segment(pos, last, first));
+            }
+            distance
+        }
+    }
+}
+
+/// Is the position inside the closed contours, according to the fill rule?
+fn is_inside<'a>(
+    pos: Pos2,
+    contours: impl Iterator<Item = &'a Vec<Pos2>>,
+    fill_rule: FillRule,
+) -> bool {
+    let mut winding = 0_i32;
+    for contour in contours {
+        let n = contour.len();
+        for i in 0..n {
+            let (a, b) = (contour[i], contour[(i + 1) % n]);
+            if (a.y <= pos.y) != (b.y <= pos.y) {
+                // The edge crosses the horizontal line through `pos`. Is it to the right?
+                let x = a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if pos.x < x {
+                    winding += if a.y < b.y { 1 } else { -1 };
+                }
+            }
+        }
+    }
+    match fill_rule {
+        FillRule::Convex | FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// Negative inside the rectangle.
+fn signed_distance_to_rounded_rect(pos: Pos2, rect: Rect, rounding: Rounding) -> f32 {
+    let center = rect.center();
+    let half_size = 0.5 * rect.size();
+    let p = pos - center;
+
+    // Pick the corner in the quadrant of the position:
+    let radius = match (p.x < 0.0, p.y < 0.0) {
+        (true, true) => rounding.nw,
+        (false, true) => rounding.ne,
+        (true, false) => rounding.sw,
+        (false, false) => rounding.se,
+    };
+    let radius = radius.clamp(0.0, half_size.min_elem().max(0.0));
+
+    let q = p.abs() - half_size + Vec2::splat(radius);
+    q.max(Vec2::ZERO).length() + q.max_elem().min(0.0) - radius
+}
+
+fn distance_to_mesh(mesh: &Mesh, pos: Pos2) -> f32 {
+    let mut distance = f32::INFINITY;
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].pos);
+
+        // A degenerate triangle has no inside, only its edges:
+        let is_degenerate = (b - a).x * (c - a).y == (b - a).y * (c - a).x;
+        if !is_degenerate {
+            let side = |a: Pos2, b: Pos2| (b - a).x * (pos - a).y - (b - a).y * (pos - a).x;
+            let (d0, d1, d2) = (side(a, b), side(b, c), side(c, a));
+            let has_negative = d0 < 0.0 || d1 < 0.0 || d2 < 0.0;
+            let has_positive = d0 > 0.0 || d1 > 0.0 || d2 > 0.0;
+            if !(has_negative && has_positive) {
+                return 0.0;
+            }
+        }
+
+        distance = distance
+            .min(distance_to_segment(pos, a, b))
+            .min(distance_to_segment(pos, b, c))
+            .min(distance_to_segment(pos, c, a));
+    }
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pos2, vec2, Color32, Stroke};
+
+    #[test]
+    fn test_circle_and_ring() {
+        let disc = Shape::circle_filled(pos2(0.0, 0.0), 10.0, Color32::RED);
+        assert!(disc.contains_point(pos2(0.0, 0.0)));
+        assert!(disc.contains_point(pos2(0.0, 10.0)));
+        assert_eq!(disc.distance_to(pos2(13.0, 0.0)), 3.0);
+
+        let ring = Shape::circle_stroke(pos2(0.0, 0.0), 10.0, Stroke::new(2.0, Color32::RED));
+        assert!(!ring.contains_point(pos2(0.0, 0.0)));
+        assert!(ring.contains_point(pos2(10.5, 0.0)));
+        assert_eq!(ring.distance_to(pos2(0.0, 5.0)), 4.0);
+    }
+
+    #[test]
+    fn test_rounded_rect() {
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 50.0));
+        let shape = Shape::rect_filled(rect, 10.0, Color32::RED);
+        assert!(shape.contains_point(pos2(50.0, 25.0)));
+        assert!(shape.contains_point(pos2(0.0, 25.0)));
+        assert!(
+            !shape.contains_point(pos2(1.0, 1.0)),
+            "The corner is rounded"
+        );
+        assert_eq!(shape.distance_to(pos2(50.0, 60.0)), 10.0);
+
+        let unfilled = Shape::rect_stroke(rect, 0.0, Stroke::new(2.0, Color32::RED));
+        assert!(!unfilled.contains_point(pos2(50.0, 25.0)));
+        assert!(unfilled.contains_point(pos2(100.5, 25.0)));
+    }
+
+    #[test]
+    fn test_line_and_path() {
+        let line = Shape::line_segment(
+            [pos2(0.0, 0.0), pos2(10.0, 0.0)],
+            Stroke::new(4.0, Color32::RED),
+        );
+        assert!(line.contains_point(pos2(5.0, 2.0)));
+        assert_eq!(line.distance_to(pos2(5.0, 5.0)), 3.0);
+
+        // A concave "L":
+        let points = vec![
+            pos2(0.0, 0.0),
+            pos2(10.0, 0.0),
+            pos2(10.0, 20.0),
+            pos2(20.0, 20.0),
+            pos2(20.0, 30.0),
+            pos2(0.0, 30.0),
+        ];
+        let path = PathShape::convex_polygon(points, Color32::RED, Stroke::NONE)
+            .with_fill_rule(FillRule::NonZero);
+        assert!(path.distance_to(pos2(5.0, 5.0)) == 0.0);
+        assert!(path.distance_to(pos2(15.0, 25.0)) == 0.0);
+        assert_eq!(path.distance_to(pos2(15.0, 10.0)), 5.0);
+
+        let with_hole = PathShape::convex_polygon(
+            vec![
+                pos2(0.0, 0.0),
+                pos2(10.0, 0.0),
+                pos2(10.0, 10.0),
+                pos2(0.0, 10.0),
+            ],
+            Color32::RED,
+            Stroke::NONE,
+        )
+        .with_contour(vec![
+            pos2(4.0, 4.0),
+            pos2(6.0, 4.0),
+            pos2(6.0, 6.0),
+            pos2(4.0, 6.0),
+        ])
+        .with_fill_rule(FillRule::EvenOdd);
+        assert!(with_hole.distance_to(pos2(2.0, 2.0)) == 0.0);
+        assert_eq!(with_hole.distance_to(pos2(5.0, 5.0)), 1.0);
+    }
+
+    #[test]
+    fn test_bezier() {
+        let curve = crate::QuadraticBezierShape::from_points_stroke(
+            [pos2(0.0, 0.0), pos2(50.0, 100.0), pos2(100.0, 0.0)],
+            false,
+            Color32::TRANSPARENT,
+            Stroke::new(2.0, Color32::RED),
+        );
+        let shape = Shape::from(curve);
+        assert!(shape.contains_point(pos2(50.0, 50.0)));
+        assert!(!shape.contains_point(pos2(50.0, 40.0)));
+        assert!(!shape.contains_point(pos2(50.0, 0.0)));
+    }
+
+    #[test]
+    fn test_mesh() {
+        let mut mesh = Mesh::default();
+        mesh.add_colored_rect(
+            Rect::from_min_size(pos2(0.0, 0.0), vec2(10.0, 10.0)),
+            Color32::RED,
+        );
+        // Degenerate triangles, e.g. from a zero-width line or a collapsed vertex:
+        for points in [
+            [pos2(20.0, 0.0), pos2(30.0, 0.0), pos2(20.0, 0.0)],
+            [pos2(40.0, 40.0); 3],
+        ] {
+            let idx = mesh.vertices.len() as u32;
+            for pos in points {
+                mesh.colored_vertex(pos, Color32::RED);
+            }
+            mesh.add_triangle(idx, idx + 1, idx + 2);
+        }
+
+        let shape = Shape::mesh(mesh);
+        assert!(shape.contains_point(pos2(5.0, 5.0)));
+        assert_eq!(shape.distance_to(pos2(15.0, 5.0)), 5.0);
+        assert_eq!(shape.distance_to(pos2(25.0, 5.0)), 5.0);
+        assert_eq!(shape.distance_to(pos2(25.0, 0.0)), 0.0);
+        assert_eq!(shape.distance_to(pos2(40.0, 50.0)), 10.0);
+    }
+}
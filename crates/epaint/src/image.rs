@@ -1,5 +1,5 @@
-use crate::{textures::TextureOptions, Color32};
-use std::sync::Arc;
+use crate::{textures::TextureOptions, Color32, Rgba};
+use std::{ops::Range, sync::Arc};
 
 /// An image stored in RAM.
 ///
@@ -259,6 +259,526 @@ impl std::fmt::Debug for ColorImage {
 
 // ----------------------------------------------------------------------------
 
+/// ## Image processing
+impl ColorImage {
+    /// Resample the image to a new size.
+    ///
+    /// The filtering is done in linear space with premultiplied alpha,
+    /// so colors don't bleed from transparent pixels.
+    /// When shrinking, every source pixel contributes to the result (except with [`ResizeFilter::Nearest`]).
+    ///
+    /// The aspect ratio is not preserved; see [`Self::size_to_fit`] for that.
+    pub fn resized(&self, size: [usize; 2], filter: ResizeFilter) -> Self {
+        crate::profile_function!();
+
+        let [src_width, src_height] = self.size;
+        let [width, height] = size;
+        if size == self.size {
+            return self.clone();
+        }
+        if src_width == 0 || src_height == 0 || width == 0 || height == 0 {
+            return Self::new(size, Color32::TRANSPARENT);
+        }
+
+        let pixels = resize_linear(&self.to_linear(), self.size, size, filter);
+        Self::from_linear(size, &pixels)
+    }
+
+    /// The mip chain of this image, starting with the image itself.
+    ///
+    /// Each level is half the size of the previous one (rounded down, but at least one pixel),
+    /// down to 1x1, for a total of [`crate::textures::mip_level_count`] levels.
+    /// Each level is shrunk from the previous one in linear space with [`ResizeFilter::Triangle`].
+    ///
+    /// This is for renderers that can't generate mipmaps on the GPU,
+    /// see [`TextureOptions::mipmap_mode`].
+    /// Use [`Self::update_mipmaps`] after changing a part of the image.
+    pub fn mipmaps(&self) -> Vec<Self> {
+        crate::profile_function!();
+
+        let level_count = crate::textures::mip_level_count(self.size) as usize;
+        let mut levels = Vec::with_capacity(level_count);
+        levels.push(self.clone());
+        if self.width() == 0 || self.height() == 0 {
+            return levels;
+        }
+
+        for _ in 1..level_count {
+            let larger = levels.last().unwrap();
+            let size = [(larger.width() / 2).max(1), (larger.height() / 2).max(1)];
+            let mut level = Self::new(size, Color32::TRANSPARENT);
+            let mip = MipWeights::new(larger.size, size);
+            mip.shrink(larger, &mut level, 0..size[0], 0..size[1]);
+            levels.push(level);
+        }
+        levels
+    }
+
+    /// Update the smaller levels of a mip chain from [`Self::mipmaps`]
+    /// after the pixels of the first level changed in the rectangle at `pos` of the given `size`.
+    ///
+    /// Only the pixels that depend on the changed ones are recomputed,
+    /// with the same result as recomputing the whole chain.
+    ///
+    /// Returns the position and size of the changed rectangle of each of the smaller levels,
+    /// starting with the second level.
+    pub fn update_mipmaps(
+        levels: &mut [Self],
+        pos: [usize; 2],
+        size: [usize; 2],
+    ) -> Vec<([usize; 2], [usize; 2])> {
+        crate::profile_function!();
+
+        let mut x_range = pos[0]..pos[0] + size[0];
+        let mut y_range = pos[1]..pos[1] + size[1];
+        let mut changed = Vec::with_capacity(levels.len().saturating_sub(1));
+        for index in 1..levels.len() {
+            let (larger, smaller) = levels.split_at_mut(index);
+            let (larger, level) = (&larger[index - 1], &mut smaller[0]);
+            let mip = MipWeights::new(larger.size, level.size);
+            x_range = affected_range(&mip.x_weights, x_range);
+            y_range = affected_range(&mip.y_weights, y_range);
+            mip.shrink(larger, level, x_range.clone(), y_range.clone());
+            changed.push((
+                [x_range.start, y_range.start],
+                [x_range.len(), y_range.len()],
+            ));
+        }
+        changed
+    }
+
+    /// The largest size with the same aspect ratio as this image that fits within `max_size`.
+    ///
+    /// Never larger than the image itself, and never smaller than one pixel.
+    pub fn size_to_fit(&self, max_size: [usize; 2]) -> [usize; 2] {
+        let [width, height] = self.size;
+        if width <= max_size[0] && height <= max_size[1] {
+            return self.size;
+        }
+        let scale = (max_size[0] as f64 / width as f64).min(max_size[1] as f64 / height as f64);
+        [
+            ((width as f64 * scale).round() as usize).max(1),
+            ((height as f64 * scale).round() as usize).max(1),
+        ]
+    }
+
+    /// Mirror the image left-to-right.
+    pub fn flip_horizontally(&mut self) {
+        let width = self.size[0];
+        if width > 0 {
+            for row in self.pixels.chunks_exact_mut(width) {
+                row.reverse();
+            }
+        }
+    }
+
+    /// Mirror the image top-to-bottom.
+    pub fn flip_vertically(&mut self) {
+        let [width, height] = self.size;
+        for y in 0..height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((height - 1 - y) * width);
+            top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+        }
+    }
+
+    /// Rotate the image a quarter turn clockwise, swapping its width and height.
+    pub fn rotate_90_cw(&mut self) {
+        let [width, height] = self.size;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..width {
+            for x in 0..height {
+                pixels.push(self.pixels[(height - 1 - x) * width + y]);
+            }
+        }
+        *self = Self {
+            size: [height, width],
+            pixels,
+        };
+    }
+
+    /// Rotate the image a quarter turn counter-clockwise, swapping its width and height.
+    pub fn rotate_90_ccw(&mut self) {
+        let [width, height] = self.size;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..width {
+            for x in 0..height {
+                pixels.push(self.pixels[x * width + (width - 1 - y)]);
+            }
+        }
+        *self = Self {
+            size: [height, width],
+            pixels,
+        };
+    }
+
+    /// Rotate the image half a turn.
+    pub fn rotate_180(&mut self) {
+        self.pixels.reverse();
+    }
+
+    /// Draw another image onto this one, with its top-left corner at `pos`.
+    ///
+    /// The parts of `image` that end up outside of this image are ignored,
+    /// so `pos` can be negative.
+    pub fn blit(&mut self, image: &Self, pos: [isize; 2], blend_mode: BlendMode) {
+        let [width, height] = self.size.map(|size| size as isize);
+        let [src_width, src_height] = image.size.map(|size| size as isize);
+
+        let x_range = (-pos[0]).max(0)..(width - pos[0]).min(src_width);
+        let y_range = (-pos[1]).max(0)..(height - pos[1]).min(src_height);
+        if x_range.is_empty() {
+            return;
+        }
+
+        for src_y in y_range {
+            let y = src_y + pos[1];
+            let src_row = (src_y * src_width) as usize;
+            let row = (y * width) as usize;
+            for src_x in x_range.clone() {
+                let x = src_x + pos[0];
+                let dst = &mut self.pixels[row + x as usize];
+                *dst = blend_mode.blend(*dst, image.pixels[src_row + src_x as usize]);
+            }
+        }
+    }
+
+    /// The pixels as flat un-multiplied sRGBA data, e.g. for saving to an image file.
+    ///
+    /// The inverse of [`Self::from_rgba_unmultiplied`].
+    pub fn to_rgba_unmultiplied(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| color.to_srgba_unmultiplied())
+            .collect()
+    }
+
+    /// The pixels as flat premultiplied sRGBA data.
+    ///
+    /// The inverse of [`Self::from_rgba_premultiplied`].
+    pub fn to_rgba_premultiplied(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| color.to_array())
+            .collect()
+    }
+
+    /// The pixels in linear space, with premultiplied alpha.
+    ///
+    /// This is what you want to do math on colors, e.g. for blurring or mixing.
+    pub fn to_linear(&self) -> Vec<Rgba> {
+        self.pixels.iter().map(|&color| Rgba::from(color)).collect()
+    }
+
+    /// Create a [`ColorImage`] from pixels in linear space, with premultiplied alpha.
+    ///
+    /// Values are clamped to the valid range.
+    /// The inverse of [`Self::to_linear`].
+    ///
+    /// Panics if `size[0] * size[1] != pixels.len()`.
+    pub fn from_linear(size: [usize; 2], pixels: &[Rgba]) -> Self {
+        assert_eq!(size[0] * size[1], pixels.len());
+        let pixels = pixels.iter().copied().map(color_from_linear).collect();
+        Self { size, pixels }
+    }
+
+    /// Convert the image to shades of gray, keeping the alpha.
+    pub fn to_grayscale(&self) -> Self {
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|&color| {
+                let rgba = Rgba::from(color);
+                let l = rgba.intensity(); // Already multiplied by alpha
+                Color32::from(Rgba::from_rgba_premultiplied(l, l, l, rgba.a()))
+            })
+            .collect();
+        Self {
+            size: self.size,
+            pixels,
+        }
+    }
+}
+
+/// How to resample an image, see [`ColorImage::resized`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ResizeFilter {
+    /// Pick the nearest pixel. Fast, and keeps pixel art crisp.
+    Nearest,
+
+    /// Linear interpolation (a "tent" filter). Smooth, but a bit blurry.
+    Triangle,
+
+    /// A cubic filter that is sharper than [`Self::Triangle`], with little ringing.
+    #[default]
+    CatmullRom,
+
+    /// The sharpest filter, but slower and with some ringing around hard edges.
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    /// How far from the center the filter reaches, in pixels of the source image when enlarging.
+    fn support(self) -> f32 {
+        match self {
+            Self::Nearest => 0.5,
+            Self::Triangle => 1.0,
+            Self::CatmullRom => 2.0,
+            Self::Lanczos3 => 3.0,
+        }
+    }
+
+    fn kernel(self, x: f32) -> f32 {
+        fn sinc(x: f32) -> f32 {
+            if x == 0.0 {
+                1.0
+            } else {
+                let x = x * std::f32::consts::PI;
+                x.sin() / x
+            }
+        }
+
+        let x = x.abs();
+        match self {
+            Self::Nearest => {
+                if x <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Triangle => (1.0 - x).max(0.0),
+            Self::CatmullRom => {
+                if x < 1.0 {
+                    (1.5 * x - 2.5) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((-0.5 * x + 2.5) * x - 4.0) * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Lanczos3 => {
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Separable resampling of linear pixels, see [`ColorImage::resized`].
+fn resize_linear(
+    linear: &[Rgba],
+    [src_width, src_height]: [usize; 2],
+    [width, height]: [usize; 2],
+    filter: ResizeFilter,
+) -> Vec<Rgba> {
+    // First resize each row:
+    let x_weights = resample_weights(src_width, width, filter);
+    let mut resized_rows = Vec::with_capacity(width * src_height);
+    for row in linear.chunks_exact(src_width) {
+        for (start, weights) in &x_weights {
+            let sources = row[*start..].iter();
+            resized_rows.push(weighted_sum(sources.zip(weights)));
+        }
+    }
+
+    // …then each column:
+    let y_weights = resample_weights(src_height, height, filter);
+    let mut pixels = Vec::with_capacity(width * height);
+    for (start, weights) in &y_weights {
+        for x in 0..width {
+            let sources = resized_rows[start * width + x..].iter().step_by(width);
+            pixels.push(weighted_sum(sources.zip(weights)));
+        }
+    }
+    pixels
+}
+
+/// For each pixel along one axis of the resized image:
+/// the first source pixel it samples, and the weights of it and the following source pixels.
+fn resample_weights(
+    src_len: usize,
+    dst_len: usize,
+    filter: ResizeFilter,
+) -> Vec<(usize, Vec<f32>)> {
+    let scale = src_len as f32 / dst_len as f32;
+
+    // When shrinking, widen the filter so that it covers all the source pixels:
+    let filter_scale = scale.max(1.0);
+    let radius = filter.support() * filter_scale;
+
+    (0..dst_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            if filter == ResizeFilter::Nearest {
+                return ((center as usize).min(src_len - 1), vec![1.0]);
+            }
+
+            let start = (center - radius).floor().max(0.0) as usize;
+            let end = ((center + radius).ceil() as usize).min(src_len);
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| filter.kernel((j as f32 + 0.5 - center) / filter_scale))
+                .collect();
+            let sum: f32 = weights.iter().sum();
+            if sum != 0.0 {
+                for weight in &mut weights {
+                    *weight /= sum;
+                }
+            }
+            (start, weights)
+        })
+        .collect()
+}
+
+/// Clamps to the valid range, see [`ColorImage::from_linear`].
+fn color_from_linear(rgba: Rgba) -> Color32 {
+    let a = rgba.a().clamp(0.0, 1.0);
+    let [r, g, b, _] = rgba.to_array().map(|c| c.clamp(0.0, a));
+    Color32::from(Rgba::from_rgba_premultiplied(r, g, b, a))
+}
+
+/// How to shrink one level of a mip chain into the next, see [`ColorImage::mipmaps`].
+struct MipWeights {
+    x_weights: Vec<(usize, Vec<f32>)>,
+    y_weights: Vec<(usize, Vec<f32>)>,
+}
+
+impl MipWeights {
+    fn new([src_width, src_height]: [usize; 2], [width, height]: [usize; 2]) -> Self {
+        Self {
+            x_weights: resample_weights(src_width, width, ResizeFilter::Triangle),
+            y_weights: resample_weights(src_height, height, ResizeFilter::Triangle),
+        }
+    }
+
+    /// Shrink `src` into the given pixels of `dst`.
+    ///
+    /// The same math as [`resize_linear`], but only for some of the pixels.
+    fn shrink(
+        &self,
+        src: &ColorImage,
+        dst: &mut ColorImage,
+        x_range: Range<usize>,
+        y_range: Range<usize>,
+    ) {
+        if x_range.is_empty() || y_range.is_empty() {
+            return;
+        }
+
+        // First resize the source rows we need…
+        let width = x_range.len();
+        let first_row = self.y_weights[y_range.start].0;
+        let (last_start, last_weights) = &self.y_weights[y_range.end - 1];
+        let rows = first_row..last_start + last_weights.len();
+        let mut resized_rows = Vec::with_capacity(width * rows.len());
+        for row in
+            src.pixels[rows.start * src.width()..rows.end * src.width()].chunks_exact(src.width())
+        {
+            let row: Vec<Rgba> = row.iter().map(|&color| Rgba::from(color)).collect();
+            for (start, weights) in &self.x_weights[x_range.clone()] {
+                resized_rows.push(weighted_sum(row[*start..].iter().zip(weights)));
+            }
+        }
+
+        // …then the columns:
+        let dst_width = dst.width();
+        for y in y_range {
+            let (start, weights) = &self.y_weights[y];
+            for (i, x) in x_range.clone().enumerate() {
+                let sources = resized_rows[(start - first_row) * width + i..]
+                    .iter()
+                    .step_by(width);
+                dst.pixels[y * dst_width + x] =
+                    color_from_linear(weighted_sum(sources.zip(weights)));
+            }
+        }
+    }
+}
+
+/// The pixels along one axis of the resized image that sample any of the source pixels in `src_range`.
+fn affected_range(weights: &[(usize, Vec<f32>)], src_range: Range<usize>) -> Range<usize> {
+    let affected = |(start, weights): &(usize, Vec<f32>)| {
+        *start < src_range.end && src_range.start < start + weights.len()
+    };
+    match weights.iter().position(affected) {
+        Some(first) => first..weights.iter().rposition(affected).unwrap_or(first) + 1,
+        None => 0..0,
+    }
+}
+
+fn weighted_sum<'a>(samples: impl Iterator<Item = (&'a Rgba, &'a f32)>) -> Rgba {
+    samples.fold(Rgba::TRANSPARENT, |sum, (&color, &weight)| {
+        sum + color * weight
+    })
+}
+
+/// How [`ColorImage::blit`] combines the pixels of two images.
+///
+/// Like when egui paints, the blending is done in gamma space with premultiplied alpha.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum BlendMode {
+    /// Replace the pixels.
+    Replace,
+
+    /// Paint on top, letting the pixels below show through where it is transparent.
+    #[default]
+    Over,
+
+    /// Add the colors, e.g. for glows and highlights.
+    Add,
+
+    /// Multiply the colors, e.g. for shading and tinting.
+    Multiply,
+}
+
+impl BlendMode {
+    /// Combine a `src` pixel painted onto a `dst` pixel.
+    pub fn blend(self, dst: Color32, src: Color32) -> Color32 {
+        /// `a * b / 255`, rounded.
+        fn mul(a: u8, b: u8) -> u32 {
+            (a as u32 * b as u32 + 127) / 255
+        }
+
+        let [sr, sg, sb, sa] = src.to_array();
+        let [dr, dg, db, da] = dst.to_array();
+        match self {
+            Self::Replace => src,
+            Self::Over => {
+                let over = |s: u8, d: u8| (s as u32 + mul(d, 255 - sa)).min(255) as u8;
+                Color32::from_rgba_premultiplied(
+                    over(sr, dr),
+                    over(sg, dg),
+                    over(sb, db),
+                    over(sa, da),
+                )
+            }
+            Self::Add => Color32::from_rgba_premultiplied(
+                sr.saturating_add(dr),
+                sg.saturating_add(dg),
+                sb.saturating_add(db),
+                sa.saturating_add(da),
+            ),
+            Self::Multiply => {
+                // Where only one of the images is opaque, it is kept as is:
+                let multiply =
+                    |s: u8, d: u8| (mul(s, d) + mul(s, 255 - da) + mul(d, 255 - sa)).min(255) as u8;
+                Color32::from_rgba_premultiplied(
+                    multiply(sr, dr),
+                    multiply(sg, dg),
+                    multiply(sb, db),
+                    (sa as u32 + mul(da, 255 - sa)).min(255) as u8,
+                )
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 /// A single-channel image designed for the font texture.
 ///
 /// Each value represents "coverage", i.e. how much a texel is covered by a character.
@@ -310,6 +830,15 @@ impl FontImage {
         })
     }
 
+    /// Resize the image, keeping the existing pixels.
+    ///
+    /// Any new area is transparent.
+    pub(crate) fn resize_height(&mut self, height: usize) {
+        self.size[1] = height;
+        let num_pixels = self.width() * self.height();
+        self.pixels.resize(num_pixels, 0.0);
+    }
+
     /// Clone a sub-region as a new image.
     pub fn region(&self, [x, y]: [usize; 2], [w, h]: [usize; 2]) -> Self {
         assert!(x + w <= self.width());
@@ -409,3 +938,222 @@ impl ImageDelta {
         self.pos.is_none()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An image where every pixel is unique.
+    fn numbered_image([width, height]: [usize; 2]) -> ColorImage {
+        let pixels = (0..width * height)
+            .map(|i| Color32::from_rgb(i as u8, (i / 256) as u8, 0))
+            .collect();
+        ColorImage {
+            size: [width, height],
+            pixels,
+        }
+    }
+
+    #[test]
+    fn test_flip_and_rotate() {
+        let original = numbered_image([3, 2]);
+
+        let mut image = original.clone();
+        image.rotate_90_cw();
+        assert_eq!(image.size, [2, 3]);
+        assert_eq!(image[(1, 0)], original[(0, 0)]);
+        assert_eq!(image[(0, 0)], original[(0, 1)]);
+        image.rotate_90_ccw();
+        assert_eq!(image, original);
+
+        let mut twice = original.clone();
+        twice.rotate_90_cw();
+        twice.rotate_90_cw();
+        let mut flipped = original.clone();
+        flipped.flip_horizontally();
+        assert_eq!(flipped[(0, 0)], original[(2, 0)]);
+        flipped.flip_vertically();
+        assert_eq!(flipped[(0, 0)], original[(2, 1)]);
+        let mut half_turn = original.clone();
+        half_turn.rotate_180();
+        assert_eq!(twice, half_turn);
+        assert_eq!(flipped, half_turn);
+    }
+
+    #[test]
+    fn test_resize() {
+        // A uniform color stays the same with every filter:
+        let color = Color32::from_rgba_unmultiplied(200, 100, 50, 128);
+        let uniform = ColorImage::new([7, 5], color);
+        for filter in [
+            ResizeFilter::Nearest,
+            ResizeFilter::Triangle,
+            ResizeFilter::CatmullRom,
+            ResizeFilter::Lanczos3,
+        ] {
+            for size in [[3, 2], [7, 5], [20, 11]] {
+                let resized = uniform.resized(size, filter);
+                assert_eq!(resized.size, size);
+                for pixel in &resized.pixels {
+                    let diff = (0..4).map(|i| pixel[i].abs_diff(color[i])).max().unwrap();
+                    assert!(diff <= 1, "{filter:?} {size:?}: {pixel:?} != {color:?}");
+                }
+            }
+        }
+
+        // Shrinking averages all the pixels, in linear space:
+        let mut checkers = ColorImage::new([4, 4], Color32::BLACK);
+        for y in 0..4 {
+            for x in 0..4 {
+                if (x + y) % 2 == 0 {
+                    checkers[(x, y)] = Color32::WHITE;
+                }
+            }
+        }
+        let gray = checkers.resized([1, 1], ResizeFilter::Triangle).pixels[0];
+        assert_eq!(gray, Color32::from(Rgba::from_gray(0.5)));
+
+        // Nearest keeps the original pixels:
+        let image = numbered_image([3, 2]);
+        let enlarged = image.resized([6, 4], ResizeFilter::Nearest);
+        assert_eq!(enlarged[(5, 3)], image[(2, 1)]);
+        assert_eq!(enlarged[(2, 1)], image[(1, 0)]);
+    }
+
+    #[test]
+    fn test_mipmaps() {
+        assert_eq!(crate::textures::mip_level_count([0, 0]), 1);
+        assert_eq!(crate::textures::mip_level_count([1, 1]), 1);
+        assert_eq!(crate::textures::mip_level_count([256, 1]), 9);
+        assert_eq!(crate::textures::mip_level_count([5, 300]), 9);
+
+        let color = Color32::from_rgba_unmultiplied(200, 100, 50, 128);
+        let levels = ColorImage::new([5, 3], color).mipmaps();
+        let sizes: Vec<_> = levels.iter().map(|level| level.size).collect();
+        assert_eq!(sizes, vec![[5, 3], [2, 1], [1, 1]]);
+        for level in &levels {
+            assert!(level.pixels.iter().all(|&pixel| pixel == color));
+        }
+
+        // A black and white checkerboard averages to about a gray (in linear space):
+        let checkerboard = ColorImage {
+            size: [8, 8],
+            pixels: (0..64)
+                .map(|i| {
+                    if (i % 8 + i / 8) % 2 == 0 {
+                        Color32::BLACK
+                    } else {
+                        Color32::WHITE
+                    }
+                })
+                .collect(),
+        };
+        let levels = checkerboard.mipmaps();
+        assert_eq!(levels.len(), 4);
+        let gray = Color32::from(Rgba::from_gray(0.5));
+        for level in &levels[1..] {
+            for pixel in &level.pixels {
+                assert!((pixel.r() as i32 - gray.r() as i32).abs() <= 4, "{pixel:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_mipmaps() {
+        let pixel = |i: usize| {
+            let [r, g, b, a] = [i * 7, i * 13, i * 29, 128 + i * 3].map(|c| (c % 256) as u8);
+            Color32::from_rgba_unmultiplied(r, g, b, a)
+        };
+        let image = ColorImage {
+            size: [37, 21],
+            pixels: (0..37 * 21).map(pixel).collect(),
+        };
+        let mut levels = image.mipmaps();
+
+        let patch = ColorImage::new([3, 2], Color32::RED);
+        levels[0].blit(&patch, [20, 10], BlendMode::Replace);
+        let changed = ColorImage::update_mipmaps(&mut levels, [20, 10], patch.size);
+        assert_eq!(
+            levels,
+            levels[0].mipmaps(),
+            "Same as recomputing everything"
+        );
+
+        assert_eq!(changed.len(), levels.len() - 1);
+        for (level, (pos, size)) in levels[1..].iter().zip(changed) {
+            assert!(size[0] > 0 && size[1] > 0);
+            assert!(pos[0] + size[0] <= level.width() && pos[1] + size[1] <= level.height());
+        }
+        let ([x, y], [width, height]) =
+            ColorImage::update_mipmaps(&mut levels, [20, 10], [3, 2])[0];
+        assert!(
+            x > 0 && y > 0 && width < 5 && height < 5,
+            "Only the pixels around the patch"
+        );
+    }
+
+    #[test]
+    fn test_size_to_fit() {
+        let image = ColorImage::new([400, 100], Color32::WHITE);
+        assert_eq!(image.size_to_fit([200, 200]), [200, 50]);
+        assert_eq!(image.size_to_fit([1000, 20]), [80, 20]);
+        assert_eq!(image.size_to_fit([1000, 1000]), [400, 100]);
+    }
+
+    #[test]
+    fn test_blit() {
+        let mut image = ColorImage::new([4, 4], Color32::BLACK);
+        let white = ColorImage::new([2, 2], Color32::WHITE);
+
+        // Partially outside the image:
+        image.blit(&white, [-1, 3], BlendMode::Over);
+        assert_eq!(image[(0, 3)], Color32::WHITE);
+        assert_eq!(image[(1, 3)], Color32::BLACK);
+        assert_eq!(image[(0, 2)], Color32::BLACK);
+
+        // Completely outside the image:
+        image.blit(&white, [4, 0], BlendMode::Over);
+        image.blit(&white, [0, -2], BlendMode::Over);
+        assert_eq!(
+            image
+                .pixels
+                .iter()
+                .filter(|&&c| c == Color32::WHITE)
+                .count(),
+            1
+        );
+
+        let half_white = Color32::from_rgba_premultiplied(128, 128, 128, 128);
+        let half_white_image = ColorImage::new([1, 1], half_white);
+        image.blit(&half_white_image, [1, 1], BlendMode::Over);
+        assert_eq!(image[(1, 1)], Color32::from_gray(128));
+        image.blit(&half_white_image, [1, 1], BlendMode::Replace);
+        assert_eq!(image[(1, 1)], half_white);
+
+        let red = ColorImage::new([1, 1], Color32::RED);
+        image.blit(&red, [0, 3], BlendMode::Multiply);
+        assert_eq!(image[(0, 3)], Color32::RED);
+        image.blit(&red, [2, 2], BlendMode::Add);
+        assert_eq!(image[(2, 2)], Color32::RED);
+    }
+
+    #[test]
+    fn test_color_conversions() {
+        let rgba = [255, 128, 0, 255, 10, 20, 30, 255];
+        let image = ColorImage::from_rgba_unmultiplied([2, 1], &rgba);
+        assert_eq!(image.to_rgba_unmultiplied(), rgba);
+        assert_eq!(
+            ColorImage::from_rgba_premultiplied([2, 1], &image.to_rgba_premultiplied()),
+            image
+        );
+        assert_eq!(
+            ColorImage::from_linear(image.size, &image.to_linear()),
+            image
+        );
+
+        let gray = image.to_grayscale();
+        for pixel in &gray.pixels {
+            assert!(pixel.r() == pixel.g() && pixel.g() == pixel.b());
+        }
+    }
+}
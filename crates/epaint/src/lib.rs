@@ -23,17 +23,25 @@
 #![allow(clippy::float_cmp)]
 #![allow(clippy::manual_range_contains)]
 
+mod bez_path;
 mod bezier;
+mod clip_shape;
 pub mod color;
+pub mod gradient;
+mod hit_test;
 pub mod image;
 mod margin;
 mod mesh;
 pub mod mutex;
+mod nine_slice;
 mod shadow;
 mod shape;
 pub mod shape_transform;
 pub mod stats;
 mod stroke;
+#[cfg(feature = "svg")]
+pub mod svg;
+pub mod tessellation_cache;
 pub mod tessellator;
 pub mod text;
 mod texture_atlas;
@@ -42,21 +50,26 @@ pub mod textures;
 pub mod util;
 
 pub use self::{
+    bez_path::{BezPathShape, ParseSvgPathError, PathCommand},
     bezier::{CubicBezierShape, QuadraticBezierShape},
+    clip_shape::ClipShape,
     color::ColorMode,
-    image::{ColorImage, FontImage, ImageData, ImageDelta},
+    gradient::Gradient,
+    image::{BlendMode, ColorImage, FontImage, ImageData, ImageDelta, ResizeFilter},
     margin::Margin,
     mesh::{Mesh, Mesh16, Vertex},
+    nine_slice::{NineSlice, NineSliceFill},
     shadow::Shadow,
     shape::{
-        CircleShape, EllipseShape, PaintCallback, PaintCallbackInfo, PathShape, RectShape,
-        Rounding, Shape, TextShape,
+        CircleShape, ClippedToShape, EllipseShape, FillRule, GradientShape, PaintCallback,
+        PaintCallbackInfo, PathShape, RectShape, Rounding, Shape, TextShape,
     },
     stats::PaintStats,
-    stroke::{PathStroke, Stroke},
+    stroke::{LineCap, LineJoin, PathStroke, Stroke},
+    tessellation_cache::TessellationCache,
     tessellator::{TessellationOptions, Tessellator},
     text::{FontFamily, FontId, Fonts, Galley},
-    texture_atlas::TextureAtlas,
+    texture_atlas::{AtlasPos, FontPage, TextureAtlas},
     texture_handle::TextureHandle,
     textures::TextureManager,
 };
@@ -0,0 +1,303 @@
+use crate::{pos2, Color32, Margin, Mesh, Rect, TextureId, Vec2};
+
+/// How the edges and center of a [`NineSlice`] fill the space between the corners.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum NineSliceFill {
+    /// Stretch the edges and center to fit.
+    #[default]
+    Stretch,
+
+    /// Repeat the edges and center at their original size (times [`NineSlice::scale`]),
+    /// cutting off the last tile.
+    Tile,
+}
+
+/// Paint an image so it can be resized without distorting its border,
+/// also known as a nine-patch.
+///
+/// The image is cut into a 3x3 grid by the [`Self::insets`].
+/// The four corners are painted at their original size,
+/// the four edges are stretched (or tiled) along one axis,
+/// and the center is stretched (or tiled) along both.
+///
+/// This is useful for skinning buttons and frames with bitmap borders.
+/// The result is a single [`Mesh`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NineSlice {
+    /// How far in from each side of the image the border goes, in texels.
+    pub insets: Margin,
+
+    /// The size of one texel of the border, in points.
+    ///
+    /// The borders are shrunk if they don't fit in the painted rectangle.
+    pub scale: f32,
+
+    /// Stretch or tile the edges and center.
+    pub fill: NineSliceFill,
+}
+
+impl Default for NineSlice {
+    fn default() -> Self {
+        Self {
+            insets: Margin::ZERO,
+            scale: 1.0,
+            fill: NineSliceFill::Stretch,
+        }
+    }
+}
+
+impl NineSlice {
+    /// The border insets are in texels.
+    pub fn new(insets: impl Into<Margin>) -> Self {
+        Self {
+            insets: insets.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The size of one texel of the border, in points. Default: `1.0`.
+    #[inline]
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Stretch or tile the edges and center. Default: [`NineSliceFill::Stretch`].
+    #[inline]
+    pub fn fill(mut self, fill: NineSliceFill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Nine-slice the `uv` region of a texture of size `texture_size` (in texels) into `rect`.
+    pub fn mesh(
+        &self,
+        rect: Rect,
+        texture_id: TextureId,
+        texture_size: Vec2,
+        uv: Rect,
+        tint: Color32,
+    ) -> Mesh {
+        let mut mesh = Mesh::with_texture(texture_id);
+        self.add_to_mesh(&mut mesh, rect, texture_size, uv, tint);
+        mesh
+    }
+
+    /// Append the nine-slice to an existing mesh, see [`Self::mesh`].
+    pub fn add_to_mesh(
+        &self,
+        mesh: &mut Mesh,
+        rect: Rect,
+        texture_size: Vec2,
+        uv: Rect,
+        tint: Color32,
+    ) {
+        if !rect.is_positive() || texture_size.x <= 0.0 || texture_size.y <= 0.0 {
+            return;
+        }
+
+        let Self {
+            insets,
+            scale,
+            fill,
+        } = *self;
+
+        let uv_insets = Margin {
+            left: insets.left / texture_size.x,
+            right: insets.right / texture_size.x,
+            top: insets.top / texture_size.y,
+            bottom: insets.bottom / texture_size.y,
+        };
+        // The size of the center of the image, in texels:
+        let center_size = uv.size() * texture_size - insets.sum();
+
+        let columns = slices(
+            [rect.left(), rect.right()],
+            [uv.left(), uv.right()],
+            [insets.left * scale, insets.right * scale],
+            [uv_insets.left, uv_insets.right],
+        );
+        let rows = slices(
+            [rect.top(), rect.bottom()],
+            [uv.top(), uv.bottom()],
+            [insets.top * scale, insets.bottom * scale],
+            [uv_insets.top, uv_insets.bottom],
+        );
+
+        let is_empty = |[min, max]: [f32; 2]| max <= min;
+        for (row, (y_range, v_range)) in rows.iter().enumerate() {
+            for (column, (x_range, u_range)) in columns.iter().enumerate() {
+                if is_empty(*x_range) || is_empty(*y_range) {
+                    continue;
+                }
+                let tile = |center: bool, tile_size: f32| {
+                    (center && fill == NineSliceFill::Tile).then_some(tile_size * scale)
+                };
+                let xs = segments(*x_range, *u_range, tile(column == 1, center_size.x));
+                let ys = segments(*y_range, *v_range, tile(row == 1, center_size.y));
+                for &([top, bottom], [v_top, v_bottom]) in &ys {
+                    for &([left, right], [u_left, u_right]) in &xs {
+                        mesh.add_rect_with_uv(
+                            Rect::from_min_max(pos2(left, top), pos2(right, bottom)),
+                            Rect::from_min_max(pos2(u_left, v_top), pos2(u_right, v_bottom)),
+                            tint,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The start, middle and end slices along one axis, as `(position range, uv range)`.
+fn slices(
+    [min, max]: [f32; 2],
+    [uv_min, uv_max]: [f32; 2],
+    [border_min, border_max]: [f32; 2],
+    [uv_border_min, uv_border_max]: [f32; 2],
+) -> [([f32; 2], [f32; 2]); 3] {
+    // Shrink the borders proportionally if they don't fit:
+    let available = max - min;
+    let shrink = if border_min + border_max > available {
+        available / (border_min + border_max)
+    } else {
+        1.0
+    };
+    let inner_min = min + border_min * shrink;
+    let inner_max = max - border_max * shrink;
+    let uv_inner_min = uv_min + uv_border_min;
+    let uv_inner_max = uv_max - uv_border_max;
+
+    [
+        ([min, inner_min], [uv_min, uv_inner_min]),
+        ([inner_min, inner_max], [uv_inner_min, uv_inner_max]),
+        ([inner_max, max], [uv_inner_max, uv_max]),
+    ]
+}
+
+/// Split a slice into tiles of `tile_size` points, or keep it whole if `None`.
+fn segments(
+    [min, max]: [f32; 2],
+    [uv_min, uv_max]: [f32; 2],
+    tile_size: Option<f32>,
+) -> Vec<([f32; 2], [f32; 2])> {
+    /// Avoid huge meshes from tiny tiles.
+    const MAX_TILES: f32 = 1024.0;
+
+    let Some(tile_size) =
+        tile_size.filter(|&tile_size| 0.0 < tile_size && (max - min) / tile_size <= MAX_TILES)
+    else {
+        return vec![([min, max], [uv_min, uv_max])];
+    };
+
+    let mut segments = vec![];
+    let mut start = min;
+    while start < max {
+        let end = (start + tile_size).min(max);
+        let uv_end = uv_min + (uv_max - uv_min) * (end - start) / tile_size;
+        segments.push(([start, end], [uv_min, uv_end]));
+        start = end;
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use emath::vec2;
+
+    fn quads(mesh: &Mesh) -> Vec<(Rect, Rect)> {
+        mesh.vertices
+            .chunks_exact(4)
+            .map(|quad| {
+                (
+                    Rect::from_two_pos(quad[0].pos, quad[3].pos),
+                    Rect::from_two_pos(quad[0].uv, quad[3].uv),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stretch() {
+        let nine_slice = NineSlice::new(4.0);
+        let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+        let rect = Rect::from_min_size(pos2(10.0, 20.0), vec2(100.0, 50.0));
+        let mesh = nine_slice.mesh(
+            rect,
+            TextureId::default(),
+            vec2(16.0, 16.0),
+            uv,
+            Color32::WHITE,
+        );
+        assert!(mesh.is_valid());
+
+        let rects = quads(&mesh);
+        assert_eq!(rects.len(), 9);
+
+        // Corners keep their size, the center stretches:
+        let (top_left, top_left_uv) = rects[0];
+        assert_eq!(top_left, Rect::from_min_size(rect.min, vec2(4.0, 4.0)));
+        assert_eq!(
+            top_left_uv,
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(0.25, 0.25))
+        );
+        let (center, center_uv) = rects[4];
+        assert_eq!(center, rect.shrink(4.0));
+        assert_eq!(
+            center_uv,
+            Rect::from_min_max(pos2(0.25, 0.25), pos2(0.75, 0.75))
+        );
+        let (bottom_right, _) = rects[8];
+        assert_eq!(bottom_right.max, rect.max);
+
+        // Scaled borders that don't fit are shrunk:
+        let small = Rect::from_min_size(pos2(0.0, 0.0), vec2(8.0, 100.0));
+        let mesh = nine_slice.scale(2.0).mesh(
+            small,
+            TextureId::default(),
+            vec2(16.0, 16.0),
+            uv,
+            Color32::WHITE,
+        );
+        let rects = quads(&mesh);
+        assert_eq!(rects.len(), 6, "There is no room for the center column");
+        assert_eq!(rects[0].0.size(), vec2(4.0, 8.0));
+    }
+
+    #[test]
+    fn test_tile() {
+        let nine_slice = NineSlice::new(4.0).fill(NineSliceFill::Tile);
+        let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+
+        // The center is 8x8 texels, so 20 points is two and a half tiles:
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(28.0, 16.0));
+        let mesh = nine_slice.mesh(
+            rect,
+            TextureId::default(),
+            vec2(16.0, 16.0),
+            uv,
+            Color32::WHITE,
+        );
+        assert!(mesh.is_valid());
+        let rects = quads(&mesh);
+        assert_eq!(rects.len(), 3 * (2 + 3));
+
+        let center: Vec<_> = rects
+            .iter()
+            .filter(|(rect, _)| rect.top() == 4.0 && rect.left() >= 4.0 && rect.right() <= 24.0)
+            .collect();
+        assert_eq!(center.len(), 3);
+        assert_eq!(
+            center[2].0,
+            Rect::from_min_max(pos2(20.0, 4.0), pos2(24.0, 12.0))
+        );
+        assert_eq!(
+            center[2].1,
+            Rect::from_min_max(pos2(0.25, 0.25), pos2(0.5, 0.75))
+        );
+    }
+}
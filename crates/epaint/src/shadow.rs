@@ -2,11 +2,13 @@ use super::*;
 
 /// The color and fuzziness of a fuzzy shape.
 ///
-/// Can be used for a rectangular shadow with a soft penumbra.
+/// Can be used for a shadow with a soft penumbra, either behind a shape (a drop shadow)
+/// or inside of it (an inset shadow).
 ///
 /// Very similar to a box-shadow in CSS.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Shadow {
     /// Move the shadow by this much.
     ///
@@ -24,6 +26,12 @@ pub struct Shadow {
 
     /// Color of the opaque center of the shadow.
     pub color: Color32,
+
+    /// Cast the shadow inwards, inside the shape, like an `inset` box-shadow in CSS.
+    ///
+    /// This makes the shape look sunken, and should be painted on top of its fill.
+    /// This is ignored by [`Self::as_shape`].
+    pub inset: bool,
 }
 
 impl Shadow {
@@ -33,9 +41,12 @@ impl Shadow {
         blur: 0.0,
         spread: 0.0,
         color: Color32::TRANSPARENT,
+        inset: false,
     };
 
     /// The argument is the rectangle of the shadow caster.
+    ///
+    /// This is always a drop shadow, ignoring [`Self::inset`].
     pub fn as_shape(&self, rect: Rect, rounding: impl Into<Rounding>) -> RectShape {
         // tessellator.clip_rect = clip_rect; // TODO(emilk): culling
 
@@ -44,6 +55,7 @@ impl Shadow {
             blur,
             spread,
             color,
+            inset: _,
         } = *self;
 
         let rect = rect.translate(offset).expand(spread);
@@ -52,14 +64,168 @@ impl Shadow {
         RectShape::filled(rect, rounding, color).with_blur_width(blur)
     }
 
+    /// The shadow of the given shape.
+    ///
+    /// Rectangles, circles, ellipses and closed paths are supported,
+    /// and [`Shape::Vec`] gives the shadows of all the shapes in it.
+    /// Other shapes cast no shadow.
+    ///
+    /// A drop shadow should be painted before the shape, and an [inset](Self::inset) shadow after its fill.
+    pub fn cast_by(&self, shape: &Shape) -> Shape {
+        if self.color == Color32::TRANSPARENT {
+            return Shape::Noop;
+        }
+
+        let outline = match shape {
+            Shape::Vec(shapes) => {
+                return Shape::Vec(shapes.iter().map(|shape| self.cast_by(shape)).collect());
+            }
+            Shape::Rect(rect_shape) => {
+                if !self.inset {
+                    return self.as_shape(rect_shape.rect, rect_shape.rounding).into();
+                }
+                let mut outline = vec![];
+                crate::tessellator::path::rounded_rectangle(
+                    &mut outline,
+                    rect_shape.rect,
+                    rect_shape.rounding,
+                );
+                outline
+            }
+            Shape::Circle(circle) => ellipse_outline(circle.center, Vec2::splat(circle.radius)),
+            Shape::Ellipse(ellipse) => ellipse_outline(ellipse.center, ellipse.radius),
+            Shape::Path(path) if path.closed => path.points.clone(),
+            _ => return Shape::Noop,
+        };
+
+        Shape::mesh(self.for_outline(&outline))
+    }
+
+    /// The given shape together with its shadow.
+    ///
+    /// A drop shadow goes behind the shape, and an [inset](Self::inset) shadow between its fill and its stroke.
+    pub fn around(&self, shape: impl Into<Shape>) -> Shape {
+        let shape = shape.into();
+        if self.color == Color32::TRANSPARENT {
+            return shape;
+        }
+        if !self.inset {
+            return Shape::Vec(vec![self.cast_by(&shape), shape]);
+        }
+
+        let (fill, stroke) = match shape {
+            Shape::Rect(rect) => (
+                Shape::Rect(RectShape {
+                    stroke: Stroke::NONE,
+                    ..rect
+                }),
+                Shape::Rect(RectShape {
+                    fill: Color32::TRANSPARENT,
+                    fill_texture_id: Default::default(),
+                    ..rect
+                }),
+            ),
+            Shape::Circle(circle) => (
+                Shape::Circle(CircleShape {
+                    stroke: Stroke::NONE,
+                    ..circle
+                }),
+                Shape::Circle(CircleShape {
+                    fill: Color32::TRANSPARENT,
+                    ..circle
+                }),
+            ),
+            Shape::Ellipse(ellipse) => (
+                Shape::Ellipse(EllipseShape {
+                    stroke: Stroke::NONE,
+                    ..ellipse
+                }),
+                Shape::Ellipse(EllipseShape {
+                    fill: Color32::TRANSPARENT,
+                    ..ellipse
+                }),
+            ),
+            Shape::Path(path) => (
+                Shape::Path(PathShape {
+                    stroke: PathStroke::NONE,
+                    ..path.clone()
+                }),
+                Shape::Path(PathShape {
+                    fill: Color32::TRANSPARENT,
+                    ..path
+                }),
+            ),
+            shape => {
+                let shadow = self.cast_by(&shape);
+                return Shape::Vec(vec![shape, shadow]);
+            }
+        };
+        let shadow = self.cast_by(&fill);
+        Shape::Vec(vec![fill, shadow, stroke])
+    }
+
+    /// The shadow of a closed outline, which may be concave.
+    pub fn for_outline(&self, outline: &[Pos2]) -> Mesh {
+        let mut mesh = Mesh::default();
+
+        let mut points = outline.to_vec();
+        points.dedup();
+        if 2 < points.len() && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 3 || self.color == Color32::TRANSPARENT {
+            return mesh;
+        }
+
+        let normals = outward_normals(&points);
+        // Going further inwards than this could make the outline cross itself:
+        let max_depth = 0.5 * Rect::from_points(&points).size().min_elem();
+
+        let Self {
+            offset,
+            blur,
+            spread,
+            color,
+            inset,
+        } = *self;
+
+        if inset {
+            add_inset_shadow(&mut mesh, &points, &normals, max_depth, *self);
+        } else {
+            if spread <= -max_depth {
+                return mesh;
+            }
+
+            // The blur fades out across the edge, from half of it inside to half of it outside:
+            let blur = blur.clamp(0.0, 2.0 * (max_depth + spread));
+            let points: Vec<Pos2> = points
+                .iter()
+                .zip(&normals)
+                .map(|(&p, &n)| p + offset + spread * n)
+                .collect();
+
+            let mut path = crate::tessellator::Path::default();
+            path.add_line_loop(&points);
+            path.fill_with_rule(&[], FillRule::NonZero, blur, color, &mut mesh);
+        }
+
+        mesh
+    }
+
     /// How much larger than the parent rect are we in each direction?
+    ///
+    /// This is zero for [inset](Self::inset) shadows.
     pub fn margin(&self) -> Margin {
         let Self {
             offset,
             blur,
             spread,
             color: _,
+            inset,
         } = *self;
+        if inset {
+            return Margin::ZERO;
+        }
         Margin {
             left: spread + 0.5 * blur - offset.x,
             right: spread + 0.5 * blur + offset.x,
@@ -68,3 +234,197 @@ impl Shadow {
         }
     }
 }
+
+/// Points around an ellipse, for casting shadows.
+fn ellipse_outline(center: Pos2, radius: Vec2) -> Vec<Pos2> {
+    let n = ((radius.abs().max_elem().sqrt() * 8.0).ceil() as usize).clamp(8, 128);
+    (0..n)
+        .map(|i| {
+            let angle = i as f32 / n as f32 * std::f32::consts::TAU;
+            center + radius * Vec2::angled(angle)
+        })
+        .collect()
+}
+
+/// Miter normals pointing out of the closed outline, with a length such that
+/// moving a point by `d * normal` moves both its edges by `d`.
+fn outward_normals(points: &[Pos2]) -> Vec<Vec2> {
+    let n = points.len();
+    let twice_area: f32 = (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    // Clockwise on screen is positive, and then `rot90` points outwards:
+    let sign = if 0.0 <= twice_area { 1.0 } else { -1.0 };
+
+    let edge_normal = |i: usize| sign * (points[(i + 1) % n] - points[i]).normalized().rot90();
+    (0..n)
+        .map(|i| {
+            let (n0, n1) = (edge_normal((i + n - 1) % n), edge_normal(i));
+            let normal = (n0 + n1) / 2.0;
+            let length_sq = normal.length_sq();
+            if length_sq < 0.1 {
+                // Very sharp corner - don't let the miter get too long:
+                normal.normalized() * 10.0_f32.sqrt()
+            } else {
+                normal / length_sq
+            }
+        })
+        .collect()
+}
+
+/// Shade the inside of the outline, where the light coming through the outline
+/// moved by `offset` and shrunk by `spread` doesn't reach.
+fn add_inset_shadow(
+    mesh: &mut Mesh,
+    points: &[Pos2],
+    normals: &[Vec2],
+    max_depth: f32,
+    shadow: Shadow,
+) {
+    let Shadow {
+        offset,
+        blur,
+        spread,
+        color,
+        inset: _,
+    } = shadow;
+    let blur = blur.max(0.0);
+
+    // Three rings inwards from the outline: where the shadow starts to fade, and where it ends.
+    let n = points.len() as u32;
+    let idx = mesh.vertices.len() as u32;
+    let mut innermost_intensity = 1.0_f32;
+    for (&pos, &normal) in points.iter().zip(normals) {
+        // How far in from the edge the light starts:
+        let depth = spread + offset.dot(-normal.normalized());
+        let intensity = |t: f32| {
+            if 0.0 < blur {
+                ((depth + 0.5 * blur - t) / blur).clamp(0.0, 1.0)
+            } else if t < depth {
+                1.0
+            } else {
+                0.0
+            }
+        };
+
+        let fade_start = (depth - 0.5 * blur).clamp(0.0, max_depth);
+        let fade_end = (depth + 0.5 * blur).clamp(0.0, max_depth);
+        for t in [0.0, fade_start, fade_end] {
+            mesh.colored_vertex(pos - t * normal, color.gamma_multiply(intensity(t)));
+        }
+        innermost_intensity = innermost_intensity.min(intensity(fade_end));
+    }
+
+    for i0 in 0..n {
+        let i1 = (i0 + 1) % n;
+        for ring in 0..2 {
+            let (a, b) = (idx + 3 * i0 + ring, idx + 3 * i1 + ring);
+            mesh.add_triangle(a, b, a + 1);
+            mesh.add_triangle(b, a + 1, b + 1);
+        }
+    }
+
+    if 0.0 < innermost_intensity {
+        // The shadow covers all of the shape:
+        let inner: Vec<Pos2> = (0..n)
+            .map(|i| mesh.vertices[(idx + 3 * i + 2) as usize].pos)
+            .collect();
+        let mut path = crate::tessellator::Path::default();
+        path.add_line_loop(&inner);
+        path.fill_with_rule(
+            &[],
+            FillRule::NonZero,
+            0.0,
+            color.gamma_multiply(innermost_intensity),
+            mesh,
+        );
+    }
+}
+
+#[test]
+fn test_shadow_cast_by() {
+    let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 50.0));
+    let shape = Shape::ellipse_filled(rect.center(), rect.size() / 2.0, Color32::WHITE);
+    let shadow = Shadow {
+        offset: vec2(0.0, 4.0),
+        blur: 8.0,
+        spread: 2.0,
+        color: Color32::BLACK,
+        inset: false,
+    };
+
+    let Shape::Mesh(drop) = shadow.cast_by(&shape) else {
+        panic!("Expected a mesh");
+    };
+    let expected = rect.translate(shadow.offset).expand(6.0);
+    let bounds = drop.calc_bounds();
+    assert!((bounds.min - expected.min).length() < 0.5, "{bounds:?}");
+    assert!((bounds.max - expected.max).length() < 0.5, "{bounds:?}");
+
+    let inset = Shadow {
+        blur: 4.0,
+        spread: 0.0,
+        inset: true,
+        ..shadow
+    };
+    let Shape::Mesh(inner) = inset.cast_by(&shape) else {
+        panic!("Expected a mesh");
+    };
+    assert!(rect.expand(0.01).contains_rect(inner.calc_bounds()));
+
+    // The light comes from above, so the top is darker than the bottom:
+    let alpha_at = |pos: Pos2| {
+        inner
+            .vertices
+            .iter()
+            .min_by(|a, b| a.pos.distance(pos).total_cmp(&b.pos.distance(pos)))
+            .map(|v| v.color.a())
+    };
+    assert!(alpha_at(rect.center_bottom()) < alpha_at(rect.center_top()));
+    assert_eq!(alpha_at(rect.center_bottom()), Some(0));
+}
+
+#[test]
+fn test_shadow_around() {
+    let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 50.0));
+    let shape = Shape::Rect(RectShape::new(
+        rect,
+        4.0,
+        Color32::WHITE,
+        Stroke::new(1.0, Color32::RED),
+    ));
+    let shadow = Shadow {
+        offset: vec2(0.0, 4.0),
+        blur: 8.0,
+        spread: 0.0,
+        color: Color32::BLACK,
+        inset: false,
+    };
+
+    assert_eq!(Shadow::NONE.around(shape.clone()), shape);
+
+    // A drop shadow goes behind the shape:
+    let Shape::Vec(shapes) = shadow.around(shape.clone()) else {
+        panic!("Expected a Vec");
+    };
+    assert_eq!(shapes.len(), 2);
+    assert_eq!(shapes[1], shape);
+
+    // An inset shadow goes between the fill and the stroke:
+    let inset = Shadow {
+        inset: true,
+        ..shadow
+    };
+    let Shape::Vec(shapes) = inset.around(shape) else {
+        panic!("Expected a Vec");
+    };
+    let [Shape::Rect(fill), Shape::Mesh(_), Shape::Rect(stroke)] = shapes.as_slice() else {
+        panic!("Expected a fill, a shadow and a stroke: {shapes:?}");
+    };
+    assert_eq!((fill.fill, fill.stroke), (Color32::WHITE, Stroke::NONE));
+    assert_eq!(stroke.fill, Color32::TRANSPARENT);
+    assert_eq!(stroke.stroke, Stroke::new(1.0, Color32::RED));
+}
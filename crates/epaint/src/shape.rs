@@ -5,7 +5,7 @@ use std::{any::Any, sync::Arc};
 use crate::{
     stroke::PathStroke,
     text::{FontId, Fonts, Galley},
-    Color32, Mesh, Stroke, TextureId,
+    BezPathShape, ClipShape, Color32, Gradient, Mesh, Stroke, TextureId,
 };
 use emath::*;
 
@@ -63,6 +63,15 @@ pub enum Shape {
     /// A cubic [Bézier Curve](https://en.wikipedia.org/wiki/B%C3%A9zier_curve).
     CubicBezier(CubicBezierShape),
 
+    /// Lines and curves with any number of subpaths, like an SVG path.
+    BezPath(BezPathShape),
+
+    /// Another shape filled with a gradient.
+    Gradient(GradientShape),
+
+    /// Another shape clipped to a [`ClipShape`], e.g. a rounded rectangle.
+    Clipped(ClippedToShape),
+
     /// Backend-specific painting.
     Callback(PaintCallback),
 }
@@ -144,6 +153,9 @@ impl Shape {
     }
 
     /// Turn a line into dashes.
+    ///
+    /// Each dash is a separate line segment. For dashes that follow the joins of the line,
+    /// use a [`PathStroke`] with [`PathStroke::with_dashes`] instead.
     pub fn dashed_line(
         path: &[Pos2],
         stroke: impl Into<Stroke>,
@@ -355,6 +367,9 @@ impl Shape {
             Self::Mesh(mesh) => mesh.calc_bounds(),
             Self::QuadraticBezier(bezier) => bezier.visual_bounding_rect(),
             Self::CubicBezier(bezier) => bezier.visual_bounding_rect(),
+            Self::BezPath(bez_path) => bez_path.visual_bounding_rect(),
+            Self::Gradient(gradient_shape) => gradient_shape.visual_bounding_rect(),
+            Self::Clipped(clipped) => clipped.visual_bounding_rect(),
             Self::Callback(custom) => custom.rect,
         }
     }
@@ -368,6 +383,10 @@ impl Shape {
             mesh.texture_id
         } else if let Self::Rect(rect_shape) = self {
             rect_shape.fill_texture_id
+        } else if let Self::Gradient(gradient_shape) = self {
+            gradient_shape.shape.texture_id()
+        } else if let Self::Clipped(clipped) = self {
+            clipped.shape.texture_id()
         } else {
             super::TextureId::default()
         }
@@ -421,6 +440,9 @@ impl Shape {
                 for p in &mut path_shape.points {
                     *p = transform * *p;
                 }
+                for p in path_shape.contours.iter_mut().flatten() {
+                    *p = transform * *p;
+                }
                 path_shape.stroke.width *= transform.scaling;
             }
             Self::Rect(rect_shape) => {
@@ -435,8 +457,11 @@ impl Shape {
                 let galley = Arc::make_mut(&mut text_shape.galley);
                 for row in &mut galley.rows {
                     row.visuals.mesh_bounds = transform.scaling * row.visuals.mesh_bounds;
-                    for v in &mut row.visuals.mesh.vertices {
-                        v.pos = Pos2::new(transform.scaling * v.pos.x, transform.scaling * v.pos.y);
+                    for mesh in row.visuals.meshes_mut() {
+                        for v in &mut mesh.vertices {
+                            v.pos =
+                                Pos2::new(transform.scaling * v.pos.x, transform.scaling * v.pos.y);
+                        }
                     }
                 }
 
@@ -458,6 +483,17 @@ impl Shape {
                 }
                 cubic_curve.stroke.width *= transform.scaling;
             }
+            Self::BezPath(bez_path) => {
+                bez_path.transform_points(|p| transform * p);
+                bez_path.stroke.width *= transform.scaling;
+            }
+            Self::Gradient(gradient_shape) => {
+                gradient_shape.shape.transform(transform);
+            }
+            Self::Clipped(clipped) => {
+                clipped.clip_shape.transform(transform);
+                clipped.shape.transform(transform);
+            }
             Self::Callback(shape) => {
                 shape.rect = transform * shape.rect;
             }
@@ -498,6 +534,12 @@ impl CircleShape {
         }
     }
 
+    /// Fill with a gradient instead of [`Self::fill`], see [`GradientShape`].
+    #[inline]
+    pub fn with_fill_gradient(self, gradient: impl Into<Arc<Gradient>>) -> GradientShape {
+        GradientShape::new(self, gradient)
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
@@ -553,6 +595,12 @@ impl EllipseShape {
         }
     }
 
+    /// Fill with a gradient instead of [`Self::fill`], see [`GradientShape`].
+    #[inline]
+    pub fn with_fill_gradient(self, gradient: impl Into<Arc<Gradient>>) -> GradientShape {
+        GradientShape::new(self, gradient)
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
@@ -586,11 +634,24 @@ pub struct PathShape {
     /// This is required if `fill != TRANSPARENT`.
     pub closed: bool,
 
-    /// Fill is only supported for convex polygons.
+    /// How to fill the closed path.
+    ///
+    /// Unless [`Self::fill_rule`] is changed, this is only supported for convex polygons.
     pub fill: Color32,
 
     /// Color and thickness of the line.
     pub stroke: PathStroke,
+
+    /// Which areas of the closed path are inside, and should be filled.
+    ///
+    /// The default is [`FillRule::Convex`], which is the fastest.
+    pub fill_rule: FillRule,
+
+    /// More closed contours, for instance holes.
+    ///
+    /// They are stroked together with [`Self::points`] (if [`Self::closed`]),
+    /// and filled together with it if [`Self::fill_rule`] is not [`FillRule::Convex`].
+    pub contours: Vec<Vec<Pos2>>,
     // TODO(emilk): Add texture support either by supplying uv for each point,
     // or by some transform from points to uv (e.g. a callback or a linear transform matrix).
 }
@@ -606,6 +667,8 @@ impl PathShape {
             closed: false,
             fill: Default::default(),
             stroke: stroke.into(),
+            fill_rule: FillRule::Convex,
+            contours: Vec::new(),
         }
     }
 
@@ -617,6 +680,8 @@ impl PathShape {
             closed: true,
             fill: Default::default(),
             stroke: stroke.into(),
+            fill_rule: FillRule::Convex,
+            contours: Vec::new(),
         }
     }
 
@@ -634,20 +699,78 @@ impl PathShape {
             closed: true,
             fill: fill.into(),
             stroke: stroke.into(),
+            fill_rule: FillRule::Convex,
+            contours: Vec::new(),
         }
     }
 
+    /// Fill with a gradient instead of [`Self::fill`], see [`GradientShape`].
+    #[inline]
+    pub fn with_fill_gradient(self, gradient: impl Into<Arc<Gradient>>) -> GradientShape {
+        GradientShape::new(self, gradient)
+    }
+
+    /// Set which areas of the closed path are filled.
+    ///
+    /// Use [`FillRule::NonZero`] or [`FillRule::EvenOdd`] to fill concave
+    /// or self-intersecting paths, and paths with holes (see [`Self::with_contour`]).
+    ///
+    /// ```
+    /// # use epaint::{pos2, Color32, FillRule, PathShape, Stroke};
+    /// let square = |min: f32, max: f32| {
+    ///     vec![pos2(min, min), pos2(max, min), pos2(max, max), pos2(min, max)]
+    /// };
+    /// let frame = PathShape::convex_polygon(square(0.0, 10.0), Color32::RED, Stroke::NONE)
+    ///     .with_contour(square(2.0, 8.0))
+    ///     .with_fill_rule(FillRule::EvenOdd);
+    /// ```
+    #[inline]
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Add another closed contour to the path, e.g. a hole. See [`Self::contours`].
+    #[inline]
+    pub fn with_contour(mut self, points: Vec<Pos2>) -> Self {
+        self.contours.push(points);
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
             Rect::NOTHING
         } else {
-            Rect::from_points(&self.points).expand(self.stroke.width / 2.0)
+            let mut rect = Rect::from_points(&self.points);
+            for contour in &self.contours {
+                rect = rect.union(Rect::from_points(contour));
+            }
+            rect.expand(self.stroke.width / 2.0)
         }
     }
 }
 
+/// Which areas of a [`PathShape`] are filled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum FillRule {
+    /// The path is assumed to be a convex polygon, and any [`PathShape::contours`] are not filled.
+    ///
+    /// This is the fastest, but concave paths will be filled incorrectly.
+    #[default]
+    Convex,
+
+    /// A point is inside if the path winds around it a non-zero number of times.
+    ///
+    /// Holes must wind in the opposite direction of the contour around them.
+    NonZero,
+
+    /// A point is inside if a ray from it crosses the path an odd number of times.
+    EvenOdd,
+}
+
 impl From<PathShape> for Shape {
     #[inline(always)]
     fn from(shape: PathShape) -> Self {
@@ -755,6 +878,12 @@ impl RectShape {
         self
     }
 
+    /// Fill with a gradient instead of [`Self::fill`], see [`GradientShape`].
+    #[inline]
+    pub fn with_fill_gradient(self, gradient: impl Into<Arc<Gradient>>) -> GradientShape {
+        GradientShape::new(self, gradient)
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
@@ -774,6 +903,198 @@ impl From<RectShape> for Shape {
     }
 }
 
+// ----------------------------------------------------------------------------
+
+/// A shape filled with a [`Gradient`] instead of its fill color.
+///
+/// Works for [`RectShape`], [`CircleShape`], [`EllipseShape`], closed [`PathShape`]s
+/// and [`BezPathShape`]s. The stroke of the shape is painted as usual.
+/// Other shapes are painted without the gradient.
+///
+/// The gradient is relative to [`Self::fill_rect`].
+///
+/// ```
+/// # use epaint::{pos2, Color32, Gradient, Rect, RectShape, Shape};
+/// let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 20.0));
+/// let shape: Shape = RectShape::filled(rect, 4.0, Color32::WHITE)
+///     .with_fill_gradient(Gradient::horizontal(Color32::RED, Color32::BLUE))
+///     .into();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientShape {
+    pub shape: Box<Shape>,
+    pub gradient: Arc<Gradient>,
+}
+
+impl GradientShape {
+    #[inline]
+    pub fn new(shape: impl Into<Shape>, gradient: impl Into<Arc<Gradient>>) -> Self {
+        Self {
+            shape: Box::new(shape.into()),
+            gradient: gradient.into(),
+        }
+    }
+
+    /// Can [`Self::shape`] be filled with the gradient?
+    pub fn is_fillable(&self) -> bool {
+        match &*self.shape {
+            Shape::Rect(_) | Shape::Circle(_) | Shape::Ellipse(_) | Shape::BezPath(_) => true,
+            Shape::Path(path) => path.closed,
+            _ => false,
+        }
+    }
+
+    /// The bounding rectangle of the filled area (excluding the stroke),
+    /// which the gradient is relative to.
+    pub fn fill_rect(&self) -> Rect {
+        match &*self.shape {
+            Shape::Rect(rect) => rect.rect,
+            Shape::Circle(circle) => {
+                Rect::from_center_size(circle.center, Vec2::splat(2.0 * circle.radius))
+            }
+            Shape::Ellipse(ellipse) => Rect::from_center_size(ellipse.center, 2.0 * ellipse.radius),
+            Shape::Path(path) => {
+                let mut rect = Rect::from_points(&path.points);
+                if path.fill_rule != FillRule::Convex {
+                    for contour in &path.contours {
+                        rect = rect.union(Rect::from_points(contour));
+                    }
+                }
+                rect
+            }
+            Shape::BezPath(path) => path.fill_bounding_rect(),
+            shape => shape.visual_bounding_rect(),
+        }
+    }
+
+    /// [`Self::shape`] filled with a solid color instead of the gradient.
+    ///
+    /// With `stroke: false` the stroke is removed.
+    pub(crate) fn shape_with_fill(&self, fill: Color32, stroke: bool) -> Shape {
+        let mut shape = (*self.shape).clone();
+        match &mut shape {
+            Shape::Rect(RectShape {
+                fill: shape_fill,
+                stroke: shape_stroke,
+                ..
+            })
+            | Shape::Circle(CircleShape {
+                fill: shape_fill,
+                stroke: shape_stroke,
+                ..
+            })
+            | Shape::Ellipse(EllipseShape {
+                fill: shape_fill,
+                stroke: shape_stroke,
+                ..
+            }) => {
+                *shape_fill = fill;
+                if !stroke {
+                    *shape_stroke = Stroke::NONE;
+                }
+            }
+            Shape::Path(PathShape {
+                fill: shape_fill,
+                stroke: shape_stroke,
+                ..
+            })
+            | Shape::BezPath(BezPathShape {
+                fill: shape_fill,
+                stroke: shape_stroke,
+                ..
+            }) => {
+                *shape_fill = fill;
+                if !stroke {
+                    *shape_stroke = PathStroke::NONE;
+                }
+            }
+            _ => {}
+        }
+        shape
+    }
+
+    /// [`Self::shape`] with a white fill where the gradient is painted,
+    /// e.g. for bounding rectangles and hit-testing.
+    pub(crate) fn shape_with_placeholder_fill(&self) -> Shape {
+        if self.is_fillable() && !self.gradient.is_transparent() {
+            self.shape_with_fill(Color32::WHITE, true)
+        } else {
+            self.shape_with_fill(Color32::TRANSPARENT, true)
+        }
+    }
+
+    /// The visual bounding rectangle (includes stroke width)
+    pub fn visual_bounding_rect(&self) -> Rect {
+        self.shape_with_placeholder_fill().visual_bounding_rect()
+    }
+}
+
+impl From<GradientShape> for Shape {
+    #[inline(always)]
+    fn from(shape: GradientShape) -> Self {
+        Self::Gradient(shape)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A shape clipped to a [`ClipShape`], in addition to the clip rectangle of its [`crate::ClippedShape`].
+///
+/// The clipping is done by the [`crate::Tessellator`], so it does not apply to [`PaintCallback`]s.
+///
+/// ```
+/// # use epaint::{pos2, ClipShape, ClippedToShape, Color32, Rect, Shape};
+/// let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 20.0));
+/// let shape: Shape = ClippedToShape::new(
+///     ClipShape::rounded_rect(rect, 8.0),
+///     Shape::rect_filled(rect, 0.0, Color32::WHITE),
+/// )
+/// .into();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClippedToShape {
+    pub clip_shape: ClipShape,
+    pub shape: Box<Shape>,
+}
+
+impl ClippedToShape {
+    #[inline]
+    pub fn new(clip_shape: ClipShape, shape: impl Into<Shape>) -> Self {
+        Self {
+            clip_shape,
+            shape: Box::new(shape.into()),
+        }
+    }
+
+    /// Also clip to `clip_shape`.
+    ///
+    /// Shapes that are already clipped get the intersection of both clip shapes.
+    pub fn clip(shape: Shape, clip_shape: &ClipShape) -> Shape {
+        match shape {
+            Shape::Noop => Shape::Noop,
+            Shape::Clipped(clipped) => Shape::Clipped(Self {
+                clip_shape: clipped.clip_shape.intersect(clip_shape),
+                shape: clipped.shape,
+            }),
+            shape => Shape::Clipped(Self::new(clip_shape.clone(), shape)),
+        }
+    }
+
+    /// The visual bounding rectangle (includes stroke width)
+    pub fn visual_bounding_rect(&self) -> Rect {
+        self.shape
+            .visual_bounding_rect()
+            .intersect(self.clip_shape.bounding_rect())
+    }
+}
+
+impl From<ClippedToShape> for Shape {
+    #[inline(always)]
+    fn from(shape: ClippedToShape) -> Self {
+        Self::Clipped(shape)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 /// How rounded the corners of things should be
@@ -15,25 +15,41 @@ pub fn adjust_colors(
                 adjust_colors(shape, adjust_color);
             }
         }
-        Shape::LineSegment { stroke, points: _ } => match &stroke.color {
-            color::ColorMode::Solid(mut col) => adjust_color(&mut col),
-            color::ColorMode::UV(callback) => {
-                let callback = callback.clone();
-                stroke.color = color::ColorMode::UV(Arc::new(Box::new(move |rect, pos| {
-                    let mut col = callback(rect, pos);
-                    adjust_color(&mut col);
-                    col
-                })));
-            }
-        },
+        Shape::LineSegment { stroke, points: _ } => {
+            adjust_color_mode(&mut stroke.color, adjust_color);
+        }
 
         Shape::Path(PathShape {
             points: _,
             closed: _,
             fill,
             stroke,
+            fill_rule: _,
+            contours: _,
         })
-        | Shape::QuadraticBezier(QuadraticBezierShape {
+        | Shape::BezPath(BezPathShape {
+            commands: _,
+            fill,
+            stroke,
+            fill_rule: _,
+        }) => {
+            adjust_color(fill);
+            adjust_color_mode(&mut stroke.color, adjust_color);
+        }
+
+        Shape::Gradient(GradientShape { shape, gradient }) => {
+            adjust_colors(shape, adjust_color);
+            Arc::make_mut(gradient).adjust_colors(adjust_color);
+        }
+
+        Shape::Clipped(ClippedToShape {
+            clip_shape: _,
+            shape,
+        }) => {
+            adjust_colors(shape, adjust_color);
+        }
+
+        Shape::QuadraticBezier(QuadraticBezierShape {
             points: _,
             closed: _,
             fill,
@@ -46,17 +62,7 @@ pub fn adjust_colors(
             stroke,
         }) => {
             adjust_color(fill);
-            match &stroke.color {
-                color::ColorMode::Solid(mut col) => adjust_color(&mut col),
-                color::ColorMode::UV(callback) => {
-                    let callback = callback.clone();
-                    stroke.color = color::ColorMode::UV(Arc::new(Box::new(move |rect, pos| {
-                        let mut col = callback(rect, pos);
-                        adjust_color(&mut col);
-                        col
-                    })));
-                }
-            }
+            adjust_color_mode(&mut stroke.color, adjust_color);
         }
 
         Shape::Circle(CircleShape {
@@ -102,8 +108,10 @@ pub fn adjust_colors(
             if !galley.is_empty() {
                 let galley = std::sync::Arc::make_mut(galley);
                 for row in &mut galley.rows {
-                    for vertex in &mut row.visuals.mesh.vertices {
-                        adjust_color(&mut vertex.color);
+                    for mesh in row.visuals.meshes_mut() {
+                        for vertex in &mut mesh.vertices {
+                            adjust_color(&mut vertex.color);
+                        }
                     }
                 }
             }
@@ -124,3 +132,23 @@ pub fn adjust_colors(
         }
     }
 }
+
+fn adjust_color_mode(
+    color_mode: &mut ColorMode,
+    adjust_color: impl Fn(&mut Color32) + Send + Sync + Copy + 'static,
+) {
+    match color_mode {
+        ColorMode::Solid(color) => adjust_color(color),
+        ColorMode::UV(callback) => {
+            let callback = callback.clone();
+            *color_mode = ColorMode::UV(Arc::new(Box::new(move |rect, pos| {
+                let mut col = callback(rect, pos);
+                adjust_color(&mut col);
+                col
+            })));
+        }
+        ColorMode::Gradient(gradient) => {
+            Arc::make_mut(gradient).adjust_colors(adjust_color);
+        }
+    }
+}
@@ -174,6 +174,11 @@ pub struct PaintStats {
     pub clipped_primitives: AllocInfo,
     pub vertices: AllocInfo,
     pub indices: AllocInfo,
+
+    /// How many layers were reused from the previous frame.
+    ///
+    /// Only filled in when a [`crate::TessellationCache`] is used.
+    pub tessellation_cache: crate::tessellation_cache::TessellationCacheStats,
 }
 
 impl PaintStats {
@@ -209,6 +214,15 @@ impl PaintStats {
             Shape::Path(path_shape) => {
                 self.shape_path += AllocInfo::from_slice(&path_shape.points);
             }
+            Shape::BezPath(bez_path) => {
+                self.shape_path += AllocInfo::from_slice(&bez_path.commands);
+            }
+            Shape::Gradient(gradient_shape) => {
+                self.add(&gradient_shape.shape);
+            }
+            Shape::Clipped(clipped) => {
+                self.add(&clipped.shape);
+            }
             Shape::Text(text_shape) => {
                 self.shape_text += AllocInfo::from_galley(&text_shape.galley);
 
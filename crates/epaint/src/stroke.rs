@@ -55,14 +55,79 @@ impl std::hash::Hash for Stroke {
     }
 }
 
+/// How the ends of open paths (and of each dash) are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LineCap {
+    /// The stroke ends exactly at the end point.
+    #[default]
+    Butt,
+
+    /// A half-circle with the diameter of the stroke width is added to each end.
+    Round,
+
+    /// The stroke is extended by half its width past each end.
+    Square,
+}
+
+/// How the segments of a path are connected to each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet,
+    /// unless that would exceed [`PathStroke::miter_limit`], in which case [`Self::Bevel`] is used.
+    #[default]
+    Miter,
+
+    /// The corner is rounded off with a circular arc.
+    Round,
+
+    /// The corner is cut off with a straight line.
+    Bevel,
+}
+
 /// Describes the width and color of paths. The color can either be solid or provided by a callback. For more information, see [`ColorMode`]
 ///
+/// A path stroke can also have a dash pattern, and a style for its caps and joins.
+/// With the default style (butt caps, miter joins with [`Self::DEFAULT_MITER_LIMIT`] and no dashes)
+/// the joins computed when the path was built are used as-is, which is the fastest.
+///
 /// The default stroke is the same as [`Stroke::NONE`].
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PathStroke {
     pub width: f32,
     pub color: ColorMode,
+
+    /// How the ends of open paths and dashes are drawn.
+    pub cap: LineCap,
+
+    /// How the segments of the path are connected.
+    pub join: LineJoin,
+
+    /// The longest allowed miter, as a ratio of the miter length to the stroke width.
+    ///
+    /// Sharper corners are beveled instead. Only used with [`LineJoin::Miter`].
+    pub miter_limit: f32,
+
+    /// Alternating lengths of dashes and gaps, in points, starting with a dash.
+    ///
+    /// If the number of lengths is odd, the pattern is repeated to make it even,
+    /// so `[5.0]` means five points on, five points off.
+    ///
+    /// An empty pattern (the default) means a solid line.
+    pub dash_pattern: Vec<f32>,
+
+    /// How far into the dash pattern the path starts, in points.
+    pub dash_offset: f32,
+}
+
+impl Default for PathStroke {
+    #[inline]
+    fn default() -> Self {
+        Self::NONE
+    }
 }
 
 impl PathStroke {
@@ -70,13 +135,22 @@ impl PathStroke {
     pub const NONE: Self = Self {
         width: 0.0,
         color: ColorMode::TRANSPARENT,
+        cap: LineCap::Butt,
+        join: LineJoin::Miter,
+        miter_limit: Self::DEFAULT_MITER_LIMIT,
+        dash_pattern: Vec::new(),
+        dash_offset: 0.0,
     };
 
+    /// The default [`Self::miter_limit`], same as in SVG.
+    pub const DEFAULT_MITER_LIMIT: f32 = 4.0;
+
     #[inline]
     pub fn new(width: impl Into<f32>, color: impl Into<Color32>) -> Self {
         Self {
             width: width.into(),
             color: ColorMode::Solid(color.into()),
+            ..Self::NONE
         }
     }
 
@@ -91,14 +165,62 @@ impl PathStroke {
         Self {
             width: width.into(),
             color: ColorMode::UV(Arc::new(callback)),
+            ..Self::NONE
         }
     }
 
+    /// Set how the ends of the path (and of each dash) are drawn.
+    #[inline]
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Set how the segments of the path are connected.
+    #[inline]
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// See [`Self::miter_limit`].
+    #[inline]
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    /// Draw the path as dashes, see [`Self::dash_pattern`] and [`Self::dash_offset`].
+    ///
+    /// ```
+    /// # use epaint::{Color32, PathStroke, LineCap};
+    /// // Round dots, 6 points apart:
+    /// let dotted = PathStroke::new(2.0, Color32::WHITE)
+    ///     .with_dashes(vec![0.0, 6.0], 0.0)
+    ///     .with_cap(LineCap::Round);
+    /// ```
+    #[inline]
+    pub fn with_dashes(mut self, dash_pattern: Vec<f32>, dash_offset: f32) -> Self {
+        self.dash_pattern = dash_pattern;
+        self.dash_offset = dash_offset;
+        self
+    }
+
     /// True if width is zero or color is solid and transparent
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.width <= 0.0 || self.color == ColorMode::TRANSPARENT
     }
+
+    /// True if this uses butt caps, miter joins with the default limit, and no dashes.
+    #[inline]
+    #[allow(clippy::float_cmp_const)] // Any other limit needs the joins to be recomputed
+    pub fn has_default_style(&self) -> bool {
+        self.cap == LineCap::Butt
+            && self.join == LineJoin::Miter
+            && self.miter_limit == Self::DEFAULT_MITER_LIMIT
+            && self.dash_pattern.is_empty()
+    }
 }
 
 impl<Color> From<(f32, Color)> for PathStroke
@@ -113,9 +235,6 @@ where
 
 impl From<Stroke> for PathStroke {
     fn from(value: Stroke) -> Self {
-        Self {
-            width: value.width,
-            color: ColorMode::Solid(value.color),
-        }
+        Self::new(value.width, value.color)
     }
 }
@@ -0,0 +1,1279 @@
+//! Export [`ClippedShape`]s as [SVG](https://www.w3.org/TR/SVG2/), e.g. for vector screenshots of a UI.
+//!
+//! The shapes are converted before they are tessellated, so the result is resolution independent:
+//! rectangles, circles, paths and curves become their SVG counterparts,
+//! text becomes `<text>` elements (or paths, see [`SvgExporter::with_text_as_paths`]),
+//! and images are embedded as PNG data URIs.
+//!
+//! ```
+//! # use epaint::{pos2, ClippedShape, Color32, Rect, Shape};
+//! use epaint::svg::SvgExporter;
+//!
+//! let screen_rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 50.0));
+//! let shapes = [ClippedShape {
+//!     clip_rect: screen_rect,
+//!     shape: Shape::circle_filled(pos2(50.0, 25.0), 20.0, Color32::RED),
+//! }];
+//!
+//! let svg = SvgExporter::default().export(screen_rect, &shapes);
+//! assert!(svg.contains(r##"<circle cx="50" cy="25" r="20" fill="#ff0000"/>"##));
+//! ```
+
+use std::fmt::{self, Write as _};
+use std::sync::Arc;
+
+use ecolor::Color32;
+use emath::{lerp, pos2, Pos2, Rect, Vec2};
+
+use crate::{
+    gradient::{GradientInterpolation, GradientKind, GradientStop},
+    text::{DecorationStyle, FontFamily, FontWeight, Fonts, Glyph},
+    BezPathShape, CircleShape, ClipShape, ClippedShape, ColorImage, ColorMode, CubicBezierShape,
+    EllipseShape, FillRule, Gradient, GradientShape, LineCap, LineJoin, Mesh, PathCommand,
+    PathShape, PathStroke, QuadraticBezierShape, RectShape, Rounding, Shape, Stroke, TextShape,
+    TextureId,
+};
+
+/// Converts [`ClippedShape`]s to an SVG document.
+///
+/// Some things can't be exported exactly:
+/// * [`Shape::Callback`]s are skipped.
+/// * A [`Mesh`] becomes flat-colored triangles, except a textured rectangle
+///   (like from [`Shape::image`]) which becomes an `<image>`.
+/// * Images are only tinted by the alpha of their tint color.
+/// * A [`ColorMode::UV`] stroke gets the color at the center of the shape.
+#[derive(Default)]
+pub struct SvgExporter<'a> {
+    textures: ahash::HashMap<TextureId, Arc<ColorImage>>,
+    fonts: Option<&'a Fonts>,
+}
+
+impl<'a> SvgExporter<'a> {
+    /// Provide the contents of a texture, so that shapes using it can embed it as an image.
+    ///
+    /// Shapes with textures that have not been provided are exported without them.
+    #[inline]
+    pub fn with_texture(
+        mut self,
+        texture_id: TextureId,
+        image: impl Into<Arc<ColorImage>>,
+    ) -> Self {
+        self.textures.insert(texture_id, image.into());
+        self
+    }
+
+    /// Convert text to paths using the glyph outlines of these fonts, instead of to `<text>` elements.
+    ///
+    /// The text will then look the same everywhere, but can no longer be selected or searched.
+    /// These must be the fonts the text was laid out with.
+    #[inline]
+    pub fn with_text_as_paths(mut self, fonts: &'a Fonts) -> Self {
+        self.fonts = Some(fonts);
+        self
+    }
+
+    /// An SVG document showing the `view_box` (in points) of the given shapes.
+    pub fn export(&self, view_box: Rect, shapes: &[ClippedShape]) -> String {
+        crate::profile_function!();
+
+        let mut writer = Writer {
+            exporter: self,
+            defs: String::new(),
+            body: String::new(),
+            next_id: 0,
+        };
+        writer
+            .shapes(view_box, shapes)
+            .expect("Writing to a String can't fail");
+
+        let Writer { defs, body, .. } = writer;
+        let (min, size) = (view_box.min, view_box.size());
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+            Num(size.x),
+            Num(size.y),
+            Num(min.x),
+            Num(min.y),
+            Num(size.x),
+            Num(size.y),
+        );
+        if !defs.is_empty() {
+            svg += "<defs>\n";
+            svg += &defs;
+            svg += "</defs>\n";
+        }
+        svg += &body;
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+struct Writer<'e, 'a> {
+    exporter: &'e SvgExporter<'a>,
+
+    /// Clip paths, gradients and filters, referenced by id from the body.
+    defs: String,
+
+    body: String,
+    next_id: usize,
+}
+
+impl Writer<'_, '_> {
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{}", self.next_id)
+    }
+
+    fn shapes(&mut self, view_box: Rect, shapes: &[ClippedShape]) -> fmt::Result {
+        // Consecutive shapes with the same clip rectangle share a group:
+        let mut current_clip = None;
+        let mut open_group = false;
+
+        for ClippedShape { clip_rect, shape } in shapes {
+            if Some(*clip_rect) != current_clip {
+                if open_group {
+                    writeln!(self.body, "</g>")?;
+                }
+                open_group = !clip_rect.contains_rect(view_box);
+                if open_group {
+                    let id = self.new_id("clip");
+                    writeln!(
+                        self.defs,
+                        r#"<clipPath id="{id}"><rect{}/></clipPath>"#,
+                        RectAttrs(*clip_rect)
+                    )?;
+                    writeln!(self.body, r#"<g clip-path="url(#{id})">"#)?;
+                }
+                current_clip = Some(*clip_rect);
+            }
+
+            self.shape(shape)?;
+        }
+
+        if open_group {
+            writeln!(self.body, "</g>")?;
+        }
+        Ok(())
+    }
+
+    fn shape(&mut self, shape: &Shape) -> fmt::Result {
+        match shape {
+            Shape::Noop | Shape::Callback(_) => Ok(()),
+            Shape::Vec(shapes) => shapes.iter().try_for_each(|shape| self.shape(shape)),
+            Shape::Circle(circle) => self.circle(circle, None),
+            Shape::Ellipse(ellipse) => self.ellipse(ellipse, None),
+            Shape::LineSegment { points, stroke } => {
+                let bounds = Rect::from_two_pos(points[0], points[1]);
+                let stroke = self.path_stroke_attrs(stroke, bounds)?;
+                if stroke.is_empty() {
+                    return Ok(());
+                }
+                writeln!(
+                    self.body,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{stroke}/>"#,
+                    Num(points[0].x),
+                    Num(points[0].y),
+                    Num(points[1].x),
+                    Num(points[1].y),
+                )
+            }
+            Shape::Path(path) => self.path(path, None),
+            Shape::Rect(rect) => self.rect(rect, None),
+            Shape::Text(text) => self.text(text),
+            Shape::Mesh(mesh) => self.mesh(mesh),
+            Shape::QuadraticBezier(bezier) => self.quadratic_bezier(bezier),
+            Shape::CubicBezier(bezier) => self.cubic_bezier(bezier),
+            Shape::BezPath(path) => self.bez_path(path, None),
+            Shape::Gradient(gradient_shape) => self.gradient_shape(gradient_shape),
+            Shape::Clipped(clipped) => {
+                let id = self.new_id("clip");
+                writeln!(
+                    self.defs,
+                    r#"<clipPath id="{id}"><path d="{}"/></clipPath>"#,
+                    ClipShapeData(&clipped.clip_shape)
+                )?;
+                writeln!(self.body, r#"<g clip-path="url(#{id})">"#)?;
+                self.shape(&clipped.shape)?;
+                writeln!(self.body, "</g>")
+            }
+        }
+    }
+
+    fn gradient_shape(&mut self, gradient_shape: &GradientShape) -> fmt::Result {
+        let gradient = Some(gradient_shape.gradient.as_ref());
+        match gradient_shape.shape.as_ref() {
+            Shape::Circle(circle) => self.circle(circle, gradient),
+            Shape::Ellipse(ellipse) => self.ellipse(ellipse, gradient),
+            Shape::Path(path) => self.path(path, gradient),
+            Shape::Rect(rect) => self.rect(rect, gradient),
+            Shape::BezPath(path) => self.bez_path(path, gradient),
+            shape => self.shape(shape),
+        }
+    }
+
+    fn circle(&mut self, circle: &CircleShape, fill_gradient: Option<&Gradient>) -> fmt::Result {
+        let CircleShape {
+            center,
+            radius,
+            fill,
+            stroke,
+        } = circle;
+        let fill = self.fill_attrs(*fill, fill_gradient)?;
+        let stroke = stroke_attrs(*stroke);
+        if fill.is_none() && stroke.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            self.body,
+            r#"<circle cx="{}" cy="{}" r="{}"{}{stroke}/>"#,
+            Num(center.x),
+            Num(center.y),
+            Num(*radius),
+            fill.as_deref().unwrap_or(NO_FILL),
+        )
+    }
+
+    fn ellipse(&mut self, ellipse: &EllipseShape, fill_gradient: Option<&Gradient>) -> fmt::Result {
+        let EllipseShape {
+            center,
+            radius,
+            fill,
+            stroke,
+        } = ellipse;
+        let fill = self.fill_attrs(*fill, fill_gradient)?;
+        let stroke = stroke_attrs(*stroke);
+        if fill.is_none() && stroke.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            self.body,
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}{stroke}/>"#,
+            Num(center.x),
+            Num(center.y),
+            Num(radius.x),
+            Num(radius.y),
+            fill.as_deref().unwrap_or(NO_FILL),
+        )
+    }
+
+    fn path(&mut self, path: &PathShape, fill_gradient: Option<&Gradient>) -> fmt::Result {
+        let PathShape {
+            points,
+            closed,
+            fill,
+            stroke,
+            fill_rule,
+            contours,
+        } = path;
+
+        let bounds = Rect::from_points(points);
+        let fill = if *closed {
+            self.fill_attrs(*fill, fill_gradient)?
+        } else {
+            None
+        };
+        let stroke = self.path_stroke_attrs(stroke, bounds)?;
+
+        let outline = PolygonData(points, *closed).to_string();
+        let mut all = outline.clone();
+        for contour in contours {
+            write!(all, " {}", PolygonData(contour, true))?;
+        }
+
+        if *fill_rule == FillRule::Convex && !contours.is_empty() {
+            // Only the outer path is filled, but the contours are stroked too:
+            if fill.is_some() {
+                self.path_element(&outline, fill.as_deref(), *fill_rule, "")?;
+            }
+            self.path_element(&all, None, *fill_rule, &stroke)
+        } else {
+            self.path_element(&all, fill.as_deref(), *fill_rule, &stroke)
+        }
+    }
+
+    fn quadratic_bezier(&mut self, bezier: &QuadraticBezierShape) -> fmt::Result {
+        let QuadraticBezierShape {
+            points: [p0, p1, p2],
+            closed,
+            fill,
+            stroke,
+        } = bezier;
+        let fill = if *closed {
+            self.fill_attrs(*fill, None)?
+        } else {
+            None
+        };
+        let stroke = self.path_stroke_attrs(stroke, Rect::from_points(&bezier.points))?;
+        let close = if *closed { " Z" } else { "" };
+        let d = format!("M{} Q{} {}{close}", Point(*p0), Point(*p1), Point(*p2));
+        self.path_element(&d, fill.as_deref(), FillRule::NonZero, &stroke)
+    }
+
+    fn cubic_bezier(&mut self, bezier: &CubicBezierShape) -> fmt::Result {
+        let CubicBezierShape {
+            points: [p0, p1, p2, p3],
+            closed,
+            fill,
+            stroke,
+        } = bezier;
+        let fill = if *closed {
+            self.fill_attrs(*fill, None)?
+        } else {
+            None
+        };
+        let stroke = self.path_stroke_attrs(stroke, Rect::from_points(&bezier.points))?;
+        let close = if *closed { " Z" } else { "" };
+        let d = format!(
+            "M{} C{} {} {}{close}",
+            Point(*p0),
+            Point(*p1),
+            Point(*p2),
+            Point(*p3)
+        );
+        self.path_element(&d, fill.as_deref(), FillRule::NonZero, &stroke)
+    }
+
+    fn bez_path(&mut self, path: &BezPathShape, fill_gradient: Option<&Gradient>) -> fmt::Result {
+        let BezPathShape {
+            commands,
+            fill,
+            stroke,
+            fill_rule,
+        } = path;
+        let fill = self.fill_attrs(*fill, fill_gradient)?;
+        let stroke = self.path_stroke_attrs(stroke, path.visual_bounding_rect())?;
+        let d = CommandsData(commands, Vec2::ZERO).to_string();
+        self.path_element(&d, fill.as_deref(), *fill_rule, &stroke)
+    }
+
+    /// A `<path>`, which is skipped if it has neither fill nor stroke.
+    fn path_element(
+        &mut self,
+        d: &str,
+        fill: Option<&str>,
+        fill_rule: FillRule,
+        stroke: &str,
+    ) -> fmt::Result {
+        if d.is_empty() || (fill.is_none() && stroke.is_empty()) {
+            return Ok(());
+        }
+        let fill_rule = if fill.is_some() && fill_rule == FillRule::EvenOdd {
+            r#" fill-rule="evenodd""#
+        } else {
+            ""
+        };
+        writeln!(
+            self.body,
+            r#"<path d="{d}"{}{fill_rule}{stroke}/>"#,
+            fill.unwrap_or(NO_FILL)
+        )
+    }
+
+    fn rect(&mut self, rect_shape: &RectShape, fill_gradient: Option<&Gradient>) -> fmt::Result {
+        let RectShape {
+            rect,
+            rounding,
+            fill,
+            stroke,
+            blur_width,
+            fill_texture_id,
+            uv,
+        } = rect_shape;
+
+        let filter = if 0.0 < *blur_width {
+            let id = self.new_id("blur");
+            writeln!(
+                self.defs,
+                r#"<filter id="{id}" x="-50%" y="-50%" width="200%" height="200%"><feGaussianBlur stdDeviation="{}"/></filter>"#,
+                Num(blur_width / 4.0)
+            )?;
+            format!(r#" filter="url(#{id})""#)
+        } else {
+            String::new()
+        };
+
+        let mut textured = false;
+        if uv.is_positive() && fill_gradient.is_none() {
+            textured = self.image(*fill_texture_id, *rect, *uv, *fill, *rounding)?;
+        }
+
+        let fill = if textured {
+            None
+        } else {
+            self.fill_attrs(*fill, fill_gradient)?
+        };
+        let stroke = stroke_attrs(*stroke);
+        if fill.is_none() && stroke.is_empty() {
+            return Ok(());
+        }
+        let fill = fill.as_deref().unwrap_or(NO_FILL);
+
+        if rounding.is_same() {
+            let radius = clamp_rounding(*rect, *rounding).nw;
+            let rx = if 0.0 < radius {
+                format!(r#" rx="{}""#, Num(radius))
+            } else {
+                String::new()
+            };
+            writeln!(
+                self.body,
+                r#"<rect{}{rx}{fill}{stroke}{filter}/>"#,
+                RectAttrs(*rect)
+            )
+        } else {
+            writeln!(
+                self.body,
+                r#"<path d="{}"{fill}{stroke}{filter}/>"#,
+                RoundedRectData(*rect, *rounding)
+            )
+        }
+    }
+
+    /// An `<image>` of the `uv` part of a texture, if we have it.
+    fn image(
+        &mut self,
+        texture_id: TextureId,
+        rect: Rect,
+        uv: Rect,
+        tint: Color32,
+        rounding: Rounding,
+    ) -> Result<bool, fmt::Error> {
+        let Some(image) = self.exporter.textures.get(&texture_id) else {
+            return Ok(false);
+        };
+        let Some(data_uri) = png_data_uri(image, uv) else {
+            return Ok(false);
+        };
+
+        let opacity = if tint.a() < 255 {
+            format!(r#" opacity="{}""#, Num(tint.a() as f32 / 255.0))
+        } else {
+            String::new()
+        };
+        let clip = if rounding == Rounding::ZERO {
+            String::new()
+        } else {
+            let id = self.new_id("clip");
+            writeln!(
+                self.defs,
+                r#"<clipPath id="{id}"><path d="{}"/></clipPath>"#,
+                RoundedRectData(rect, rounding)
+            )?;
+            format!(r#" clip-path="url(#{id})""#)
+        };
+
+        writeln!(
+            self.body,
+            r#"<image{} preserveAspectRatio="none"{opacity}{clip} xlink:href="{data_uri}"/>"#,
+            RectAttrs(rect)
+        )?;
+        Ok(true)
+    }
+
+    fn mesh(&mut self, mesh: &Mesh) -> fmt::Result {
+        if let Some((rect, uv, tint)) = textured_rect(mesh) {
+            if self.image(mesh.texture_id, rect, uv, tint, Rounding::ZERO)? {
+                return Ok(());
+            }
+        }
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let average = |channel: usize| {
+                let sum =
+                    a.color[channel] as u32 + b.color[channel] as u32 + c.color[channel] as u32;
+                (sum / 3) as u8
+            };
+            let color =
+                Color32::from_rgba_premultiplied(average(0), average(1), average(2), average(3));
+            if color == Color32::TRANSPARENT {
+                continue;
+            }
+            writeln!(
+                self.body,
+                r#"<path d="M{} L{} L{} Z"{}/>"#,
+                Point(a.pos),
+                Point(b.pos),
+                Point(c.pos),
+                ColorAttrs("fill", color)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text_shape: &TextShape) -> fmt::Result {
+        let TextShape {
+            pos,
+            galley,
+            underline,
+            fallback_color,
+            override_text_color,
+            opacity_factor,
+            angle,
+        } = text_shape;
+
+        if galley.is_empty() || *opacity_factor <= 0.0 {
+            return Ok(());
+        }
+
+        // Same as the tessellator:
+        let color = |mut color: Color32| {
+            if color == Color32::PLACEHOLDER {
+                color = *fallback_color;
+            }
+            if *opacity_factor < 1.0 {
+                color = color.gamma_multiply(*opacity_factor);
+            }
+            color
+        };
+        let glyph_color =
+            |format_color: Color32| color(override_text_color.unwrap_or(format_color));
+
+        write!(
+            self.body,
+            r#"<g transform="translate({} {})"#,
+            Num(pos.x),
+            Num(pos.y)
+        )?;
+        if *angle != 0.0 {
+            write!(self.body, " rotate({})", Num(angle.to_degrees()))?;
+        }
+        writeln!(self.body, r#"">"#)?;
+
+        for row in &galley.rows {
+            let glyphs: Vec<Glyph> = row.glyphs_with_hyphen().copied().collect();
+
+            // Glyphs of the same section are exported together:
+            let mut runs: Vec<&[Glyph]> = vec![];
+            let mut start = 0;
+            for i in 1..=glyphs.len() {
+                if i == glyphs.len() || glyphs[i].section_index != glyphs[start].section_index {
+                    runs.push(&glyphs[start..i]);
+                    start = i;
+                }
+            }
+
+            // Backgrounds first, so that they don't cover the glyphs of neighboring runs:
+            for run in &runs {
+                let format = &galley.job.sections[run[0].section_index as usize].format;
+                let background = color(format.background);
+                if background != Color32::TRANSPARENT {
+                    let rect = run_rect(run).expand(1.0); // Same as the text layout
+                    writeln!(
+                        self.body,
+                        r#"<rect{}{}/>"#,
+                        RectAttrs(rect),
+                        ColorAttrs("fill", background)
+                    )?;
+                }
+            }
+
+            for run in &runs {
+                let format = &galley.job.sections[run[0].section_index as usize].format;
+
+                let fill = glyph_color(format.color);
+                if fill != Color32::TRANSPARENT {
+                    if let Some(fonts) = self.exporter.fonts {
+                        let mut fonts = fonts.lock();
+                        let font = fonts
+                            .fonts
+                            .font_with_style(&format.font_id, format.font_style());
+                        let text: String = run.iter().map(|glyph| glyph.chr).collect();
+                        let mut d = String::new();
+                        for (char_index, outline) in font.text_outlines(&text) {
+                            if !outline.is_empty() {
+                                if !d.is_empty() {
+                                    d.push(' ');
+                                }
+                                let pos = run[char_index].pos;
+                                write!(d, "{}", CommandsData(&outline, pos.to_vec2()))?;
+                            }
+                        }
+                        if !d.is_empty() {
+                            writeln!(self.body, r#"<path d="{d}"{}/>"#, ColorAttrs("fill", fill))?;
+                        }
+                    } else {
+                        self.text_element(run, format, fill)?;
+                    }
+                }
+
+                let rect = run_rect(run);
+                let decorations = [
+                    (format.underline, format.underline_style, rect.bottom()),
+                    (
+                        format.strikethrough,
+                        format.strikethrough_style,
+                        rect.center().y,
+                    ),
+                ];
+                for (stroke, style, y) in decorations {
+                    let stroke = Stroke::new(stroke.width, color(stroke.color));
+                    self.hline(rect.left(), rect.right(), y, stroke, style)?;
+                }
+            }
+
+            if *underline != Stroke::NONE {
+                self.hline(
+                    row.rect.left(),
+                    row.rect.right(),
+                    row.rect.bottom(),
+                    *underline,
+                    DecorationStyle::Solid,
+                )?;
+            }
+        }
+
+        writeln!(self.body, "</g>")
+    }
+
+    /// A `<text>` element for glyphs of the same section, with the glyph positions from the layout.
+    fn text_element(
+        &mut self,
+        run: &[Glyph],
+        format: &crate::text::TextFormat,
+        fill: Color32,
+    ) -> fmt::Result {
+        let mut x = String::new();
+        let mut text = String::new();
+        for glyph in run {
+            if !x.is_empty() {
+                x.push(' ');
+            }
+            write!(x, "{}", Num(glyph.pos.x))?;
+            // Keep one character per x position:
+            let chr = if glyph.chr.is_control() {
+                ' '
+            } else {
+                glyph.chr
+            };
+            text.push(chr);
+        }
+
+        let family = match &format.font_id.family {
+            FontFamily::Proportional => "sans-serif".to_owned(),
+            FontFamily::Monospace => "monospace".to_owned(),
+            FontFamily::Name(name) => format!("'{}', sans-serif", Escaped(name)),
+        };
+        let weight = if format.weight == FontWeight::REGULAR {
+            String::new()
+        } else {
+            format!(r#" font-weight="{}""#, format.weight.0)
+        };
+        let style = if format.italics {
+            r#" font-style="italic""#
+        } else {
+            ""
+        };
+
+        writeln!(
+            self.body,
+            r#"<text x="{x}" y="{}" font-family="{family}" font-size="{}"{weight}{style}{} xml:space="preserve">{}</text>"#,
+            Num(run[0].pos.y),
+            Num(format.font_id.size),
+            ColorAttrs("fill", fill),
+            Escaped(&text),
+        )
+    }
+
+    /// A text decoration line.
+    fn hline(
+        &mut self,
+        left: f32,
+        right: f32,
+        y: f32,
+        stroke: Stroke,
+        style: DecorationStyle,
+    ) -> fmt::Result {
+        if stroke.is_empty() {
+            return Ok(());
+        }
+        let bounds = Rect::from_x_y_ranges(left..=right, y..=y);
+        for (points, stroke) in style.lines([pos2(left, y), pos2(right, y)], stroke) {
+            let stroke = self.path_stroke_attrs(&stroke, bounds)?;
+            let d = PolygonData(&points, false).to_string();
+            self.path_element(&d, None, FillRule::NonZero, &stroke)?;
+        }
+        Ok(())
+    }
+
+    /// The fill attributes, or `None` if nothing is filled.
+    fn fill_attrs(
+        &mut self,
+        fill: Color32,
+        fill_gradient: Option<&Gradient>,
+    ) -> Result<Option<String>, fmt::Error> {
+        let is_filled = match fill_gradient {
+            Some(gradient) => !gradient.is_transparent(),
+            None => fill != Color32::TRANSPARENT,
+        };
+        if !is_filled {
+            return Ok(None);
+        }
+        Ok(Some(match fill_gradient {
+            Some(gradient) => {
+                let id = self.gradient(gradient)?;
+                format!(r#" fill="url(#{id})""#)
+            }
+            None => ColorAttrs("fill", fill).to_string(),
+        }))
+    }
+
+    /// The stroke attributes, or an empty string if nothing is stroked.
+    fn path_stroke_attrs(
+        &mut self,
+        stroke: &PathStroke,
+        bounds: Rect,
+    ) -> Result<String, fmt::Error> {
+        let PathStroke {
+            width,
+            color,
+            cap,
+            join,
+            miter_limit,
+            dash_pattern,
+            dash_offset,
+        } = stroke;
+
+        if *width <= 0.0 {
+            return Ok(String::new());
+        }
+        let mut attrs = match color {
+            ColorMode::Solid(color) => {
+                if *color == Color32::TRANSPARENT {
+                    return Ok(String::new());
+                }
+                ColorAttrs("stroke", *color).to_string()
+            }
+            ColorMode::UV(callback) => {
+                ColorAttrs("stroke", callback(bounds, bounds.center())).to_string()
+            }
+            ColorMode::Gradient(gradient) => {
+                if gradient.is_transparent() {
+                    return Ok(String::new());
+                }
+                let id = self.gradient(gradient)?;
+                format!(r#" stroke="url(#{id})""#)
+            }
+        };
+
+        write!(attrs, r#" stroke-width="{}""#, Num(*width))?;
+        match cap {
+            LineCap::Butt => {}
+            LineCap::Round => attrs += r#" stroke-linecap="round""#,
+            LineCap::Square => attrs += r#" stroke-linecap="square""#,
+        }
+        match join {
+            LineJoin::Miter => write!(attrs, r#" stroke-miterlimit="{}""#, Num(*miter_limit))?,
+            LineJoin::Round => attrs += r#" stroke-linejoin="round""#,
+            LineJoin::Bevel => attrs += r#" stroke-linejoin="bevel""#,
+        }
+        if !dash_pattern.is_empty() {
+            let dashes: Vec<String> = dash_pattern.iter().map(|d| Num(*d).to_string()).collect();
+            write!(attrs, r#" stroke-dasharray="{}""#, dashes.join(" "))?;
+            if *dash_offset != 0.0 {
+                write!(attrs, r#" stroke-dashoffset="{}""#, Num(*dash_offset))?;
+            }
+        }
+        Ok(attrs)
+    }
+
+    /// Define a gradient, and return its id.
+    fn gradient(&mut self, gradient: &Gradient) -> Result<String, fmt::Error> {
+        let id = self.new_id("gradient");
+
+        // Gradients are relative to the bounding box, which is also the default in SVG:
+        let (tag, geometry) = match gradient.kind {
+            GradientKind::Linear { start, end } => (
+                "linearGradient",
+                format!(
+                    r#"x1="{}" y1="{}" x2="{}" y2="{}""#,
+                    Num(start.x),
+                    Num(start.y),
+                    Num(end.x),
+                    Num(end.y)
+                ),
+            ),
+            GradientKind::Radial { center, radius } => (
+                "radialGradient",
+                format!(
+                    r#"cx="{}" cy="{}" r="{}""#,
+                    Num(center.x),
+                    Num(center.y),
+                    Num(radius)
+                ),
+            ),
+        };
+
+        let stops = match gradient.interpolation {
+            GradientInterpolation::LinearRgb => {
+                writeln!(
+                    self.defs,
+                    r#"<{tag} id="{id}" {geometry} color-interpolation="linearRGB">"#
+                )?;
+                gradient.stops().to_vec()
+            }
+            GradientInterpolation::Oklab => {
+                // SVG can't blend in Oklab, so we add stops in between:
+                writeln!(self.defs, r#"<{tag} id="{id}" {geometry}>"#)?;
+                match (gradient.stops().first(), gradient.stops().last()) {
+                    (Some(first), Some(last)) => {
+                        const STEPS: usize = 16;
+                        (0..=STEPS)
+                            .map(|i| {
+                                let offset =
+                                    lerp(first.offset..=last.offset, i as f32 / STEPS as f32);
+                                GradientStop {
+                                    offset,
+                                    color: gradient.color_at_offset(offset),
+                                }
+                            })
+                            .collect()
+                    }
+                    _ => vec![],
+                }
+            }
+        };
+
+        for stop in stops {
+            let [r, g, b, a] = stop.color.to_srgba_unmultiplied();
+            write!(
+                self.defs,
+                "<stop offset=\"{}\" stop-color=\"#{r:02x}{g:02x}{b:02x}\"",
+                Num(stop.offset)
+            )?;
+            if a < 255 {
+                write!(self.defs, r#" stop-opacity="{}""#, Num(a as f32 / 255.0))?;
+            }
+            writeln!(self.defs, "/>")?;
+        }
+        writeln!(self.defs, "</{tag}>")?;
+
+        Ok(id)
+    }
+}
+
+/// Used when a shape is only stroked, since SVG fills with black by default.
+const NO_FILL: &str = r#" fill="none""#;
+
+/// The stroke attributes, or an empty string if nothing is stroked.
+fn stroke_attrs(stroke: Stroke) -> String {
+    if stroke.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#"{} stroke-width="{}""#,
+        ColorAttrs("stroke", stroke.color),
+        Num(stroke.width)
+    )
+}
+
+/// The logical rectangle of some glyphs on the same row.
+fn run_rect(run: &[Glyph]) -> Rect {
+    let first = run[0].logical_rect();
+    let last = run[run.len() - 1].logical_rect();
+    Rect::from_min_max(first.left_top(), pos2(last.right(), first.bottom()))
+}
+
+/// If the mesh is a textured rectangle, its rectangle, uv and tint.
+fn textured_rect(mesh: &Mesh) -> Option<(Rect, Rect, Color32)> {
+    if mesh.vertices.len() != 4 || mesh.indices.len() != 6 {
+        return None;
+    }
+    let rect = Rect::from_points(&mesh.vertices.iter().map(|v| v.pos).collect::<Vec<_>>());
+    let first = mesh.vertices[0];
+    let is_corner = |p: Pos2| {
+        (p.x == rect.min.x || p.x == rect.max.x) && (p.y == rect.min.y || p.y == rect.max.y)
+    };
+    if !mesh
+        .vertices
+        .iter()
+        .all(|v| is_corner(v.pos) && v.color == first.color)
+    {
+        return None;
+    }
+    let uv_at = |pos: Pos2| mesh.vertices.iter().find(|v| v.pos == pos).map(|v| v.uv);
+    let uv = Rect::from_min_max(uv_at(rect.min)?, uv_at(rect.max)?);
+    Some((rect, uv, first.color))
+}
+
+/// The `uv` part of the image, as a PNG data URI.
+fn png_data_uri(image: &ColorImage, uv: Rect) -> Option<String> {
+    use base64::Engine as _;
+
+    let [width, height] = image.size;
+    let to_pixels = |t: f32, size: usize| ((t * size as f32).round().max(0.0) as usize).min(size);
+    let (x0, x1) = (
+        to_pixels(uv.min.x.min(uv.max.x), width),
+        to_pixels(uv.min.x.max(uv.max.x), width),
+    );
+    let (y0, y1) = (
+        to_pixels(uv.min.y.min(uv.max.y), height),
+        to_pixels(uv.min.y.max(uv.max.y), height),
+    );
+    if x0 == x1 || y0 == y1 {
+        return None;
+    }
+
+    let mut rgba = Vec::with_capacity((x1 - x0) * (y1 - y0) * 4);
+    for y in y0..y1 {
+        for pixel in &image.pixels[y * width + x0..y * width + x1] {
+            rgba.extend_from_slice(&pixel.to_srgba_unmultiplied());
+        }
+    }
+
+    let mut png_bytes = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut png_bytes, (x1 - x0) as u32, (y1 - y0) as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().ok()?;
+        writer.write_image_data(&rgba).ok()?;
+    }
+
+    Some(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png_bytes)
+    ))
+}
+
+/// Rounding that fits within the rectangle, like the tessellator does.
+fn clamp_rounding(rect: Rect, rounding: Rounding) -> Rounding {
+    let half = 0.5 * rect.width().min(rect.height());
+    let clamp = |r: f32| r.clamp(0.0, half);
+    Rounding {
+        nw: clamp(rounding.nw),
+        ne: clamp(rounding.ne),
+        sw: clamp(rounding.sw),
+        se: clamp(rounding.se),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Formatting helpers:
+
+/// A number without needless decimals.
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Adding zero turns -0 into 0:
+        let value = (self.0 * 1000.0).round() / 1000.0 + 0.0;
+        write!(f, "{value}")
+    }
+}
+
+struct Point(Pos2);
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", Num(self.0.x), Num(self.0.y))
+    }
+}
+
+/// ` x="…" y="…" width="…" height="…"`
+struct RectAttrs(Rect);
+
+impl fmt::Display for RectAttrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Rect { min, max } = self.0;
+        write!(
+            f,
+            r#" x="{}" y="{}" width="{}" height="{}""#,
+            Num(min.x),
+            Num(min.y),
+            Num(max.x - min.x),
+            Num(max.y - min.y)
+        )
+    }
+}
+
+/// A color attribute (like `fill` or `stroke`), and its opacity if it's not opaque.
+struct ColorAttrs(&'static str, Color32);
+
+impl fmt::Display for ColorAttrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(name, color) = *self;
+        if color == Color32::TRANSPARENT {
+            return write!(f, r#" {name}="none""#);
+        }
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        write!(f, " {name}=\"#{r:02x}{g:02x}{b:02x}\"")?;
+        if a < 255 {
+            write!(f, r#" {name}-opacity="{}""#, Num(a as f32 / 255.0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Path data for a polygon.
+struct PolygonData<'a>(&'a [Pos2], bool);
+
+impl fmt::Display for PolygonData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(points, closed) = *self;
+        for (i, point) in points.iter().enumerate() {
+            let command = if i == 0 { "M" } else { " L" };
+            write!(f, "{command}{}", Point(*point))?;
+        }
+        if closed && !points.is_empty() {
+            write!(f, " Z")?;
+        }
+        Ok(())
+    }
+}
+
+/// Path data for a rectangle with rounded corners.
+struct RoundedRectData(Rect, Rounding);
+
+impl fmt::Display for RoundedRectData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(rect, rounding) = *self;
+        let Rounding { nw, ne, sw, se } = clamp_rounding(rect, rounding);
+        let Rect { min, max } = rect;
+
+        let arc = |f: &mut fmt::Formatter<'_>, r: f32, to: Pos2| {
+            if 0.0 < r {
+                write!(f, " A{} {} 0 0 1 {}", Num(r), Num(r), Point(to))
+            } else {
+                Ok(())
+            }
+        };
+
+        write!(f, "M{}", Point(pos2(min.x + nw, min.y)))?;
+        write!(f, " H{}", Num(max.x - ne))?;
+        arc(f, ne, pos2(max.x, min.y + ne))?;
+        write!(f, " V{}", Num(max.y - se))?;
+        arc(f, se, pos2(max.x - se, max.y))?;
+        write!(f, " H{}", Num(min.x + sw))?;
+        arc(f, sw, pos2(min.x, max.y - sw))?;
+        write!(f, " V{}", Num(min.y + nw))?;
+        arc(f, nw, pos2(min.x + nw, min.y))?;
+        write!(f, " Z")
+    }
+}
+
+struct ClipShapeData<'a>(&'a ClipShape);
+
+impl fmt::Display for ClipShapeData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ClipShape::RoundedRect { rect, rounding } => RoundedRectData(*rect, *rounding).fmt(f),
+            ClipShape::Convex(points) => PolygonData(points, true).fmt(f),
+        }
+    }
+}
+
+/// Path data for [`PathCommand`]s, moved by an offset.
+struct CommandsData<'a>(&'a [PathCommand], Vec2);
+
+impl fmt::Display for CommandsData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(commands, offset) = *self;
+        for (i, command) in commands.iter().enumerate() {
+            if 0 < i {
+                write!(f, " ")?;
+            }
+            match *command {
+                PathCommand::MoveTo(p) => write!(f, "M{}", Point(p + offset))?,
+                PathCommand::LineTo(p) => write!(f, "L{}", Point(p + offset))?,
+                PathCommand::QuadTo(c, p) => {
+                    write!(f, "Q{} {}", Point(c + offset), Point(p + offset))?;
+                }
+                PathCommand::CubicTo(c0, c1, p) => write!(
+                    f,
+                    "C{} {} {}",
+                    Point(c0 + offset),
+                    Point(c1 + offset),
+                    Point(p + offset)
+                )?,
+                PathCommand::Close => write!(f, "Z")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Text with the XML special characters escaped.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::{FontDefinitions, FontId};
+
+    fn clipped(clip_rect: Rect, shape: impl Into<Shape>) -> ClippedShape {
+        ClippedShape {
+            clip_rect,
+            shape: shape.into(),
+        }
+    }
+
+    #[test]
+    fn test_export_shapes() {
+        let screen = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 100.0));
+        let panel = Rect::from_min_max(pos2(10.0, 10.0), pos2(50.0, 50.0));
+
+        let mut path = PathShape::convex_polygon(
+            vec![pos2(0.0, 0.0), pos2(10.0, 0.0), pos2(0.0, 10.0)],
+            Color32::RED,
+            Stroke::NONE,
+        );
+        path.fill_rule = FillRule::EvenOdd;
+
+        let shapes = [
+            clipped(
+                screen,
+                RectShape::filled(panel, Rounding::same(4.0), Color32::from_gray(40)),
+            ),
+            clipped(
+                panel,
+                Shape::line_segment([panel.min, panel.max], (2.0, Color32::WHITE)),
+            ),
+            clipped(panel, path),
+            clipped(
+                screen,
+                RectShape::filled(panel, Rounding::ZERO, Color32::WHITE)
+                    .with_fill_gradient(Gradient::horizontal(Color32::BLACK, Color32::TRANSPARENT)),
+            ),
+        ];
+        let svg = SvgExporter::default().export(screen, &shapes);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(
+            svg.contains(r##"<rect x="10" y="10" width="40" height="40" rx="4" fill="#282828"/>"##)
+        );
+
+        // The line and path are clipped to the panel, together:
+        assert_eq!(svg.matches("<clipPath ").count(), 1);
+        assert!(svg.contains(
+            r#"<clipPath id="clip1"><rect x="10" y="10" width="40" height="40"/></clipPath>"#
+        ));
+        assert!(svg.contains(
+            r##"<line x1="10" y1="10" x2="50" y2="50" stroke="#ffffff" stroke-width="2""##
+        ));
+        assert!(
+            svg.contains(r##"<path d="M0,0 L10,0 L0,10 Z" fill="#ff0000" fill-rule="evenodd"/>"##)
+        );
+
+        assert!(svg.contains(r#"<linearGradient id="gradient2" x1="0" y1="0.5" x2="1" y2="0.5""#));
+        assert!(svg.contains(r#"fill="url(#gradient2)""#));
+    }
+
+    #[test]
+    fn test_export_image() {
+        let screen = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 100.0));
+        let texture_id = TextureId::User(1);
+        let image = Shape::image(
+            texture_id,
+            Rect::from_min_max(pos2(10.0, 10.0), pos2(30.0, 30.0)),
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        let shapes = [clipped(screen, image)];
+
+        // Without the texture we fall back to triangles:
+        let svg = SvgExporter::default().export(screen, &shapes);
+        assert_eq!(svg.matches("<path ").count(), 2);
+
+        let svg = SvgExporter::default()
+            .with_texture(texture_id, ColorImage::new([2, 2], Color32::RED))
+            .export(screen, &shapes);
+        assert!(svg.contains(
+            r#"<image x="10" y="10" width="20" height="20" preserveAspectRatio="none" xlink:href="data:image/png;base64,"#
+        ));
+    }
+
+    #[test]
+    fn test_export_text() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley = fonts.layout_no_wrap(
+            "a <b> & c".to_owned(),
+            FontId::proportional(14.0),
+            Color32::WHITE,
+        );
+        let screen = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 100.0));
+        let shapes = [clipped(
+            screen,
+            Shape::galley(pos2(10.0, 20.0), galley, Color32::WHITE),
+        )];
+
+        let svg = SvgExporter::default().export(screen, &shapes);
+        assert!(svg.contains(r#"<g transform="translate(10 20)">"#));
+        assert!(svg.contains(r#"font-family="sans-serif" font-size="14""#));
+        assert!(svg.contains(">a &lt;b&gt; &amp; c</text>"));
+
+        let svg = SvgExporter::default()
+            .with_text_as_paths(&fonts)
+            .export(screen, &shapes);
+        assert!(!svg.contains("<text"));
+        assert!(svg.contains(r#"<path d="M"#));
+    }
+
+    #[test]
+    fn test_export_synthetic_bold_text() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let screen = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 100.0));
+        let num_contours = |weight| {
+            let mut job = crate::text::LayoutJob::default();
+            job.append(
+                "l",
+                0.0,
+                crate::text::TextFormat {
+                    font_id: FontId::proportional(14.0),
+                    weight,
+                    ..Default::default()
+                },
+            );
+            let galley = fonts.layout_job(job);
+            let shapes = [clipped(
+                screen,
+                Shape::galley(pos2(10.0, 20.0), galley, Color32::WHITE),
+            )];
+            let svg = SvgExporter::default()
+                .with_text_as_paths(&fonts)
+                .export(screen, &shapes);
+            svg.matches('M').count()
+        };
+
+        // There is no bold face of the default font, so the outline is smeared like the rasterized glyph:
+        let regular = num_contours(crate::text::FontWeight::REGULAR);
+        assert!(0 < regular);
+        assert_eq!(num_contours(crate::text::FontWeight::BOLD), 2 * regular);
+    }
+
+    #[cfg(feature = "shaping")]
+    #[test]
+    fn test_text_outlines_follow_shaping() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let mut fonts = fonts.lock();
+        let font = fonts
+            .fonts
+            .font_with_style(&FontId::proportional(14.0), crate::text::FontStyle::REGULAR);
+
+        // `fi` is a ligature in Ubuntu-Light, so it is outlined as one cluster:
+        let clusters: Vec<usize> = font
+            .text_outlines("fit")
+            .into_iter()
+            .map(|(char_index, _)| char_index)
+            .collect();
+        assert_eq!(clusters, vec![0, 2]);
+    }
+}
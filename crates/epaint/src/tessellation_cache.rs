@@ -0,0 +1,170 @@
+//! Reuse the tessellation of layers that haven't changed since the previous frame.
+//!
+//! Most of a typical UI is static most of the time,
+//! so instead of tessellating every shape every frame we can remember
+//! the shapes each layer had last frame, and the primitives they produced.
+//! If a layer paints exactly the same shapes again, we reuse those primitives.
+
+use std::hash::Hash;
+
+use crate::{ClippedPrimitive, ClippedShape, Tessellator};
+
+struct CachedLayer {
+    /// The shapes that were tessellated into [`Self::primitives`].
+    shapes: Vec<ClippedShape>,
+
+    primitives: Vec<ClippedPrimitive>,
+
+    /// Was this layer painted in the current frame?
+    used: bool,
+}
+
+/// How many layers were reused vs tessellated in one frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TessellationCacheStats {
+    /// Layers whose primitives were reused from the previous frame.
+    pub hits: usize,
+
+    /// Layers that had to be tessellated.
+    pub misses: usize,
+}
+
+/// Caches the tessellated primitives of each layer between frames.
+///
+/// A layer is identified by a `Key` of your choice (egui uses its `LayerId`).
+/// The shapes of a layer (including their clip rectangles) are compared with what
+/// the same layer painted last frame, and if they are identical the previous primitives are reused.
+///
+/// Comparing shapes is a lot cheaper than tessellating them,
+/// but still not free, so this is mostly a win for UIs that are static most of the time.
+///
+/// Shapes with [`crate::ColorMode::UV`] never compare equal, so layers containing them are always re-tessellated.
+pub struct TessellationCache<Key> {
+    /// The tessellator the cached primitives were created with.
+    ///
+    /// If the settings change, we need to throw away everything.
+    tessellator: Option<Tessellator>,
+
+    layers: ahash::HashMap<Key, CachedLayer>,
+}
+
+impl<Key> Default for TessellationCache<Key> {
+    fn default() -> Self {
+        Self {
+            tessellator: None,
+            layers: Default::default(),
+        }
+    }
+}
+
+impl<Key: Eq + Hash> TessellationCache<Key> {
+    /// Tessellate the shapes of all layers, in order.
+    ///
+    /// Layers that are identical to last time are reused,
+    /// and layers that were not part of this call are forgotten.
+    pub fn tessellate_layers(
+        &mut self,
+        tessellator: &mut Tessellator,
+        layers: impl IntoIterator<Item = (Key, Vec<ClippedShape>)>,
+    ) -> (Vec<ClippedPrimitive>, TessellationCacheStats) {
+        crate::profile_function!();
+
+        if !self
+            .tessellator
+            .as_ref()
+            .is_some_and(|previous| previous.has_same_settings(tessellator))
+        {
+            self.layers.clear();
+            self.tessellator = Some(tessellator.clone());
+        }
+
+        let mut stats = TessellationCacheStats::default();
+        let mut clipped_primitives = Vec::new();
+
+        for (key, shapes) in layers {
+            match self.layers.get_mut(&key) {
+                Some(cached) if !cached.used && cached.shapes == shapes => {
+                    stats.hits += 1;
+                    cached.used = true;
+                    clipped_primitives.extend(cached.primitives.iter().cloned());
+                }
+                _ => {
+                    stats.misses += 1;
+                    let primitives = tessellator.tessellate_shapes(shapes.clone());
+                    clipped_primitives.extend(primitives.iter().cloned());
+                    self.layers.insert(
+                        key,
+                        CachedLayer {
+                            shapes,
+                            primitives,
+                            used: true,
+                        },
+                    );
+                }
+            }
+        }
+
+        self.layers
+            .retain(|_, cached| std::mem::take(&mut cached.used));
+
+        (clipped_primitives, stats)
+    }
+
+    /// Forget everything.
+    pub fn clear(&mut self) {
+        self.tessellator = None;
+        self.layers.clear();
+    }
+
+    /// Number of layers currently cached.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Is the cache empty?
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color32, Rect, Shape, TessellationOptions};
+    use emath::pos2;
+
+    fn layer(color: Color32) -> Vec<ClippedShape> {
+        vec![ClippedShape {
+            clip_rect: Rect::EVERYTHING,
+            shape: Shape::circle_filled(pos2(10.0, 10.0), 5.0, color),
+        }]
+    }
+
+    #[test]
+    fn test_reuse_unchanged_layers() {
+        let mut tessellator = Tessellator::new(1.0, TessellationOptions::default(), [1, 1], vec![]);
+        let mut cache = TessellationCache::default();
+
+        let (first, stats) = cache.tessellate_layers(
+            &mut tessellator,
+            [(0, layer(Color32::RED)), (1, layer(Color32::GREEN))],
+        );
+        assert_eq!(stats, TessellationCacheStats { hits: 0, misses: 2 });
+
+        let (second, stats) = cache.tessellate_layers(
+            &mut tessellator,
+            [(0, layer(Color32::RED)), (1, layer(Color32::BLUE))],
+        );
+        assert_eq!(stats, TessellationCacheStats { hits: 1, misses: 1 });
+        assert_eq!(first.len(), second.len());
+
+        // Layer 1 is dropped when not painted:
+        cache.tessellate_layers(&mut tessellator, [(0, layer(Color32::RED))]);
+        assert_eq!(cache.len(), 1);
+
+        // Changing the settings invalidates everything:
+        let mut tessellator = Tessellator::new(2.0, TessellationOptions::default(), [1, 1], vec![]);
+        let (_, stats) = cache.tessellate_layers(&mut tessellator, [(0, layer(Color32::RED))]);
+        assert_eq!(stats, TessellationCacheStats { hits: 0, misses: 1 });
+    }
+}
@@ -5,7 +5,8 @@
 
 #![allow(clippy::identity_op)]
 
-use crate::texture_atlas::PreparedDisc;
+use crate::clip_shape::ConvexClip;
+use crate::texture_atlas::{FontPage, PreparedDisc};
 use crate::*;
 use emath::*;
 
@@ -515,6 +516,30 @@ impl Path {
     ) {
         fill_closed_path_with_uv(feathering, &mut self.0, color, texture_id, uv_from_pos, out);
     }
+
+    /// Fill this closed path together with some other closed paths (e.g. holes),
+    /// using the given [`FillRule`].
+    ///
+    /// Unlike [`Self::fill`], this supports concave and self-intersecting paths.
+    /// With [`FillRule::Convex`] this is the same as [`Self::fill`], and the other paths are ignored.
+    pub fn fill_with_rule(
+        &mut self,
+        other_contours: &[Self],
+        fill_rule: FillRule,
+        feathering: f32,
+        color: Color32,
+        out: &mut Mesh,
+    ) {
+        if fill_rule == FillRule::Convex {
+            self.fill(feathering, color, out);
+        } else {
+            let contours: Vec<&[PathPoint]> = std::iter::once(&self.0)
+                .chain(other_contours.iter().map(|path| &path.0))
+                .map(|points| points.as_slice())
+                .collect();
+            fill_closed_paths_with_rule(feathering, &contours, fill_rule, color, out);
+        }
+    }
 }
 
 pub mod path {
@@ -785,6 +810,296 @@ fn fill_closed_path(feathering: f32, path: &mut [PathPoint], color: Color32, out
     }
 }
 
+/// An edge of a polygon, going downwards.
+#[derive(Clone, Copy)]
+struct PolygonEdge {
+    top: Pos2,
+    bottom: Pos2,
+
+    /// `1` if the contour goes down along this edge, `-1` if it goes up.
+    winding: i32,
+
+    /// Which edge of the contours this is, in the order of [`contour_edges`].
+    index: usize,
+}
+
+impl PolygonEdge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        lerp(self.top.x..=self.bottom.x, t)
+    }
+}
+
+/// The edges of the given closed contours, from each point to the next.
+fn contour_edges(contours: &[Vec<Pos2>]) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+    contours.iter().flat_map(|contour| {
+        let next = contour.iter().cycle().skip(1);
+        contour.iter().copied().zip(next.copied())
+    })
+}
+
+/// The non-horizontal edges of the given closed contours, sorted by their top.
+fn polygon_edges(contours: &[Vec<Pos2>]) -> Vec<PolygonEdge> {
+    let mut edges = vec![];
+    for (index, (p0, p1)) in contour_edges(contours).enumerate() {
+        if p0.y < p1.y {
+            edges.push(PolygonEdge {
+                top: p0,
+                bottom: p1,
+                winding: 1,
+                index,
+            });
+        } else if p1.y < p0.y {
+            edges.push(PolygonEdge {
+                top: p1,
+                bottom: p0,
+                winding: -1,
+                index,
+            });
+        }
+    }
+    edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+    edges
+}
+
+fn is_inside(fill_rule: FillRule, winding_number: i32) -> bool {
+    match fill_rule {
+        FillRule::Convex | FillRule::NonZero => winding_number != 0,
+        FillRule::EvenOdd => winding_number % 2 != 0,
+    }
+}
+
+/// Cut the polygon into horizontal slabs where no edges start, end or cross each other.
+///
+/// `on_slab` is called with the top and bottom of each slab,
+/// and the edges crossing it sorted from left to right (with their x in the middle of the slab).
+fn for_each_slab(edges: &[PolygonEdge], mut on_slab: impl FnMut(f32, f32, &[(f32, PolygonEdge)])) {
+    // The slabs are between every vertex and every crossing of two edges:
+    let mut ys: Vec<f32> = edges.iter().flat_map(|e| [e.top.y, e.bottom.y]).collect();
+    for (i, a) in edges.iter().enumerate() {
+        for b in edges[i + 1..].iter().take_while(|b| b.top.y < a.bottom.y) {
+            let (y0, y1) = (b.top.y, a.bottom.y.min(b.bottom.y));
+            let (d0, d1) = (a.x_at(y0) - b.x_at(y0), a.x_at(y1) - b.x_at(y1));
+            if (d0 < 0.0 && 0.0 < d1) || (d1 < 0.0 && 0.0 < d0) {
+                ys.push(lerp(y0..=y1, d0 / (d0 - d1)));
+            }
+        }
+    }
+    ys.sort_by(f32::total_cmp);
+    ys.dedup();
+
+    let mut active: Vec<(f32, PolygonEdge)> = vec![];
+    let mut next_edge = 0;
+    for slab in ys.windows(2) {
+        let (y0, y1) = (slab[0], slab[1]);
+        let y_mid = 0.5 * (y0 + y1);
+
+        active.retain(|(_, edge)| y0 < edge.bottom.y);
+        while next_edge < edges.len() && edges[next_edge].top.y <= y0 {
+            if y0 < edges[next_edge].bottom.y {
+                active.push((0.0, edges[next_edge]));
+            }
+            next_edge += 1;
+        }
+        for (x_mid, edge) in &mut active {
+            *x_mid = edge.x_at(y_mid);
+        }
+        active.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        on_slab(y0, y1, &active);
+    }
+}
+
+/// Fill each slab with a trapezoid between every pair of edges bounding the inside.
+fn fill_slabs(edges: &[PolygonEdge], fill_rule: FillRule, color: Color32, out: &mut Mesh) {
+    for_each_slab(edges, |y0, y1, active| {
+        let mut winding = 0;
+        let mut left_edge = None;
+        for (_, edge) in active {
+            let was_inside = is_inside(fill_rule, winding);
+            winding += edge.winding;
+            match (was_inside, is_inside(fill_rule, winding)) {
+                (false, true) => left_edge = Some(edge),
+                (true, false) => {
+                    if let Some(left) = left_edge.take() {
+                        let idx = out.vertices.len() as u32;
+                        out.colored_vertex(pos2(left.x_at(y0), y0), color);
+                        out.colored_vertex(pos2(edge.x_at(y0), y0), color);
+                        out.colored_vertex(pos2(left.x_at(y1), y1), color);
+                        out.colored_vertex(pos2(edge.x_at(y1), y1), color);
+                        out.add_triangle(idx, idx + 1, idx + 2);
+                        out.add_triangle(idx + 1, idx + 2, idx + 3);
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Which side of each edge of the contours (in the order of [`contour_edges`]) is outside.
+///
+/// `1.0` if the outside is along the normal of the edge ([`Vec2::rot90`] of its direction),
+/// `-1.0` if it is the other way, and `0.0` if the edge doesn't separate the inside from the outside.
+///
+/// This is found from the winding numbers while sweeping the slabs.
+fn outside_sides(contours: &[Vec<Pos2>], edges: &[PolygonEdge], fill_rule: FillRule) -> Vec<f32> {
+    fn side(inside_along_normal: bool, inside_against_normal: bool) -> f32 {
+        match (inside_along_normal, inside_against_normal) {
+            (false, true) => 1.0,
+            (true, false) => -1.0,
+            _ => 0.0,
+        }
+    }
+
+    let normals: Vec<Vec2> = contour_edges(contours)
+        .map(|(p0, p1)| (p1 - p0).rot90())
+        .collect();
+    let mut sides = vec![0.0; normals.len()];
+
+    // Horizontal edges lie between two slabs, so we need to know what is inside above and below them:
+    struct HorizontalEdge {
+        index: usize,
+        y: f32,
+        x_mid: f32,
+        inside_above: bool,
+        inside_below: bool,
+    }
+    let mut horizontal: Vec<HorizontalEdge> = contour_edges(contours)
+        .enumerate()
+        .filter(|(_, (p0, p1))| p0.y == p1.y && p0.x != p1.x)
+        .map(|(index, (p0, p1))| HorizontalEdge {
+            index,
+            y: p0.y,
+            x_mid: 0.5 * (p0.x + p1.x),
+            inside_above: false,
+            inside_below: false,
+        })
+        .collect();
+    horizontal.sort_by(|a, b| a.y.total_cmp(&b.y));
+    let horizontal_range = |horizontal: &[HorizontalEdge], y: f32| {
+        horizontal.partition_point(|h| h.y < y)..horizontal.partition_point(|h| h.y <= y)
+    };
+
+    for_each_slab(edges, |y0, y1, active| {
+        let mut winding = 0;
+        for (_, edge) in active {
+            let inside_left = is_inside(fill_rule, winding);
+            winding += edge.winding;
+            let inside_right = is_inside(fill_rule, winding);
+
+            // Each edge is decided in the slab with its middle:
+            let y_mid = 0.5 * (edge.top.y + edge.bottom.y);
+            if y0 <= y_mid && y_mid < y1 {
+                sides[edge.index] = if 0.0 < normals[edge.index].x {
+                    side(inside_right, inside_left)
+                } else {
+                    side(inside_left, inside_right)
+                };
+            }
+        }
+
+        let winding_at = |y: f32, x: f32| -> i32 {
+            active
+                .iter()
+                .filter(|(_, edge)| edge.x_at(y) < x)
+                .map(|(_, edge)| edge.winding)
+                .sum()
+        };
+        let range = horizontal_range(&horizontal, y1);
+        for h in &mut horizontal[range] {
+            h.inside_above = is_inside(fill_rule, winding_at(y1, h.x_mid));
+        }
+        let range = horizontal_range(&horizontal, y0);
+        for h in &mut horizontal[range] {
+            h.inside_below = is_inside(fill_rule, winding_at(y0, h.x_mid));
+        }
+    });
+
+    for h in horizontal {
+        sides[h.index] = if 0.0 < normals[h.index].y {
+            side(h.inside_below, h.inside_above)
+        } else {
+            side(h.inside_above, h.inside_below)
+        };
+    }
+
+    sides
+}
+
+/// Tessellate the area inside the given closed contours, which can be concave,
+/// self-intersecting and have holes.
+///
+/// The polygon is cut into horizontal slabs where no edges start, end or cross each other,
+/// and each slab is filled with a trapezoid between every pair of edges bounding the inside.
+fn fill_closed_paths_with_rule(
+    feathering: f32,
+    contours: &[&[PathPoint]],
+    fill_rule: FillRule,
+    color: Color32,
+    out: &mut Mesh,
+) {
+    if color == Color32::TRANSPARENT {
+        return;
+    }
+
+    let positions: Vec<Vec<Pos2>> = contours
+        .iter()
+        .map(|contour| contour.iter().map(|p| p.pos).collect())
+        .collect();
+    let edges = polygon_edges(&positions);
+
+    if feathering <= 0.0 {
+        fill_slabs(&edges, fill_rule, color, out);
+        return;
+    }
+
+    // Like in `fill_closed_path`, the fill is inset by half the feathering,
+    // and the feathering fades out to half of it outside the path:
+    let half_feathering = 0.5 * feathering;
+    let sides = outside_sides(&positions, &edges, fill_rule);
+
+    let mut first_edge = 0;
+    let mut inset = Vec::with_capacity(contours.len());
+    for contour in contours {
+        let n = contour.len();
+        let points = contour.iter().enumerate().map(|(i, p)| {
+            let (previous, next) = (sides[first_edge + (i + n - 1) % n], sides[first_edge + i]);
+            let side = if next != 0.0 { next } else { previous };
+            p.pos - half_feathering * side * p.normal
+        });
+        inset.push(points.collect());
+        first_edge += n;
+    }
+    fill_slabs(&polygon_edges(&inset), fill_rule, color, out);
+
+    // Fade out along the edges that separate the inside from the outside:
+    let color_outer = Color32::TRANSPARENT;
+    let mut index = 0;
+    for contour in contours {
+        for (i0, p0) in contour.iter().enumerate() {
+            let p1 = &contour[(i0 + 1) % contour.len()];
+            let side = sides[index];
+            index += 1;
+            if side == 0.0 {
+                continue;
+            }
+
+            let (d0, d1) = (
+                half_feathering * side * p0.normal,
+                half_feathering * side * p1.normal,
+            );
+            let idx = out.vertices.len() as u32;
+            out.colored_vertex(p0.pos - d0, color);
+            out.colored_vertex(p1.pos - d1, color);
+            out.colored_vertex(p0.pos + d0, color_outer);
+            out.colored_vertex(p1.pos + d1, color_outer);
+            out.add_triangle(idx, idx + 1, idx + 2);
+            out.add_triangle(idx + 1, idx + 2, idx + 3);
+        }
+    }
+}
+
 /// Like [`fill_closed_path`] but with texturing.
 ///
 /// The `uv_from_pos` is called for each vertex position.
@@ -882,15 +1197,46 @@ fn stroke_path(
         return;
     }
 
-    let idx = out.vertices.len() as u32;
-
     // expand the bounding box to include the thickness of the path
     let bbox = Rect::from_points(&path.iter().map(|p| p.pos).collect::<Vec<Pos2>>())
         .expand((stroke.width / 2.0) + feathering);
 
+    if let ColorMode::Gradient(gradient) = &stroke.color {
+        // Tessellate in white, and then color it (which can add vertices):
+        let index_start = out.indices.len();
+        let white = PathStroke {
+            color: ColorMode::Solid(Color32::WHITE),
+            ..stroke.clone()
+        };
+        stroke_path(feathering, path, path_type, &white, out);
+        gradient.paint_mesh(bbox, out, index_start);
+        return;
+    }
+
+    if stroke.has_default_style() {
+        stroke_path_points(feathering, path, path_type, stroke, bbox, out);
+    } else {
+        stroke_path_styled(feathering, path, path_type, stroke, bbox, out);
+    }
+}
+
+/// Tessellate the given path as a stroke with thickness,
+/// using the joins that are already in the path.
+fn stroke_path_points(
+    feathering: f32,
+    path: &[PathPoint],
+    path_type: PathType,
+    stroke: &PathStroke,
+    bbox: Rect,
+    out: &mut Mesh,
+) {
+    let n = path.len() as u32;
+    let idx = out.vertices.len() as u32;
+
     let get_color = |col: &ColorMode, pos: Pos2| match col {
         ColorMode::Solid(col) => *col,
         ColorMode::UV(fun) => fun(bbox, pos),
+        ColorMode::Gradient(gradient) => gradient.color_at(bbox, pos),
     };
 
     if feathering > 0.0 {
@@ -1152,12 +1498,331 @@ fn stroke_path(
     }
 }
 
+/// Tessellate the given path as a stroke with the dashes, caps and joins of the stroke.
+///
+/// The joins already in the path are ignored, and recomputed from its positions.
+fn stroke_path_styled(
+    feathering: f32,
+    path: &[PathPoint],
+    path_type: PathType,
+    stroke: &PathStroke,
+    bbox: Rect,
+    out: &mut Mesh,
+) {
+    let mut points: Vec<Pos2> = Vec::with_capacity(path.len());
+    for p in path {
+        // Paths can have duplicated points, e.g. for cut-off corners:
+        if points.last() != Some(&p.pos) {
+            points.push(p.pos);
+        }
+    }
+    if path_type == PathType::Closed && 2 < points.len() && points.first() == points.last() {
+        points.pop();
+    }
+
+    let radius = stroke.width / 2.0;
+    let mut styled = Vec::new();
+
+    if let Some(dashes) = dashes_of_path(&points, path_type, stroke) {
+        for dash in dashes {
+            styled.clear();
+            add_styled_open_points(&mut styled, &dash.points, dash.direction, radius, stroke);
+            stroke_path_points(feathering, &styled, PathType::Open, stroke, bbox, out);
+        }
+    } else if path_type == PathType::Closed && 2 < points.len() {
+        add_styled_line_loop(&mut styled, &points, radius, stroke);
+        stroke_path_points(feathering, &styled, PathType::Closed, stroke, bbox, out);
+    } else {
+        // A closed path with only two points is just a line going back and forth.
+        add_styled_open_points(&mut styled, &points, Vec2::X, radius, stroke);
+        stroke_path_points(feathering, &styled, PathType::Open, stroke, bbox, out);
+    }
+}
+
+/// A piece of a dashed path.
+struct Dash {
+    points: Vec<Pos2>,
+
+    /// Direction of the path where the dash starts, for dashes of zero length.
+    direction: Vec2,
+}
+
+/// Split the path into dashes using [`PathStroke::dash_pattern`],
+/// or return `None` if it should be drawn as a solid line.
+fn dashes_of_path(points: &[Pos2], path_type: PathType, stroke: &PathStroke) -> Option<Vec<Dash>> {
+    let pattern = &stroke.dash_pattern;
+    if pattern.is_empty()
+        || points.len() < 2
+        || pattern
+            .iter()
+            .any(|length| !length.is_finite() || *length < 0.0)
+    {
+        return None;
+    }
+
+    // An odd pattern is repeated to make it even:
+    let num_entries = if pattern.len() % 2 == 0 {
+        pattern.len()
+    } else {
+        2 * pattern.len()
+    };
+    let entry_length = |index: usize| pattern[index % pattern.len()];
+    let pattern_length: f32 = (0..num_entries).map(entry_length).sum();
+    if pattern_length <= 0.0 {
+        return None;
+    }
+
+    let closing_segment =
+        (path_type == PathType::Closed).then(|| [points[points.len() - 1], points[0]]);
+    let segments = points
+        .windows(2)
+        .map(|w| [w[0], w[1]])
+        .chain(closing_segment);
+
+    let path_length: f32 = segments.clone().map(|[a, b]| a.distance(b)).sum();
+    if 100_000.0 < path_length / pattern_length * num_entries as f32 {
+        // Far too small to see the individual dashes anyway.
+        return None;
+    }
+
+    // Skip ahead to the dash offset:
+    let mut index = 0;
+    let mut remaining = entry_length(0);
+    let mut offset = stroke.dash_offset.rem_euclid(pattern_length);
+    while remaining < offset {
+        offset -= remaining;
+        index = (index + 1) % num_entries;
+        remaining = entry_length(index);
+    }
+    remaining -= offset;
+
+    let add_point = |dash: &mut Dash, point: Pos2| {
+        if dash.points.last() != Some(&point) {
+            dash.points.push(point);
+        }
+    };
+
+    let mut dashes = vec![];
+    let mut current = (index % 2 == 0).then(|| Dash {
+        points: vec![points[0]],
+        direction: (points[1] - points[0]).normalized(),
+    });
+
+    for [a, b] in segments {
+        let length = a.distance(b);
+        if length <= 0.0 {
+            continue;
+        }
+        let direction = (b - a) / length;
+
+        let mut t = 0.0;
+        while remaining < length - t {
+            t += remaining;
+            let point = a + t * direction;
+            if let Some(mut dash) = current.take() {
+                add_point(&mut dash, point);
+                dashes.push(dash);
+            } else {
+                current = Some(Dash {
+                    points: vec![point],
+                    direction,
+                });
+            }
+            index = (index + 1) % num_entries;
+            remaining = entry_length(index);
+        }
+        remaining -= length - t;
+
+        if let Some(dash) = &mut current {
+            add_point(dash, b);
+        }
+    }
+
+    dashes.extend(current);
+    Some(dashes)
+}
+
+/// Add an open path with the caps and joins of the given stroke.
+///
+/// `direction` is used for the caps if all points are the same.
+fn add_styled_open_points(
+    out: &mut Vec<PathPoint>,
+    points: &[Pos2],
+    direction: Vec2,
+    radius: f32,
+    stroke: &PathStroke,
+) {
+    let n = points.len();
+    if n == 0 || (n == 1 && stroke.cap == LineCap::Butt) {
+        return;
+    }
+
+    let (first_dir, last_dir) = if n == 1 {
+        (direction, direction)
+    } else {
+        (
+            (points[1] - points[0]).normalized(),
+            (points[n - 1] - points[n - 2]).normalized(),
+        )
+    };
+
+    // Start cap:
+    let (start, normal) = (points[0], first_dir.rot90());
+    match stroke.cap {
+        LineCap::Butt => out.push(PathPoint { pos: start, normal }),
+        LineCap::Square => out.push(PathPoint {
+            pos: start - radius * first_dir,
+            normal,
+        }),
+        LineCap::Round => {
+            // A half circle, made from pairs of points across the path:
+            let steps = arc_steps(radius, std::f32::consts::FRAC_PI_2);
+            for i in 0..=steps {
+                let angle = std::f32::consts::FRAC_PI_2 * (1.0 - i as f32 / steps as f32);
+                out.push(PathPoint {
+                    pos: start - radius * angle.sin() * first_dir,
+                    normal: angle.cos() * normal,
+                });
+            }
+        }
+    }
+
+    // Joins:
+    for i in 1..n.saturating_sub(1) {
+        let n0 = (points[i] - points[i - 1]).normalized().rot90();
+        let n1 = (points[i + 1] - points[i]).normalized().rot90();
+        add_join(out, points[i], n0, n1, radius, stroke);
+    }
+
+    // End cap:
+    let (end, normal) = (points[n - 1], last_dir.rot90());
+    match stroke.cap {
+        LineCap::Butt => out.push(PathPoint { pos: end, normal }),
+        LineCap::Square => out.push(PathPoint {
+            pos: end + radius * last_dir,
+            normal,
+        }),
+        LineCap::Round => {
+            let steps = arc_steps(radius, std::f32::consts::FRAC_PI_2);
+            for i in 0..=steps {
+                let angle = std::f32::consts::FRAC_PI_2 * (i as f32 / steps as f32);
+                out.push(PathPoint {
+                    pos: end + radius * angle.sin() * last_dir,
+                    normal: angle.cos() * normal,
+                });
+            }
+        }
+    }
+}
+
+/// Add a closed path with the joins of the given stroke.
+fn add_styled_line_loop(
+    out: &mut Vec<PathPoint>,
+    points: &[Pos2],
+    radius: f32,
+    stroke: &PathStroke,
+) {
+    let n = points.len();
+    let mut n0 = (points[0] - points[n - 1]).normalized().rot90();
+    for i in 0..n {
+        let next_i = if i + 1 == n { 0 } else { i + 1 };
+        let n1 = (points[next_i] - points[i]).normalized().rot90();
+        add_join(out, points[i], n0, n1, radius, stroke);
+        n0 = n1;
+    }
+}
+
+/// Add the corner between two segments with the normals `n0` and `n1`.
+fn add_join(
+    out: &mut Vec<PathPoint>,
+    pos: Pos2,
+    n0: Vec2,
+    n1: Vec2,
+    radius: f32,
+    stroke: &PathStroke,
+) {
+    if (n1 - n0).length_sq() < 1e-6 {
+        // (Almost) straight:
+        out.push(PathPoint { pos, normal: n0 });
+        return;
+    }
+
+    match stroke.join {
+        LineJoin::Miter => {
+            let normal = (n0 + n1) / 2.0;
+            let length_sq = normal.length_sq();
+            // The ratio of the miter length to the stroke width is `1 / normal.length()`:
+            if 0.0 < length_sq && 1.0 <= stroke.miter_limit * stroke.miter_limit * length_sq {
+                out.push(PathPoint {
+                    pos,
+                    normal: normal / length_sq,
+                });
+            } else {
+                out.push(PathPoint { pos, normal: n0 });
+                out.push(PathPoint { pos, normal: n1 });
+            }
+        }
+        LineJoin::Bevel => {
+            out.push(PathPoint { pos, normal: n0 });
+            out.push(PathPoint { pos, normal: n1 });
+        }
+        LineJoin::Round => {
+            let angle = n0.x.mul_add(n1.y, -n0.y * n1.x).atan2(n0.dot(n1));
+            let steps = arc_steps(radius, angle.abs());
+            let start_angle = n0.angle();
+            for i in 0..=steps {
+                let normal = Vec2::angled(start_angle + angle * i as f32 / steps as f32);
+                out.push(PathPoint { pos, normal });
+            }
+        }
+    }
+}
+
+/// How many steps to split an arc into, so that it is off by at most a tenth of a point.
+fn arc_steps(radius: f32, angle: f32) -> usize {
+    const TOLERANCE: f32 = 0.1;
+    let max_step = 2.0 * (1.0 - TOLERANCE / radius).clamp(-1.0, 1.0).acos();
+    ((angle / max_step).ceil() as usize).clamp(1, 64)
+}
+
 fn mul_color(color: Color32, factor: f32) -> Color32 {
     // The fast gamma-space multiply also happens to be perceptually better.
     // Win-win!
     color.gamma_multiply(factor)
 }
 
+/// The mesh to append a shape with the given clip rectangle and texture to.
+///
+/// Starts a new mesh if the last one doesn't match.
+fn mesh_for(
+    out_primitives: &mut Vec<ClippedPrimitive>,
+    clip_rect: Rect,
+    texture_id: TextureId,
+) -> &mut Mesh {
+    let start_new_mesh = match out_primitives.last() {
+        None => true,
+        Some(output_clipped_primitive) => {
+            output_clipped_primitive.clip_rect != clip_rect
+                || match &output_clipped_primitive.primitive {
+                    Primitive::Mesh(output_mesh) => output_mesh.texture_id != texture_id,
+                    Primitive::Callback(_) => true,
+                }
+        }
+    };
+
+    if start_new_mesh {
+        out_primitives.push(ClippedPrimitive {
+            clip_rect,
+            primitive: Primitive::Mesh(Mesh::with_texture(texture_id)),
+        });
+    }
+
+    match &mut out_primitives.last_mut().unwrap().primitive {
+        Primitive::Mesh(out_mesh) => out_mesh,
+        Primitive::Callback(_) => unreachable!(),
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 /// Converts [`Shape`]s into triangles ([`Mesh`]).
@@ -1174,12 +1839,18 @@ pub struct Tessellator {
     /// See [`TextureAtlas::prepared_discs`].
     prepared_discs: Vec<PreparedDisc>,
 
+    /// See [`TextureAtlas::font_pages`].
+    font_pages: Vec<Option<FontPage>>,
+
     /// size of feathering in points. normally the size of a physical pixel. 0.0 if disabled
     feathering: f32,
 
     /// Only used for culling
     clip_rect: Rect,
 
+    /// The most recently used [`ClipShape`]s, prepared for cutting meshes.
+    convex_clips: Vec<(ClipShape, ConvexClip)>,
+
     scratchpad_points: Vec<Pos2>,
     scratchpad_path: Path,
 }
@@ -1209,13 +1880,47 @@ impl Tessellator {
             options,
             font_tex_size,
             prepared_discs,
+            font_pages: Vec::new(),
             feathering,
             clip_rect: Rect::EVERYTHING,
+            convex_clips: Default::default(),
             scratchpad_points: Default::default(),
             scratchpad_path: Default::default(),
         }
     }
 
+    /// Set the textures of the font atlas pages other than the first.
+    ///
+    /// Pass in what [`TextureAtlas::font_pages`] returns.
+    /// Without this, only glyphs on the first page are painted.
+    #[inline]
+    pub fn with_font_pages(mut self, font_pages: Vec<Option<FontPage>>) -> Self {
+        self.font_pages = font_pages;
+        self
+    }
+
+    /// Would `self` and `other` produce the same output for the same shapes?
+    pub(crate) fn has_same_settings(&self, other: &Self) -> bool {
+        let Self {
+            pixels_per_point,
+            options,
+            font_tex_size,
+            prepared_discs,
+            font_pages,
+            feathering: _,        // derived from the above
+            clip_rect: _,         // set per shape
+            convex_clips: _,      // cache
+            scratchpad_points: _, // temporary storage
+            scratchpad_path: _,   // temporary storage
+        } = self;
+
+        *pixels_per_point == other.pixels_per_point
+            && *options == other.options
+            && *font_tex_size == other.font_tex_size
+            && *prepared_discs == other.prepared_discs
+            && *font_pages == other.font_pages
+    }
+
     /// Set the [`Rect`] to use for culling.
     pub fn set_clip_rect(&mut self, clip_rect: Rect) {
         self.clip_rect = clip_rect;
@@ -1242,13 +1947,45 @@ impl Tessellator {
             return; // skip empty clip rectangles
         }
 
+        self.tessellate_clipped_shape_impl(clip_rect, clip_rect, None, shape, out_primitives);
+    }
+
+    fn tessellate_clipped_shape_impl(
+        &mut self,
+        clip_rect: Rect,
+        culling_rect: Rect,
+        clip_shape: Option<&ClipShape>,
+        shape: Shape,
+        out_primitives: &mut Vec<ClippedPrimitive>,
+    ) {
         if let Shape::Vec(shapes) = shape {
             for shape in shapes {
-                self.tessellate_clipped_shape(ClippedShape { clip_rect, shape }, out_primitives);
+                self.tessellate_clipped_shape_impl(
+                    clip_rect,
+                    culling_rect,
+                    clip_shape,
+                    shape,
+                    out_primitives,
+                );
             }
             return;
         }
 
+        if let Shape::Clipped(clipped) = shape {
+            let inner_clip_shape = match clip_shape {
+                Some(outer) => outer.intersect(&clipped.clip_shape),
+                None => clipped.clip_shape,
+            };
+            self.tessellate_clipped_shape_impl(
+                clip_rect,
+                culling_rect.intersect(inner_clip_shape.bounding_rect()),
+                Some(&inner_clip_shape),
+                *clipped.shape,
+                out_primitives,
+            );
+            return;
+        }
+
         if let Shape::Callback(callback) = shape {
             out_primitives.push(ClippedPrimitive {
                 clip_rect,
@@ -1257,34 +1994,69 @@ impl Tessellator {
             return;
         }
 
-        let start_new_mesh = match out_primitives.last() {
-            None => true,
-            Some(output_clipped_primitive) => {
-                output_clipped_primitive.clip_rect != clip_rect
-                    || match &output_clipped_primitive.primitive {
-                        Primitive::Mesh(output_mesh) => {
-                            output_mesh.texture_id != shape.texture_id()
-                        }
-                        Primitive::Callback(_) => true,
-                    }
+        // Text with glyphs on other pages of the font atlas needs more than one texture:
+        let text_on_other_pages = match &shape {
+            Shape::Text(text_shape)
+                if text_shape
+                    .galley
+                    .rows
+                    .iter()
+                    .any(|row| !row.visuals.other_pages.is_empty()) =>
+            {
+                Some(text_shape.clone())
             }
+            _ => None,
         };
 
-        if start_new_mesh {
-            out_primitives.push(ClippedPrimitive {
-                clip_rect,
-                primitive: Primitive::Mesh(Mesh::default()),
-            });
+        self.clip_rect = culling_rect;
+        let feathering = self.feathering;
+        let texture_id = shape.texture_id();
+        let out = mesh_for(out_primitives, clip_rect, texture_id);
+        let (first_vertex, first_index) = (out.vertices.len(), out.indices.len());
+        self.tessellate_shape(shape, out);
+        if let Some(clip_shape) = clip_shape {
+            self.convex_clip(clip_shape)
+                .clip_mesh_tail(out, first_vertex, first_index, feathering);
         }
 
-        let out = out_primitives.last_mut().unwrap();
+        if let Some(text_shape) = text_on_other_pages {
+            for (page, font_page) in self.other_font_pages(&text_shape) {
+                let out = mesh_for(out_primitives, clip_rect, font_page.texture_id);
+                let (first_vertex, first_index) = (out.vertices.len(), out.indices.len());
+                self.tessellate_text_page(&text_shape, page, font_page.size, out);
+                if let Some(clip_shape) = clip_shape {
+                    self.convex_clip(clip_shape).clip_mesh_tail(
+                        out,
+                        first_vertex,
+                        first_index,
+                        feathering,
+                    );
+                }
+            }
+        }
+    }
 
-        if let Primitive::Mesh(out_mesh) = &mut out.primitive {
-            self.clip_rect = clip_rect;
-            self.tessellate_shape(shape, out_mesh);
+    /// The given [`ClipShape`] prepared for cutting meshes.
+    ///
+    /// Consecutive shapes usually share their clip shape, so the recently used ones are kept around.
+    fn convex_clip(&mut self, clip_shape: &ClipShape) -> &ConvexClip {
+        const MAX_CONVEX_CLIPS: usize = 16;
+
+        let index = if let Some(index) = self
+            .convex_clips
+            .iter()
+            .rposition(|(cached, _)| cached == clip_shape)
+        {
+            index
         } else {
-            unreachable!();
-        }
+            if self.convex_clips.len() == MAX_CONVEX_CLIPS {
+                self.convex_clips.remove(0);
+            }
+            let convex_clip = ConvexClip::new(&clip_shape.outline());
+            self.convex_clips.push((clip_shape.clone(), convex_clip));
+            self.convex_clips.len() - 1
+        };
+        &self.convex_clips[index].1
     }
 
     /// Tessellate a single [`Shape`] into a [`Mesh`].
@@ -1345,6 +2117,22 @@ impl Tessellator {
                 self.tessellate_quadratic_bezier(&quadratic_shape, out);
             }
             Shape::CubicBezier(cubic_shape) => self.tessellate_cubic_bezier(&cubic_shape, out),
+            Shape::BezPath(bez_path) => self.tessellate_bez_path(&bez_path, out),
+            Shape::Gradient(gradient_shape) => self.tessellate_gradient(&gradient_shape, out),
+            Shape::Clipped(clipped) => {
+                let clip_rect = self.clip_rect;
+                self.clip_rect = clip_rect.intersect(clipped.clip_shape.bounding_rect());
+                let (first_vertex, first_index) = (out.vertices.len(), out.indices.len());
+                self.tessellate_shape(*clipped.shape, out);
+                let feathering = self.feathering;
+                self.convex_clip(&clipped.clip_shape).clip_mesh_tail(
+                    out,
+                    first_vertex,
+                    first_index,
+                    feathering,
+                );
+                self.clip_rect = clip_rect;
+            }
             Shape::Callback(_) => {
                 panic!("Shape::Callback passed to Tessellator");
             }
@@ -1544,6 +2332,8 @@ impl Tessellator {
             closed,
             fill,
             stroke,
+            fill_rule,
+            contours,
         } = path_shape;
 
         self.scratchpad_path.clear();
@@ -1553,7 +2343,24 @@ impl Tessellator {
             self.scratchpad_path.add_open_points(points);
         }
 
-        if *fill != Color32::TRANSPARENT {
+        let contours: Vec<Path> = if *closed {
+            contours
+                .iter()
+                .filter(|contour| 2 <= contour.len())
+                .map(|contour| {
+                    let mut path = Path::default();
+                    path.add_line_loop(contour);
+                    path
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        if *closed && *fill_rule != FillRule::Convex {
+            self.scratchpad_path
+                .fill_with_rule(&contours, *fill_rule, self.feathering, *fill, out);
+        } else if *fill != Color32::TRANSPARENT {
             debug_assert!(
                 closed,
                 "You asked to fill a path that is not closed. That makes no sense."
@@ -1567,6 +2374,9 @@ impl Tessellator {
         };
         self.scratchpad_path
             .stroke(self.feathering, typ, stroke, out);
+        for contour in &contours {
+            contour.stroke_closed(self.feathering, stroke, out);
+        }
     }
 
     /// Tessellate a single [`Rect`] into a [`Mesh`].
@@ -1667,18 +2477,44 @@ impl Tessellator {
         self.feathering = old_feathering; // restore
     }
 
+    /// Tessellate a single [`GradientShape`] into a [`Mesh`].
+    ///
+    /// * `gradient_shape`: the shape to tessellate.
+    /// * `out`: triangles are appended to this.
+    pub fn tessellate_gradient(&mut self, gradient_shape: &GradientShape, out: &mut Mesh) {
+        if !gradient_shape.is_fillable() {
+            self.tessellate_shape(gradient_shape.shape.as_ref().clone(), out);
+            return;
+        }
+
+        if !gradient_shape.gradient.is_transparent() {
+            // Fill in white, and then color it (which can add vertices):
+            let index_start = out.indices.len();
+            let fill = gradient_shape.shape_with_fill(Color32::WHITE, false);
+            self.tessellate_shape(fill, out);
+            gradient_shape
+                .gradient
+                .paint_mesh(gradient_shape.fill_rect(), out, index_start);
+        }
+
+        let stroke = gradient_shape.shape_with_fill(Color32::TRANSPARENT, true);
+        self.tessellate_shape(stroke, out);
+    }
+
     /// Tessellate a single [`TextShape`] into a [`Mesh`].
+    ///
+    /// Glyphs on other pages of the font atlas than the first are not included,
+    /// since they need another texture.
+    /// Use [`Self::tessellate_text_meshes`] to get those too.
+    ///
     /// * `text_shape`: the text to tessellate.
     /// * `out`: triangles are appended to this.
     pub fn tessellate_text(&mut self, text_shape: &TextShape, out: &mut Mesh) {
         let TextShape {
-            pos: galley_pos,
             galley,
             underline,
-            override_text_color,
-            fallback_color,
             opacity_factor,
-            angle,
+            ..
         } = text_shape;
 
         if galley.is_empty() {
@@ -1697,83 +2533,22 @@ impl Tessellator {
         out.vertices.reserve(galley.num_vertices);
         out.indices.reserve(galley.num_indices);
 
-        // The contents of the galley is already snapped to pixel coordinates,
-        // but we need to make sure the galley ends up on the start of a physical pixel:
-        let galley_pos = pos2(
-            self.round_to_pixel(galley_pos.x),
-            self.round_to_pixel(galley_pos.y),
-        );
-
-        let uv_normalizer = vec2(
-            1.0 / self.font_tex_size[0] as f32,
-            1.0 / self.font_tex_size[1] as f32,
-        );
-
-        let rotator = Rot2::from_angle(*angle);
-
+        let font_tex_size = self.font_tex_size;
         for row in &galley.rows {
             if row.visuals.mesh.is_empty() {
                 continue;
             }
 
-            let mut row_rect = row.visuals.mesh_bounds;
-            if *angle != 0.0 {
-                row_rect = row_rect.rotate_bb(rotator);
-            }
-            row_rect = row_rect.translate(galley_pos.to_vec2());
-
-            if self.options.coarse_tessellation_culling && !self.clip_rect.intersects(row_rect) {
-                // culling individual lines of text is important, since a single `Shape::Text`
-                // can span hundreds of lines.
+            let Some(row_rect) = self.visible_row_rect(text_shape, row) else {
                 continue;
-            }
-
-            let index_offset = out.vertices.len() as u32;
+            };
 
-            out.indices.extend(
-                row.visuals
-                    .mesh
-                    .indices
-                    .iter()
-                    .map(|index| index + index_offset),
-            );
-
-            out.vertices.extend(
-                row.visuals
-                    .mesh
-                    .vertices
-                    .iter()
-                    .enumerate()
-                    .map(|(i, vertex)| {
-                        let Vertex { pos, uv, mut color } = *vertex;
-
-                        if let Some(override_text_color) = override_text_color {
-                            // Only override the glyph color (not background color, strike-through color, etc)
-                            if row.visuals.glyph_vertex_range.contains(&i) {
-                                color = *override_text_color;
-                            }
-                        } else if color == Color32::PLACEHOLDER {
-                            color = *fallback_color;
-                        }
-
-                        if *opacity_factor < 1.0 {
-                            color = color.gamma_multiply(*opacity_factor);
-                        }
-
-                        debug_assert!(color != Color32::PLACEHOLDER, "A placeholder color made it to the tessellator. You forgot to set a fallback color.");
-
-                        let offset = if *angle == 0.0 {
-                            pos.to_vec2()
-                        } else {
-                            rotator * pos.to_vec2()
-                        };
-
-                        Vertex {
-                            pos: galley_pos + offset,
-                            uv: (uv.to_vec2() * uv_normalizer).to_pos2(),
-                            color,
-                        }
-                    }),
+            self.add_text_row_mesh(
+                text_shape,
+                &row.visuals.mesh,
+                &row.visuals.glyph_vertex_range,
+                font_tex_size,
+                out,
             );
 
             if *underline != Stroke::NONE {
@@ -1789,6 +2564,165 @@ impl Tessellator {
         }
     }
 
+    /// Tessellate a [`TextShape`] into one [`Mesh`] per page of the font atlas that it uses.
+    ///
+    /// The first page uses [`crate::TextureId::default`], like [`Self::tessellate_text`].
+    /// Glyphs on pages without a texture (see [`Self::with_font_pages`]) are skipped.
+    pub fn tessellate_text_meshes(&mut self, text_shape: &TextShape) -> Vec<Mesh> {
+        let mut first_page = Mesh::default();
+        self.tessellate_text(text_shape, &mut first_page);
+        let mut meshes = vec![first_page];
+
+        for (page, font_page) in self.other_font_pages(text_shape) {
+            let mut mesh = Mesh::with_texture(font_page.texture_id);
+            self.tessellate_text_page(text_shape, page, font_page.size, &mut mesh);
+            meshes.push(mesh);
+        }
+
+        meshes.retain(|mesh| !mesh.is_empty());
+        meshes
+    }
+
+    /// The pages of the font atlas other than the first that the text uses, and that have a texture.
+    fn other_font_pages(&self, text_shape: &TextShape) -> Vec<(usize, FontPage)> {
+        let mut pages: Vec<usize> = text_shape
+            .galley
+            .rows
+            .iter()
+            .flat_map(|row| row.visuals.other_pages.iter().map(|page| page.page))
+            .collect();
+        pages.sort_unstable();
+        pages.dedup();
+
+        pages
+            .into_iter()
+            .filter_map(|page| {
+                // Without a texture we don't know how to paint it:
+                let font_page = (*self.font_pages.get(page - 1)?)?;
+                Some((page, font_page))
+            })
+            .collect()
+    }
+
+    /// Tessellate the glyphs of a [`TextShape`] that are on the given page of the font atlas.
+    ///
+    /// * `page_size`: size of that page of the font atlas, in texels.
+    /// * `out`: triangles are appended to this. Should use the texture of the page.
+    pub fn tessellate_text_page(
+        &mut self,
+        text_shape: &TextShape,
+        page: usize,
+        page_size: [usize; 2],
+        out: &mut Mesh,
+    ) {
+        if text_shape.galley.is_empty() || text_shape.opacity_factor <= 0.0 {
+            return;
+        }
+
+        for row in &text_shape.galley.rows {
+            let Some(page_visuals) = row.visuals.other_pages.iter().find(|p| p.page == page) else {
+                continue;
+            };
+            if self.visible_row_rect(text_shape, row).is_none() {
+                continue;
+            }
+            self.add_text_row_mesh(
+                text_shape,
+                &page_visuals.mesh,
+                &page_visuals.glyph_vertex_range,
+                page_size,
+                out,
+            );
+        }
+    }
+
+    /// The screen rectangle of a row of text, or `None` if it is culled.
+    fn visible_row_rect(&self, text_shape: &TextShape, row: &crate::text::Row) -> Option<Rect> {
+        let galley_pos = self.galley_pos(text_shape);
+
+        let mut row_rect = row.visuals.mesh_bounds;
+        if text_shape.angle != 0.0 {
+            row_rect = row_rect.rotate_bb(Rot2::from_angle(text_shape.angle));
+        }
+        row_rect = row_rect.translate(galley_pos.to_vec2());
+
+        if self.options.coarse_tessellation_culling && !self.clip_rect.intersects(row_rect) {
+            // culling individual lines of text is important, since a single `Shape::Text`
+            // can span hundreds of lines.
+            None
+        } else {
+            Some(row_rect)
+        }
+    }
+
+    fn galley_pos(&self, text_shape: &TextShape) -> Pos2 {
+        // The contents of the galley is already snapped to pixel coordinates,
+        // but we need to make sure the galley ends up on the start of a physical pixel:
+        pos2(
+            self.round_to_pixel(text_shape.pos.x),
+            self.round_to_pixel(text_shape.pos.y),
+        )
+    }
+
+    /// Add the mesh of a row of text, with colors and position from the [`TextShape`].
+    fn add_text_row_mesh(
+        &self,
+        text_shape: &TextShape,
+        mesh: &Mesh,
+        glyph_vertex_range: &std::ops::Range<usize>,
+        tex_size: [usize; 2],
+        out: &mut Mesh,
+    ) {
+        let TextShape {
+            override_text_color,
+            fallback_color,
+            opacity_factor,
+            angle,
+            ..
+        } = text_shape;
+
+        let galley_pos = self.galley_pos(text_shape);
+        let uv_normalizer = vec2(1.0 / tex_size[0] as f32, 1.0 / tex_size[1] as f32);
+        let rotator = Rot2::from_angle(*angle);
+
+        let index_offset = out.vertices.len() as u32;
+
+        out.indices
+            .extend(mesh.indices.iter().map(|index| index + index_offset));
+
+        out.vertices
+            .extend(mesh.vertices.iter().enumerate().map(|(i, vertex)| {
+                let Vertex { pos, uv, mut color } = *vertex;
+
+                if let Some(override_text_color) = override_text_color {
+                    // Only override the glyph color (not background color, strike-through color, etc)
+                    if glyph_vertex_range.contains(&i) {
+                        color = *override_text_color;
+                    }
+                } else if color == Color32::PLACEHOLDER {
+                    color = *fallback_color;
+                }
+
+                if *opacity_factor < 1.0 {
+                    color = color.gamma_multiply(*opacity_factor);
+                }
+
+                debug_assert!(color != Color32::PLACEHOLDER, "A placeholder color made it to the tessellator. You forgot to set a fallback color.");
+
+                let offset = if *angle == 0.0 {
+                    pos.to_vec2()
+                } else {
+                    rotator * pos.to_vec2()
+                };
+
+                Vertex {
+                    pos: galley_pos + offset,
+                    uv: (uv.to_vec2() * uv_normalizer).to_pos2(),
+                    color,
+                }
+            }));
+    }
+
     /// Tessellate a single [`QuadraticBezierShape`] into a [`Mesh`].
     ///
     /// * `quadratic_shape`: the shape to tessellate.
@@ -1845,6 +2779,56 @@ impl Tessellator {
         }
     }
 
+    /// Tessellate a single [`BezPathShape`] into a [`Mesh`].
+    ///
+    /// * `bez_path`: the shape to tessellate.
+    /// * `out`: triangles are appended to this.
+    pub fn tessellate_bez_path(&mut self, bez_path: &BezPathShape, out: &mut Mesh) {
+        if self.options.coarse_tessellation_culling
+            && !bez_path.visual_bounding_rect().intersects(self.clip_rect)
+        {
+            return;
+        }
+
+        crate::profile_function!();
+
+        let subpaths = bez_path.flatten(self.options.bezier_tolerance);
+
+        if bez_path.fill != Color32::TRANSPARENT {
+            // Open subpaths are filled as if they were closed:
+            let mut contours: Vec<Path> = subpaths
+                .iter()
+                .map(|(points, _closed)| {
+                    let mut path = Path::default();
+                    path.add_line_loop(points);
+                    path
+                })
+                .collect();
+
+            let color = bez_path.fill;
+            if bez_path.fill_rule == FillRule::Convex {
+                for contour in &mut contours {
+                    contour.fill(self.feathering, color, out);
+                }
+            } else if let Some((first, others)) = contours.split_first_mut() {
+                first.fill_with_rule(others, bez_path.fill_rule, self.feathering, color, out);
+            }
+        }
+
+        for (points, closed) in &subpaths {
+            self.scratchpad_path.clear();
+            if *closed {
+                self.scratchpad_path.add_line_loop(points);
+                self.scratchpad_path
+                    .stroke_closed(self.feathering, &bez_path.stroke, out);
+            } else {
+                self.scratchpad_path.add_open_points(points);
+                self.scratchpad_path
+                    .stroke_open(self.feathering, &bez_path.stroke, out);
+            }
+        }
+    }
+
     fn tessellate_bezier_complete(
         &mut self,
         points: &[Pos2],
@@ -1970,7 +2954,14 @@ impl Tessellator {
 
                 Shape::Path(path_shape) => 32 < path_shape.points.len(),
 
-                Shape::QuadraticBezier(_) | Shape::CubicBezier(_) | Shape::Ellipse(_) => true,
+                Shape::Gradient(gradient_shape) => should_parallelize(&gradient_shape.shape),
+
+                Shape::Clipped(clipped) => should_parallelize(&clipped.shape),
+
+                Shape::QuadraticBezier(_)
+                | Shape::CubicBezier(_)
+                | Shape::BezPath(_)
+                | Shape::Ellipse(_) => true,
 
                 Shape::Noop
                 | Shape::Text(_)
@@ -2062,6 +3053,54 @@ fn test_tessellator() {
     assert_eq!(primitives.len(), 2);
 }
 
+#[test]
+fn test_text_on_several_font_pages() {
+    use crate::{text::Fonts, *};
+
+    // Huge glyphs, so they need several pages:
+    let fonts = Fonts::new(1.0, 1024, Default::default());
+    let text: String = ('a'..='z').chain('A'..='Z').collect();
+    let galley = fonts.layout_no_wrap(text, FontId::proportional(300.0), Color32::WHITE);
+
+    let mut next_texture = 0;
+    let page_textures: Vec<TextureId> = fonts
+        .font_page_deltas(|| {
+            next_texture += 1;
+            TextureId::Managed(next_texture)
+        })
+        .into_iter()
+        .map(|(texture_id, _)| texture_id)
+        .collect();
+    assert!(
+        page_textures.len() > 1,
+        "The glyphs should not fit on one page"
+    );
+
+    let atlas = fonts.texture_atlas();
+    let atlas = atlas.lock();
+    let mut tessellator = Tessellator::new(
+        1.0,
+        Default::default(),
+        atlas.size(),
+        atlas.prepared_discs(),
+    )
+    .with_font_pages(atlas.font_pages());
+    let text_shape = TextShape::new(pos2(0.0, 0.0), galley, Color32::WHITE);
+    let meshes = tessellator.tessellate_text_meshes(&text_shape);
+
+    let mut texture_ids: Vec<TextureId> = meshes.iter().map(|mesh| mesh.texture_id).collect();
+    texture_ids.dedup();
+    assert_eq!(texture_ids.len(), meshes.len(), "One mesh per page");
+    assert_eq!(texture_ids[0], TextureId::default());
+    assert!(meshes.len() > 1);
+    for texture_id in &texture_ids {
+        assert!(page_textures.contains(texture_id));
+    }
+
+    let num_glyphs: usize = meshes.iter().map(|mesh| mesh.vertices.len() / 4).sum();
+    assert_eq!(num_glyphs, 52, "No glyph is lost");
+}
+
 #[test]
 fn path_bounding_box() {
     use crate::*;
@@ -2106,3 +3145,312 @@ fn path_bounding_box() {
         );
     }
 }
+
+#[test]
+fn test_dashes() {
+    use crate::*;
+
+    let line = [pos2(0.0, 0.0), pos2(20.0, 0.0)];
+    let dash_ranges = |stroke: &PathStroke| -> Vec<(f32, f32)> {
+        dashes_of_path(&line, PathType::Open, stroke)
+            .unwrap()
+            .iter()
+            .map(|dash| (dash.points[0].x, dash.points.last().unwrap().x))
+            .collect()
+    };
+
+    let stroke = PathStroke::new(1.0, Color32::WHITE).with_dashes(vec![5.0], 0.0);
+    assert_eq!(dash_ranges(&stroke), vec![(0.0, 5.0), (10.0, 15.0)]);
+
+    let stroke = stroke.with_dashes(vec![5.0], 2.5);
+    assert_eq!(
+        dash_ranges(&stroke),
+        vec![(0.0, 2.5), (7.5, 12.5), (17.5, 20.0)]
+    );
+
+    // Dots:
+    let stroke = stroke.with_dashes(vec![0.0, 10.0], 0.0);
+    assert_eq!(dash_ranges(&stroke), vec![(0.0, 0.0), (10.0, 10.0)]);
+
+    // A dashed corner is joined:
+    let corner = [pos2(0.0, 0.0), pos2(10.0, 0.0), pos2(10.0, 10.0)];
+    let stroke = stroke.with_dashes(vec![15.0, 1.0], 0.0);
+    let dashes = dashes_of_path(&corner, PathType::Open, &stroke).unwrap();
+    assert_eq!(dashes.len(), 2);
+    assert_eq!(
+        dashes[0].points,
+        corner[..2]
+            .iter()
+            .copied()
+            .chain([pos2(10.0, 5.0)])
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_caps_and_joins() {
+    use crate::*;
+
+    let stroke_bounds = |points: &[Pos2], stroke: PathStroke| -> Rect {
+        let mut path = Path::default();
+        path.add_open_points(points);
+        let mut mesh = Mesh::default();
+        path.stroke_open(0.0, &stroke, &mut mesh);
+        mesh.calc_bounds()
+    };
+
+    let line = [pos2(0.0, 0.0), pos2(20.0, 0.0)];
+    let stroke = PathStroke::new(4.0, Color32::WHITE);
+    let butt = stroke_bounds(&line, stroke.clone());
+    assert_eq!(butt, Rect::from_min_max(pos2(0.0, -2.0), pos2(20.0, 2.0)));
+
+    let square = stroke_bounds(&line, stroke.clone().with_cap(LineCap::Square));
+    assert_eq!(
+        square,
+        Rect::from_min_max(pos2(-2.0, -2.0), pos2(22.0, 2.0))
+    );
+
+    let round = stroke_bounds(&line, stroke.clone().with_cap(LineCap::Round));
+    assert!((round.left() + 2.0).abs() < 1e-3 && (round.right() - 22.0).abs() < 1e-3);
+
+    // A sharp corner (a miter of about 10 times the width) is beveled by a lower miter limit:
+    let corner = [pos2(0.0, 0.0), pos2(100.0, 0.0), pos2(0.0, 10.0)];
+    let miter = stroke_bounds(&corner, stroke.clone().with_miter_limit(100.0));
+    assert!(120.0 < miter.right());
+    let beveled = stroke_bounds(&corner, stroke.clone().with_miter_limit(5.0));
+    assert!(beveled.right() < 103.0);
+    let bevel = stroke_bounds(&corner, stroke.clone().with_join(LineJoin::Bevel));
+    assert_eq!(beveled, bevel);
+    let round = stroke_bounds(&corner, stroke.with_join(LineJoin::Round));
+    assert!((round.right() - 102.0).abs() <= 0.1, "{round:?}");
+}
+
+#[test]
+fn test_fill_rules() {
+    use crate::*;
+
+    let fill_area = |shape: PathShape| -> f32 {
+        let mut tessellator = Tessellator::new(1.0, Default::default(), [1, 1], vec![]);
+        tessellator.feathering = 0.0;
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_path(&shape, &mut mesh);
+        mesh.indices
+            .chunks(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].pos);
+                0.5 * (b - a).x.mul_add((c - a).y, -(b - a).y * (c - a).x).abs()
+            })
+            .sum()
+    };
+    let square = |min: f32, max: f32| {
+        vec![
+            pos2(min, min),
+            pos2(max, min),
+            pos2(max, max),
+            pos2(min, max),
+        ]
+    };
+    let polygon = |points: Vec<Pos2>, fill_rule: FillRule| {
+        PathShape::convex_polygon(points, Color32::WHITE, Stroke::NONE).with_fill_rule(fill_rule)
+    };
+
+    // Concave:
+    let l_shape = vec![
+        pos2(0.0, 0.0),
+        pos2(10.0, 0.0),
+        pos2(10.0, 2.0),
+        pos2(2.0, 2.0),
+        pos2(2.0, 10.0),
+        pos2(0.0, 10.0),
+    ];
+    assert_eq!(fill_area(polygon(l_shape, FillRule::NonZero)), 36.0);
+
+    // A hole:
+    let mut hole = square(2.0, 8.0);
+    let frame = polygon(square(0.0, 10.0), FillRule::EvenOdd).with_contour(hole.clone());
+    assert_eq!(fill_area(frame), 64.0);
+    hole.reverse();
+    let frame = polygon(square(0.0, 10.0), FillRule::NonZero).with_contour(hole);
+    assert_eq!(fill_area(frame), 64.0);
+
+    // Overlapping contours:
+    let overlap = |fill_rule| polygon(square(0.0, 10.0), fill_rule).with_contour(square(5.0, 15.0));
+    assert_eq!(fill_area(overlap(FillRule::NonZero)), 175.0);
+    assert_eq!(fill_area(overlap(FillRule::EvenOdd)), 150.0);
+
+    // Self-intersecting bow tie:
+    let bow_tie = vec![
+        pos2(0.0, 0.0),
+        pos2(10.0, 10.0),
+        pos2(10.0, 0.0),
+        pos2(0.0, 10.0),
+    ];
+    assert_eq!(fill_area(polygon(bow_tie, FillRule::NonZero)), 50.0);
+}
+
+#[test]
+fn test_tessellate_clipped_shapes() {
+    use crate::*;
+
+    let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 100.0));
+    let circle = ClipShape::circle(rect.center(), 25.0);
+    let right_half = ClipShape::from(Rect::from_min_max(pos2(50.0, 0.0), rect.max));
+    let clipped = |shape: Shape| ClippedShape {
+        clip_rect: Rect::EVERYTHING,
+        shape: ClippedToShape::clip(shape, &circle),
+    };
+
+    // Shapes that are already clipped get the intersection:
+    let half = ClippedToShape::clip(Shape::rect_filled(rect, 0.0, Color32::WHITE), &right_half);
+    let shapes = vec![
+        clipped(Shape::rect_filled(rect, 0.0, Color32::RED)),
+        clipped(half),
+        clipped(Shape::rect_filled(rect, 0.0, Color32::RED)),
+    ];
+
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1, 1], vec![]);
+    let primitives = tessellator.tessellate_shapes(shapes);
+    let Primitive::Mesh(mesh) = &primitives[0].primitive else {
+        panic!("Expected a mesh");
+    };
+    assert!(mesh.is_valid());
+    let bounds = |color| {
+        Rect::from_points(
+            &mesh
+                .vertices
+                .iter()
+                .filter(|v| v.color == color)
+                .map(|v| v.pos)
+                .collect::<Vec<_>>(),
+        )
+    };
+    let red = bounds(Color32::RED);
+    assert!(circle.bounding_rect().contains_rect(red), "{red:?}");
+    let white = bounds(Color32::WHITE);
+    assert!(circle.bounding_rect().contains_rect(white), "{white:?}");
+    assert!(50.0 <= white.min.x, "{white:?}");
+
+    // Each distinct clip shape is only prepared once:
+    assert_eq!(tessellator.convex_clips.len(), 2);
+}
+
+#[test]
+fn test_feathered_fill_rules() {
+    use crate::*;
+
+    // The bounds of the opaque and of the transparent vertices:
+    let feathered_bounds = |shape: PathShape| -> (Rect, Rect) {
+        let mut tessellator = Tessellator::new(1.0, Default::default(), [1, 1], vec![]);
+        tessellator.feathering = 1.0;
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_path(&shape, &mut mesh);
+        let bounds = |opaque: bool| {
+            Rect::from_points(
+                &mesh
+                    .vertices
+                    .iter()
+                    .filter(|v| (v.color == Color32::WHITE) == opaque)
+                    .map(|v| v.pos)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        (bounds(true), bounds(false))
+    };
+    let square = |min: f32, max: f32| {
+        vec![
+            pos2(min, min),
+            pos2(max, min),
+            pos2(max, max),
+            pos2(min, max),
+        ]
+    };
+    let polygon = |points: Vec<Pos2>, fill_rule: FillRule| {
+        PathShape::convex_polygon(points, Color32::WHITE, Stroke::NONE).with_fill_rule(fill_rule)
+    };
+
+    // The same extent as the convex fill, in both directions of the contour:
+    let convex = feathered_bounds(polygon(square(0.0, 10.0), FillRule::Convex));
+    assert_eq!(convex.0, Rect::from_min_max(pos2(0.5, 0.5), pos2(9.5, 9.5)));
+    assert_eq!(
+        convex.1,
+        Rect::from_min_max(pos2(-0.5, -0.5), pos2(10.5, 10.5))
+    );
+    for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+        assert_eq!(
+            feathered_bounds(polygon(square(0.0, 10.0), fill_rule)),
+            convex
+        );
+        let mut reversed = square(0.0, 10.0);
+        reversed.reverse();
+        assert_eq!(feathered_bounds(polygon(reversed, fill_rule)), convex);
+    }
+
+    // The fill around a hole is inset away from it:
+    let frame = polygon(square(0.0, 10.0), FillRule::EvenOdd).with_contour(square(2.0, 8.0));
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1, 1], vec![]);
+    tessellator.feathering = 1.0;
+    let mut mesh = Mesh::default();
+    tessellator.tessellate_path(&frame, &mut mesh);
+    let hole = Rect::from_min_max(pos2(1.5, 1.5), pos2(8.5, 8.5));
+    for vertex in &mesh.vertices {
+        if vertex.color == Color32::WHITE {
+            assert!(!hole.shrink(0.01).contains(vertex.pos), "{:?}", vertex.pos);
+        } else {
+            assert!(!hole.shrink(1.01).contains(vertex.pos), "{:?}", vertex.pos);
+        }
+    }
+}
+
+#[test]
+fn test_gradient_fill_bounds() {
+    use crate::*;
+
+    let square = vec![
+        pos2(0.0, 0.0),
+        pos2(100.0, 0.0),
+        pos2(100.0, 100.0),
+        pos2(0.0, 100.0),
+    ];
+    let stroke = Stroke::new(20.0, Color32::GREEN);
+    let convex = PathShape::convex_polygon(square.clone(), Color32::WHITE, stroke);
+    let mut bez_path = BezPathShape::new(Color32::WHITE, stroke);
+    bez_path
+        .move_to(square[0])
+        .line_to(square[1])
+        .line_to(square[2])
+        .line_to(square[3])
+        .close();
+    let shapes: [Shape; 4] = [
+        RectShape::new(Rect::from_points(&square), 0.0, Color32::WHITE, stroke).into(),
+        convex.clone().into(),
+        convex.with_fill_rule(FillRule::NonZero).into(),
+        bez_path.into(),
+    ];
+
+    // The gradient goes from edge to edge of the fill, no matter how wide the stroke is:
+    let gradient = std::sync::Arc::new(Gradient::horizontal(Color32::RED, Color32::BLUE));
+    for shape in shapes {
+        let mut tessellator = Tessellator::new(1.0, Default::default(), [1, 1], vec![]);
+        tessellator.feathering = 0.0;
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_shape(
+            GradientShape::new(shape, gradient.clone()).into(),
+            &mut mesh,
+        );
+
+        let fill: Vec<&Vertex> = mesh
+            .vertices
+            .iter()
+            .filter(|v| v.color != Color32::GREEN)
+            .collect();
+        assert!(!fill.is_empty());
+        for vertex in fill {
+            assert!((0.0..=100.0).contains(&vertex.pos.x), "{vertex:?}");
+            if vertex.pos.x == 0.0 {
+                assert_eq!(vertex.color, Color32::RED);
+            } else if vertex.pos.x == 100.0 {
+                assert_eq!(vertex.color, Color32::BLUE);
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Rasterizing color glyphs (e.g. emojis).
+//!
+//! `ab_glyph` only gives us outlines, so we read the color tables ourselves:
+//! * `COLR`: layers of outlines, each with its own color (e.g. Twemoji, Segoe UI Emoji).
+//! * `CBDT` and `sbix`: a bitmap per glyph, usually PNG (e.g. Noto Color Emoji, Apple Color Emoji).
+
+use ab_glyph::{Font as _, ScaleFont as _};
+use ecolor::Color32;
+use emath::{vec2, Vec2};
+
+use crate::{ColorImage, ResizeFilter};
+
+/// Color of `COLR` layers that should use the text color.
+///
+/// We can't tint just parts of a glyph, so we use white instead.
+const FOREGROUND_COLOR: Color32 = Color32::WHITE;
+
+/// A rasterized color glyph.
+pub(crate) struct ColorGlyphImage {
+    /// Width and height in pixels.
+    pub size: [usize; 2],
+
+    /// Position of the top left corner relative to the glyph origin (on the baseline), in pixels.
+    pub offset: Vec2,
+
+    /// Premultiplied colors, row by row.
+    pub pixels: Vec<Color32>,
+}
+
+/// Returns `None` if this is not a color glyph.
+pub(crate) fn rasterize(
+    face: &ttf_parser::Face<'_>,
+    font: &ab_glyph::FontArc,
+    glyph_id: ab_glyph::GlyphId,
+    scale_in_pixels: f32,
+) -> Option<ColorGlyphImage> {
+    let ttf_glyph_id = ttf_parser::GlyphId(glyph_id.0);
+    if face.is_color_glyph(ttf_glyph_id) {
+        rasterize_layers(face, font, ttf_glyph_id, scale_in_pixels)
+    } else {
+        let pixels_per_em =
+            font.as_scaled(scale_in_pixels).h_scale_factor() * face.units_per_em() as f32;
+        rasterize_bitmap(face, ttf_glyph_id, pixels_per_em)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[derive(Default)]
+struct LayerCollector {
+    outline: Option<ttf_parser::GlyphId>,
+    layers: Vec<(ttf_parser::GlyphId, Color32)>,
+}
+
+impl ttf_parser::colr::Painter for LayerCollector {
+    fn outline(&mut self, glyph_id: ttf_parser::GlyphId) {
+        self.outline = Some(glyph_id);
+    }
+
+    fn paint_foreground(&mut self) {
+        if let Some(glyph_id) = self.outline {
+            self.layers.push((glyph_id, FOREGROUND_COLOR));
+        }
+    }
+
+    fn paint_color(&mut self, color: ttf_parser::RgbaColor) {
+        if let Some(glyph_id) = self.outline {
+            let color =
+                Color32::from_rgba_unmultiplied(color.red, color.green, color.blue, color.alpha);
+            self.layers.push((glyph_id, color));
+        }
+    }
+}
+
+/// `COLR`: paint each layer on top of the previous ones.
+fn rasterize_layers(
+    face: &ttf_parser::Face<'_>,
+    font: &ab_glyph::FontArc,
+    glyph_id: ttf_parser::GlyphId,
+    scale_in_pixels: f32,
+) -> Option<ColorGlyphImage> {
+    let mut collector = LayerCollector::default();
+    face.paint_color_glyph(glyph_id, 0, &mut collector)?;
+
+    let layers: Vec<(ab_glyph::OutlinedGlyph, Color32)> = collector
+        .layers
+        .into_iter()
+        .filter_map(|(layer_id, color)| {
+            let glyph = ab_glyph::GlyphId(layer_id.0)
+                .with_scale_and_position(scale_in_pixels, ab_glyph::point(0.0, 0.0));
+            Some((font.outline_glyph(glyph)?, color))
+        })
+        .collect();
+
+    let bounds = layers
+        .iter()
+        .map(|(glyph, _)| glyph.px_bounds())
+        .reduce(|a, b| ab_glyph::Rect {
+            min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+            max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+        })?;
+
+    let [w, h] = [bounds.width() as usize, bounds.height() as usize];
+    let mut pixels = vec![Color32::TRANSPARENT; w * h];
+
+    for (glyph, color) in &layers {
+        // `px_bounds` are whole pixels, so this is exact:
+        let glyph_bounds = glyph.px_bounds();
+        let dx = (glyph_bounds.min.x - bounds.min.x) as usize;
+        let dy = (glyph_bounds.min.y - bounds.min.y) as usize;
+        glyph.draw(|x, y, coverage| {
+            let (x, y) = (dx + x as usize, dy + y as usize);
+            if 0.0 < coverage && x < w && y < h {
+                let pixel = &mut pixels[y * w + x];
+                *pixel = blend_over(*pixel, color.gamma_multiply(coverage));
+            }
+        });
+    }
+
+    Some(ColorGlyphImage {
+        size: [w, h],
+        offset: vec2(bounds.min.x, bounds.min.y),
+        pixels,
+    })
+}
+
+/// Paint premultiplied `src` on top of premultiplied `dst`.
+fn blend_over(dst: Color32, src: Color32) -> Color32 {
+    let [sr, sg, sb, sa] = src.to_array();
+    let [dr, dg, db, da] = dst.to_array();
+    let keep = 255 - sa as u32;
+    let over = |s: u8, d: u8| (s as u32 + (d as u32 * keep + 127) / 255).min(255) as u8;
+    Color32::from_rgba_premultiplied(over(sr, dr), over(sg, dg), over(sb, db), over(sa, da))
+}
+
+// ----------------------------------------------------------------------------
+
+/// `CBDT` or `sbix`: decode the bitmap closest in size, and scale it to the size we want.
+fn rasterize_bitmap(
+    face: &ttf_parser::Face<'_>,
+    glyph_id: ttf_parser::GlyphId,
+    pixels_per_em: f32,
+) -> Option<ColorGlyphImage> {
+    use ttf_parser::RasterImageFormat;
+
+    let image = face.glyph_raster_image(glyph_id, pixels_per_em.round() as u16)?;
+
+    let (size, pixels) = match image.format {
+        RasterImageFormat::PNG => decode_png(image.data)?,
+        RasterImageFormat::BitmapPremulBgra32 => {
+            let size = [image.width as usize, image.height as usize];
+            let pixels: Vec<Color32> = image
+                .data
+                .chunks_exact(4)
+                .map(|p| Color32::from_rgba_premultiplied(p[2], p[1], p[0], p[3]))
+                .collect();
+            if pixels.len() != size[0] * size[1] {
+                return None;
+            }
+            (size, pixels)
+        }
+        _ => {
+            return None; // Monochrome bitmaps - we use the outlines for those.
+        }
+    };
+
+    let scale = pixels_per_em / image.pixels_per_em.max(1) as f32;
+    let new_size = [
+        ((size[0] as f32 * scale).round() as usize).max(1),
+        ((size[1] as f32 * scale).round() as usize).max(1),
+    ];
+
+    // The offset is to the bottom left corner, with y pointing up:
+    let offset = vec2(
+        image.x as f32,
+        -(image.y as f32 + size[1] as f32), // to top left, y down
+    ) * scale;
+
+    Some(ColorGlyphImage {
+        size: new_size,
+        offset,
+        // Bitmap emoji fonts usually only come in one large size, so this is mostly downscaling:
+        pixels: ColorImage { size, pixels }
+            .resized(new_size, ResizeFilter::Triangle)
+            .pixels,
+    })
+}
+
+/// Returns the size and premultiplied pixels.
+fn decode_png(data: &[u8]) -> Option<([usize; 2], Vec<Color32>)> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    let bytes = &buffer[..info.buffer_size()];
+
+    let pixels: Vec<Color32> = match info.color_type {
+        png::ColorType::Rgba => bytes
+            .chunks_exact(4)
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]))
+            .collect(),
+        png::ColorType::Rgb => bytes
+            .chunks_exact(3)
+            .map(|p| Color32::from_rgb(p[0], p[1], p[2]))
+            .collect(),
+        png::ColorType::GrayscaleAlpha => bytes
+            .chunks_exact(2)
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[0], p[0], p[1]))
+            .collect(),
+        png::ColorType::Grayscale => bytes.iter().map(|&l| Color32::from_gray(l)).collect(),
+        png::ColorType::Indexed => {
+            return None; // should have been expanded by `normalize_to_color8`
+        }
+    };
+
+    Some(([info.width as usize, info.height as usize], pixels))
+}
+
+#[test]
+fn test_blend_over() {
+    assert_eq!(blend_over(Color32::RED, Color32::TRANSPARENT), Color32::RED);
+    assert_eq!(blend_over(Color32::RED, Color32::BLUE), Color32::BLUE);
+    let half_blue = Color32::from_rgba_premultiplied(0, 0, 128, 128);
+    assert_eq!(
+        blend_over(Color32::RED, half_blue),
+        Color32::from_rgba_premultiplied(127, 0, 128, 255)
+    );
+}
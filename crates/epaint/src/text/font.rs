@@ -1,7 +1,7 @@
 use crate::{
     mutex::{Mutex, RwLock},
     text::FontTweak,
-    TextureAtlas,
+    AtlasPos, TextureAtlas,
 };
 use emath::{vec2, Vec2};
 use std::collections::BTreeSet;
@@ -19,17 +19,40 @@ pub struct UvRect {
     /// Note that the height is different from the font height.
     pub size: Vec2,
 
+    /// Which page of the [`TextureAtlas`] the glyph is on.
+    pub page: u16,
+
     /// Top left corner UV in texture.
     pub min: [u16; 2],
 
     /// Bottom right corner (exclusive).
     pub max: [u16; 2],
+
+    /// Is this a color glyph (e.g. an emoji)?
+    ///
+    /// Color glyphs are stored as colors in the texture atlas,
+    /// and should not be tinted by the text color.
+    pub is_color: bool,
 }
 
 impl UvRect {
     pub fn is_nothing(&self) -> bool {
         self.min == self.max
     }
+
+    /// Where in the [`TextureAtlas`] the glyph was allocated.
+    pub fn atlas_pos(&self) -> AtlasPos {
+        AtlasPos {
+            page: self.page as usize,
+            x: self.min[0] as usize,
+            y: self.min[1] as usize,
+        }
+    }
+
+    /// Was this glyph evicted from the [`TextureAtlas`]?
+    fn is_evicted(&self, evicted: &ahash::HashSet<AtlasPos>) -> bool {
+        !self.is_nothing() && evicted.contains(&self.atlas_pos())
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -60,6 +83,200 @@ impl Default for GlyphInfo {
 
 // ----------------------------------------------------------------------------
 
+/// Bold and italic that we fake, because the font has no such face.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Synthesis {
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// How much we slant glyphs to fake italics (about 11 degrees).
+const SYNTHETIC_ITALIC_SKEW: f32 = 0.2;
+
+impl Synthesis {
+    /// How many pixels wider to make each glyph to fake bold.
+    fn embolden_pixels(self, scale_in_pixels: u32) -> usize {
+        if self.bold {
+            ((scale_in_pixels as f32 / 24.0).round() as usize).max(1)
+        } else {
+            0
+        }
+    }
+
+    /// Apply the synthesis to the coverage of a rasterized glyph.
+    ///
+    /// The bounds are in pixels, relative to the glyph origin on the baseline.
+    /// Returns the new bounds and coverage.
+    fn apply(
+        self,
+        scale_in_pixels: u32,
+        mut bounds: ab_glyph::Rect,
+        mut coverage: Vec<f32>,
+    ) -> (ab_glyph::Rect, Vec<f32>) {
+        let height = bounds.height() as usize;
+
+        let embolden = self.embolden_pixels(scale_in_pixels);
+        if 0 < embolden {
+            // Smear the glyph to the right:
+            let width = bounds.width() as usize;
+            let new_width = width + embolden;
+            let mut bold = vec![0.0_f32; new_width * height];
+            for y in 0..height {
+                for x in 0..width {
+                    let v = coverage[y * width + x];
+                    for dx in 0..=embolden {
+                        let pixel = &mut bold[y * new_width + x + dx];
+                        *pixel = pixel.max(v);
+                    }
+                }
+            }
+            coverage = bold;
+            bounds.max.x += embolden as f32;
+        }
+
+        if self.italic {
+            // Shift each row right by its height above the baseline:
+            let width = bounds.width() as usize;
+            let shift = |y: usize| -(bounds.min.y + y as f32 + 0.5) * SYNTHETIC_ITALIC_SKEW;
+            let left = shift(height.saturating_sub(1)).floor();
+            let new_width = width + (shift(0) - left).ceil() as usize + 1;
+            let mut italic = vec![0.0_f32; new_width * height];
+            for y in 0..height {
+                let s = shift(y) - left;
+                let (whole, frac) = (s.floor() as usize, s.fract());
+                for x in 0..width {
+                    let v = coverage[y * width + x];
+                    italic[y * new_width + x + whole] += v * (1.0 - frac);
+                    italic[y * new_width + x + whole + 1] += v * frac;
+                }
+            }
+            for v in &mut italic {
+                *v = v.min(1.0);
+            }
+            coverage = italic;
+            bounds.min.x += left;
+            bounds.max.x = bounds.min.x + new_width as f32;
+        }
+
+        (bounds, coverage)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// An outline glyph (or cluster of glyphs) that can also be rasterized at subpixel offsets.
+struct SubpixelVariants {
+    /// How it was rasterized at offset zero.
+    base: UvRect,
+
+    /// The glyphs and their positions in pixels.
+    glyphs: Vec<(ab_glyph::GlyphId, [f32; 2])>,
+
+    /// Index `i` is rasterized `(i + 1) / subpixel_positions` of a pixel to the right.
+    variants: Vec<Option<UvRect>>,
+}
+
+// ----------------------------------------------------------------------------
+
+/// The raw bytes of a font file, shared by all sizes of the same font.
+///
+/// Used for things `ab_glyph` can't do for us, like shaping and color glyphs.
+#[cfg(any(feature = "shaping", feature = "color_emoji"))]
+#[derive(Clone)]
+pub(crate) struct FontBytes {
+    bytes: Arc<std::borrow::Cow<'static, [u8]>>,
+    index: u32,
+
+    #[cfg(feature = "shaping")]
+    variations: Arc<[crate::text::FontVariation]>,
+
+    /// Parsed the first time we shape with this font.
+    #[cfg(feature = "shaping")]
+    shaping_face: Arc<std::sync::OnceLock<Option<ShapingFace>>>,
+}
+
+#[cfg(feature = "shaping")]
+use shaping_face::ShapingFace;
+
+#[cfg(feature = "shaping")]
+#[allow(clippy::mem_forget)] // inside the macro
+mod shaping_face {
+    use std::sync::Arc;
+
+    type RustybuzzFace<'a> = rustybuzz::Face<'a>;
+
+    self_cell::self_cell!(
+        /// A [`rustybuzz::Face`] together with the font file it borrows.
+        pub(crate) struct ShapingFace {
+            owner: Arc<std::borrow::Cow<'static, [u8]>>,
+
+            #[covariant]
+            dependent: RustybuzzFace,
+        }
+    );
+}
+
+#[cfg(any(feature = "shaping", feature = "color_emoji"))]
+impl FontBytes {
+    pub fn new(
+        font_data: &crate::text::FontData,
+        variations: &[crate::text::FontVariation],
+    ) -> Self {
+        Self {
+            bytes: Arc::new(font_data.font.clone()),
+            index: font_data.index,
+            #[cfg(feature = "shaping")]
+            variations: variations.into(),
+            #[cfg(feature = "shaping")]
+            shaping_face: Default::default(),
+        }
+    }
+
+    #[cfg(feature = "color_emoji")]
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Which font in a font collection (`.ttc`) to use.
+    #[cfg(feature = "color_emoji")]
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The font parsed for shaping, with the variation axes of the [`crate::text::FontData`] applied.
+    ///
+    /// `None` if `rustybuzz` can't parse the font.
+    #[cfg(feature = "shaping")]
+    pub fn shaping_face(&self) -> Option<&rustybuzz::Face<'_>> {
+        self.shaping_face
+            .get_or_init(|| {
+                crate::profile_function!();
+                ShapingFace::try_new(self.bytes.clone(), |bytes| -> Result<_, ()> {
+                    let mut face = rustybuzz::Face::from_slice(bytes, self.index).ok_or(())?;
+                    if !self.variations.is_empty() {
+                        let variations: Vec<rustybuzz::Variation> = self
+                            .variations
+                            .iter()
+                            .map(|variation| rustybuzz::Variation {
+                                tag: rustybuzz::ttf_parser::Tag::from_bytes(&variation.axis),
+                                value: variation.value,
+                            })
+                            .collect();
+                        face.set_variations(&variations);
+                    }
+                    Ok(face)
+                })
+                .ok()
+            })
+            .as_ref()
+            .map(|face| face.borrow_dependent())
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 /// A specific font with a size.
 /// The interface uses points as the unit for everything.
 pub struct FontImpl {
@@ -77,6 +294,34 @@ pub struct FontImpl {
     ascent: f32,
     pixels_per_point: f32,
     glyph_info_cache: RwLock<ahash::HashMap<char, GlyphInfo>>, // TODO(emilk): standard Mutex
+
+    /// Glyphs that have been rasterized into the atlas, so we only do it once per glyph.
+    glyph_id_cache: RwLock<ahash::HashMap<ab_glyph::GlyphId, GlyphInfo>>,
+
+    /// The raw font file, needed for shaping and color glyphs.
+    #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+    font_bytes: Option<FontBytes>,
+
+    /// Does the font have `COLR`, `CBDT` or `sbix` tables?
+    #[cfg(feature = "color_emoji")]
+    has_color_glyphs: bool,
+
+    /// Clusters of several shaped glyphs, rasterized together.
+    #[cfg(feature = "shaping")]
+    cluster_cache: RwLock<ahash::HashMap<Vec<(ab_glyph::GlyphId, [i32; 2])>, UvRect>>,
+
+    /// Faked bold and italics.
+    synthesis: Synthesis,
+
+    /// See [`FontTweak::subpixel_positions`].
+    subpixel_positions: u8,
+
+    /// Outline glyphs by where they are in the atlas, if [`Self::subpixel_positions`] > 1.
+    subpixel_variants: RwLock<ahash::HashMap<AtlasPos, SubpixelVariants>>,
+
+    /// See [`FontTweak::signed_distance_field`].
+    signed_distance_field: bool,
+
     atlas: Arc<Mutex<TextureAtlas>>,
 }
 
@@ -131,10 +376,60 @@ impl FontImpl {
             ascent: ascent + baseline_offset,
             pixels_per_point,
             glyph_info_cache: Default::default(),
+            glyph_id_cache: Default::default(),
+            #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+            font_bytes: None,
+            #[cfg(feature = "color_emoji")]
+            has_color_glyphs: false,
+            #[cfg(feature = "shaping")]
+            cluster_cache: Default::default(),
+            synthesis: Default::default(),
+            // Distance fields are smooth enough to be painted at any offset:
+            subpixel_positions: if tweak.signed_distance_field {
+                1
+            } else {
+                tweak.subpixel_positions.max(1)
+            },
+            subpixel_variants: Default::default(),
+            signed_distance_field: tweak.signed_distance_field,
             atlas,
         }
     }
 
+    /// Fake bold and/or italics, for when there is no such face of the font.
+    pub(crate) fn with_synthesis(self, synthesis: Synthesis) -> Self {
+        Self { synthesis, ..self }
+    }
+
+    /// How much wider each glyph is because of faked bold, in points.
+    #[inline]
+    pub(crate) fn synthetic_advance(&self) -> f32 {
+        self.synthesis.embolden_pixels(self.scale_in_pixels) as f32 / self.pixels_per_point
+    }
+
+    /// Provide the raw font file, which is needed for shaping and color glyphs.
+    #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+    pub(crate) fn with_font_bytes(mut self, font_bytes: FontBytes) -> Self {
+        #[cfg(feature = "color_emoji")]
+        {
+            self.has_color_glyphs =
+                ttf_parser::Face::parse(font_bytes.as_slice(), font_bytes.index()).is_ok_and(
+                    |face| {
+                        let tables = face.tables();
+                        tables.colr.is_some() || tables.cbdt.is_some() || tables.sbix.is_some()
+                    },
+                );
+        }
+        self.font_bytes = Some(font_bytes);
+        self
+    }
+
+    /// The raw font file, if available.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn font_bytes(&self) -> Option<&FontBytes> {
+        self.font_bytes.as_ref()
+    }
+
     /// Code points that will always be replaced by the replacement character.
     ///
     /// See also [`invisible_char`].
@@ -225,12 +520,23 @@ impl FontImpl {
         if glyph_id.0 == 0 {
             None // unsupported character
         } else {
-            let glyph_info = self.allocate_glyph(glyph_id);
+            let glyph_info = self.glyph_info_from_id(glyph_id);
             self.glyph_info_cache.write().insert(c, glyph_info);
             Some(glyph_info)
         }
     }
 
+    /// Rasterizes the glyph (if it hasn't been already).
+    pub(crate) fn glyph_info_from_id(&self, glyph_id: ab_glyph::GlyphId) -> GlyphInfo {
+        if let Some(glyph_info) = self.glyph_id_cache.read().get(&glyph_id) {
+            return *glyph_info;
+        }
+
+        let glyph_info = self.allocate_glyph(glyph_id);
+        self.glyph_id_cache.write().insert(glyph_id, glyph_info);
+        glyph_info
+    }
+
     #[inline]
     pub fn pair_kerning(
         &self,
@@ -267,52 +573,19 @@ impl FontImpl {
         assert!(glyph_id.0 != 0);
         use ab_glyph::{Font as _, ScaleFont};
 
-        let glyph = glyph_id.with_scale_and_position(
-            self.scale_in_pixels as f32,
-            ab_glyph::Point { x: 0.0, y: 0.0 },
-        );
-
-        let uv_rect = self.ab_glyph_font.outline_glyph(glyph).map(|glyph| {
-            let bb = glyph.px_bounds();
-            let glyph_width = bb.width() as usize;
-            let glyph_height = bb.height() as usize;
-            if glyph_width == 0 || glyph_height == 0 {
-                UvRect::default()
-            } else {
-                let glyph_pos = {
-                    let atlas = &mut self.atlas.lock();
-                    let (glyph_pos, image) = atlas.allocate((glyph_width, glyph_height));
-                    glyph.draw(|x, y, v| {
-                        if 0.0 < v {
-                            let px = glyph_pos.0 + x as usize;
-                            let py = glyph_pos.1 + y as usize;
-                            image[(px, py)] = v;
-                        }
-                    });
-                    glyph_pos
-                };
-
-                let offset_in_pixels = vec2(bb.min.x, bb.min.y);
-                let offset =
-                    offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
-                UvRect {
-                    offset,
-                    size: vec2(glyph_width as f32, glyph_height as f32) / self.pixels_per_point,
-                    min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
-                    max: [
-                        (glyph_pos.0 + glyph_width) as u16,
-                        (glyph_pos.1 + glyph_height) as u16,
-                    ],
-                }
-            }
-        });
-        let uv_rect = uv_rect.unwrap_or_default();
+        #[cfg(feature = "color_emoji")]
+        let uv_rect = self
+            .allocate_color_glyph(glyph_id)
+            .unwrap_or_else(|| self.allocate_outline(glyph_id));
+        #[cfg(not(feature = "color_emoji"))]
+        let uv_rect = self.allocate_outline(glyph_id);
 
         let advance_width_in_points = self
             .ab_glyph_font
             .as_scaled(self.scale_in_pixels as f32)
             .h_advance(glyph_id)
-            / self.pixels_per_point;
+            / self.pixels_per_point
+            + self.synthetic_advance();
 
         GlyphInfo {
             id: glyph_id,
@@ -320,9 +593,390 @@ impl FontImpl {
             uv_rect,
         }
     }
+
+    /// Rasterize the outline of the glyph as coverage.
+    fn allocate_outline(&self, glyph_id: ab_glyph::GlyphId) -> UvRect {
+        self.allocate_outlines(vec![(glyph_id, [0.0, 0.0])])
+    }
+
+    /// Rasterize the outlines of glyphs at the given pixel positions together, as one image.
+    ///
+    /// Remembers them, so they can be rasterized again at subpixel offsets.
+    fn allocate_outlines(&self, glyphs: Vec<(ab_glyph::GlyphId, [f32; 2])>) -> UvRect {
+        let uv_rect = self.rasterize_outlines(&glyphs);
+        if 1 < self.subpixel_positions && !uv_rect.is_nothing() {
+            self.subpixel_variants.write().insert(
+                uv_rect.atlas_pos(),
+                SubpixelVariants {
+                    base: uv_rect,
+                    glyphs,
+                    variants: vec![None; self.subpixel_positions as usize - 1],
+                },
+            );
+        }
+        uv_rect
+    }
+
+    fn rasterize_outlines(&self, glyphs: &[(ab_glyph::GlyphId, [f32; 2])]) -> UvRect {
+        use ab_glyph::Font as _;
+
+        // Distance fields are rasterized larger, and scaled down when painted:
+        let upscale = if self.signed_distance_field {
+            (super::sdf::MIN_SCALE_IN_PIXELS / self.scale_in_pixels as f32).max(1.0)
+        } else {
+            1.0
+        };
+        let raster_scale = self.scale_in_pixels as f32 * upscale;
+
+        let outlined: Vec<ab_glyph::OutlinedGlyph> = glyphs
+            .iter()
+            .filter_map(|&(glyph_id, [x, y])| {
+                let glyph = glyph_id.with_scale_and_position(
+                    raster_scale,
+                    ab_glyph::point(x * upscale, y * upscale),
+                );
+                self.ab_glyph_font.outline_glyph(glyph)
+            })
+            .collect();
+
+        let Some(bb) = outlined
+            .iter()
+            .map(|glyph| glyph.px_bounds())
+            .reduce(|a, b| ab_glyph::Rect {
+                min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+                max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+            })
+        else {
+            return UvRect::default();
+        };
+
+        let width = bb.width() as usize;
+        let height = bb.height() as usize;
+        if width == 0 || height == 0 {
+            return UvRect::default();
+        }
+
+        let mut coverage = vec![0.0_f32; width * height];
+        for glyph in &outlined {
+            // `px_bounds` are whole pixels, so this is exact:
+            let glyph_bb = glyph.px_bounds();
+            let dx = (glyph_bb.min.x - bb.min.x) as usize;
+            let dy = (glyph_bb.min.y - bb.min.y) as usize;
+            glyph.draw(|x, y, v| {
+                let pixel = &mut coverage[(dy + y as usize) * width + dx + x as usize];
+                *pixel = pixel.max(v);
+            });
+        }
+
+        if self.signed_distance_field {
+            self.allocate_distance_field(bb, coverage, upscale)
+        } else {
+            self.allocate_coverage(bb, coverage)
+        }
+    }
+
+    /// Do we rasterize glyphs at subpixel offsets?
+    #[inline]
+    pub(crate) fn has_subpixel_positions(&self) -> bool {
+        1 < self.subpixel_positions
+    }
+
+    /// The version of a glyph (or cluster) rasterized for the subpixel offset of `x_in_pixels`.
+    ///
+    /// Returns `None` if the glyph isn't from this font.
+    pub(crate) fn subpixel_uv_rect(&self, uv_rect: &UvRect, x_in_pixels: f32) -> Option<UvRect> {
+        if !self.has_subpixel_positions() || uv_rect.is_nothing() {
+            return None;
+        }
+
+        let n = self.subpixel_positions as usize;
+        let index = ((x_in_pixels - x_in_pixels.floor()) * n as f32).round() as usize % n;
+        if index == 0 {
+            // Rounding to the closest whole pixel gets us there.
+            return self
+                .subpixel_variants
+                .read()
+                .contains_key(&uv_rect.atlas_pos())
+                .then_some(*uv_rect);
+        }
+
+        let mut subpixel_variants = self.subpixel_variants.write();
+        let SubpixelVariants {
+            base,
+            glyphs,
+            variants,
+        } = subpixel_variants.get_mut(&uv_rect.atlas_pos())?;
+        let mut variant = *variants[index - 1].get_or_insert_with(|| {
+            let shift = index as f32 / n as f32;
+            let glyphs: Vec<_> = glyphs
+                .iter()
+                .map(|&(glyph_id, [x, y])| (glyph_id, [x + shift, y]))
+                .collect();
+            let mut variant = self.rasterize_outlines(&glyphs);
+            // Relative to where the glyph would be without the shift:
+            variant.offset.x -= shift / self.pixels_per_point;
+            variant
+        });
+
+        // Keep any extra offset from the layout (e.g. from shaping):
+        variant.offset += uv_rect.offset - base.offset;
+        Some(variant)
+    }
+
+    /// Put the coverage of a rasterized glyph into the atlas, faking bold and italics if needed.
+    fn allocate_coverage(&self, bounds: ab_glyph::Rect, coverage: Vec<f32>) -> UvRect {
+        let (bounds, coverage) = self.synthesis.apply(self.scale_in_pixels, bounds, coverage);
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let (glyph_pos, image) = atlas.allocate((width, height));
+            for y in 0..height {
+                for x in 0..width {
+                    let v = coverage[y * width + x];
+                    if 0.0 < v {
+                        image[(glyph_pos.x + x, glyph_pos.y + y)] = v;
+                    }
+                }
+            }
+            glyph_pos
+        };
+
+        self.uv_rect_from_bounds(bounds, glyph_pos)
+    }
+
+    /// Put a rasterized glyph into the atlas as a signed distance field, faking bold and italics if needed.
+    ///
+    /// The glyph was rasterized `upscale` times larger than the font size.
+    fn allocate_distance_field(
+        &self,
+        bounds: ab_glyph::Rect,
+        coverage: Vec<f32>,
+        upscale: f32,
+    ) -> UvRect {
+        let raster_scale = (self.scale_in_pixels as f32 * upscale).round() as u32;
+        let (bounds, coverage) = self.synthesis.apply(raster_scale, bounds, coverage);
+        let ([width, height], field) = super::sdf::distance_field(
+            [bounds.width() as usize, bounds.height() as usize],
+            &coverage,
+        );
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let (glyph_pos, image) = atlas.allocate_sdf((width, height));
+            for y in 0..height {
+                for x in 0..width {
+                    let v = field[y * width + x];
+                    if 0.0 < v {
+                        image[(glyph_pos.x + x, glyph_pos.y + y)] = v;
+                    }
+                }
+            }
+            glyph_pos
+        };
+
+        let spread = super::sdf::SPREAD as f32;
+        let offset_in_pixels = vec2(bounds.min.x - spread, bounds.min.y - spread) / upscale;
+        let size_in_pixels = vec2(width as f32, height as f32) / upscale;
+        UvRect {
+            offset: offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y,
+            size: size_in_pixels / self.pixels_per_point,
+            page: glyph_pos.page as u16,
+            min: [glyph_pos.x as u16, glyph_pos.y as u16],
+            max: [(glyph_pos.x + width) as u16, (glyph_pos.y + height) as u16],
+            is_color: false,
+        }
+    }
+
+    /// Rasterize the glyph in color, if it is a color glyph (e.g. an emoji).
+    #[cfg(feature = "color_emoji")]
+    fn allocate_color_glyph(&self, glyph_id: ab_glyph::GlyphId) -> Option<UvRect> {
+        if !self.has_color_glyphs {
+            return None;
+        }
+        let font_bytes = self.font_bytes.as_ref()?;
+        let face = ttf_parser::Face::parse(font_bytes.as_slice(), font_bytes.index()).ok()?;
+        let image = super::color_glyph::rasterize(
+            &face,
+            &self.ab_glyph_font,
+            glyph_id,
+            self.scale_in_pixels as f32,
+        )?;
+
+        let [width, height] = image.size;
+        if width == 0 || height == 0 {
+            return Some(UvRect::default());
+        }
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            if atlas.size()[0] < width {
+                return None; // Too big for the atlas, so use the outline instead.
+            }
+            atlas.allocate_color((width, height), &image.pixels)
+        };
+
+        let offset = image.offset / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
+        Some(UvRect {
+            offset,
+            size: vec2(width as f32, height as f32) / self.pixels_per_point,
+            page: glyph_pos.page as u16,
+            min: [glyph_pos.x as u16, glyph_pos.y as u16],
+            max: [(glyph_pos.x + width) as u16, (glyph_pos.y + height) as u16],
+            is_color: true,
+        })
+    }
+
+    /// Will this glyph be drawn in color?
+    #[cfg(feature = "shaping")]
+    pub(crate) fn is_color_glyph(&self, glyph_id: ab_glyph::GlyphId) -> bool {
+        self.glyph_info_from_id(glyph_id).uv_rect.is_color
+    }
+
+    /// Forget all glyphs that were evicted from the atlas, so they are rasterized again when needed.
+    pub(crate) fn forget_evicted(&self, evicted: &ahash::HashSet<AtlasPos>) {
+        self.glyph_info_cache
+            .write()
+            .retain(|_, glyph_info| !glyph_info.uv_rect.is_evicted(evicted));
+        self.glyph_id_cache
+            .write()
+            .retain(|_, glyph_info| !glyph_info.uv_rect.is_evicted(evicted));
+        #[cfg(feature = "shaping")]
+        self.cluster_cache
+            .write()
+            .retain(|_, uv_rect| !uv_rect.is_evicted(evicted));
+        self.subpixel_variants.write().retain(|_, entry| {
+            for variant in &mut entry.variants {
+                if variant.is_some_and(|uv_rect| uv_rect.is_evicted(evicted)) {
+                    *variant = None;
+                }
+            }
+            !entry.base.is_evicted(evicted)
+        });
+    }
+
+    /// `bounds` is the pixel bounds of what we rasterized at `glyph_pos` in the atlas.
+    fn uv_rect_from_bounds(&self, bounds: ab_glyph::Rect, glyph_pos: AtlasPos) -> UvRect {
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+        let offset_in_pixels = vec2(bounds.min.x, bounds.min.y);
+        let offset = offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
+        UvRect {
+            offset,
+            size: vec2(width as f32, height as f32) / self.pixels_per_point,
+            page: glyph_pos.page as u16,
+            min: [glyph_pos.x as u16, glyph_pos.y as u16],
+            max: [(glyph_pos.x + width) as u16, (glyph_pos.y + height) as u16],
+            is_color: false,
+        }
+    }
+
+    /// How many physical pixels there are to one font design unit.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn pixels_per_font_unit(&self) -> f32 {
+        use ab_glyph::{Font as _, ScaleFont as _};
+        self.ab_glyph_font
+            .as_scaled(self.scale_in_pixels as f32)
+            .h_scale_factor()
+    }
+
+    /// The vector outline of a glyph, in points, relative to the glyph position on the baseline
+    /// moved by `offset`.
+    ///
+    /// Synthetic italics and bold are applied.
+    #[cfg(feature = "svg")]
+    pub(crate) fn glyph_outline(
+        &self,
+        glyph_id: ab_glyph::GlyphId,
+        offset: Vec2,
+    ) -> Vec<crate::PathCommand> {
+        use crate::PathCommand;
+        use ab_glyph::{Font as _, OutlineCurve, ScaleFont as _};
+
+        let Some(outline) = self.ab_glyph_font.outline(glyph_id) else {
+            return vec![];
+        };
+
+        let scaled = self.ab_glyph_font.as_scaled(self.scale_in_pixels as f32);
+        let scale_x = scaled.h_scale_factor() / self.pixels_per_point;
+        let scale_y = scaled.v_scale_factor() / self.pixels_per_point;
+        let skew = if self.synthesis.italic {
+            SYNTHETIC_ITALIC_SKEW
+        } else {
+            0.0
+        };
+
+        // Like the rasterized glyph, a bold glyph is smeared to the right,
+        // here by painting the outline twice:
+        let mut offsets = vec![offset];
+        let embolden = self.synthetic_advance();
+        if 0.0 < embolden {
+            offsets.push(offset + embolden * Vec2::X);
+        }
+
+        let mut commands = Vec::with_capacity(offsets.len() * (outline.curves.len() + 1));
+        for offset in offsets {
+            let to_pos = |p: ab_glyph::Point| {
+                // Font units have y pointing up:
+                let y = -p.y * scale_y;
+                emath::pos2(p.x * scale_x - y * skew, y + self.y_offset_in_points) + offset
+            };
+
+            let mut last = None;
+            for curve in &outline.curves {
+                let (start, command) = match *curve {
+                    OutlineCurve::Line(p0, p1) => (p0, PathCommand::LineTo(to_pos(p1))),
+                    OutlineCurve::Quad(p0, p1, p2) => {
+                        (p0, PathCommand::QuadTo(to_pos(p1), to_pos(p2)))
+                    }
+                    OutlineCurve::Cubic(p0, p1, p2, p3) => {
+                        (p0, PathCommand::CubicTo(to_pos(p1), to_pos(p2), to_pos(p3)))
+                    }
+                };
+                let start = to_pos(start);
+                if last != Some(start) {
+                    if last.is_some() {
+                        commands.push(PathCommand::Close);
+                    }
+                    commands.push(PathCommand::MoveTo(start));
+                }
+                last = command.end_point();
+                commands.push(command);
+            }
+            if last.is_some() {
+                commands.push(PathCommand::Close);
+            }
+        }
+        commands
+    }
+
+    /// Rasterize several glyphs together, as one image.
+    ///
+    /// Each glyph comes with its position relative to the start of the cluster, in physical pixels.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn cluster_uv_rect(&self, glyphs: &[(ab_glyph::GlyphId, [i32; 2])]) -> UvRect {
+        if let Some(uv_rect) = self.cluster_cache.read().get(glyphs) {
+            return *uv_rect;
+        }
+
+        let uv_rect = self.allocate_cluster(glyphs);
+        self.cluster_cache.write().insert(glyphs.to_vec(), uv_rect);
+        uv_rect
+    }
+
+    #[cfg(feature = "shaping")]
+    fn allocate_cluster(&self, glyphs: &[(ab_glyph::GlyphId, [i32; 2])]) -> UvRect {
+        self.allocate_outlines(
+            glyphs
+                .iter()
+                .map(|&(glyph_id, [x, y])| (glyph_id, [x as f32, y as f32]))
+                .collect(),
+        )
+    }
 }
 
-type FontIndex = usize;
+pub(crate) type FontIndex = usize;
 
 // TODO(emilk): rename?
 /// Wrapper over multiple [`FontImpl`] (e.g. a primary + fallbacks for emojis)
@@ -336,6 +990,10 @@ pub struct Font {
     pixels_per_point: f32,
     row_height: f32,
     glyph_info_cache: ahash::HashMap<char, (FontIndex, GlyphInfo)>,
+
+    /// Characters we have already looked for system fonts for.
+    #[cfg(feature = "system_fonts")]
+    system_fallback_checked: ahash::HashSet<char>,
 }
 
 impl Font {
@@ -348,6 +1006,8 @@ impl Font {
                 pixels_per_point: 1.0,
                 row_height: 0.0,
                 glyph_info_cache: Default::default(),
+                #[cfg(feature = "system_fonts")]
+                system_fallback_checked: Default::default(),
             };
         }
 
@@ -361,6 +1021,8 @@ impl Font {
             pixels_per_point,
             row_height,
             glyph_info_cache: Default::default(),
+            #[cfg(feature = "system_fonts")]
+            system_fallback_checked: Default::default(),
         };
 
         const PRIMARY_REPLACEMENT_CHAR: char = '◻'; // white medium square
@@ -412,6 +1074,21 @@ impl Font {
         (point * self.pixels_per_point).round() / self.pixels_per_point
     }
 
+    /// Do any of the fonts rasterize glyphs at subpixel offsets?
+    ///
+    /// See [`FontTweak::subpixel_positions`].
+    pub fn has_subpixel_positions(&self) -> bool {
+        self.fonts.iter().any(|font| font.has_subpixel_positions())
+    }
+
+    /// The version of a glyph rasterized for where it ends up, if it uses subpixel positioning.
+    pub(crate) fn subpixel_uv_rect(&self, uv_rect: &UvRect, x_in_pixels: f32) -> UvRect {
+        self.fonts
+            .iter()
+            .find_map(|font| font.subpixel_uv_rect(uv_rect, x_in_pixels))
+            .unwrap_or(*uv_rect)
+    }
+
     /// Height of one row of text. In points
     #[inline(always)]
     pub fn row_height(&self) -> f32 {
@@ -462,6 +1139,125 @@ impl Font {
         (Some(font_impl), glyph_info)
     }
 
+    /// The vector outline of the glyph for this character, in points, relative to its baseline position.
+    #[cfg(feature = "svg")]
+    fn char_outline(&mut self, c: char) -> Vec<crate::PathCommand> {
+        match self.font_impl_and_glyph_info(c) {
+            (Some(font_impl), glyph_info) => font_impl.glyph_outline(glyph_info.id, Vec2::ZERO),
+            (None, _) => vec![],
+        }
+    }
+
+    /// The vector outlines of the glyphs of this text, shaped the same way as by the text layout.
+    ///
+    /// Returns the index of the first `char` of each cluster of glyphs together with their outline,
+    /// in points, relative to the baseline position of that `char`.
+    #[cfg(feature = "svg")]
+    pub(crate) fn text_outlines(&mut self, text: &str) -> Vec<(usize, Vec<crate::PathCommand>)> {
+        #[cfg(feature = "shaping")]
+        {
+            use super::shaping;
+
+            let mut outlines = vec![];
+            let mut char_index = 0;
+            for run in shaping::itemize(self, text) {
+                let run_text = &text[run.byte_range];
+
+                let font_impl = run
+                    .font_index
+                    .and_then(|index| self.fonts.get(index).cloned());
+                let clusters = font_impl.and_then(|font_impl| {
+                    let clusters = shaping::shape(&font_impl, run_text, run.script)?;
+                    Some((font_impl, clusters))
+                });
+
+                let Some((font_impl, clusters)) = clusters else {
+                    for chr in run_text.chars() {
+                        outlines.push((char_index, self.char_outline(chr)));
+                        char_index += 1;
+                    }
+                    continue;
+                };
+
+                let points_per_font_unit =
+                    font_impl.pixels_per_font_unit() / font_impl.pixels_per_point();
+                for cluster in clusters {
+                    let mut outline = vec![];
+                    for &(glyph_id, [x, y]) in &cluster.glyphs {
+                        let offset = points_per_font_unit * vec2(x as f32, y as f32);
+                        outline.extend(font_impl.glyph_outline(glyph_id, offset));
+                    }
+                    outlines.push((char_index, outline));
+                    char_index += cluster.num_chars;
+                }
+            }
+            outlines
+        }
+
+        #[cfg(not(feature = "shaping"))]
+        text.chars()
+            .enumerate()
+            .map(|(i, chr)| (i, self.char_outline(chr)))
+            .collect()
+    }
+
+    /// Which of our fonts should be used to shape this character?
+    ///
+    /// Returns `None` for characters that need special treatment (tabs, thin spaces, …),
+    /// and for characters that none of our fonts support.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn font_index_for_shaping(&mut self, c: char) -> Option<FontIndex> {
+        if c == '\t' || c == '\u{2009}' || invisible_char(c) {
+            return None;
+        }
+        let (font_index, _) = self.glyph_info(c);
+        let font_impl = self.fonts.get(font_index)?;
+        // Make sure it isn't the replacement glyph:
+        let supported = font_impl.glyph_info(c).is_some_and(|glyph| glyph.id.0 != 0);
+        supported.then_some(font_index)
+    }
+
+    #[cfg(feature = "shaping")]
+    pub(crate) fn font_impl(&self, font_index: FontIndex) -> Option<&FontImpl> {
+        self.fonts.get(font_index).map(|font_impl| &**font_impl)
+    }
+
+    /// Should we look for a system font with this character?
+    ///
+    /// Only true the first time we are asked about a missing character.
+    #[cfg(feature = "system_fonts")]
+    pub(crate) fn needs_system_fallback(&mut self, c: char) -> bool {
+        self.system_fallback_checked.insert(c) && !self.has_glyph(c)
+    }
+
+    /// Add a font to the end of the fallback chain, e.g. a system font.
+    #[cfg(feature = "system_fonts")]
+    pub(crate) fn add_fallback(&mut self, font_impl: Arc<FontImpl>) {
+        if self.fonts.iter().any(|font| Arc::ptr_eq(font, &font_impl)) {
+            return;
+        }
+        self.fonts.push(font_impl);
+        self.characters = None;
+
+        // Characters we previously couldn't find may be in the new font:
+        let replacement_glyph = self.replacement_glyph;
+        self.glyph_info_cache
+            .retain(|_, glyph_info| *glyph_info != replacement_glyph);
+    }
+
+    /// Forget all glyphs that were evicted from the atlas.
+    ///
+    /// Call [`FontImpl::forget_evicted`] on all the fonts first.
+    pub(crate) fn forget_evicted(&mut self, evicted: &ahash::HashSet<AtlasPos>) {
+        self.glyph_info_cache
+            .retain(|_, (_, glyph_info)| !glyph_info.uv_rect.is_evicted(evicted));
+
+        if self.replacement_glyph.1.uv_rect.is_evicted(evicted) {
+            let (font_index, glyph_info) = self.replacement_glyph;
+            self.replacement_glyph.1 = self.fonts[font_index].glyph_info_from_id(glyph_info.id);
+        }
+    }
+
     fn glyph_info_no_cache_or_fallback(&mut self, c: char) -> Option<(FontIndex, GlyphInfo)> {
         for (font_index, font_impl) in self.fonts.iter().enumerate() {
             if let Some(glyph_info) = font_impl.glyph_info(c) {
@@ -477,7 +1273,7 @@ impl Font {
 ///
 /// See also [`FontImpl::ignore_character`].
 #[inline]
-fn invisible_char(c: char) -> bool {
+pub(crate) fn invisible_char(c: char) -> bool {
     if c == '\r' {
         // A character most vile and pernicious. Don't display it.
         return true;
@@ -1,12 +1,15 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Weak},
+};
 
 use crate::{
     mutex::{Mutex, MutexGuard},
     text::{
-        font::{Font, FontImpl},
+        font::{Font, FontImpl, Synthesis},
         Galley, LayoutJob,
     },
-    TextureAtlas,
+    AtlasPos, TextureAtlas,
 };
 use emath::{NumExt as _, OrderedFloat};
 
@@ -143,6 +146,120 @@ impl FontData {
 
 // ----------------------------------------------------------------------------
 
+/// Another face (e.g. bold or italic) of a font, see [`FontDefinitions::faces`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct FontFace {
+    /// Key into [`FontDefinitions::font_data`].
+    pub name: String,
+
+    /// The weight and slant of this face.
+    ///
+    /// Used to pick a face for bold and italic text.
+    pub style: FontStyle,
+
+    /// Values for the axes of a variable font, e.g. `wght` (weight) or `slnt` (slant).
+    ///
+    /// Axes not listed here use the default of the font.
+    pub variations: Vec<FontVariation>,
+}
+
+impl FontFace {
+    pub fn new(name: impl Into<String>, style: FontStyle) -> Self {
+        Self {
+            name: name.into(),
+            style,
+            variations: Default::default(),
+        }
+    }
+
+    /// Set an axis of a variable font, e.g. `b"wght"`.
+    ///
+    /// ```
+    /// # use epaint::text::{FontFace, FontStyle};
+    /// let bold = FontFace::new("Inter-Bold", FontStyle::BOLD).variation(b"wght", 700.0);
+    /// ```
+    pub fn variation(mut self, axis: &[u8; 4], value: f32) -> Self {
+        self.variations.push(FontVariation { axis: *axis, value });
+        self
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// How thick the strokes of a font face are,
+/// from 100 ([`Self::THIN`]) to 900 ([`Self::BLACK`]).
+///
+/// This is the same scale as CSS `font-weight` and the `wght` axis of variable fonts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    pub const THIN: Self = Self(100);
+    pub const LIGHT: Self = Self(300);
+    pub const REGULAR: Self = Self(400);
+    pub const MEDIUM: Self = Self(500);
+    pub const SEMIBOLD: Self = Self(600);
+    pub const BOLD: Self = Self(700);
+    pub const BLACK: Self = Self(900);
+}
+
+impl Default for FontWeight {
+    #[inline]
+    fn default() -> Self {
+        Self::REGULAR
+    }
+}
+
+/// The weight and slant of a font face.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct FontStyle {
+    pub weight: FontWeight,
+    pub italic: bool,
+}
+
+impl FontStyle {
+    pub const REGULAR: Self = Self {
+        weight: FontWeight::REGULAR,
+        italic: false,
+    };
+
+    pub const BOLD: Self = Self {
+        weight: FontWeight::BOLD,
+        italic: false,
+    };
+
+    pub const ITALIC: Self = Self {
+        weight: FontWeight::REGULAR,
+        italic: true,
+    };
+
+    pub const BOLD_ITALIC: Self = Self {
+        weight: FontWeight::BOLD,
+        italic: true,
+    };
+
+    /// Is this bold enough that we should fake it if there is no bold face?
+    #[inline]
+    pub fn is_bold(&self) -> bool {
+        FontWeight::SEMIBOLD <= self.weight
+    }
+}
+
+/// The value of one axis of a variable font.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct FontVariation {
+    /// The tag of the axis, e.g. `wght` (weight), `wdth` (width) or `slnt` (slant).
+    pub axis: [u8; 4],
+
+    pub value: f32,
+}
+
+// ----------------------------------------------------------------------------
+
 /// Extra scale and vertical tweak to apply to all text of a certain font.
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -174,6 +291,31 @@ pub struct FontTweak {
     /// A positive value shifts the text downwards.
     /// A negative value shifts it upwards.
     pub baseline_offset_factor: f32,
+
+    /// At how many horizontal offsets within a pixel to rasterize each glyph.
+    ///
+    /// With `1`, every glyph starts on a whole pixel,
+    /// which can make the spacing between letters uneven and makes moving text jitter.
+    /// With e.g. `4`, glyphs are positioned with a quarter pixel precision,
+    /// at the cost of up to four times as much space in the font atlas.
+    ///
+    /// Default: `1` (off).
+    pub subpixel_positions: u8,
+
+    /// Rasterize the glyphs as signed distance fields instead of coverage.
+    ///
+    /// The glyphs are rasterized once at a fairly large size, and the renderer
+    /// reconstructs a sharp edge from the distances at whatever scale the text is painted,
+    /// e.g. inside a zoomed layer (see `Context::set_transform_layer`).
+    /// Small text looks a little softer than with normal rasterization.
+    ///
+    /// Only the glyph fill is supported: outlines and glows would need their own
+    /// thresholds passed to the shaders, and are not implemented.
+    ///
+    /// Requires a renderer that supports [`crate::textures::TextureOptions::signed_distance_field`].
+    ///
+    /// Default: `false`.
+    pub signed_distance_field: bool,
 }
 
 impl Default for FontTweak {
@@ -183,26 +325,43 @@ impl Default for FontTweak {
             y_offset_factor: 0.0,
             y_offset: 0.0,
             baseline_offset_factor: -0.0333, // makes the default fonts look more centered in buttons and such
+            subpixel_positions: 1,
+            signed_distance_field: false,
         }
     }
 }
 
 // ----------------------------------------------------------------------------
 
-fn ab_glyph_font_from_font_data(name: &str, data: &FontData) -> ab_glyph::FontArc {
+fn ab_glyph_font_from_font_data(
+    name: &str,
+    data: &FontData,
+    variations: &[FontVariation],
+) -> ab_glyph::FontArc {
     match &data.font {
         std::borrow::Cow::Borrowed(bytes) => {
-            ab_glyph::FontRef::try_from_slice_and_index(bytes, data.index)
-                .map(ab_glyph::FontArc::from)
+            ab_glyph::FontRef::try_from_slice_and_index(bytes, data.index).map(|mut font| {
+                set_variations(&mut font, variations);
+                ab_glyph::FontArc::from(font)
+            })
         }
         std::borrow::Cow::Owned(bytes) => {
-            ab_glyph::FontVec::try_from_vec_and_index(bytes.clone(), data.index)
-                .map(ab_glyph::FontArc::from)
+            ab_glyph::FontVec::try_from_vec_and_index(bytes.clone(), data.index).map(|mut font| {
+                set_variations(&mut font, variations);
+                ab_glyph::FontArc::from(font)
+            })
         }
     }
     .unwrap_or_else(|err| panic!("Error parsing {name:?} TTF/OTF font file: {err}"))
 }
 
+fn set_variations(font: &mut impl ab_glyph::VariableFont, variations: &[FontVariation]) {
+    for variation in variations {
+        // Axes the font doesn't have are ignored:
+        font.set_variation(&variation.axis, variation.value);
+    }
+}
+
 /// Describes the font data and the sizes to use.
 ///
 /// Often you would start with [`FontDefinitions::default()`] and then add/change the contents.
@@ -245,6 +404,14 @@ pub struct FontDefinitions {
     /// the first font and then move to the second, and so on.
     /// So the first font is the primary, and then comes a list of fallbacks in order of priority.
     pub families: BTreeMap<FontFamily, Vec<String>>,
+
+    /// Other faces (e.g. bold and italic) of the fonts in [`Self::families`].
+    ///
+    /// The key is the name of the font used in [`Self::families`],
+    /// which is treated as the regular face.
+    /// For bold or italic text, the face with the closest [`FontFace::style`] is used.
+    /// If there is no bold or italic face, it is faked.
+    pub faces: BTreeMap<String, Vec<FontFace>>,
 }
 
 impl Default for FontDefinitions {
@@ -319,6 +486,7 @@ impl Default for FontDefinitions {
         Self {
             font_data,
             families,
+            faces: Default::default(),
         }
     }
 }
@@ -333,9 +501,66 @@ impl FontDefinitions {
         Self {
             font_data: Default::default(),
             families,
+            faces: Default::default(),
         }
     }
 
+    /// Add another face (e.g. bold or italic) of a font in [`Self::families`].
+    ///
+    /// ```
+    /// # use epaint::text::{FontData, FontDefinitions, FontFace, FontStyle};
+    /// # let bold_bytes: &'static [u8] = &[];
+    /// let mut fonts = FontDefinitions::default();
+    /// fonts.add_face(
+    ///     "Ubuntu-Light",
+    ///     FontFace::new("Ubuntu-Bold", FontStyle::BOLD),
+    ///     FontData::from_static(bold_bytes),
+    /// );
+    /// ```
+    pub fn add_face(&mut self, font_name: &str, face: FontFace, data: FontData) {
+        self.font_data.insert(face.name.clone(), data);
+        let faces = self.faces.entry(font_name.to_owned()).or_default();
+        faces.retain(|existing| existing.name != face.name);
+        faces.push(face);
+    }
+
+    /// The variable font axes set for the given key into [`Self::font_data`].
+    fn variations(&self, font_name: &str) -> &[FontVariation] {
+        self.faces
+            .values()
+            .flatten()
+            .find(|face| face.name == font_name)
+            .map_or(&[], |face| &face.variations)
+    }
+
+    /// The face of the given font that best matches the style,
+    /// and what we need to fake to get the rest of the way.
+    fn face_for_style<'a>(&'a self, font_name: &'a str, style: FontStyle) -> (&'a str, Synthesis) {
+        let other_faces = self.faces.get(font_name).into_iter().flatten();
+        let candidates = std::iter::once((font_name, FontStyle::REGULAR))
+            .chain(other_faces.map(|face| (face.name.as_str(), face.style)))
+            .filter(|(name, _)| self.font_data.contains_key(*name));
+
+        // Like CSS: slant first, then the closest weight, preferring bolder for bold text.
+        let best = candidates.min_by_key(|(_, face_style)| {
+            let wrong_slant = face_style.italic != style.italic;
+            let distance = face_style.weight.0.abs_diff(style.weight.0);
+            let wrong_direction = if style.weight <= FontWeight::MEDIUM {
+                style.weight < face_style.weight
+            } else {
+                face_style.weight < style.weight
+            };
+            (wrong_slant, distance, wrong_direction)
+        });
+
+        let (name, face_style) = best.unwrap_or((font_name, FontStyle::REGULAR));
+        let synthesis = Synthesis {
+            bold: style.is_bold() && !face_style.is_bold(),
+            italic: style.italic && !face_style.italic,
+        };
+        (name, synthesis)
+    }
+
     /// List of all the builtin font names used by `epaint`.
     #[cfg(feature = "default_fonts")]
     pub fn builtin_font_names() -> &'static [&'static str] {
@@ -398,27 +623,84 @@ impl Fonts {
 
         let pixels_per_point_changed = fonts_and_cache.fonts.pixels_per_point != pixels_per_point;
         let max_texture_side_changed = fonts_and_cache.fonts.max_texture_side != max_texture_side;
-        let font_atlas_almost_full = fonts_and_cache.fonts.atlas.lock().fill_ratio() > 0.8;
+
+        let mut font_atlas_almost_full = fonts_and_cache.fonts.atlas.lock().fill_ratio() > 0.8;
+        if font_atlas_almost_full {
+            // Make room by evicting the glyphs that haven't been used for the longest time,
+            // except those of galleys that are still around:
+            let FontsAndCache {
+                fonts,
+                galley_cache,
+            } = &mut *fonts_and_cache;
+            let evicted = {
+                let mut atlas = fonts.atlas.lock();
+                galley_cache.mark_retired_used(&mut atlas);
+                atlas.evict_unused(0.6)
+            };
+            if !evicted.is_empty() {
+                let evicted: ahash::HashSet<AtlasPos> = evicted.into_iter().collect();
+                fonts_and_cache.fonts.forget_evicted(&evicted);
+                fonts_and_cache.galley_cache.forget_evicted(&evicted);
+            }
+            font_atlas_almost_full = fonts_and_cache.fonts.atlas.lock().fill_ratio() > 0.8;
+        }
+
         let needs_recreate =
             pixels_per_point_changed || max_texture_side_changed || font_atlas_almost_full;
 
         if needs_recreate {
             let definitions = fonts_and_cache.fonts.definitions.clone();
+            let page_texture_ids = fonts_and_cache.fonts.atlas.lock().page_texture_ids();
 
             *fonts_and_cache = FontsAndCache {
                 fonts: FontsImpl::new(pixels_per_point, max_texture_side, definitions),
                 galley_cache: Default::default(),
             };
+
+            // Reuse the textures of the old pages when the new atlas needs more pages:
+            fonts_and_cache.fonts.unused_page_texture_ids = page_texture_ids;
         }
 
+        fonts_and_cache.fonts.atlas.lock().begin_frame();
+
         fonts_and_cache.galley_cache.flush_cache();
     }
 
     /// Call at the end of each frame (before painting) to get the change to the font texture since last call.
+    ///
+    /// This is only the first page of the font atlas. Use [`Self::font_page_deltas`] to support more pages.
     pub fn font_image_delta(&self) -> Option<crate::ImageDelta> {
         self.lock().fonts.atlas.lock().take_delta()
     }
 
+    /// Call at the end of each frame (before painting) to get the changes to all pages of the font atlas.
+    ///
+    /// The first page is painted with [`crate::TextureId::default`].
+    /// The other pages need a texture each, which is allocated with `alloc_texture` the first time it is needed.
+    pub fn font_page_deltas(
+        &self,
+        mut alloc_texture: impl FnMut() -> crate::TextureId,
+    ) -> Vec<(crate::TextureId, crate::ImageDelta)> {
+        let fonts_and_cache = &mut *self.lock();
+        let fonts = &mut fonts_and_cache.fonts;
+        let mut atlas = fonts.atlas.lock();
+        atlas
+            .take_deltas()
+            .into_iter()
+            .map(|(page, delta)| {
+                let texture_id = atlas.page_texture_id(page).unwrap_or_else(|| {
+                    let texture_id = fonts
+                        .unused_page_texture_ids
+                        .pop()
+                        .unwrap_or_else(&mut alloc_texture);
+                    atlas.set_page_texture_id(page, texture_id);
+                    texture_id
+                });
+                (texture_id, delta)
+            })
+            .collect()
+    }
+
     /// Access the underlying [`FontsAndCache`].
     #[doc(hidden)]
     #[inline]
@@ -576,7 +858,13 @@ pub struct FontsImpl {
     definitions: FontDefinitions,
     atlas: Arc<Mutex<TextureAtlas>>,
     font_impl_cache: FontImplCache,
-    sized_family: ahash::HashMap<(OrderedFloat<f32>, FontFamily), Font>,
+    sized_family: ahash::HashMap<(OrderedFloat<f32>, FontFamily, FontStyle), Font>,
+
+    /// Textures of the pages of a previous atlas, that can be reused for new pages.
+    unused_page_texture_ids: Vec<crate::TextureId>,
+
+    #[cfg(feature = "system_fonts")]
+    system_fonts: super::system_fonts::SystemFonts,
 }
 
 impl FontsImpl {
@@ -598,8 +886,7 @@ impl FontsImpl {
 
         let atlas = Arc::new(Mutex::new(atlas));
 
-        let font_impl_cache =
-            FontImplCache::new(atlas.clone(), pixels_per_point, &definitions.font_data);
+        let font_impl_cache = FontImplCache::new(atlas.clone(), pixels_per_point, &definitions);
 
         Self {
             pixels_per_point,
@@ -608,6 +895,9 @@ impl FontsImpl {
             atlas,
             font_impl_cache,
             sized_family: Default::default(),
+            unused_page_texture_ids: Default::default(),
+            #[cfg(feature = "system_fonts")]
+            system_fonts: Default::default(),
         }
     }
 
@@ -623,10 +913,18 @@ impl FontsImpl {
 
     /// Get the right font implementation from size and [`FontFamily`].
     pub fn font(&mut self, font_id: &FontId) -> &mut Font {
+        self.font_with_style(font_id, FontStyle::REGULAR)
+    }
+
+    /// Get the right font implementation from size, [`FontFamily`] and [`FontStyle`].
+    ///
+    /// Uses the closest face in [`FontDefinitions::faces`] for each font in the family,
+    /// and fakes bold and italic if there is no such face.
+    pub fn font_with_style(&mut self, font_id: &FontId, style: FontStyle) -> &mut Font {
         let FontId { size, family } = font_id;
 
         self.sized_family
-            .entry((OrderedFloat(*size), family.clone()))
+            .entry((OrderedFloat(*size), family.clone(), style))
             .or_insert_with(|| {
                 let fonts = &self.definitions.families.get(family);
                 let fonts = fonts
@@ -634,13 +932,58 @@ impl FontsImpl {
 
                 let fonts: Vec<Arc<FontImpl>> = fonts
                     .iter()
-                    .map(|font_name| self.font_impl_cache.font_impl(*size, font_name))
+                    .map(|font_name| {
+                        let (face_name, synthesis) =
+                            self.definitions.face_for_style(font_name, style);
+                        self.font_impl_cache.font_impl(*size, face_name, synthesis)
+                    })
                     .collect();
 
                 Font::new(fonts)
             })
     }
 
+    /// Look for system fonts with the characters in `text` that the font is missing,
+    /// and add them as fallbacks.
+    #[cfg(feature = "system_fonts")]
+    pub(crate) fn add_system_fallbacks(&mut self, font_id: &FontId, style: FontStyle, text: &str) {
+        let font = self.font_with_style(font_id, style);
+        let missing: Vec<char> = text
+            .chars()
+            .filter(|&chr| !chr.is_control() && !super::font::invisible_char(chr))
+            .filter(|&chr| font.needs_system_fallback(chr))
+            .collect();
+
+        for chr in missing {
+            let Some((font_name, font_data)) = self.system_fonts.font_for_char(chr) else {
+                continue;
+            };
+            if let Some(font_data) = font_data {
+                self.font_impl_cache.add_font(font_name.clone(), &font_data);
+            }
+            if self.font_impl_cache.has_font(&font_name) {
+                let synthesis = Synthesis {
+                    bold: style.is_bold(),
+                    italic: style.italic,
+                };
+                let font_impl = self
+                    .font_impl_cache
+                    .font_impl(font_id.size, &font_name, synthesis);
+                self.font_with_style(font_id, style).add_fallback(font_impl);
+            }
+        }
+    }
+
+    /// Forget all glyphs that were evicted from the font atlas.
+    fn forget_evicted(&mut self, evicted: &ahash::HashSet<AtlasPos>) {
+        for font_impl in self.font_impl_cache.cache.values() {
+            font_impl.forget_evicted(evicted);
+        }
+        for font in self.sized_family.values_mut() {
+            font.forget_evicted(evicted);
+        }
+    }
+
     /// Width of this character in points.
     fn glyph_width(&mut self, font_id: &FontId, c: char) -> f32 {
         self.font(font_id).glyph_width(c)
@@ -668,6 +1011,35 @@ struct CachedGalley {
     /// When it was last used
     last_used: u32,
     galley: Arc<Galley>,
+
+    /// Where the glyphs of the galley are in the font atlas.
+    atlas_positions: Vec<AtlasPos>,
+}
+
+impl CachedGalley {
+    fn new(galley: Arc<Galley>, last_used: u32) -> Self {
+        let mut atlas_positions: Vec<AtlasPos> = galley
+            .rows
+            .iter()
+            .flat_map(|row| row.glyphs_with_hyphen())
+            .filter(|glyph| !glyph.uv_rect.is_nothing())
+            .map(|glyph| glyph.uv_rect.atlas_pos())
+            .collect();
+        atlas_positions.sort_unstable_by_key(|pos| (pos.page, pos.y, pos.x));
+        atlas_positions.dedup();
+        Self {
+            last_used,
+            galley,
+            atlas_positions,
+        }
+    }
+
+    /// Tell the atlas that the glyphs are in use, so they won't be evicted.
+    fn mark_used(&self, atlas: &mut TextureAtlas) {
+        for &pos in &self.atlas_positions {
+            atlas.mark_used(pos);
+        }
+    }
 }
 
 #[derive(Default)]
@@ -675,6 +1047,12 @@ struct GalleyCache {
     /// Frame counter used to do garbage collection on the cache
     generation: u32,
     cache: nohash_hasher::IntMap<u64, CachedGalley>,
+
+    /// Galleys that were dropped from the cache, but that are still held by someone else,
+    /// e.g. the app, or a [`crate::TessellationCache`].
+    ///
+    /// Their glyphs must stay in the atlas for as long as they are alive.
+    retired: Vec<(Weak<Galley>, Vec<AtlasPos>)>,
 }
 
 impl GalleyCache {
@@ -684,16 +1062,18 @@ impl GalleyCache {
         match self.cache.entry(hash) {
             std::collections::hash_map::Entry::Occupied(entry) => {
                 let cached = entry.into_mut();
-                cached.last_used = self.generation;
+                if cached.last_used != self.generation {
+                    cached.last_used = self.generation;
+                    cached.mark_used(&mut fonts.atlas.lock());
+                }
                 cached.galley.clone()
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
                 let galley = super::layout(fonts, job.into());
                 let galley = Arc::new(galley);
-                entry.insert(CachedGalley {
-                    last_used: self.generation,
-                    galley: galley.clone(),
-                });
+                let cached = CachedGalley::new(galley.clone(), self.generation);
+                cached.mark_used(&mut fonts.atlas.lock());
+                entry.insert(cached);
                 galley
             }
         }
@@ -703,11 +1083,43 @@ impl GalleyCache {
         self.cache.len()
     }
 
+    /// Forget the galleys that use glyphs that were evicted from the font atlas.
+    fn forget_evicted(&mut self, evicted: &ahash::HashSet<AtlasPos>) {
+        self.cache.retain(|_, cached| {
+            !cached
+                .atlas_positions
+                .iter()
+                .any(|pos| evicted.contains(pos))
+        });
+    }
+
+    /// Mark the glyphs of the galleys that left the cache but are still alive as used.
+    fn mark_retired_used(&mut self, atlas: &mut TextureAtlas) {
+        self.retired.retain(|(galley, atlas_positions)| {
+            let is_alive = galley.strong_count() > 0;
+            if is_alive {
+                for &pos in atlas_positions {
+                    atlas.mark_used(pos);
+                }
+            }
+            is_alive
+        });
+    }
+
     /// Must be called once per frame to clear the [`Galley`] cache.
     pub fn flush_cache(&mut self) {
         let current_generation = self.generation;
+        let retired = &mut self.retired;
+        retired.retain(|(galley, _)| galley.strong_count() > 0);
         self.cache.retain(|_key, cached| {
-            cached.last_used == current_generation // only keep those that were used this frame
+            let keep = cached.last_used == current_generation; // only keep those that were used this frame
+            if !keep && Arc::strong_count(&cached.galley) > 1 {
+                retired.push((
+                    Arc::downgrade(&cached.galley),
+                    std::mem::take(&mut cached.atlas_positions),
+                ));
+            }
+            keep
         });
         self.generation = self.generation.wrapping_add(1);
     }
@@ -720,21 +1132,26 @@ struct FontImplCache {
     pixels_per_point: f32,
     ab_glyph_fonts: BTreeMap<String, (FontTweak, ab_glyph::FontArc)>,
 
-    /// Map font pixel sizes and names to the cached [`FontImpl`].
-    cache: ahash::HashMap<(u32, String), Arc<FontImpl>>,
+    #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+    font_bytes: BTreeMap<String, super::font::FontBytes>,
+
+    /// Map font pixel sizes, names and synthesis to the cached [`FontImpl`].
+    cache: ahash::HashMap<(u32, String, Synthesis), Arc<FontImpl>>,
 }
 
 impl FontImplCache {
     pub fn new(
         atlas: Arc<Mutex<TextureAtlas>>,
         pixels_per_point: f32,
-        font_data: &BTreeMap<String, FontData>,
+        definitions: &FontDefinitions,
     ) -> Self {
-        let ab_glyph_fonts = font_data
+        let ab_glyph_fonts = definitions
+            .font_data
             .iter()
             .map(|(name, font_data)| {
                 let tweak = font_data.tweak;
-                let ab_glyph = ab_glyph_font_from_font_data(name, font_data);
+                let variations = definitions.variations(name);
+                let ab_glyph = ab_glyph_font_from_font_data(name, font_data, variations);
                 (name.clone(), (tweak, ab_glyph))
             })
             .collect();
@@ -743,11 +1160,51 @@ impl FontImplCache {
             atlas,
             pixels_per_point,
             ab_glyph_fonts,
+            #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+            font_bytes: definitions
+                .font_data
+                .iter()
+                .map(|(name, font_data)| {
+                    let variations = definitions.variations(name);
+                    let font_bytes = super::font::FontBytes::new(font_data, variations);
+                    (name.clone(), font_bytes)
+                })
+                .collect(),
             cache: Default::default(),
         }
     }
 
-    pub fn font_impl(&mut self, scale_in_points: f32, font_name: &str) -> Arc<FontImpl> {
+    #[cfg(feature = "system_fonts")]
+    fn has_font(&self, font_name: &str) -> bool {
+        self.ab_glyph_fonts.contains_key(font_name)
+    }
+
+    /// Add a font that wasn't part of the [`FontDefinitions`].
+    #[cfg(feature = "system_fonts")]
+    fn add_font(&mut self, font_name: String, font_data: &FontData) {
+        let ab_glyph = match ab_glyph::FontArc::try_from_vec(font_data.font.to_vec()) {
+            Ok(ab_glyph) => ab_glyph,
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                log::warn!("Error parsing {font_name:?} TTF/OTF font file: {_err}");
+                return;
+            }
+        };
+        #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+        self.font_bytes.insert(
+            font_name.clone(),
+            super::font::FontBytes::new(font_data, &[]),
+        );
+        self.ab_glyph_fonts
+            .insert(font_name, (font_data.tweak, ab_glyph));
+    }
+
+    pub fn font_impl(
+        &mut self,
+        scale_in_points: f32,
+        font_name: &str,
+        synthesis: Synthesis,
+    ) -> Arc<FontImpl> {
         use ab_glyph::Font as _;
 
         let (tweak, ab_glyph_font) = self
@@ -769,17 +1226,142 @@ impl FontImplCache {
             .entry((
                 (scale_in_pixels * tweak.scale).round() as u32,
                 font_name.to_owned(),
+                synthesis,
             ))
             .or_insert_with(|| {
-                Arc::new(FontImpl::new(
+                let font_impl = FontImpl::new(
                     self.atlas.clone(),
                     self.pixels_per_point,
                     font_name.to_owned(),
                     ab_glyph_font,
                     scale_in_pixels,
                     tweak,
-                ))
+                )
+                .with_synthesis(synthesis);
+                #[cfg(any(feature = "shaping", feature = "color_emoji"))]
+                let font_impl = match self.font_bytes.get(font_name) {
+                    Some(font_bytes) => font_impl.with_font_bytes(font_bytes.clone()),
+                    None => font_impl,
+                };
+                Arc::new(font_impl)
             })
             .clone()
     }
 }
+
+#[test]
+fn test_face_for_style() {
+    let mut fonts = FontDefinitions::empty();
+    let data = || FontData::from_static(&[]);
+    fonts.font_data.insert("Sans".to_owned(), data());
+    fonts.add_face("Sans", FontFace::new("Sans-Bold", FontStyle::BOLD), data());
+    fonts.add_face(
+        "Sans",
+        FontFace::new(
+            "Sans-Light",
+            FontStyle {
+                weight: FontWeight::LIGHT,
+                italic: false,
+            },
+        )
+        .variation(b"wght", 300.0),
+        data(),
+    );
+    assert_eq!(fonts.variations("Sans-Light").len(), 1);
+    assert!(fonts.variations("Sans").is_empty());
+
+    let no_synthesis = Synthesis::default();
+    assert_eq!(
+        fonts.face_for_style("Sans", FontStyle::REGULAR),
+        ("Sans", no_synthesis)
+    );
+    assert_eq!(
+        fonts.face_for_style("Sans", FontStyle::BOLD),
+        ("Sans-Bold", no_synthesis)
+    );
+    assert_eq!(
+        fonts.face_for_style("Sans", FontStyle::ITALIC),
+        (
+            "Sans",
+            Synthesis {
+                bold: false,
+                italic: true
+            }
+        )
+    );
+    assert_eq!(
+        fonts.face_for_style("Sans", FontStyle::BOLD_ITALIC),
+        (
+            "Sans-Bold",
+            Synthesis {
+                bold: false,
+                italic: true
+            }
+        )
+    );
+
+    // A font without other faces fakes it all:
+    assert_eq!(
+        fonts.face_for_style("Mono", FontStyle::BOLD),
+        (
+            "Mono",
+            Synthesis {
+                bold: true,
+                italic: false
+            }
+        )
+    );
+}
+
+/// Evict all glyphs that aren't in use from the font atlas.
+#[cfg(test)]
+fn evict(fonts: &Fonts) -> Vec<AtlasPos> {
+    let FontsAndCache {
+        fonts,
+        galley_cache,
+    } = &mut *fonts.lock();
+    let mut atlas = fonts.atlas.lock();
+    galley_cache.mark_retired_used(&mut atlas);
+    atlas.evict_unused(0.0)
+}
+
+#[test]
+fn test_glyphs_of_live_galleys_are_not_evicted() {
+    let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+    let galley = fonts.layout_no_wrap("Hello".to_owned(), FontId::default(), crate::Color32::WHITE);
+    let glyph_pos = galley.rows[0].glyphs[0].uv_rect.atlas_pos();
+
+    // The galley is no longer in the cache, but we still hold on to it:
+    for _ in 0..5 {
+        fonts.begin_frame(1.0, 1024);
+    }
+    assert!(!evict(&fonts).contains(&glyph_pos));
+
+    drop(galley);
+    for _ in 0..5 {
+        fonts.begin_frame(1.0, 1024);
+    }
+    assert!(evict(&fonts).contains(&glyph_pos));
+}
+
+#[cfg(feature = "hyphenation")]
+#[test]
+fn test_hyphens_of_cached_galleys_are_not_evicted() {
+    let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+    let layout = || {
+        let text = "An extraordinarily incomprehensible sentence";
+        let mut job = LayoutJob::single_section(text.into(), Default::default());
+        job.wrap.max_width = 100.0;
+        job.wrap.hyphenation_language = Some(*b"en");
+        fonts.layout_job(job)
+    };
+    let hyphen = layout().rows.iter().find_map(|row| row.hyphen).unwrap();
+
+    // The galley stays in the cache as long as we keep laying it out:
+    for _ in 0..5 {
+        fonts.begin_frame(1.0, 1024);
+        layout();
+    }
+    assert!(!evict(&fonts).contains(&hyphen.uv_rect.atlas_pos()));
+    assert!(layout().rows.iter().any(|row| row.hyphen == Some(hyphen)));
+}
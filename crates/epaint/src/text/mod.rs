@@ -1,8 +1,15 @@
 //! Everything related to text, fonts, text layout, cursors etc.
 
+#[cfg(feature = "color_emoji")]
+mod color_glyph;
 pub mod cursor;
 mod font;
 mod fonts;
+mod sdf;
+#[cfg(feature = "shaping")]
+mod shaping;
+#[cfg(feature = "system_fonts")]
+mod system_fonts;
 mod text_layout;
 mod text_layout_types;
 
@@ -10,7 +17,10 @@ mod text_layout_types;
 pub const TAB_SIZE: usize = 4;
 
 pub use {
-    fonts::{FontData, FontDefinitions, FontFamily, FontId, FontTweak, Fonts, FontsImpl},
+    fonts::{
+        FontData, FontDefinitions, FontFace, FontFamily, FontId, FontStyle, FontTweak,
+        FontVariation, FontWeight, Fonts, FontsImpl,
+    },
     text_layout::layout,
     text_layout_types::*,
 };
@@ -0,0 +1,98 @@
+//! Signed distance fields for glyphs, see [`crate::text::FontTweak::signed_distance_field`].
+
+/// How far inside and outside of the edge the distance field reaches, in texels.
+pub(crate) const SPREAD: usize = 4;
+
+/// Glyphs are rasterized at least this large before being turned into distance fields,
+/// so that they stay sharp when scaled up.
+pub(crate) const MIN_SCALE_IN_PIXELS: f32 = 48.0;
+
+/// Turn the coverage of a glyph into a signed distance field,
+/// with [`SPREAD`] texels of padding on all sides.
+///
+/// Returns the size of the field and its values, where `0.5` is the edge,
+/// `1.0` is [`SPREAD`] texels (or more) inside, and `0.0` is [`SPREAD`] texels (or more) outside.
+pub(crate) fn distance_field([w, h]: [usize; 2], coverage: &[f32]) -> ([usize; 2], Vec<f32>) {
+    debug_assert_eq!(coverage.len(), w * h);
+
+    let coverage_at = |x: isize, y: isize| -> f32 {
+        if 0 <= x && 0 <= y && (x as usize) < w && (y as usize) < h {
+            coverage[y as usize * w + x as usize]
+        } else {
+            0.0
+        }
+    };
+
+    let spread = SPREAD as isize;
+    let size = [w + 2 * SPREAD, h + 2 * SPREAD];
+    let mut field = Vec::with_capacity(size[0] * size[1]);
+    for field_y in 0..size[1] as isize {
+        for field_x in 0..size[0] as isize {
+            let (x, y) = (field_x - spread, field_y - spread);
+            let coverage = coverage_at(x, y);
+            let inside = 0.5 <= coverage;
+
+            let signed_distance = if 0.0 < coverage && coverage < 1.0 {
+                // The edge goes through this texel, and the coverage tells us where:
+                coverage - 0.5
+            } else {
+                // Brute force search for the closest texel on the other side of the edge:
+                let mut closest_squared = f32::INFINITY;
+                for dy in -spread..=spread {
+                    for dx in -spread..=spread {
+                        if (0.5 <= coverage_at(x + dx, y + dy)) != inside {
+                            closest_squared = closest_squared.min((dx * dx + dy * dy) as f32);
+                        }
+                    }
+                }
+
+                // The edge is about half way to that texel:
+                let distance = (closest_squared.sqrt() - 0.5).min(SPREAD as f32);
+                if inside {
+                    distance
+                } else {
+                    -distance
+                }
+            };
+
+            field.push(0.5 + signed_distance / (2.0 * SPREAD as f32));
+        }
+    }
+
+    (size, field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_field_of_square() {
+        // A 6x6 square in the middle of a 10x10 image:
+        let (w, h) = (10, 10);
+        let coverage: Vec<f32> = (0..w * h)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                if (2..8).contains(&x) && (2..8).contains(&y) {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let (size, field) = distance_field([w, h], &coverage);
+        assert_eq!(size, [w + 2 * SPREAD, h + 2 * SPREAD]);
+        let at = |x: usize, y: usize| field[(y + SPREAD) * size[0] + x + SPREAD];
+
+        assert_eq!(field[0], 0.0, "Far outside");
+        assert_eq!(at(5, 5), 0.5 + 2.5 / (2.0 * SPREAD as f32), "Center");
+        assert!(0.5 < at(2, 5), "Just inside the edge");
+        assert!(at(1, 5) < 0.5, "Just outside the edge");
+
+        // Increasing towards the center, which is a plateau since the square has an even width:
+        for x in 0..4 {
+            assert!(at(x, 5) < at(x + 1, 5));
+        }
+    }
+}
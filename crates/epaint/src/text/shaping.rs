@@ -0,0 +1,222 @@
+//! Text shaping using [`rustybuzz`].
+//!
+//! Shaping is what turns a string of characters into positioned glyphs.
+//! It is needed for ligatures, `OpenType` kerning, Arabic joining,
+//! and complex scripts such as Devanagari and Thai.
+//!
+//! Bidirectional text is not supported:
+//! right-to-left runs are shaped, but laid out left-to-right like everything else.
+//!
+//! The text is first split into runs that use the same font and script,
+//! and each run is then shaped into clusters.
+//! A cluster is one or more `char`s that are drawn as one or more glyphs.
+//! We rasterize each cluster as one image, so that the rest of the layout
+//! can keep treating the text as one glyph per `char`,
+//! which is what the cursor logic of [`crate::text::Galley`] relies on.
+
+use std::ops::Range;
+
+use rustybuzz::{script, Script};
+
+use super::font::{invisible_char, Font, FontImpl, FontIndex, UvRect};
+
+/// A part of the text that uses the same font and script.
+pub(crate) struct Run {
+    pub byte_range: Range<usize>,
+
+    /// `None` for characters that should not be shaped (tabs, unsupported characters, …).
+    pub font_index: Option<FontIndex>,
+
+    pub script: Script,
+}
+
+/// Split the text into runs that can be shaped.
+pub(crate) fn itemize(font: &mut Font, text: &str) -> Vec<Run> {
+    let mut runs: Vec<Run> = vec![];
+
+    for (i, chr) in text.char_indices() {
+        let end = i + chr.len_utf8();
+        let script = script_of(chr);
+
+        let font_index = if invisible_char(chr) {
+            // Zero-width joiners etc. are important for shaping, so keep them in the run:
+            runs.last().and_then(|run| run.font_index)
+        } else {
+            font.font_index_for_shaping(chr)
+        };
+
+        if let Some(run) = runs.last_mut() {
+            if font_index.is_some() && run.font_index == font_index {
+                let neutral = script == script::COMMON || script == script::INHERITED;
+                if neutral || script == run.script {
+                    run.byte_range.end = end;
+                    continue;
+                }
+                if run.script == script::COMMON {
+                    // E.g. a number followed by a word.
+                    run.script = script;
+                    run.byte_range.end = end;
+                    continue;
+                }
+            }
+        }
+
+        runs.push(Run {
+            byte_range: i..end,
+            font_index,
+            script,
+        });
+    }
+
+    runs
+}
+
+fn script_of(chr: char) -> Script {
+    let name = unicode_script::UnicodeScript::script(&chr).short_name();
+    Script::from_iso15924_tag(rustybuzz::Tag::from_bytes_lossy(name.as_bytes()))
+        .unwrap_or(script::UNKNOWN)
+}
+
+/// One or more `char`s drawn as one or more glyphs.
+pub(crate) struct ShapedCluster {
+    pub num_chars: usize,
+
+    /// Unit: points.
+    pub advance_width: f32,
+
+    /// All the glyphs of the cluster.
+    pub uv_rect: UvRect,
+
+    /// The glyphs of the cluster, with their position relative to the start of the cluster, in font units.
+    pub glyphs: Vec<(ab_glyph::GlyphId, [i32; 2])>,
+}
+
+impl ShapedCluster {
+    /// Used for pair-kerning with whatever comes after the run.
+    pub fn last_glyph_id(&self) -> ab_glyph::GlyphId {
+        self.glyphs
+            .last()
+            .map_or(ab_glyph::GlyphId(0), |glyph| glyph.0)
+    }
+}
+
+/// Shape a run of text with the given font.
+///
+/// Returns `None` if the font does not support shaping,
+/// in which case the caller should fall back to laying out one `char` at a time.
+pub(crate) fn shape(
+    font_impl: &FontImpl,
+    text: &str,
+    script: Script,
+) -> Option<Vec<ShapedCluster>> {
+    crate::profile_function!();
+
+    let face = font_impl.font_bytes()?.shaping_face()?;
+
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(text);
+    if script != script::COMMON && script != script::INHERITED && script != script::UNKNOWN {
+        buffer.set_script(script);
+    }
+    buffer.guess_segment_properties(); // direction from the script, e.g. right-to-left for Arabic
+    let is_rtl = buffer.direction() == rustybuzz::Direction::RightToLeft;
+
+    let glyph_buffer = rustybuzz::shape(face, &[], buffer);
+
+    // The glyphs come in visual order. We want them in the order of the text,
+    // since the layout places the clusters one `char` at a time
+    // (so right-to-left runs end up laid out left-to-right, see the module docs).
+    let mut glyphs: Vec<_> = glyph_buffer
+        .glyph_infos()
+        .iter()
+        .zip(glyph_buffer.glyph_positions())
+        .collect();
+    if is_rtl {
+        glyphs.reverse();
+    }
+
+    let pixels_per_font_unit = font_impl.pixels_per_font_unit();
+    let points_per_font_unit = pixels_per_font_unit / font_impl.pixels_per_point();
+
+    let mut clusters = vec![];
+    let mut num_chars_total = 0;
+    let mut cluster_byte_start = 0; // Include any characters that were removed during shaping
+    let mut start = 0;
+
+    while start < glyphs.len() {
+        let cluster = glyphs[start].0.cluster;
+        let end = glyphs[start..]
+            .iter()
+            .position(|(info, _)| info.cluster != cluster)
+            .map_or(glyphs.len(), |len| start + len);
+        let next_cluster = glyphs
+            .get(end)
+            .map_or(text.len(), |(info, _)| info.cluster as usize);
+        let num_chars = text.get(cluster_byte_start..next_cluster)?.chars().count();
+        cluster_byte_start = next_cluster;
+
+        // Position of each glyph relative to the start of the cluster, in font units:
+        let mut pen_x = 0;
+        let mut cluster_glyphs = Vec::with_capacity(end - start);
+        let mut place_glyph = |(info, pos): &(&rustybuzz::GlyphInfo, &rustybuzz::GlyphPosition)| {
+            let glyph_id = ab_glyph::GlyphId(info.glyph_id as u16);
+            if glyph_id.0 != 0 {
+                cluster_glyphs.push((glyph_id, [pen_x + pos.x_offset, -pos.y_offset]));
+            }
+            pen_x += pos.x_advance;
+        };
+        if is_rtl {
+            // Back to visual order within the cluster:
+            glyphs[start..end].iter().rev().for_each(&mut place_glyph);
+        } else {
+            glyphs[start..end].iter().for_each(&mut place_glyph);
+        }
+        let glyphs = cluster_glyphs;
+
+        let uv_rect = match glyphs.as_slice() {
+            [] => UvRect::default(),
+            [(glyph_id, [x, y])] => {
+                let mut uv_rect = font_impl.glyph_info_from_id(*glyph_id).uv_rect;
+                if !uv_rect.is_nothing() {
+                    uv_rect.offset += points_per_font_unit * emath::vec2(*x as f32, *y as f32);
+                }
+                uv_rect
+            }
+            _ if glyphs.iter().any(|(id, _)| font_impl.is_color_glyph(*id)) => {
+                // We can't composite color glyphs with outlines, so just show the color glyph,
+                // e.g. the emoji of a sequence with a skin tone modifier:
+                let (glyph_id, [x, y]) = glyphs
+                    .iter()
+                    .rev()
+                    .find(|(id, _)| font_impl.is_color_glyph(*id))?;
+                let mut uv_rect = font_impl.glyph_info_from_id(*glyph_id).uv_rect;
+                uv_rect.offset += points_per_font_unit * emath::vec2(*x as f32, *y as f32);
+                uv_rect
+            }
+            _ => {
+                let in_pixels: Vec<_> = glyphs
+                    .iter()
+                    .map(|&(glyph_id, [x, y])| {
+                        let x = (x as f32 * pixels_per_font_unit).round() as i32;
+                        let y = (y as f32 * pixels_per_font_unit).round() as i32;
+                        (glyph_id, [x, y])
+                    })
+                    .collect();
+                font_impl.cluster_uv_rect(&in_pixels)
+            }
+        };
+
+        clusters.push(ShapedCluster {
+            num_chars,
+            advance_width: pen_x as f32 * points_per_font_unit
+                + glyphs.len() as f32 * font_impl.synthetic_advance(),
+            uv_rect,
+            glyphs,
+        });
+        num_chars_total += num_chars;
+        start = end;
+    }
+
+    // The layout relies on every `char` belonging to exactly one cluster:
+    (num_chars_total == text.chars().count()).then_some(clusters)
+}
@@ -0,0 +1,520 @@
+//! Finding installed system fonts to use for characters that none of our own fonts have.
+//!
+//! The first time a character is missing we scan the system font directories
+//! and index which characters each font covers, reading only the `cmap` table of each font.
+//! This is done once per process, and shared by all [`crate::text::Fonts`].
+//! A font is only loaded into memory once it is chosen for a character.
+
+use std::{
+    io::{Read as _, Seek as _, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use unicode_script::{Script, UnicodeScript as _};
+
+use super::FontData;
+
+/// An installed font file, and what it covers.
+struct SystemFace {
+    path: PathBuf,
+
+    /// Sorted, non-overlapping, inclusive ranges of the supported code points.
+    coverage: Vec<(u32, u32)>,
+
+    /// How many code points of each script the font supports.
+    scripts: ahash::HashMap<Script, u32>,
+
+    /// Regular fonts are preferred over bold, italic, condensed, …
+    is_regular: bool,
+}
+
+impl SystemFace {
+    fn load(path: PathBuf) -> Option<Self> {
+        let cmap = read_cmap(&path)?;
+        let cmap = ttf_parser::cmap::Table::parse(&cmap)?;
+
+        let mut chars: Vec<char> = vec![];
+        for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+            subtable.codepoints(|codepoint| {
+                if subtable.glyph_index(codepoint).is_some_and(|id| id.0 != 0) {
+                    chars.extend(char::from_u32(codepoint));
+                }
+            });
+        }
+        chars.sort_unstable();
+        chars.dedup();
+
+        let mut coverage: Vec<(u32, u32)> = vec![];
+        let mut scripts = ahash::HashMap::default();
+        for chr in chars {
+            *scripts.entry(chr.script()).or_default() += 1;
+            match coverage.last_mut() {
+                Some((_, last)) if *last + 1 == chr as u32 => *last = chr as u32,
+                _ => coverage.push((chr as u32, chr as u32)),
+            }
+        }
+
+        let is_regular = is_regular_style(&path);
+
+        Some(Self {
+            path,
+            coverage,
+            scripts,
+            is_regular,
+        })
+    }
+
+    fn covers(&self, chr: char) -> bool {
+        let chr = chr as u32;
+        let i = self.coverage.partition_point(|&(_, last)| last < chr);
+        self.coverage.get(i).is_some_and(|&(first, _)| first <= chr)
+    }
+
+    /// The name we register the font under.
+    fn name(&self) -> String {
+        format!("system:{}", self.path.display())
+    }
+}
+
+/// Read just the `cmap` table of a font file, so we don't need to read all of it.
+///
+/// For collections (`.ttc`) we only look at the first font, which is usually the regular one.
+fn read_cmap(path: &Path) -> Option<Vec<u8>> {
+    /// Larger tables are surely broken.
+    const MAX_TABLE_SIZE: usize = 16 * 1024 * 1024;
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut read_at = |offset: u32, len: usize| -> Option<Vec<u8>> {
+        file.seek(SeekFrom::Start(offset.into())).ok()?;
+        let mut bytes = vec![0; len];
+        file.read_exact(&mut bytes).ok()?;
+        Some(bytes)
+    };
+    let u16_at = |bytes: &[u8], i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+    let u32_at = |bytes: &[u8], i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+
+    let header = read_at(0, 16)?;
+    let font_offset = if &header[..4] == b"ttcf" {
+        u32_at(&header, 12)
+    } else {
+        0
+    };
+
+    let num_tables = u16_at(&read_at(font_offset, 12)?, 4) as usize;
+    let records = read_at(font_offset + 12, 16 * num_tables)?;
+    let record = records
+        .chunks_exact(16)
+        .find(|record| &record[..4] == b"cmap")?;
+    let (offset, length) = (u32_at(record, 8), u32_at(record, 12) as usize);
+    if MAX_TABLE_SIZE < length {
+        return None;
+    }
+    read_at(offset, length)
+}
+
+/// Guess from the file name if this is the regular style of a font.
+fn is_regular_style(path: &Path) -> bool {
+    let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+        return false;
+    };
+    let stem = stem.to_lowercase();
+    ![
+        "bold",
+        "italic",
+        "oblique",
+        "light",
+        "thin",
+        "black",
+        "heavy",
+        "medium",
+        "semi",
+        "condensed",
+        "narrow",
+    ]
+    .iter()
+    .any(|style| stem.contains(style))
+}
+
+// ----------------------------------------------------------------------------
+
+/// Finds and loads system fonts for characters that are missing from all our fonts.
+#[derive(Default)]
+pub(crate) struct SystemFonts {
+    /// Lazily scanned the first time a character is missing, see [`system_faces`].
+    faces: Option<Arc<Vec<SystemFace>>>,
+
+    /// Faces that failed to load, so we don't try them again.
+    failed: ahash::HashSet<usize>,
+
+    /// The preferred face for each script.
+    by_script: ahash::HashMap<Script, usize>,
+
+    /// Which face to use for each character we have been asked about.
+    by_char: ahash::HashMap<char, Option<usize>>,
+
+    /// The faces that have been loaded already.
+    loaded: ahash::HashSet<usize>,
+}
+
+impl SystemFonts {
+    /// Find an installed font that has the given character.
+    ///
+    /// Returns the name of the font, and the font data the first time that font is returned.
+    /// The answer is cached, so this is cheap to call again for the same character.
+    pub fn font_for_char(&mut self, chr: char) -> Option<(String, Option<FontData>)> {
+        let face_index = if let Some(face_index) = self.by_char.get(&chr) {
+            *face_index
+        } else {
+            let face_index = self.find_face(chr);
+            self.by_char.insert(chr, face_index);
+            face_index
+        }?;
+
+        let face = &self.faces.as_ref()?[face_index];
+        if self.loaded.contains(&face_index) {
+            return Some((face.name(), None));
+        }
+
+        match std::fs::read(&face.path) {
+            Ok(bytes) => {
+                #[cfg(feature = "log")]
+                log::debug!("Using system font {:?} for {chr:?}", face.path);
+                self.loaded.insert(face_index);
+                Some((face.name(), Some(FontData::from_owned(bytes))))
+            }
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                log::warn!("Failed to load system font {:?}: {_err}", face.path);
+                // Don't try this font again:
+                self.failed.insert(face_index);
+                self.by_char.clear();
+                self.by_script.retain(|_, index| *index != face_index);
+                None
+            }
+        }
+    }
+
+    fn find_face(&mut self, chr: char) -> Option<usize> {
+        crate::profile_function!();
+
+        let faces = self.faces.get_or_insert_with(system_faces);
+        let script = chr.script();
+
+        // Use the same font for the whole script, as long as it has the character:
+        if let Some(&face_index) = self.by_script.get(&script) {
+            if faces[face_index].covers(chr) {
+                return Some(face_index);
+            }
+        }
+
+        let face_index = faces
+            .iter()
+            .enumerate()
+            .filter(|(face_index, face)| !self.failed.contains(face_index) && face.covers(chr))
+            .max_by_key(|(_, face)| {
+                let script_coverage = face.scripts.get(&script).copied().unwrap_or_default();
+                // Reverse the path so that we pick the first one in case of a tie:
+                (
+                    script_coverage,
+                    face.is_regular,
+                    std::cmp::Reverse(&face.path),
+                )
+            })
+            .map(|(face_index, _)| face_index)?;
+
+        self.by_script.entry(script).or_insert(face_index);
+        Some(face_index)
+    }
+}
+
+/// All installed fonts, scanned the first time this is called.
+fn system_faces() -> Arc<Vec<SystemFace>> {
+    static FACES: OnceLock<Arc<Vec<SystemFace>>> = OnceLock::new();
+    FACES.get_or_init(|| Arc::new(scan_system_fonts())).clone()
+}
+
+fn scan_system_fonts() -> Vec<SystemFace> {
+    crate::profile_function!();
+
+    let mut paths = vec![];
+    let mut visited_dirs = Default::default();
+    for dir in font_dirs() {
+        find_font_files(&dir, &mut visited_dirs, &mut paths);
+    }
+    paths.sort();
+    paths.dedup();
+
+    let faces: Vec<SystemFace> = paths.into_iter().filter_map(SystemFace::load).collect();
+    #[cfg(feature = "log")]
+    log::debug!("Found {} system fonts", faces.len());
+    faces
+}
+
+/// Font files in `dir` and its subdirectories.
+///
+/// Symbolic links are followed, but each directory is only visited once, so link loops are fine.
+fn find_font_files(dir: &Path, visited_dirs: &mut ahash::HashSet<PathBuf>, out: &mut Vec<PathBuf>) {
+    let Ok(canonical_dir) = dir.canonicalize() else {
+        return;
+    };
+    if !visited_dirs.insert(canonical_dir) {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_font_files(&path, visited_dirs, out);
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                ["ttf", "otf", "ttc", "otc"]
+                    .iter()
+                    .any(|font_ext| ext.eq_ignore_ascii_case(font_ext))
+            })
+        {
+            out.push(path);
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Where to look for fonts.
+fn font_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = vec![];
+
+    if cfg!(target_os = "windows") {
+        if let Some(windir) = std::env::var_os("WINDIR") {
+            dirs.push(PathBuf::from(windir).join("Fonts"));
+        }
+        if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local_app_data).join("Microsoft/Windows/Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push("/System/Library/Fonts".into());
+        dirs.push("/Library/Fonts".into());
+        if let Some(home) = &home {
+            dirs.push(home.join("Library/Fonts"));
+        }
+    } else {
+        let xdg_data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+        let mut config = String::new();
+        let mut config_files = vec![PathBuf::from("/etc/fonts/fonts.conf")];
+        if let Ok(entries) = std::fs::read_dir("/etc/fonts/conf.d") {
+            let mut conf_d: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+            conf_d.sort();
+            config_files.extend(conf_d);
+        }
+        for path in config_files {
+            if let Ok(contents) = std::fs::read_to_string(path) {
+                config += &contents;
+            }
+        }
+
+        dirs.extend(fontconfig_dirs(
+            &config,
+            home.as_deref(),
+            xdg_data_home.as_deref(),
+        ));
+
+        if dirs.is_empty() {
+            // No fontconfig - use the usual places:
+            dirs.push("/usr/share/fonts".into());
+            dirs.push("/usr/local/share/fonts".into());
+            if let Some(xdg_data_home) = &xdg_data_home {
+                dirs.push(xdg_data_home.join("fonts"));
+            }
+            if let Some(home) = &home {
+                dirs.push(home.join(".fonts"));
+            }
+        }
+    }
+
+    dirs
+}
+
+/// The `<dir>` entries of a fontconfig configuration.
+///
+/// This is not a full XML parser, but good enough for the configuration files distributions ship.
+fn fontconfig_dirs(
+    config: &str,
+    home: Option<&Path>,
+    xdg_data_home: Option<&Path>,
+) -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    let mut rest = config;
+
+    while let Some(start) = rest.find("<dir") {
+        rest = &rest[start + "<dir".len()..];
+
+        // Skip `<directory>` etc:
+        if !rest.starts_with(['>', ' ', '\t', '\n']) {
+            continue;
+        }
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let attributes = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        let Some(end) = rest.find("</dir>") else {
+            break;
+        };
+        let dir = rest[..end].trim();
+        rest = &rest[end..];
+
+        let dir = if attributes.contains(r#"prefix="xdg""#) {
+            xdg_data_home.map(|xdg_data_home| xdg_data_home.join(dir))
+        } else if let Some(relative) = dir.strip_prefix("~/") {
+            home.map(|home| home.join(relative))
+        } else if dir.starts_with('/') {
+            Some(PathBuf::from(dir))
+        } else {
+            None // relative to the config file or the working directory - ignore
+        };
+        dirs.extend(dir);
+    }
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fontconfig_dirs() {
+        let config = r#"<?xml version="1.0"?>
+<!DOCTYPE fontconfig SYSTEM "urn:fontconfig:fonts.dtd">
+<fontconfig>
+	<description>Default configuration file</description>
+	<dir>/usr/share/fonts</dir>
+	<dir>/usr/local/share/fonts</dir>
+	<dir prefix="xdg">fonts</dir>
+	<!-- the following element will be removed in the future -->
+	<dir>~/.fonts</dir>
+	<dir prefix="cwd">fonts</dir>
+	<cachedir>/var/cache/fontconfig</cachedir>
+</fontconfig>
+"#;
+        let dirs = fontconfig_dirs(
+            config,
+            Some(Path::new("/home/user")),
+            Some(Path::new("/home/user/.local/share")),
+        );
+        assert_eq!(
+            dirs,
+            vec![
+                PathBuf::from("/usr/share/fonts"),
+                PathBuf::from("/usr/local/share/fonts"),
+                PathBuf::from("/home/user/.local/share/fonts"),
+                PathBuf::from("/home/user/.fonts"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_regular_style() {
+        assert!(is_regular_style(Path::new("/fonts/DejaVuSans.ttf")));
+        assert!(is_regular_style(Path::new(
+            "/fonts/NotoSansCJK-Regular.ttc"
+        )));
+        assert!(!is_regular_style(Path::new("/fonts/DejaVuSans-Bold.ttf")));
+        assert!(!is_regular_style(Path::new(
+            "/fonts/DejaVuSansCondensed.ttf"
+        )));
+    }
+
+    fn face(path: &str, coverage: Vec<(u32, u32)>) -> SystemFace {
+        let mut scripts: ahash::HashMap<Script, u32> = Default::default();
+        for &(first, last) in &coverage {
+            for chr in (first..=last).filter_map(char::from_u32) {
+                *scripts.entry(chr.script()).or_default() += 1;
+            }
+        }
+        SystemFace {
+            is_regular: is_regular_style(Path::new(path)),
+            path: path.into(),
+            coverage,
+            scripts,
+        }
+    }
+
+    #[test]
+    fn test_find_face_by_script_coverage() {
+        let mut system_fonts = SystemFonts {
+            faces: Some(Arc::new(vec![
+                face("/fonts/Symbols.ttf", vec![(0x416, 0x416), (0x2190, 0x21FF)]),
+                face("/fonts/Cyrillic-Bold.ttf", vec![(0x400, 0x4FF)]),
+                face("/fonts/Cyrillic.ttf", vec![(0x400, 0x4FF)]),
+                face("/fonts/Cyrillic-Extended.ttf", vec![(0x500, 0x52F)]),
+            ])),
+            ..Default::default()
+        };
+
+        // The font with the most Cyrillic wins, and regular is preferred over bold:
+        assert_eq!(system_fonts.find_face('Ж'), Some(2));
+        assert_eq!(system_fonts.find_face('→'), Some(0));
+        assert_eq!(system_fonts.find_face('中'), None);
+
+        // Characters the preferred font for the script doesn't have are found elsewhere:
+        assert_eq!(system_fonts.find_face('\u{0500}'), Some(3));
+        assert_eq!(system_fonts.by_script.get(&Script::Cyrillic), Some(&2));
+
+        // Fonts that failed to load are skipped:
+        system_fonts.failed.insert(2);
+        system_fonts.by_script.clear();
+        assert_eq!(system_fonts.find_face('Ж'), Some(1));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let face = SystemFace {
+            path: PathBuf::new(),
+            coverage: vec![(0x20, 0x7E), (0x400, 0x4FF)],
+            scripts: Default::default(),
+            is_regular: true,
+        };
+        assert!(face.covers('a'));
+        assert!(face.covers(' '));
+        assert!(face.covers('~'));
+        assert!(face.covers('Ж'));
+        assert!(!face.covers('\n'));
+        assert!(!face.covers('ä'));
+        assert!(!face.covers('中'));
+    }
+
+    #[test]
+    fn test_load_face() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fonts/Hack-Regular.ttf");
+        let face = SystemFace::load(path).unwrap();
+        assert!(face.covers('a'));
+        assert!(face.covers('Ж'));
+        assert!(!face.covers('中'));
+        assert!(face.scripts[&Script::Latin] > 26);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[allow(clippy::disallowed_methods)] // Ok for tests
+    fn test_find_font_files_in_link_loop() {
+        let dir = std::env::temp_dir().join(format!("epaint_font_dir_{}", std::process::id()));
+        let subdir = dir.join("subdir");
+        std::fs::create_dir_all(&subdir).unwrap();
+        std::fs::write(subdir.join("font.ttf"), []).unwrap();
+        let _ = std::os::unix::fs::symlink(&dir, subdir.join("loop"));
+
+        let mut paths = vec![];
+        find_font_files(&dir, &mut Default::default(), &mut paths);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(paths, vec![subdir.join("font.ttf")]);
+    }
+}
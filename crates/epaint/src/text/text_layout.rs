@@ -3,9 +3,12 @@ use std::sync::Arc;
 
 use emath::*;
 
-use crate::{stroke::PathStroke, text::font::Font, Color32, Mesh, Stroke, Vertex};
+use crate::{stroke::PathStroke, text::font::Font, Color32, Mesh, Stroke};
 
-use super::{FontsImpl, Galley, Glyph, LayoutJob, LayoutSection, Row, RowVisuals};
+use super::{
+    DecorationStyle, ElidedChars, ElisionPosition, FontsImpl, Galley, Glyph, LayoutJob,
+    LayoutSection, PageVisuals, Row, RowVisuals, TabAlign, TabStop,
+};
 
 // ----------------------------------------------------------------------------
 
@@ -50,6 +53,11 @@ struct Paragraph {
 
     pub glyphs: Vec<Glyph>,
 
+    /// The hyphen to draw if a row is broken after the glyph with the same index.
+    ///
+    /// Empty unless we are hyphenating this paragraph.
+    pub hyphens: Vec<Option<Glyph>>,
+
     /// In case of an empty paragraph ("\n"), use this as height.
     pub empty_paragraph_height: f32,
 }
@@ -60,6 +68,7 @@ impl Paragraph {
             cursor_x: 0.0,
             section_index_at_start,
             glyphs: vec![],
+            hyphens: vec![],
             empty_paragraph_height: 0.0,
         }
     }
@@ -91,37 +100,101 @@ pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
         layout_section(fonts, &job, section_index as u32, section, &mut paragraphs);
     }
 
+    if !job.tab_stops.is_empty() {
+        for paragraph in &mut paragraphs {
+            apply_tab_stops(&job.tab_stops, job.first_row_indent, &mut paragraph.glyphs);
+        }
+    }
+
+    #[cfg(feature = "hyphenation")]
+    if let Some(lang) = job
+        .wrap
+        .hyphenation_language
+        .and_then(hypher::Lang::from_iso)
+    {
+        if !job.wrap.break_anywhere {
+            for paragraph in &mut paragraphs {
+                add_hyphens(fonts, &job, lang, paragraph);
+            }
+        }
+    }
+
     let point_scale = PointScale::new(fonts.pixels_per_point());
 
     let mut elided = false;
-    let mut rows = rows_from_paragraphs(paragraphs, &job, &mut elided);
-    if elided {
-        if let Some(last_row) = rows.last_mut() {
-            replace_last_glyph_with_overflow_character(fonts, &job, last_row);
+    let mut rows = if let Some(row) = elide_start_or_middle(fonts, &job, &paragraphs) {
+        elided = true;
+        vec![row]
+    } else {
+        let mut rows = rows_from_paragraphs(paragraphs, &job, &mut elided);
+        if elided {
+            if let Some(last_row) = rows.last_mut() {
+                replace_last_glyph_with_overflow_character(fonts, &job, last_row);
+            }
         }
-    }
+        rows
+    };
 
     let justify = job.justify && job.wrap.max_width.is_finite();
 
     if justify || job.halign != Align::LEFT {
         let num_rows = rows.len();
+        let mut starts_paragraph = true;
         for (i, row) in rows.iter_mut().enumerate() {
             let is_last_row = i + 1 == num_rows;
             let justify_row = justify && !row.ends_with_newline && !is_last_row;
+            let indent = if starts_paragraph {
+                job.first_row_indent
+            } else {
+                job.hanging_indent
+            };
+            starts_paragraph = row.ends_with_newline;
             halign_and_justify_row(
                 point_scale,
                 row,
                 job.halign,
                 job.wrap.max_width,
+                indent,
                 justify_row,
             );
         }
     }
 
+    select_subpixel_glyphs(fonts, point_scale, &job, &mut rows);
+
     // Calculate the Y positions and tessellate the text:
     galley_from_rows(point_scale, job, rows, elided)
 }
 
+/// Now that we know where each glyph ends up,
+/// pick the version of it that is rasterized at the right subpixel offset.
+fn select_subpixel_glyphs(
+    fonts: &mut FontsImpl,
+    point_scale: PointScale,
+    job: &LayoutJob,
+    rows: &mut [Row],
+) {
+    let mut section_uses_subpixels = vec![None; job.sections.len()];
+
+    for row in rows {
+        for glyph in row.glyphs.iter_mut().chain(&mut row.hyphen) {
+            if glyph.uv_rect.is_nothing() || glyph.uv_rect.is_color {
+                continue;
+            }
+
+            let format = &job.sections[glyph.section_index as usize].format;
+            let font = fonts.font_with_style(&format.font_id, format.font_style());
+            let uses_subpixels = *section_uses_subpixels[glyph.section_index as usize]
+                .get_or_insert_with(|| font.has_subpixel_positions());
+            if uses_subpixels {
+                let x_in_pixels =
+                    (glyph.pos.x + glyph.uv_rect.offset.x) * point_scale.pixels_per_point();
+                glyph.uv_rect = font.subpixel_uv_rect(&glyph.uv_rect, x_in_pixels);
+            }
+        }
+    }
+}
+
 // Ignores the Y coordinate.
 fn layout_section(
     fonts: &mut FontsImpl,
@@ -135,7 +208,13 @@ fn layout_section(
         byte_range,
         format,
     } = section;
-    let font = fonts.font(&format.font_id);
+    #[cfg(feature = "system_fonts")]
+    fonts.add_system_fallbacks(
+        &format.font_id,
+        format.font_style(),
+        &job.text[byte_range.clone()],
+    );
+    let font = fonts.font_with_style(&format.font_id, format.font_style());
     let line_height = section
         .format
         .line_height
@@ -151,36 +230,306 @@ fn layout_section(
 
     let mut last_glyph_id = None;
 
-    for chr in job.text[byte_range.clone()].chars() {
+    let text = &job.text[byte_range.clone()];
+    let mut line_start = 0;
+    for (i, chr) in text.char_indices() {
         if job.break_on_newline && chr == '\n' {
+            layout_text(
+                font,
+                &text[line_start..i],
+                section_index,
+                line_height,
+                extra_letter_spacing,
+                paragraph,
+                &mut last_glyph_id,
+            );
+            line_start = i + chr.len_utf8();
+
             out_paragraphs.push(Paragraph::from_section_index(section_index));
             paragraph = out_paragraphs.last_mut().unwrap();
             paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
-        } else {
-            let (font_impl, glyph_info) = font.font_impl_and_glyph_info(chr);
-            if let Some(font_impl) = font_impl {
-                if let Some(last_glyph_id) = last_glyph_id {
-                    paragraph.cursor_x += font_impl.pair_kerning(last_glyph_id, glyph_info.id);
-                    paragraph.cursor_x += extra_letter_spacing;
-                }
+        }
+    }
+    layout_text(
+        font,
+        &text[line_start..],
+        section_index,
+        line_height,
+        extra_letter_spacing,
+        paragraph,
+        &mut last_glyph_id,
+    );
+}
+
+/// Layout text without any newlines, one `char` at a time.
+#[cfg(not(feature = "shaping"))]
+fn layout_text(
+    font: &mut Font,
+    text: &str,
+    section_index: u32,
+    line_height: f32,
+    extra_letter_spacing: f32,
+    paragraph: &mut Paragraph,
+    last_glyph_id: &mut Option<ab_glyph::GlyphId>,
+) {
+    for chr in text.chars() {
+        layout_char(
+            font,
+            chr,
+            section_index,
+            line_height,
+            extra_letter_spacing,
+            paragraph,
+            last_glyph_id,
+        );
+    }
+}
+
+/// Layout text without any newlines, shaping runs of the same font and script together.
+#[cfg(feature = "shaping")]
+fn layout_text(
+    font: &mut Font,
+    text: &str,
+    section_index: u32,
+    line_height: f32,
+    extra_letter_spacing: f32,
+    paragraph: &mut Paragraph,
+    last_glyph_id: &mut Option<ab_glyph::GlyphId>,
+) {
+    use super::shaping;
+
+    for run in shaping::itemize(font, text) {
+        let run_text = &text[run.byte_range];
+
+        let font_impl = run.font_index.and_then(|index| font.font_impl(index));
+        let subpixel_positions =
+            font_impl.is_some_and(|font_impl| font_impl.has_subpixel_positions());
+        let clusters = font_impl.and_then(|font_impl| {
+            Some((
+                font_impl.ascent(),
+                shaping::shape(font_impl, run_text, run.script)?,
+            ))
+        });
+
+        let Some((ascent, clusters)) = clusters else {
+            for chr in run_text.chars() {
+                layout_char(
+                    font,
+                    chr,
+                    section_index,
+                    line_height,
+                    extra_letter_spacing,
+                    paragraph,
+                    last_glyph_id,
+                );
             }
+            continue;
+        };
 
-            paragraph.glyphs.push(Glyph {
-                chr,
-                pos: pos2(paragraph.cursor_x, f32::NAN),
-                size: vec2(glyph_info.advance_width, line_height),
-                ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
-                uv_rect: glyph_info.uv_rect,
-                section_index,
-            });
+        let mut chars = run_text.chars();
+        for cluster in clusters {
+            if last_glyph_id.is_some() {
+                // Kerning is already part of the shaping.
+                paragraph.cursor_x += extra_letter_spacing;
+            }
+
+            // Split the cluster evenly between its characters, so we can put a cursor inside a ligature:
+            let advance_width = cluster.advance_width / cluster.num_chars as f32;
+            for (i, chr) in chars.by_ref().take(cluster.num_chars).enumerate() {
+                paragraph.glyphs.push(Glyph {
+                    chr,
+                    pos: pos2(paragraph.cursor_x + i as f32 * advance_width, f32::NAN),
+                    size: vec2(advance_width, line_height),
+                    ascent,
+                    uv_rect: if i == 0 {
+                        cluster.uv_rect
+                    } else {
+                        Default::default()
+                    },
+                    section_index,
+                });
+            }
+
+            paragraph.cursor_x += cluster.advance_width;
+            if !subpixel_positions {
+                paragraph.cursor_x = font.round_to_pixel(paragraph.cursor_x);
+            }
+            *last_glyph_id = Some(cluster.last_glyph_id());
+        }
+    }
+}
+
+fn layout_char(
+    font: &mut Font,
+    chr: char,
+    section_index: u32,
+    line_height: f32,
+    extra_letter_spacing: f32,
+    paragraph: &mut Paragraph,
+    last_glyph_id: &mut Option<ab_glyph::GlyphId>,
+) {
+    let (font_impl, glyph_info) = font.font_impl_and_glyph_info(chr);
+    if let Some(font_impl) = font_impl {
+        if let Some(last_glyph_id) = *last_glyph_id {
+            paragraph.cursor_x += font_impl.pair_kerning(last_glyph_id, glyph_info.id);
+            paragraph.cursor_x += extra_letter_spacing;
+        }
+    }
+
+    paragraph.glyphs.push(Glyph {
+        chr,
+        pos: pos2(paragraph.cursor_x, f32::NAN),
+        size: vec2(glyph_info.advance_width, line_height),
+        ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
+        uv_rect: glyph_info.uv_rect,
+        section_index,
+    });
+
+    paragraph.cursor_x += glyph_info.advance_width;
+    if !font_impl.is_some_and(|font_impl| font_impl.has_subpixel_positions()) {
+        paragraph.cursor_x = font.round_to_pixel(paragraph.cursor_x);
+    }
+    *last_glyph_id = Some(glyph_info.id);
+}
+
+/// Change the width of each `\t` so that the text after it lines up with the next [`TabStop`].
+///
+/// The glyph positions are relative to the start of the paragraph,
+/// and `indent` is where its first row starts.
+///
+/// Ignores the Y coordinate.
+fn apply_tab_stops(tab_stops: &[TabStop], indent: f32, glyphs: &mut [Glyph]) {
+    for i in 0..glyphs.len() {
+        if glyphs[i].chr != '\t' {
+            continue;
+        }
+
+        let tab = glyphs[i];
+        let Some(stop) = tab_stops
+            .iter()
+            .filter(|stop| tab.pos.x + indent < stop.x)
+            .min_by(|a, b| a.x.total_cmp(&b.x))
+        else {
+            continue; // Past the last stop, so keep the default width.
+        };
+
+        // The text after the tab, up to the next tab:
+        let segment_end = glyphs[i + 1..]
+            .iter()
+            .position(|glyph| glyph.chr == '\t')
+            .map_or(glyphs.len(), |n| i + 1 + n);
+        let segment = &glyphs[i + 1..segment_end];
+        let start = segment.first().map_or(tab.max_x(), |glyph| glyph.pos.x);
+        let end = segment.last().map_or(tab.max_x(), |glyph| glyph.max_x());
+
+        // Which x of the segment should end up at the stop:
+        let anchor = match stop.align {
+            TabAlign::Left => start,
+            TabAlign::Right => end,
+            TabAlign::Decimal => segment
+                .iter()
+                .find(|glyph| glyph.chr == '.')
+                .map_or(end, |glyph| glyph.pos.x),
+        };
 
-            paragraph.cursor_x += glyph_info.advance_width;
-            paragraph.cursor_x = font.round_to_pixel(paragraph.cursor_x);
-            last_glyph_id = Some(glyph_info.id);
+        // A tab never moves the text to the left of where the tab starts:
+        let shift = (stop.x - indent - anchor).at_least(-tab.size.x);
+        glyphs[i].size.x += shift;
+        for glyph in &mut glyphs[i + 1..] {
+            glyph.pos.x += shift;
         }
     }
 }
 
+/// Find where the words of a paragraph that doesn't fit on one row can be split,
+/// and prepare the hyphens to draw there.
+///
+/// Ignores the Y coordinate.
+#[cfg(feature = "hyphenation")]
+fn add_hyphens(
+    fonts: &mut FontsImpl,
+    job: &LayoutJob,
+    lang: hypher::Lang,
+    paragraph: &mut Paragraph,
+) {
+    let Some(last_glyph) = paragraph.glyphs.last() else {
+        return;
+    };
+    if last_glyph.max_x() + job.first_row_indent <= job.wrap.max_width {
+        return; // The paragraph will fit on one row
+    }
+
+    let glyphs = &paragraph.glyphs;
+    let mut hyphens = vec![None; glyphs.len()];
+
+    let mut word_start = 0;
+    while word_start < glyphs.len() {
+        let word_len = glyphs[word_start..]
+            .iter()
+            .take_while(|glyph| glyph.chr.is_alphabetic())
+            .count();
+        if word_len == 0 {
+            word_start += 1;
+            continue;
+        }
+
+        let word_end = word_start + word_len;
+        let word: String = glyphs[word_start..word_end]
+            .iter()
+            .map(|glyph| glyph.chr)
+            .collect();
+
+        let mut syllable_end = word_start;
+        for syllable in hypher::hyphenate(&word, lang) {
+            syllable_end += syllable.chars().count();
+            if syllable_end < word_end {
+                let before = &glyphs[syllable_end - 1];
+                let format = &job.sections[before.section_index as usize].format;
+                let font = fonts.font_with_style(&format.font_id, format.font_style());
+                let (font_impl, glyph_info) = font.font_impl_and_glyph_info('-');
+                hyphens[syllable_end - 1] = Some(Glyph {
+                    chr: '-',
+                    pos: pos2(before.max_x() + format.extra_letter_spacing, f32::NAN),
+                    size: vec2(glyph_info.advance_width, before.size.y),
+                    ascent: font_impl.map_or(0.0, |font| font.ascent()),
+                    uv_rect: glyph_info.uv_rect,
+                    section_index: before.section_index,
+                });
+            }
+        }
+
+        word_start = word_end;
+    }
+
+    paragraph.hyphens = hyphens;
+}
+
+/// For each glyph, whether the [Unicode line breaking algorithm](https://www.unicode.org/reports/tr14/)
+/// allows a row break right after it.
+///
+/// This is where we prefer to break rows: after spaces, dashes and zero-width spaces,
+/// between ideographs, etc. Never at a non-breaking space.
+fn break_opportunities(glyphs: &[Glyph]) -> Vec<bool> {
+    let text: String = glyphs.iter().map(|glyph| glyph.chr).collect();
+    let mut can_break_after = vec![false; glyphs.len()];
+
+    let mut num_chars = 0;
+    let mut byte_index = 0;
+    for (break_index, _) in unicode_linebreak::linebreaks(&text) {
+        // `break_index` is the byte index of the character after the break.
+        // `\n` has already been handled, so we treat mandatory breaks like allowed ones.
+        while byte_index < break_index {
+            byte_index += glyphs[num_chars].chr.len_utf8();
+            num_chars += 1;
+        }
+        if let Some(last_index) = num_chars.checked_sub(1) {
+            can_break_after[last_index] = true;
+        }
+    }
+
+    can_break_after
+}
+
 /// We ignore y at this stage
 fn rect_from_x_range(x_range: RangeInclusive<f32>) -> Rect {
     Rect::from_x_y_ranges(x_range, 0.0..=0.0)
@@ -208,21 +557,32 @@ fn rows_from_paragraphs(
             rows.push(Row {
                 section_index_at_start: paragraph.section_index_at_start,
                 glyphs: vec![],
+                hyphen: None,
+                elided: None,
                 visuals: Default::default(),
                 rect: Rect::from_min_size(
-                    pos2(paragraph.cursor_x, 0.0),
+                    pos2(paragraph.cursor_x + job.first_row_indent, 0.0),
                     vec2(0.0, paragraph.empty_paragraph_height),
                 ),
                 ends_with_newline: !is_last_paragraph,
             });
         } else {
-            let paragraph_max_x = paragraph.glyphs.last().unwrap().max_x();
+            let indent = job.first_row_indent;
+            let paragraph_max_x = paragraph.glyphs.last().unwrap().max_x() + indent;
             if paragraph_max_x <= job.wrap.max_width {
                 // Early-out optimization: the whole paragraph fits on one row.
-                let paragraph_min_x = paragraph.glyphs[0].pos.x;
+                let mut glyphs = paragraph.glyphs;
+                if indent != 0.0 {
+                    for glyph in &mut glyphs {
+                        glyph.pos.x += indent;
+                    }
+                }
+                let paragraph_min_x = glyphs[0].pos.x;
                 rows.push(Row {
                     section_index_at_start: paragraph.section_index_at_start,
-                    glyphs: paragraph.glyphs,
+                    glyphs,
+                    hyphen: None,
+                    elided: None,
                     visuals: Default::default(),
                     rect: rect_from_x_range(paragraph_min_x..=paragraph_max_x),
                     ends_with_newline: !is_last_paragraph,
@@ -240,18 +600,22 @@ fn rows_from_paragraphs(
 fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, elided: &mut bool) {
     // Keeps track of good places to insert row break if we exceed `wrap_width`.
     let mut row_break_candidates = RowBreakCandidates::default();
+    let can_break_after = break_opportunities(&paragraph.glyphs);
 
     let mut first_row_indentation = paragraph.glyphs[0].pos.x;
     let mut row_start_x = 0.0;
     let mut row_start_idx = 0;
 
-    for i in 0..paragraph.glyphs.len() {
+    // The first row of the paragraph is indented differently from the rest:
+    let mut row_indent = job.first_row_indent;
+
+    for (i, &is_break_opportunity) in can_break_after.iter().enumerate() {
         if job.wrap.max_rows <= out_rows.len() {
             *elided = true;
             break;
         }
 
-        let potential_row_width = paragraph.glyphs[i].max_x() - row_start_x;
+        let potential_row_width = paragraph.glyphs[i].max_x() - row_start_x + row_indent;
 
         if job.wrap.max_width < potential_row_width {
             // Row break:
@@ -261,33 +625,51 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
             {
                 // Allow the first row to be completely empty, because we know there will be more space on the next row:
                 // TODO(emilk): this records the height of this first row as zero, though that is probably fine since first_row_indentation usually comes with a first_row_min_height.
+                let x = first_row_indentation + row_indent;
                 out_rows.push(Row {
                     section_index_at_start: paragraph.section_index_at_start,
                     glyphs: vec![],
+                    hyphen: None,
+                    elided: None,
                     visuals: Default::default(),
-                    rect: rect_from_x_range(first_row_indentation..=first_row_indentation),
+                    rect: rect_from_x_range(x..=x),
                     ends_with_newline: false,
                 });
                 row_start_x += first_row_indentation;
                 first_row_indentation = 0.0;
+                row_indent = job.hanging_indent;
             } else if let Some(last_kept_index) = row_break_candidates.get(job.wrap.break_anywhere)
             {
                 let glyphs: Vec<Glyph> = paragraph.glyphs[row_start_idx..=last_kept_index]
                     .iter()
                     .copied()
                     .map(|mut glyph| {
-                        glyph.pos.x -= row_start_x;
+                        glyph.pos.x += row_indent - row_start_x;
                         glyph
                     })
                     .collect();
 
+                // Are we breaking in the middle of a word?
+                let hyphen = if row_break_candidates.hyphen == Some(last_kept_index) {
+                    paragraph.hyphens[last_kept_index].map(|mut hyphen| {
+                        hyphen.pos.x += row_indent - row_start_x;
+                        hyphen
+                    })
+                } else {
+                    None
+                };
+
                 let section_index_at_start = glyphs[0].section_index;
                 let paragraph_min_x = glyphs[0].pos.x;
-                let paragraph_max_x = glyphs.last().unwrap().max_x();
+                let paragraph_max_x = hyphen
+                    .as_ref()
+                    .map_or_else(|| glyphs.last().unwrap().max_x(), |hyphen| hyphen.max_x());
 
                 out_rows.push(Row {
                     section_index_at_start,
                     glyphs,
+                    hyphen,
+                    elided: None,
                     visuals: Default::default(),
                     rect: rect_from_x_range(paragraph_min_x..=paragraph_max_x),
                     ends_with_newline: false,
@@ -296,13 +678,25 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
                 // Start a new row:
                 row_start_idx = last_kept_index + 1;
                 row_start_x = paragraph.glyphs[row_start_idx].pos.x;
+                row_indent = job.hanging_indent;
                 row_break_candidates.forget_before_idx(row_start_idx);
             } else {
                 // Found no place to break, so we have to overrun wrap_width.
             }
         }
 
-        row_break_candidates.add(i, &paragraph.glyphs[i..]);
+        let hyphen_fits = paragraph
+            .hyphens
+            .get(i)
+            .copied()
+            .flatten()
+            .is_some_and(|hyphen| hyphen.max_x() - row_start_x + row_indent <= job.wrap.max_width);
+        row_break_candidates.add(
+            i,
+            paragraph.glyphs[i].chr,
+            is_break_opportunity,
+            hyphen_fits,
+        );
     }
 
     if row_start_idx < paragraph.glyphs.len() {
@@ -315,7 +709,7 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
                 .iter()
                 .copied()
                 .map(|mut glyph| {
-                    glyph.pos.x -= row_start_x;
+                    glyph.pos.x += row_indent - row_start_x;
                     glyph
                 })
                 .collect();
@@ -327,6 +721,8 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
             out_rows.push(Row {
                 section_index_at_start,
                 glyphs,
+                hyphen: None,
+                elided: None,
                 visuals: Default::default(),
                 rect: rect_from_x_range(paragraph_min_x..=paragraph_max_x),
                 ends_with_newline: false,
@@ -358,6 +754,9 @@ fn replace_last_glyph_with_overflow_character(
             .unwrap_or_else(|| font.row_height())
     }
 
+    // The overflow character replaces any hyphen:
+    row.hyphen = None;
+
     let Some(overflow_character) = job.wrap.overflow_character else {
         return;
     };
@@ -366,7 +765,7 @@ fn replace_last_glyph_with_overflow_character(
     if let Some(last_glyph) = row.glyphs.last() {
         let section_index = last_glyph.section_index;
         let section = &job.sections[section_index as usize];
-        let font = fonts.font(&section.format.font_id);
+        let font = fonts.font_with_style(&section.format.font_id, section.format.font_style());
         let line_height = row_height(section, font);
 
         let (_, last_glyph_info) = font.font_impl_and_glyph_info(last_glyph.chr);
@@ -394,7 +793,7 @@ fn replace_last_glyph_with_overflow_character(
     } else {
         let section_index = row.section_index_at_start;
         let section = &job.sections[section_index as usize];
-        let font = fonts.font(&section.format.font_id);
+        let font = fonts.font_with_style(&section.format.font_id, section.format.font_style());
         let line_height = row_height(section, font);
 
         let x = 0.0; // TODO(emilk): heed paragraph leading_space 😬
@@ -418,6 +817,33 @@ fn replace_last_glyph_with_overflow_character(
     // We didn't fit it. Remove it again…
     row.glyphs.pop();
 
+    if !job.wrap.break_anywhere {
+        // Rather than cutting a word in half, put the overflow character where a row break is allowed:
+        let can_break_after = break_opportunities(&row.glyphs);
+        let min_x = row.glyphs[0].pos.x;
+        let replaced_index = (0..row.glyphs.len() - 1)
+            .rev()
+            .filter(|&i| can_break_after[i])
+            .map(|i| {
+                // Spaces are replaced by the overflow character, anything else is kept:
+                if row.glyphs[i].chr.is_whitespace() {
+                    i
+                } else {
+                    i + 1
+                }
+            })
+            .find(|&i| {
+                let glyph = &row.glyphs[i];
+                let format = &job.sections[glyph.section_index as usize].format;
+                let font = fonts.font_with_style(&format.font_id, format.font_style());
+                let (_, glyph_info) = font.font_impl_and_glyph_info(overflow_character);
+                glyph.pos.x + glyph_info.advance_width - min_x <= job.wrap.max_width
+            });
+        if let Some(replaced_index) = replaced_index {
+            row.glyphs.truncate(replaced_index + 1);
+        }
+    }
+
     // …then go into a loop where we replace the last character with the overflow character
     // until we fit within the max_width:
 
@@ -432,7 +858,7 @@ fn replace_last_glyph_with_overflow_character(
 
         let section = &job.sections[last_glyph.section_index as usize];
         let extra_letter_spacing = section.format.extra_letter_spacing;
-        let font = fonts.font(&section.format.font_id);
+        let font = fonts.font_with_style(&section.format.font_id, section.format.font_style());
         let line_height = row_height(section, font);
 
         if let Some(prev_glyph) = prev_glyph {
@@ -475,6 +901,109 @@ fn replace_last_glyph_with_overflow_character(
     }
 }
 
+/// Elides the start or the middle of text that doesn't fit on a single row,
+/// as set by [`super::TextWrapping::elision_position`].
+///
+/// Returns `None` if the text fits, or if it should be elided at the end as usual.
+///
+/// Called before we have any Y coordinates.
+fn elide_start_or_middle(
+    fonts: &mut FontsImpl,
+    job: &LayoutJob,
+    paragraphs: &[Paragraph],
+) -> Option<Row> {
+    let elision_position = job.wrap.elision_position;
+    if elision_position == ElisionPosition::End || job.wrap.max_rows != 1 {
+        return None;
+    }
+    let [paragraph] = paragraphs else {
+        return None; // Only the first paragraph fits on the row, so elide at the end.
+    };
+    let glyphs = &paragraph.glyphs;
+    let (first_glyph, last_glyph) = (glyphs.first()?, glyphs.last()?);
+    let start_x = first_glyph.pos.x + job.first_row_indent;
+    if last_glyph.max_x() + job.first_row_indent <= job.wrap.max_width {
+        return None; // It all fits
+    }
+
+    // The overflow character uses the format of the start of the text:
+    let overflow_glyph = job.wrap.overflow_character.map(|chr| {
+        let section_index = first_glyph.section_index;
+        let format = &job.sections[section_index as usize].format;
+        let font = fonts.font_with_style(&format.font_id, format.font_style());
+        let line_height = format.line_height.unwrap_or_else(|| font.row_height());
+        let (font_impl, glyph_info) = font.font_impl_and_glyph_info(chr);
+        Glyph {
+            chr,
+            pos: pos2(f32::NAN, f32::NAN),
+            size: vec2(glyph_info.advance_width, line_height),
+            ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
+            uv_rect: glyph_info.uv_rect,
+            section_index,
+        }
+    });
+    let overflow_width = overflow_glyph.map_or(0.0, |glyph| glyph.size.x);
+    let budget = (job.wrap.max_width - start_x - overflow_width).at_least(0.0);
+
+    // How many glyphs to keep at the start:
+    let num_start_glyphs = match elision_position {
+        ElisionPosition::Start | ElisionPosition::End => 0,
+        ElisionPosition::Middle => glyphs
+            .iter()
+            .take_while(|glyph| glyph.max_x() - first_glyph.pos.x <= budget / 2.0)
+            .count(),
+    };
+    let start_width = num_start_glyphs
+        .checked_sub(1)
+        .map_or(0.0, |last| glyphs[last].max_x() - first_glyph.pos.x);
+
+    // The rest of the budget goes to the end:
+    let end_start_index = (num_start_glyphs..glyphs.len())
+        .find(|&i| last_glyph.max_x() - glyphs[i].pos.x <= budget - start_width)
+        .unwrap_or(glyphs.len());
+
+    let mut row_glyphs = Vec::with_capacity(num_start_glyphs + 1 + glyphs.len() - end_start_index);
+    let mut x = start_x;
+    for glyph in &glyphs[..num_start_glyphs] {
+        let mut glyph = *glyph;
+        glyph.pos.x += start_x - first_glyph.pos.x;
+        x = glyph.max_x();
+        row_glyphs.push(glyph);
+    }
+    let elided = ElidedChars {
+        glyph_range: num_start_glyphs..num_start_glyphs + overflow_glyph.is_some() as usize,
+        char_range: num_start_glyphs..end_start_index,
+    };
+    if let Some(mut overflow_glyph) = overflow_glyph {
+        overflow_glyph.pos.x = x;
+        x = overflow_glyph.max_x();
+        row_glyphs.push(overflow_glyph);
+    }
+    if let Some(end_start_glyph) = glyphs.get(end_start_index) {
+        let shift = x - end_start_glyph.pos.x;
+        for glyph in &glyphs[end_start_index..] {
+            let mut glyph = *glyph;
+            glyph.pos.x += shift;
+            x = glyph.max_x();
+            row_glyphs.push(glyph);
+        }
+    }
+
+    Some(Row {
+        section_index_at_start: row_glyphs
+            .first()
+            .map_or(paragraph.section_index_at_start, |glyph| {
+                glyph.section_index
+            }),
+        glyphs: row_glyphs,
+        hyphen: None,
+        elided: Some(elided),
+        visuals: Default::default(),
+        rect: rect_from_x_range(start_x..=x),
+        ends_with_newline: false,
+    })
+}
+
 /// Horizontally aligned the text on a row.
 ///
 /// /// Ignores the Y coordinate.
@@ -483,12 +1012,17 @@ fn halign_and_justify_row(
     row: &mut Row,
     halign: Align,
     wrap_width: f32,
+    indent: f32,
     justify: bool,
 ) {
     if row.glyphs.is_empty() {
         return;
     }
 
+    // The hyphen moves along with the glyphs, and takes up space on the row:
+    let has_hyphen = row.hyphen.is_some();
+    row.glyphs.extend(row.hyphen.take());
+
     let num_leading_spaces = row
         .glyphs
         .iter()
@@ -516,13 +1050,13 @@ fn halign_and_justify_row(
     let original_width = original_max_x - original_min_x;
 
     let target_width = if justify && num_glyphs_in_range > 1 {
-        wrap_width
+        wrap_width - indent
     } else {
         original_width
     };
 
     let (target_min_x, target_max_x) = match halign {
-        Align::LEFT => (0.0, target_width),
+        Align::LEFT => (indent, indent + target_width),
         Align::Center => (-target_width / 2.0, target_width / 2.0),
         Align::RIGHT => (-target_width, 0.0),
     };
@@ -563,6 +1097,10 @@ fn halign_and_justify_row(
         }
     }
 
+    if has_hyphen {
+        row.hyphen = row.glyphs.pop();
+    }
+
     // Note we ignore the leading/trailing whitespace here!
     row.rect.min.x = target_min_x;
     row.rect.max.x = target_max_x;
@@ -579,7 +1117,14 @@ fn galley_from_rows(
     let mut cursor_y = 0.0;
     let mut min_x: f32 = 0.0;
     let mut max_x: f32 = 0.0;
+    let paragraph_spacing = job.paragraph_spacing_after + job.paragraph_spacing_before;
+    let mut starts_paragraph = false; // No spacing above the first paragraph
     for row in &mut rows {
+        if starts_paragraph && paragraph_spacing != 0.0 {
+            cursor_y = point_scale.round_to_pixel(cursor_y + paragraph_spacing);
+        }
+        starts_paragraph = row.ends_with_newline;
+
         let mut line_height = first_row_min_height.max(row.rect.height());
         let mut row_ascent = 0.0f32;
         first_row_min_height = 0.0;
@@ -596,7 +1141,7 @@ fn galley_from_rows(
         line_height = point_scale.round_to_pixel(line_height);
 
         // Now positions each glyph:
-        for glyph in &mut row.glyphs {
+        for glyph in row.glyphs.iter_mut().chain(&mut row.hyphen) {
             let format = &job.sections[glyph.section_index as usize].format;
 
             let align_offset = match format.valign {
@@ -681,33 +1226,56 @@ fn tessellate_row(
     }
 
     let glyph_vertex_start = mesh.vertices.len();
-    tessellate_glyphs(point_scale, job, row, &mut mesh);
+    tessellate_glyphs(point_scale, job, row, &mut mesh, 0, false);
     let glyph_vertex_end = mesh.vertices.len();
 
+    // Color glyphs (emojis) go outside of `glyph_vertex_range`, so they are not tinted by `override_text_color`:
+    tessellate_glyphs(point_scale, job, row, &mut mesh, 0, true);
+
     if format_summary.any_underline {
         add_row_hline(point_scale, row, &mut mesh, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
-            let stroke = format.underline;
             let y = glyph.logical_rect().bottom();
-            (stroke, y)
+            (format.underline, format.underline_style, y)
         });
     }
 
     if format_summary.any_strikethrough {
         add_row_hline(point_scale, row, &mut mesh, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
-            let stroke = format.strikethrough;
             let y = glyph.logical_rect().center().y;
-            (stroke, y)
+            (format.strikethrough, format.strikethrough_style, y)
         });
     }
 
-    let mesh_bounds = mesh.calc_bounds();
+    let mut mesh_bounds = mesh.calc_bounds();
+
+    let num_pages = row
+        .glyphs_with_hyphen()
+        .map(|glyph| glyph.uv_rect.page as usize + 1)
+        .max()
+        .unwrap_or_default();
+    let mut other_pages = vec![];
+    for page in 1..num_pages {
+        let mut mesh = Mesh::default();
+        tessellate_glyphs(point_scale, job, row, &mut mesh, page, false);
+        let glyph_vertex_range = 0..mesh.vertices.len();
+        tessellate_glyphs(point_scale, job, row, &mut mesh, page, true);
+        if !mesh.is_empty() {
+            mesh_bounds = mesh_bounds.union(mesh.calc_bounds());
+            other_pages.push(PageVisuals {
+                page,
+                mesh,
+                glyph_vertex_range,
+            });
+        }
+    }
 
     RowVisuals {
         mesh,
         mesh_bounds,
         glyph_vertex_range: glyph_vertex_start..glyph_vertex_end,
+        other_pages,
     }
 }
 
@@ -729,7 +1297,7 @@ fn add_row_backgrounds(job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
     let mut run_start = None;
     let mut last_rect = Rect::NAN;
 
-    for glyph in &row.glyphs {
+    for glyph in row.glyphs_with_hyphen() {
         let format = &job.sections[glyph.section_index as usize].format;
         let color = format.background;
         let rect = glyph.logical_rect();
@@ -756,10 +1324,22 @@ fn add_row_backgrounds(job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
     end_run(run_start.take(), last_rect.right());
 }
 
-fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
-    for glyph in &row.glyphs {
+/// Only tessellates the glyphs on the given page of the font atlas,
+/// whose [`super::UvRect::is_color`] matches `color_glyphs`.
+fn tessellate_glyphs(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    row: &Row,
+    mesh: &mut Mesh,
+    page: usize,
+    color_glyphs: bool,
+) {
+    for glyph in row.glyphs_with_hyphen() {
         let uv_rect = glyph.uv_rect;
-        if !uv_rect.is_nothing() {
+        if !uv_rect.is_nothing()
+            && uv_rect.page as usize == page
+            && uv_rect.is_color == color_glyphs
+        {
             let mut left_top = glyph.pos + uv_rect.offset;
             left_top.x = point_scale.round_to_pixel(left_top.x);
             left_top.y = point_scale.round_to_pixel(left_top.y);
@@ -772,72 +1352,55 @@ fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh:
 
             let format = &job.sections[glyph.section_index as usize].format;
 
-            let color = format.color;
-
-            if format.italics {
-                let idx = mesh.vertices.len() as u32;
-                mesh.add_triangle(idx, idx + 1, idx + 2);
-                mesh.add_triangle(idx + 2, idx + 1, idx + 3);
-
-                let top_offset = rect.height() * 0.25 * Vec2::X;
-
-                mesh.vertices.push(Vertex {
-                    pos: rect.left_top() + top_offset,
-                    uv: uv.left_top(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.right_top() + top_offset,
-                    uv: uv.right_top(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.left_bottom(),
-                    uv: uv.left_bottom(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.right_bottom(),
-                    uv: uv.right_bottom(),
-                    color,
-                });
+            let color = if uv_rect.is_color {
+                // Keep the colors of the glyph, but respect the opacity of the text:
+                Color32::from_white_alpha(format.color.a())
             } else {
-                mesh.add_rect_with_uv(rect, uv, color);
-            }
+                format.color
+            };
+
+            // Italics are already slanted, by the font face or by faking it when rasterizing.
+            mesh.add_rect_with_uv(rect, uv, color);
         }
     }
 }
 
-/// Add a horizontal line over a row of glyphs with a stroke and y decided by a callback.
+/// Add a horizontal line over a row of glyphs with a stroke, style and y decided by a callback.
 fn add_row_hline(
     point_scale: PointScale,
     row: &Row,
     mesh: &mut Mesh,
-    stroke_and_y: impl Fn(&Glyph) -> (Stroke, f32),
+    stroke_style_and_y: impl Fn(&Glyph) -> (Stroke, DecorationStyle, f32),
 ) {
-    let mut end_line = |start: Option<(Stroke, Pos2)>, stop_x: f32| {
-        if let Some((stroke, start)) = start {
-            add_hline(point_scale, [start, pos2(stop_x, start.y)], stroke, mesh);
+    let mut end_line = |start: Option<(Stroke, DecorationStyle, Pos2)>, stop_x: f32| {
+        if let Some((stroke, style, start)) = start {
+            add_hline(
+                point_scale,
+                [start, pos2(stop_x, start.y)],
+                stroke,
+                style,
+                mesh,
+            );
         }
     };
 
     let mut line_start = None;
     let mut last_right_x = f32::NAN;
 
-    for glyph in &row.glyphs {
-        let (stroke, y) = stroke_and_y(glyph);
+    for glyph in row.glyphs_with_hyphen() {
+        let (stroke, style, y) = stroke_style_and_y(glyph);
 
         if stroke == Stroke::NONE {
             end_line(line_start.take(), last_right_x);
-        } else if let Some((existing_stroke, start)) = line_start {
-            if existing_stroke == stroke && start.y == y {
+        } else if let Some((existing_stroke, existing_style, start)) = line_start {
+            if existing_stroke == stroke && existing_style == style && start.y == y {
                 // continue the same line
             } else {
                 end_line(line_start.take(), last_right_x);
-                line_start = Some((stroke, pos2(glyph.pos.x, y)));
+                line_start = Some((stroke, style, pos2(glyph.pos.x, y)));
             }
         } else {
-            line_start = Some((stroke, pos2(glyph.pos.x, y)));
+            line_start = Some((stroke, style, pos2(glyph.pos.x, y)));
         }
 
         last_right_x = glyph.max_x();
@@ -846,10 +1409,23 @@ fn add_row_hline(
     end_line(line_start.take(), last_right_x);
 }
 
-fn add_hline(point_scale: PointScale, [start, stop]: [Pos2; 2], stroke: Stroke, mesh: &mut Mesh) {
+fn add_hline(
+    point_scale: PointScale,
+    [start, stop]: [Pos2; 2],
+    stroke: Stroke,
+    style: DecorationStyle,
+    mesh: &mut Mesh,
+) {
     let antialiased = true;
 
-    if antialiased {
+    if style != DecorationStyle::Solid {
+        let feathering = 1.0 / point_scale.pixels_per_point();
+        for (points, stroke) in style.lines([start, stop], stroke) {
+            let mut path = crate::tessellator::Path::default();
+            path.add_open_points(&points);
+            path.stroke_open(feathering, &stroke, mesh);
+        }
+    } else if antialiased {
         let mut path = crate::tessellator::Path::default(); // TODO(emilk): reuse this to avoid re-allocations.
         path.add_line_segment([start, stop]);
         let feathering = 1.0 / point_scale.pixels_per_point();
@@ -874,22 +1450,18 @@ fn add_hline(point_scale: PointScale, [start, stop]: [Pos2; 2], stroke: Stroke,
 // ----------------------------------------------------------------------------
 
 /// Keeps track of good places to break a long row of text.
-/// Will focus primarily on spaces, secondarily on things like `-`
+/// Will focus primarily on where the Unicode line breaking algorithm allows it,
+/// secondarily on hyphenating words and on punctuation.
 #[derive(Clone, Copy, Default)]
 struct RowBreakCandidates {
-    /// Breaking at ` ` or other whitespace
+    /// Breaking where the [Unicode line breaking algorithm](https://www.unicode.org/reports/tr14/) allows it,
+    /// e.g. after a space or a dash, or between two ideographs,
     /// is always the primary candidate.
-    space: Option<usize>,
-
-    /// Logograms (single character representing a whole word) or kana (Japanese hiragana and katakana) are good candidates for line break.
-    cjk: Option<usize>,
+    opportunity: Option<usize>,
 
-    /// Breaking anywhere before a CJK character is acceptable too.
-    pre_cjk: Option<usize>,
-
-    /// Breaking at a dash is a super-
-    /// good idea.
-    dash: Option<usize>,
+    /// Breaking between two syllables of a word, adding a hyphen.
+    /// Only when hyphenating, and only if the hyphen fits on the row.
+    hyphen: Option<usize>,
 
     /// This is nicer for things like URLs, e.g. www.
     /// example.com.
@@ -901,28 +1473,20 @@ struct RowBreakCandidates {
 }
 
 impl RowBreakCandidates {
-    fn add(&mut self, index: usize, glyphs: &[Glyph]) {
-        let chr = glyphs[0].chr;
-        const NON_BREAKING_SPACE: char = '\u{A0}';
-        if chr.is_whitespace() && chr != NON_BREAKING_SPACE {
-            self.space = Some(index);
-        } else if is_cjk(chr) && (glyphs.len() == 1 || is_cjk_break_allowed(glyphs[1].chr)) {
-            self.cjk = Some(index);
-        } else if chr == '-' {
-            self.dash = Some(index);
+    fn add(&mut self, index: usize, chr: char, is_break_opportunity: bool, hyphen_fits: bool) {
+        if is_break_opportunity {
+            self.opportunity = Some(index);
+        } else if hyphen_fits {
+            self.hyphen = Some(index);
         } else if chr.is_ascii_punctuation() {
             self.punctuation = Some(index);
-        } else if glyphs.len() > 1 && is_cjk(glyphs[1].chr) {
-            self.pre_cjk = Some(index);
         }
         self.any = Some(index);
     }
 
+    /// The last place where we can break the row without splitting a word, or by hyphenating it.
     fn word_boundary(&self) -> Option<usize> {
-        [self.space, self.cjk, self.pre_cjk]
-            .into_iter()
-            .max()
-            .flatten()
+        self.opportunity.max(self.hyphen)
     }
 
     fn has_good_candidate(&self, break_anywhere: bool) -> bool {
@@ -937,33 +1501,22 @@ impl RowBreakCandidates {
         if break_anywhere {
             self.any
         } else {
-            self.word_boundary()
-                .or(self.dash)
-                .or(self.punctuation)
-                .or(self.any)
+            self.word_boundary().or(self.punctuation).or(self.any)
         }
     }
 
     fn forget_before_idx(&mut self, index: usize) {
         let Self {
-            space,
-            cjk,
-            pre_cjk,
-            dash,
+            opportunity,
+            hyphen,
             punctuation,
             any,
         } = self;
-        if space.map_or(false, |s| s < index) {
-            *space = None;
-        }
-        if cjk.map_or(false, |s| s < index) {
-            *cjk = None;
+        if opportunity.map_or(false, |s| s < index) {
+            *opportunity = None;
         }
-        if pre_cjk.map_or(false, |s| s < index) {
-            *pre_cjk = None;
-        }
-        if dash.map_or(false, |s| s < index) {
-            *dash = None;
+        if hyphen.map_or(false, |s| s < index) {
+            *hyphen = None;
         }
         if punctuation.map_or(false, |s| s < index) {
             *punctuation = None;
@@ -974,31 +1527,6 @@ impl RowBreakCandidates {
     }
 }
 
-#[inline]
-fn is_cjk_ideograph(c: char) -> bool {
-    ('\u{4E00}' <= c && c <= '\u{9FFF}')
-        || ('\u{3400}' <= c && c <= '\u{4DBF}')
-        || ('\u{2B740}' <= c && c <= '\u{2B81F}')
-}
-
-#[inline]
-fn is_kana(c: char) -> bool {
-    ('\u{3040}' <= c && c <= '\u{309F}') // Hiragana block
-        || ('\u{30A0}' <= c && c <= '\u{30FF}') // Katakana block
-}
-
-#[inline]
-fn is_cjk(c: char) -> bool {
-    // TODO(bigfarts): Add support for Korean Hangul.
-    is_cjk_ideograph(c) || is_kana(c)
-}
-
-#[inline]
-fn is_cjk_break_allowed(c: char) -> bool {
-    // See: https://en.wikipedia.org/wiki/Line_breaking_rules_in_East_Asian_languages#Characters_not_permitted_on_the_start_of_a_line.
-    !")]｝〕〉》」』】〙〗〟'\"｠»ヽヾーァィゥェォッャュョヮヵヶぁぃぅぇぉっゃゅょゎゕゖㇰㇱㇲㇳㇴㇵㇶㇷㇸㇹㇺㇻㇼㇽㇾㇿ々〻‐゠–〜?!‼⁇⁈⁉・、:;,。.".contains(c)
-}
-
 // ----------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -1061,6 +1589,317 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_special_spaces() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let width_of = |fonts: &mut FontsImpl, text: &str| {
+            let job = LayoutJob::single_section(text.into(), TextFormat::default());
+            layout(fonts, job.into()).rect.width()
+        };
+        let rows_of = |fonts: &mut FontsImpl, text: &str, max_width: f32| {
+            let mut layout_job = LayoutJob::single_section(text.into(), TextFormat::default());
+            layout_job.wrap.max_width = max_width;
+            let galley = layout(fonts, layout_job.into());
+            galley.rows.iter().map(|row| row.text()).collect::<Vec<_>>()
+        };
+
+        // Never break at a non-breaking space:
+        let max_width = width_of(&mut fonts, "one two\u{A0}thr");
+        assert_eq!(
+            rows_of(&mut fonts, "one two\u{A0}three", max_width),
+            vec!["one ", "two\u{A0}three"]
+        );
+
+        // Break at a zero-width space:
+        let max_width = width_of(&mut fonts, "abcdef\u{200B}ghi");
+        assert_eq!(
+            rows_of(&mut fonts, "abcdef\u{200B}ghijkl", max_width),
+            vec!["abcdef\u{200B}", "ghijkl"]
+        );
+    }
+
+    #[test]
+    fn test_elide_at_word_boundary() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let width = layout(
+            &mut fonts,
+            LayoutJob::single_section("Hello world".into(), TextFormat::default()).into(),
+        )
+        .rect
+        .width();
+
+        // The first row fits exactly, but the `…` doesn't fit after it:
+        let mut layout_job =
+            LayoutJob::single_section("Hello world\nfoo".into(), TextFormat::default());
+        layout_job.wrap.max_width = width;
+        layout_job.wrap.max_rows = 1;
+        let galley = layout(&mut fonts, layout_job.into());
+        assert!(galley.elided);
+        assert_eq!(galley.rows[0].text(), "Hello…");
+    }
+
+    #[test]
+    fn test_elision_position() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let path = "/home/user/project/src/main.rs";
+        let mut elide = |elision_position: ElisionPosition| {
+            let mut layout_job = LayoutJob::single_section(path.into(), TextFormat::default());
+            layout_job.wrap = TextWrapping {
+                elision_position,
+                ..TextWrapping::truncate_at_width(150.0)
+            };
+            let galley = layout(&mut fonts, layout_job.into());
+            assert!(galley.elided, "{elision_position:?}");
+            assert_eq!(galley.text(), path, "The galley still knows the full text");
+            assert_eq!(galley.rows.len(), 1);
+            let row = &galley.rows[0];
+            assert!(row.rect.width() <= 150.0, "{elision_position:?}");
+            for pair in row.glyphs.windows(2) {
+                let gap = pair[1].pos.x - pair[0].max_x();
+                assert!(gap.abs() < 1.0, "{elision_position:?}: {pair:?}");
+            }
+            row.text()
+        };
+
+        let end = elide(ElisionPosition::End);
+        assert!(end.starts_with("/home/") && end.ends_with('…'), "{end}");
+
+        let start = elide(ElisionPosition::Start);
+        assert!(
+            start.starts_with('…') && start.ends_with("/main.rs"),
+            "{start}"
+        );
+
+        let middle = elide(ElisionPosition::Middle);
+        let (before, after) = middle.split_once('…').unwrap();
+        assert!(
+            path.starts_with(before) && before.starts_with("/home"),
+            "{middle}"
+        );
+        assert!(
+            path.ends_with(after) && after.ends_with("main.rs"),
+            "{middle}"
+        );
+
+        // Text that fits is not elided:
+        let mut layout_job = LayoutJob::single_section("short".into(), TextFormat::default());
+        layout_job.wrap = TextWrapping {
+            elision_position: ElisionPosition::Middle,
+            ..TextWrapping::truncate_at_width(100.0)
+        };
+        let galley = layout(&mut fonts, layout_job.into());
+        assert!(!galley.elided);
+        assert_eq!(galley.rows[0].text(), "short");
+    }
+
+    #[test]
+    fn test_elided_cursors() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let path = "/home/user/project/src/main.rs";
+        let chars: Vec<char> = path.chars().collect();
+        for elision_position in [ElisionPosition::Start, ElisionPosition::Middle] {
+            let mut layout_job = LayoutJob::single_section(path.into(), TextFormat::default());
+            layout_job.wrap = TextWrapping {
+                elision_position,
+                ..TextWrapping::truncate_at_width(150.0)
+            };
+            let galley = layout(&mut fonts, layout_job.into());
+            let row = &galley.rows[0];
+            let elided = row.elided.clone().unwrap();
+            assert!(!elided.char_range.is_empty(), "{elision_position:?}");
+            assert_eq!(galley.end().ccursor.index, chars.len());
+
+            // Every visible character maps to the same character of the text, and back:
+            for (i, glyph) in row.glyphs.iter().enumerate() {
+                let cursor = galley.cursor_from_pos(vec2(glyph.pos.x + 0.1, row.rect.center().y));
+                let index = cursor.ccursor.index;
+                if elided.glyph_range.contains(&i) {
+                    assert_eq!(index, elided.char_range.start, "{elision_position:?}");
+                } else {
+                    assert_eq!(chars[index], glyph.chr, "{elision_position:?}: glyph {i}");
+                }
+                let x = galley.pos_from_ccursor(cursor.ccursor).min.x;
+                assert_eq!(x, glyph.pos.x, "{elision_position:?}: glyph {i}");
+            }
+
+            // Selecting across the overflow character selects the elided characters:
+            let overflow = row.glyphs[elided.glyph_range.start];
+            let y = row.rect.center().y;
+            let min = galley.cursor_from_pos(vec2(overflow.pos.x - 0.1, y));
+            let max = galley.cursor_from_pos(vec2(overflow.max_x() + 0.1, y));
+            assert_eq!(
+                min.ccursor.index..max.ccursor.index,
+                elided.char_range,
+                "{elision_position:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "hyphenation")]
+    #[test]
+    fn test_hyphenation() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let text = "An extraordinarily incomprehensible sentence";
+        let mut layout_job = LayoutJob::single_section(text.into(), TextFormat::default());
+        layout_job.wrap.max_width = 100.0;
+        layout_job.wrap.hyphenation_language = Some(*b"en");
+        let galley = layout(&mut fonts, layout_job.into());
+
+        assert!(galley.rows.iter().any(|row| row.hyphen.is_some()));
+        for row in &galley.rows {
+            assert!(row.rect.max.x <= 100.0, "{:?} is too wide", row.text());
+            if let Some(hyphen) = row.hyphen {
+                assert_eq!(hyphen.chr, '-');
+                assert!((hyphen.pos.x - row.glyphs.last().unwrap().max_x()).abs() < 0.01);
+                assert!(row.text().ends_with(char::is_alphabetic));
+            }
+        }
+
+        // The hyphens are not part of the text:
+        let rows_text: String = galley.rows.iter().map(|row| row.text()).collect();
+        assert_eq!(rows_text, text);
+        assert_eq!(galley.text(), text);
+    }
+
+    #[test]
+    fn test_subpixel_positions() {
+        let mut definitions = FontDefinitions::default();
+        for font_data in definitions.font_data.values_mut() {
+            font_data.tweak.subpixel_positions = 4;
+        }
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+        let layout_job = LayoutJob::single_section("iiiiiiiiii".into(), TextFormat::default());
+        let galley = layout(&mut fonts, layout_job.into());
+        let glyphs = &galley.rows[0].glyphs;
+
+        assert!(
+            glyphs.iter().any(|glyph| glyph.pos.x.fract() != 0.0),
+            "Glyphs are no longer snapped to whole pixels"
+        );
+
+        let mut uv_rects: Vec<[u16; 2]> = glyphs.iter().map(|glyph| glyph.uv_rect.min).collect();
+        uv_rects.dedup();
+        assert!(
+            1 < uv_rects.len(),
+            "Glyphs at different offsets are rasterized differently"
+        );
+
+        for glyph in glyphs {
+            // The texture ends up on whole pixels:
+            let left = glyph.pos.x + glyph.uv_rect.offset.x;
+            assert!(
+                (left - left.round()).abs() < 1.0 / 8.0,
+                "Bad subpixel selection for glyph at {}",
+                glyph.pos.x
+            );
+        }
+    }
+
+    #[test]
+    fn test_decoration_styles() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut decoration_bounds = |style: DecorationStyle| {
+            let text_format = TextFormat {
+                underline: Stroke::new(1.0, Color32::RED),
+                underline_style: style,
+                ..Default::default()
+            };
+            let galley = layout(
+                &mut fonts,
+                // Spaces, so that only the underline is in the mesh:
+                LayoutJob::single_section("          ".into(), text_format).into(),
+            );
+            let row = &galley.rows[0];
+            assert!(!row.visuals.mesh.is_empty());
+            (row.visuals.mesh.calc_bounds(), row.rect)
+        };
+
+        let (solid, row_rect) = decoration_bounds(DecorationStyle::Solid);
+        assert!((solid.center().y - row_rect.bottom()).abs() < 1.0);
+
+        // Double and wavy lines extend further up and down:
+        for style in [DecorationStyle::Double, DecorationStyle::Wavy] {
+            let (bounds, _) = decoration_bounds(style);
+            assert!(solid.height() + 1.0 < bounds.height(), "{style:?}");
+            assert!((bounds.left() - solid.left()).abs() < 1.0, "{style:?}");
+        }
+
+        let (dotted, _) = decoration_bounds(DecorationStyle::Dotted);
+        assert!((dotted.height() - solid.height()).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_tab_stops() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_job =
+            LayoutJob::single_section("a\t1.5\tx\nbb\t123.25\tyy".into(), TextFormat::default());
+        layout_job.tab_stops = vec![TabStop::decimal(100.0), TabStop::right(200.0)];
+        let galley = layout(&mut fonts, layout_job.into());
+        assert_eq!(galley.rows.len(), 2);
+
+        let glyph_x = |row: usize, chr: char| {
+            let glyph = galley.rows[row]
+                .glyphs
+                .iter()
+                .find(|g| g.chr == chr)
+                .unwrap();
+            (glyph.pos.x, glyph.max_x())
+        };
+
+        // The decimal points line up with the first stop:
+        for row in 0..2 {
+            let (dot_x, _) = glyph_x(row, '.');
+            assert!((dot_x - 100.0).abs() < 1.0, "row {row}: {dot_x}");
+        }
+
+        // The last column is right-aligned at the second stop:
+        let (_, x_max) = glyph_x(0, 'x');
+        let y_max = galley.rows[1].glyphs.last().unwrap().max_x();
+        assert!((x_max - 200.0).abs() < 1.0, "{x_max}");
+        assert!((y_max - 200.0).abs() < 1.0, "{y_max}");
+    }
+
+    #[test]
+    fn test_indents() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_job = LayoutJob::single_section(
+            "one two three four five six seven eight nine ten".into(),
+            TextFormat::default(),
+        );
+        layout_job.wrap.max_width = 100.0;
+        layout_job.first_row_indent = 20.0;
+        layout_job.hanging_indent = 10.0;
+        let galley = layout(&mut fonts, layout_job.into());
+        assert!(2 < galley.rows.len());
+
+        assert_eq!(galley.rows[0].glyphs[0].pos.x, 20.0);
+        for row in &galley.rows[1..] {
+            assert_eq!(row.glyphs[0].pos.x, 10.0);
+        }
+        for row in &galley.rows {
+            assert!(row.rect.max.x <= 100.0, "{:?} is too wide", row.text());
+        }
+    }
+
+    #[test]
+    fn test_paragraph_spacing() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut row_tops = |before: f32, after: f32| {
+            let mut layout_job =
+                LayoutJob::single_section("first\nsecond".into(), TextFormat::default());
+            layout_job.paragraph_spacing_before = before;
+            layout_job.paragraph_spacing_after = after;
+            let galley = layout(&mut fonts, layout_job.into());
+            assert_eq!(galley.rows.len(), 2);
+            (galley.rows[0].rect.top(), galley.rows[1].rect.top())
+        };
+
+        let (first, second) = row_tops(0.0, 0.0);
+        let (spaced_first, spaced_second) = row_tops(4.0, 6.0);
+        assert_eq!(first, spaced_first, "No spacing above the first paragraph");
+        assert_eq!(spaced_second - second, 10.0);
+    }
+
     #[test]
     fn test_cjk() {
         let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
@@ -1090,4 +1929,35 @@ mod tests {
             vec!["日本語とEnglish", "の混在した文章"]
         );
     }
+
+    #[cfg(feature = "shaping")]
+    #[test]
+    fn test_shaping_keeps_one_glyph_per_char() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        for (text, font_id) in [
+            ("fit file", FontId::proportional(14.0)), // `fi` is a ligature in Ubuntu-Light
+            ("cafe\u{301}", FontId::monospace(14.0)), // combining acute accent
+        ] {
+            let layout_job = LayoutJob::single_section(
+                text.into(),
+                TextFormat {
+                    font_id,
+                    ..Default::default()
+                },
+            );
+            let galley = layout(&mut fonts, layout_job.into());
+            assert_eq!(galley.rows.len(), 1);
+            let row = &galley.rows[0];
+            assert_eq!(row.text(), text);
+            assert_eq!(row.char_count_excluding_newline(), text.chars().count());
+            for pair in row.glyphs.windows(2) {
+                assert!(pair[0].pos.x < pair[1].pos.x, "{text:?}: {pair:?}");
+            }
+
+            // The second character of a cluster is drawn together with the first:
+            let second = if text.starts_with("fi") { 1 } else { 4 };
+            assert!(!row.glyphs[second - 1].uv_rect.is_nothing());
+            assert!(row.glyphs[second].uv_rect.is_nothing(), "{text:?}");
+        }
+    }
 }
@@ -4,8 +4,8 @@
 use std::ops::Range;
 use std::sync::Arc;
 
-use super::{cursor::*, font::UvRect};
-use crate::{Color32, FontId, Mesh, Stroke};
+use super::{cursor::*, font::UvRect, FontStyle, FontWeight};
+use crate::{Color32, FontId, Mesh, PathStroke, Stroke};
 use emath::*;
 
 /// Describes the task of laying out text.
@@ -74,6 +74,29 @@ pub struct LayoutJob {
 
     /// Justify text so that word-wrapped rows fill the whole [`TextWrapping::max_width`].
     pub justify: bool,
+
+    /// Where a `\t` moves the text after it.
+    ///
+    /// A tab goes to the first stop to the right of where it starts.
+    /// Past the last stop (or if there are none) a tab is [`super::TAB_SIZE`] spaces wide.
+    ///
+    /// The stops are measured from the left edge of the galley,
+    /// as if each paragraph was on one row.
+    pub tab_stops: Vec<TabStop>,
+
+    /// Indentation of the first row of each paragraph, in points.
+    pub first_row_indent: f32,
+
+    /// Indentation of the rest of the rows each paragraph wraps onto, in points.
+    ///
+    /// With a [`TabStop`] at the same position, this makes lists with hanging bullets: `"•\tSome long text"`.
+    pub hanging_indent: f32,
+
+    /// Extra space above each paragraph except the first, in points.
+    pub paragraph_spacing_before: f32,
+
+    /// Extra space below each paragraph except the last, in points.
+    pub paragraph_spacing_after: f32,
 }
 
 impl Default for LayoutJob {
@@ -87,6 +110,11 @@ impl Default for LayoutJob {
             break_on_newline: true,
             halign: Align::LEFT,
             justify: false,
+            tab_stops: Vec::new(),
+            first_row_indent: 0.0,
+            hanging_indent: 0.0,
+            paragraph_spacing_before: 0.0,
+            paragraph_spacing_after: 0.0,
         }
     }
 }
@@ -180,6 +208,11 @@ impl std::hash::Hash for LayoutJob {
             break_on_newline,
             halign,
             justify,
+            tab_stops,
+            first_row_indent,
+            hanging_indent,
+            paragraph_spacing_before,
+            paragraph_spacing_after,
         } = self;
 
         text.hash(state);
@@ -189,6 +222,77 @@ impl std::hash::Hash for LayoutJob {
         break_on_newline.hash(state);
         halign.hash(state);
         justify.hash(state);
+        tab_stops.hash(state);
+        emath::OrderedFloat(*first_row_indent).hash(state);
+        emath::OrderedFloat(*hanging_indent).hash(state);
+        emath::OrderedFloat(*paragraph_spacing_before).hash(state);
+        emath::OrderedFloat(*paragraph_spacing_after).hash(state);
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// How the text after a `\t` lines up with its [`TabStop`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TabAlign {
+    /// The text starts at the tab stop.
+    #[default]
+    Left,
+
+    /// The text ends at the tab stop.
+    Right,
+
+    /// The first `.` of the text is at the tab stop, to line up a column of numbers.
+    ///
+    /// Text without a `.` ends at the tab stop.
+    Decimal,
+}
+
+/// A position that a `\t` moves the text after it to, see [`LayoutJob::tab_stops`].
+///
+/// The text after a tab goes up to the next tab or the end of the paragraph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TabStop {
+    /// Distance from the left edge of the galley, in points.
+    pub x: f32,
+
+    pub align: TabAlign,
+}
+
+impl TabStop {
+    #[inline]
+    pub fn left(x: f32) -> Self {
+        Self {
+            x,
+            align: TabAlign::Left,
+        }
+    }
+
+    #[inline]
+    pub fn right(x: f32) -> Self {
+        Self {
+            x,
+            align: TabAlign::Right,
+        }
+    }
+
+    #[inline]
+    pub fn decimal(x: f32) -> Self {
+        Self {
+            x,
+            align: TabAlign::Decimal,
+        }
+    }
+}
+
+impl std::hash::Hash for TabStop {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let Self { x, align } = self;
+        OrderedFloat(*x).hash(state);
+        align.hash(state);
     }
 }
 
@@ -249,12 +353,26 @@ pub struct TextFormat {
 
     pub background: Color32,
 
+    /// How bold the text is.
+    ///
+    /// Uses the closest face in [`crate::text::FontDefinitions::faces`],
+    /// or fakes bold if there is no bold face.
+    pub weight: FontWeight,
+
+    /// Uses an italic face from [`crate::text::FontDefinitions::faces`],
+    /// or slants the glyphs if there is no italic face.
     pub italics: bool,
 
     pub underline: Stroke,
 
+    /// How the [`Self::underline`] is drawn, e.g. wavy for spelling errors.
+    pub underline_style: DecorationStyle,
+
     pub strikethrough: Stroke,
 
+    /// How the [`Self::strikethrough`] is drawn.
+    pub strikethrough_style: DecorationStyle,
+
     /// If you use a small font and [`Align::TOP`] you
     /// can get the effect of raised text.
     pub valign: Align,
@@ -270,9 +388,12 @@ impl Default for TextFormat {
             line_height: None,
             color: Color32::GRAY,
             background: Color32::TRANSPARENT,
+            weight: FontWeight::REGULAR,
             italics: false,
             underline: Stroke::NONE,
+            underline_style: DecorationStyle::Solid,
             strikethrough: Stroke::NONE,
+            strikethrough_style: DecorationStyle::Solid,
             valign: Align::BOTTOM,
         }
     }
@@ -287,9 +408,12 @@ impl std::hash::Hash for TextFormat {
             line_height,
             color,
             background,
+            weight,
             italics,
             underline,
+            underline_style,
             strikethrough,
+            strikethrough_style,
             valign,
         } = self;
         font_id.hash(state);
@@ -299,9 +423,12 @@ impl std::hash::Hash for TextFormat {
         }
         color.hash(state);
         background.hash(state);
+        weight.hash(state);
         italics.hash(state);
         underline.hash(state);
+        underline_style.hash(state);
         strikethrough.hash(state);
+        strikethrough_style.hash(state);
         valign.hash(state);
     }
 }
@@ -315,6 +442,84 @@ impl TextFormat {
             ..Default::default()
         }
     }
+
+    /// The weight and slant to pick the font face with.
+    #[inline]
+    pub fn font_style(&self) -> FontStyle {
+        FontStyle {
+            weight: self.weight,
+            italic: self.italics,
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// How a line under or through text ([`TextFormat::underline`], [`TextFormat::strikethrough`]) is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum DecorationStyle {
+    /// One straight line.
+    #[default]
+    Solid,
+
+    /// Two parallel lines, as wide as the stroke and one stroke width apart.
+    Double,
+
+    /// Round dots, e.g. for hints.
+    Dotted,
+
+    /// Short dashes.
+    Dashed,
+
+    /// A wavy line, e.g. for spelling errors and other diagnostics.
+    Wavy,
+}
+
+impl DecorationStyle {
+    /// The lines to stroke for a decoration from `start` to `stop`, which are at the same height.
+    pub fn lines(self, [start, stop]: [Pos2; 2], stroke: Stroke) -> Vec<(Vec<Pos2>, PathStroke)> {
+        let width = stroke.width;
+        match self {
+            Self::Solid => vec![(vec![start, stop], stroke.into())],
+            Self::Double => [-width, width]
+                .into_iter()
+                .map(|dy| {
+                    (
+                        vec![start + dy * Vec2::Y, stop + dy * Vec2::Y],
+                        stroke.into(),
+                    )
+                })
+                .collect(),
+            Self::Dotted => {
+                let stroke = PathStroke::from(stroke)
+                    .with_cap(crate::LineCap::Round)
+                    .with_dashes(vec![0.0, 2.0 * width], 0.0);
+                vec![(vec![start, stop], stroke)]
+            }
+            Self::Dashed => {
+                let stroke =
+                    PathStroke::from(stroke).with_dashes(vec![3.0 * width, 2.0 * width], 0.0);
+                vec![(vec![start, stop], stroke)]
+            }
+            Self::Wavy => {
+                let amplitude = width.max(1.0);
+                let wavelength = 4.0 * amplitude;
+                let length = stop.x - start.x;
+                // Eight points per wave is smooth enough at these sizes:
+                let steps = (8.0 * length / wavelength).ceil().max(1.0) as usize;
+                let points = (0..=steps)
+                    .map(|i| {
+                        let x = length * i as f32 / steps as f32;
+                        let y = amplitude * (std::f32::consts::TAU * x / wavelength).sin();
+                        start + vec2(x, y)
+                    })
+                    .collect();
+                let stroke = PathStroke::from(stroke).with_join(crate::LineJoin::Round);
+                vec![(points, stroke)]
+            }
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -342,28 +547,48 @@ pub struct TextWrapping {
     /// If set to `0`, no text will be outputted.
     ///
     /// If set to `1`, a single row will be outputted,
-    /// eliding the text after [`Self::max_width`] is reached.
+    /// eliding the text after [`Self::max_width`] is reached
+    /// (or at the start or in the middle, see [`Self::elision_position`]).
     /// When you set `max_rows = 1`, it is recommended you also set [`Self::break_anywhere`] to `true`.
     ///
     /// Default value: `usize::MAX`.
     pub max_rows: usize,
 
     /// If `true`: Allow breaking between any characters.
-    /// If `false` (default): prefer breaking between words, etc.
+    /// If `false` (default): prefer breaking where the
+    /// [Unicode line breaking algorithm](https://www.unicode.org/reports/tr14/) allows it,
+    /// e.g. after spaces and hyphens or between ideographs, but never at a non-breaking space.
     ///
-    /// NOTE: Due to limitations in the current implementation,
-    /// when truncating text using [`Self::max_rows`] the text may be truncated
-    /// in the middle of a word even if [`Self::break_anywhere`] is `false`.
-    /// Therefore it is recommended to set [`Self::break_anywhere`] to `true`
-    /// whenever [`Self::max_rows`] is set to `1`.
+    /// When truncating text using [`Self::max_rows`], the text is cut at such a break
+    /// if the [`Self::overflow_character`] fits there,
+    /// and in the middle of a word otherwise.
     pub break_anywhere: bool,
 
+    /// Hyphenate long words in this language, given as an ISO 639-1 code, e.g. `Some(*b"en")`.
+    ///
+    /// A word that doesn't fit on a row is then split between syllables,
+    /// with a visible hyphen (see [`Row::hyphen`]).
+    ///
+    /// Requires the `hyphenation` feature.
+    /// Ignored for unsupported languages, and if [`Self::break_anywhere`] is `true`.
+    ///
+    /// Default: `None`.
+    pub hyphenation_language: Option<[u8; 2]>,
+
     /// Character to use to represent elided text.
     ///
     /// The default is `…`.
     ///
     /// If not set, no character will be used (but the text will still be elided).
     pub overflow_character: Option<char>,
+
+    /// Which part of the text to elide when it doesn't fit on a single row.
+    ///
+    /// Only used when [`Self::max_rows`] is `1` and the text has no newlines to break on;
+    /// otherwise the text is always elided at the end.
+    ///
+    /// Default: [`ElisionPosition::End`].
+    pub elision_position: ElisionPosition,
 }
 
 impl std::hash::Hash for TextWrapping {
@@ -373,12 +598,16 @@ impl std::hash::Hash for TextWrapping {
             max_width,
             max_rows,
             break_anywhere,
+            hyphenation_language,
             overflow_character,
+            elision_position,
         } = self;
         emath::OrderedFloat(*max_width).hash(state);
         max_rows.hash(state);
         break_anywhere.hash(state);
+        hyphenation_language.hash(state);
         overflow_character.hash(state);
+        elision_position.hash(state);
     }
 }
 
@@ -388,7 +617,9 @@ impl Default for TextWrapping {
             max_width: f32::INFINITY,
             max_rows: usize::MAX,
             break_anywhere: false,
+            hyphenation_language: None,
             overflow_character: Some('…'),
+            elision_position: ElisionPosition::End,
         }
     }
 }
@@ -413,6 +644,21 @@ impl TextWrapping {
     }
 }
 
+/// Where to elide text that doesn't fit on a single row, see [`TextWrapping::elision_position`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ElisionPosition {
+    /// Keep the start of the text: `The quick brown…`
+    #[default]
+    End,
+
+    /// Keep the end of the text, e.g. for file paths: `…/project/src/main.rs`
+    Start,
+
+    /// Keep both the start and the end of the text: `/home/…/src/main.rs`
+    Middle,
+}
+
 // ----------------------------------------------------------------------------
 
 /// Text that has been laid out, ready for painting.
@@ -429,7 +675,7 @@ impl TextWrapping {
 ///
 /// The name comes from typography, where a "galley" is a metal tray
 /// containing a column of set type, usually the size of a page of text.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Galley {
     /// The job that this galley is the result of.
@@ -484,6 +730,18 @@ pub struct Row {
     /// One for each `char`.
     pub glyphs: Vec<Glyph>,
 
+    /// If the row was broken in the middle of a word, this is the hyphen drawn after the last glyph.
+    ///
+    /// It is not part of the text, so it is not one of the [`Self::glyphs`].
+    /// See [`TextWrapping::hyphenation_language`].
+    pub hyphen: Option<Glyph>,
+
+    /// If characters were elided from the start or middle of the row, this tells
+    /// which of the [`Self::glyphs`] stand in for them.
+    ///
+    /// See [`TextWrapping::elision_position`].
+    pub elided: Option<ElidedChars>,
+
     /// Logical bounding rectangle based on font heights etc.
     /// Use this when drawing a selection or similar!
     /// Includes leading and trailing whitespace.
@@ -500,6 +758,20 @@ pub struct Row {
     pub ends_with_newline: bool,
 }
 
+/// The characters elided from the start or middle of a [`Row`].
+///
+/// The glyphs in [`Self::glyph_range`] (the overflow character, if any) replace the
+/// characters in [`Self::char_range`], so the columns of the row still count the characters of the text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ElidedChars {
+    /// The indices of the [`Row::glyphs`] that replace the elided characters.
+    pub glyph_range: Range<usize>,
+
+    /// The columns of the row that were elided.
+    pub char_range: Range<usize>,
+}
+
 /// The tessellated output of a row.
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -515,7 +787,13 @@ pub struct RowVisuals {
     /// The range of vertices in the mesh that contain glyphs (as opposed to background, underlines, strikethorugh, etc).
     ///
     /// The glyph vertices comes before backgrounds (if any), and after any underlines and strikethrough.
+    /// Color glyphs (e.g. emojis) are not included, since they should not be tinted.
     pub glyph_vertex_range: Range<usize>,
+
+    /// Glyphs that are on other pages of the font atlas than the first.
+    ///
+    /// Usually empty. See [`crate::TextureAtlas`].
+    pub other_pages: Vec<PageVisuals>,
 }
 
 impl Default for RowVisuals {
@@ -524,10 +802,35 @@ impl Default for RowVisuals {
             mesh: Default::default(),
             mesh_bounds: Rect::NOTHING,
             glyph_vertex_range: 0..0,
+            other_pages: Vec::new(),
         }
     }
 }
 
+impl RowVisuals {
+    /// All meshes of the row: [`Self::mesh`] and the meshes of [`Self::other_pages`].
+    pub fn meshes_mut(&mut self) -> impl Iterator<Item = &mut Mesh> {
+        std::iter::once(&mut self.mesh)
+            .chain(self.other_pages.iter_mut().map(|page| &mut page.mesh))
+    }
+}
+
+/// The glyphs of a row that are on one of the other pages of the font atlas.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PageVisuals {
+    /// Which page of the [`crate::TextureAtlas`].
+    pub page: usize,
+
+    /// The tessellated glyphs, using non-normalized (texel) UV coordinates of the page.
+    pub mesh: Mesh,
+
+    /// The range of vertices in the mesh that may be tinted.
+    ///
+    /// Color glyphs (e.g. emojis) come after this range.
+    pub glyph_vertex_range: Range<usize>,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Glyph {
@@ -569,20 +872,56 @@ impl Glyph {
 
 impl Row {
     /// The text on this row, excluding the implicit `\n` if any.
+    ///
+    /// Elided characters are replaced by the overflow character, see [`Self::elided`].
     pub fn text(&self) -> String {
         self.glyphs.iter().map(|g| g.chr).collect()
     }
 
+    /// The glyphs to draw: [`Self::glyphs`], followed by the [`Self::hyphen`], if any.
+    pub fn glyphs_with_hyphen(&self) -> impl Iterator<Item = &Glyph> {
+        self.glyphs.iter().chain(&self.hyphen)
+    }
+
     /// Excludes the implicit `\n` after the [`Row`], if any.
     #[inline]
     pub fn char_count_excluding_newline(&self) -> usize {
-        self.glyphs.len()
+        match &self.elided {
+            Some(elided) => self.glyphs.len() - elided.glyph_range.len() + elided.char_range.len(),
+            None => self.glyphs.len(),
+        }
     }
 
     /// Includes the implicit `\n` after the [`Row`], if any.
     #[inline]
     pub fn char_count_including_newline(&self) -> usize {
-        self.glyphs.len() + (self.ends_with_newline as usize)
+        self.char_count_excluding_newline() + (self.ends_with_newline as usize)
+    }
+
+    /// The column of the character at the given index of [`Self::glyphs`].
+    ///
+    /// A glyph replacing elided characters maps to the first of them.
+    pub fn column_from_glyph_index(&self, glyph_index: usize) -> usize {
+        match &self.elided {
+            Some(elided) if elided.glyph_range.end <= glyph_index => {
+                glyph_index - elided.glyph_range.end + elided.char_range.end
+            }
+            Some(elided) if elided.glyph_range.start <= glyph_index => elided.char_range.start,
+            _ => glyph_index,
+        }
+    }
+
+    /// The index in [`Self::glyphs`] of the character at the given column.
+    ///
+    /// Elided characters map to the first glyph replacing them.
+    pub fn glyph_index_from_column(&self, column: usize) -> usize {
+        match &self.elided {
+            Some(elided) if elided.char_range.end <= column => {
+                column - elided.char_range.end + elided.glyph_range.end
+            }
+            Some(elided) if elided.char_range.start <= column => elided.glyph_range.start,
+            _ => column,
+        }
     }
 
     #[inline]
@@ -605,14 +944,14 @@ impl Row {
     pub fn char_at(&self, desired_x: f32) -> usize {
         for (i, glyph) in self.glyphs.iter().enumerate() {
             if desired_x < glyph.logical_rect().center().x {
-                return i;
+                return self.column_from_glyph_index(i);
             }
         }
         self.char_count_excluding_newline()
     }
 
     pub fn x_offset(&self, column: usize) -> f32 {
-        if let Some(glyph) = self.glyphs.get(column) {
+        if let Some(glyph) = self.glyphs.get(self.glyph_index_from_column(column)) {
             glyph.pos.x
         } else {
             self.rect.right()
@@ -620,6 +959,36 @@ impl Row {
     }
 }
 
+impl PartialEq for Galley {
+    fn eq(&self, other: &Self) -> bool {
+        // Galleys are usually shared with `Arc`, and comparing the rows is expensive,
+        // so check for the common case of comparing a galley with itself first:
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+
+        let Self {
+            job,
+            rows,
+            elided,
+            rect,
+            mesh_bounds,
+            num_vertices,
+            num_indices,
+            pixels_per_point,
+        } = self;
+
+        *job == other.job
+            && *rows == other.rows
+            && *elided == other.elided
+            && *rect == other.rect
+            && *mesh_bounds == other.mesh_bounds
+            && *num_vertices == other.num_vertices
+            && *num_indices == other.num_indices
+            && *pixels_per_point == other.pixels_per_point
+    }
+}
+
 impl Galley {
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -1,6 +1,6 @@
 use emath::{remap_clamp, Rect};
 
-use crate::{FontImage, ImageDelta};
+use crate::{Color32, ColorImage, FontImage, ImageData, ImageDelta, TextureId};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct Rectu {
@@ -39,7 +39,7 @@ struct PrerasterizedDisc {
 }
 
 /// A pre-rasterized disc (filled circle), somewhere in the texture atlas.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct PreparedDisc {
     /// The radius of this disc in texels.
     pub r: f32,
@@ -52,24 +52,256 @@ pub struct PreparedDisc {
     pub uv: Rect,
 }
 
-/// Contains font data in an atlas, where each character occupied a small rectangle.
-///
-/// More characters can be added, possibly expanding the texture.
+/// Where in the [`TextureAtlas`] something was allocated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AtlasPos {
+    /// Which page (texture) of the atlas.
+    pub page: usize,
+
+    /// Left side, in texels.
+    pub x: usize,
+
+    /// Top side, in texels.
+    pub y: usize,
+}
+
+/// A page of the atlas that is not the first one, and the texture it is painted with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FontPage {
+    pub texture_id: TextureId,
+
+    /// Size of the page in texels.
+    pub size: [usize; 2],
+}
+
+/// On some low-precision GPUs (my old iPad) characters get muddled up
+/// if we don't add some empty pixels between the characters.
+/// On modern high-precision GPUs this is not needed.
+const PADDING: usize = 1;
+
+/// How many pages (textures) the atlas may use before it has to evict glyphs.
+const MAX_PAGES: usize = 4;
+
+/// Never evict anything used in the last this many frames.
+const MIN_FRAMES_UNUSED: u64 = 2;
+
+/// A rectangle in a [`Shelf`].
+#[derive(Clone, Debug)]
+struct Slot {
+    x: usize,
+
+    /// Including padding.
+    width: usize,
+
+    /// Frame number of when this was last used, or `None` if the slot is free.
+    last_used: Option<u64>,
+}
+
+/// A row of slots of the same height.
+#[derive(Clone, Debug)]
+struct Shelf {
+    y: usize,
+
+    /// Including padding.
+    height: usize,
+
+    /// Sorted by `x`, without gaps.
+    slots: Vec<Slot>,
+}
+
+impl Shelf {
+    fn end_x(&self) -> usize {
+        self.slots.last().map_or(0, |slot| slot.x + slot.width)
+    }
+}
+
 #[derive(Clone)]
-pub struct TextureAtlas {
+struct Page {
     image: FontImage,
 
+    /// Premultiplied colors of color glyphs (e.g. emojis), used instead of the coverage where not transparent.
+    ///
+    /// Empty until the first color glyph is added, and then the same size as [`Self::image`].
+    colors: Vec<Color32>,
+
     /// What part of the image that is dirty
     dirty: Rectu,
 
-    /// Used for when allocating new rectangles.
-    cursor: (usize, usize),
+    /// Sorted by `y`, without gaps.
+    shelves: Vec<Shelf>,
 
-    row_height: usize,
+    /// Assigned by the integration, see [`TextureAtlas::set_page_texture_id`].
+    texture_id: Option<TextureId>,
+
+    /// Does this page contain signed distance fields instead of coverage?
+    ///
+    /// See [`TextureAtlas::allocate_sdf`].
+    sdf: bool,
+}
+
+impl Page {
+    fn new(size: [usize; 2], sdf: bool) -> Self {
+        Self {
+            image: FontImage::new(size),
+            colors: vec![],
+            dirty: Rectu::EVERYTHING,
+            shelves: vec![],
+            texture_id: None,
+            sdf,
+        }
+    }
+
+    fn max_height(&self) -> usize {
+        // the initial width is likely the max texture side size
+        self.image.width()
+    }
+
+    fn used_height(&self) -> usize {
+        self.shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height)
+    }
+
+    /// Find room for a rectangle of the given size, including padding.
+    fn allocate(&mut self, (w, h): (usize, usize), frame: u64) -> Option<(usize, usize)> {
+        let page_width = self.image.width();
+
+        let max_height = self.max_height();
+        let num_shelves = self.shelves.len();
+
+        // Prefer the shelf where we waste the least height:
+        let mut best: Option<(usize, usize, Option<usize>)> = None; // (waste, shelf, free slot)
+        for (shelf_index, shelf) in self.shelves.iter().enumerate() {
+            let is_last = shelf_index + 1 == num_shelves;
+            let fits_at_end = shelf.end_x() + w <= page_width;
+            if shelf.height < h {
+                // Nothing is below the last shelf, so it can grow:
+                if is_last && fits_at_end && shelf.y + h <= max_height {
+                    best = Some((0, shelf_index, None));
+                }
+                continue;
+            }
+            if shelf.height > 2 * h + 4 {
+                continue; // Too much waste
+            }
+            let waste = shelf.height - h;
+            if best.is_some_and(|(best_waste, _, _)| best_waste <= waste) {
+                continue;
+            }
+            let free_slot = shelf
+                .slots
+                .iter()
+                .position(|slot| slot.last_used.is_none() && w <= slot.width);
+            if free_slot.is_some() || fits_at_end {
+                best = Some((waste, shelf_index, free_slot));
+            }
+        }
+
+        let (shelf_index, free_slot) = if let Some((_, shelf_index, free_slot)) = best {
+            (shelf_index, free_slot)
+        } else {
+            // New shelf:
+            let y = self.used_height();
+            if max_height < y + h {
+                return None;
+            }
+            self.shelves.push(Shelf {
+                y,
+                height: h,
+                slots: vec![],
+            });
+            (self.shelves.len() - 1, None)
+        };
+
+        let shelf = &mut self.shelves[shelf_index];
+        shelf.height = shelf.height.max(h);
+        let x = if let Some(slot_index) = free_slot {
+            let slot = &mut shelf.slots[slot_index];
+            let x = slot.x;
+            if w < slot.width {
+                // Split the slot, keeping the remainder free:
+                let remainder = Slot {
+                    x: x + w,
+                    width: slot.width - w,
+                    last_used: None,
+                };
+                slot.width = w;
+                shelf.slots.insert(slot_index + 1, remainder);
+            }
+            shelf.slots[slot_index].last_used = Some(frame);
+            x
+        } else {
+            let x = shelf.end_x();
+            shelf.slots.push(Slot {
+                x,
+                width: w,
+                last_used: Some(frame),
+            });
+            x
+        };
+        let y = shelf.y;
+
+        let used_height = self.used_height();
+        if resize_to_min_height(&mut self.image, used_height) {
+            if !self.colors.is_empty() {
+                self.colors
+                    .resize(self.image.pixels.len(), Color32::TRANSPARENT);
+            }
+            self.dirty = Rectu::EVERYTHING;
+        }
+
+        Some((x, y))
+    }
+
+    fn slot_mut(&mut self, x: usize, y: usize) -> Option<&mut Slot> {
+        let shelf_index = self.shelves.partition_point(|shelf| shelf.y < y);
+        let shelf = self
+            .shelves
+            .get_mut(shelf_index)
+            .filter(|shelf| shelf.y == y)?;
+        let slot_index = shelf.slots.partition_point(|slot| slot.x < x);
+        shelf.slots.get_mut(slot_index).filter(|slot| slot.x == x)
+    }
+
+    fn mark_dirty(&mut self, (x, y): (usize, usize), (w, h): (usize, usize)) {
+        self.dirty.min_x = self.dirty.min_x.min(x);
+        self.dirty.min_y = self.dirty.min_y.min(y);
+        self.dirty.max_x = self.dirty.max_x.max(x + w);
+        self.dirty.max_y = self.dirty.max_y.max(y + h);
+    }
+
+    /// Area of all slots in use, in texels.
+    fn used_area(&self) -> usize {
+        self.shelves
+            .iter()
+            .flat_map(|shelf| {
+                shelf
+                    .slots
+                    .iter()
+                    .filter(|slot| slot.last_used.is_some())
+                    .map(|slot| slot.width * shelf.height)
+            })
+            .sum()
+    }
+}
+
+/// Contains font data in an atlas, where each character occupied a small rectangle.
+///
+/// More characters can be added, possibly expanding the texture.
+/// When the first page (texture) is full, more pages are added.
+/// Glyphs that have not been used for a while are evicted by [`Self::evict_unused`]
+/// to make room for new ones.
+#[derive(Clone)]
+pub struct TextureAtlas {
+    /// The first page contains the pre-rasterized discs and is painted with [`TextureId::default`].
+    pages: Vec<Page>,
 
     /// Set when someone requested more space than was available.
     overflowed: bool,
 
+    /// Incremented by [`Self::begin_frame`]. Used to find the least recently used glyphs.
+    frame: u64,
+
     /// pre-rasterized discs of radii `2^i`, where `i` is the index.
     discs: Vec<PrerasterizedDisc>,
 }
@@ -78,18 +310,16 @@ impl TextureAtlas {
     pub fn new(size: [usize; 2]) -> Self {
         assert!(size[0] >= 1024, "Tiny texture atlas");
         let mut atlas = Self {
-            image: FontImage::new(size),
-            dirty: Rectu::EVERYTHING,
-            cursor: (0, 0),
-            row_height: 0,
+            pages: vec![Page::new(size, false)],
             overflowed: false,
+            frame: 0,
             discs: vec![], // will be filled in below
         };
 
         // Make the top left pixel fully white for `WHITE_UV`, i.e. painting something with solid color:
         let (pos, image) = atlas.allocate((1, 1));
-        assert_eq!(pos, (0, 0));
-        image[pos] = 1.0;
+        assert_eq!(pos, AtlasPos::default());
+        image[(pos.x, pos.y)] = 1.0;
 
         // Allocate a series of anti-aliased discs used to render small filled circles:
         // TODO(emilk): these circles can be packed A LOT better.
@@ -105,7 +335,7 @@ impl TextureAtlas {
             }
             let hw = (r + 0.5).ceil() as i32;
             let w = (2 * hw + 1) as usize;
-            let ((x, y), image) = atlas.allocate((w, w));
+            let (AtlasPos { x, y, .. }, image) = atlas.allocate((w, w));
             for dx in -hw..=hw {
                 for dy in -hw..=hw {
                     let distance_to_center = ((dx * dx + dy * dy) as f32).sqrt();
@@ -126,11 +356,19 @@ impl TextureAtlas {
             });
         }
 
+        // The white pixel and the discs are used all the time, so never evict them:
+        for shelf in &mut atlas.pages[0].shelves {
+            for slot in &mut shelf.slots {
+                slot.last_used = Some(u64::MAX);
+            }
+        }
+
         atlas
     }
 
+    /// Size of the first page.
     pub fn size(&self) -> [usize; 2] {
-        self.image.size
+        self.pages[0].image.size
     }
 
     /// Returns the locations and sizes of pre-rasterized discs (filled circles) in this atlas.
@@ -158,17 +396,14 @@ impl TextureAtlas {
             .collect()
     }
 
-    fn max_height(&self) -> usize {
-        // the initial width is likely the max texture side size
-        self.image.width()
-    }
-
-    /// When this get high, it might be time to clear and start over!
+    /// When this get high, it might be time to evict unused glyphs, or to clear and start over!
     pub fn fill_ratio(&self) -> f32 {
         if self.overflowed {
             1.0
         } else {
-            (self.cursor.1 + self.row_height) as f32 / self.max_height() as f32
+            let max_area = MAX_PAGES * self.size()[0] * self.pages[0].max_height();
+            let used_area: usize = self.pages.iter().map(Page::used_area).sum();
+            used_area as f32 / max_area as f32
         }
     }
 
@@ -178,87 +413,499 @@ impl TextureAtlas {
         crate::textures::TextureOptions::LINEAR
     }
 
-    /// The full font atlas image.
+    /// The texture options suitable for a page of signed distance fields.
+    ///
+    /// See [`Self::allocate_sdf`].
+    #[inline]
+    pub fn sdf_texture_options() -> crate::textures::TextureOptions {
+        crate::textures::TextureOptions::LINEAR.with_signed_distance_field(true)
+    }
+
+    /// The first page of the font atlas.
     #[inline]
     pub fn image(&self) -> &FontImage {
-        &self.image
+        &self.pages[0].image
     }
 
-    /// Call to get the change to the image since last call.
-    pub fn take_delta(&mut self) -> Option<ImageDelta> {
-        let texture_options = Self::texture_options();
+    /// How many pages (textures) the atlas currently uses.
+    #[inline]
+    pub fn num_pages(&self) -> usize {
+        self.pages.len()
+    }
 
-        let dirty = std::mem::replace(&mut self.dirty, Rectu::NOTHING);
-        if dirty == Rectu::NOTHING {
-            None
-        } else if dirty == Rectu::EVERYTHING {
-            Some(ImageDelta::full(self.image.clone(), texture_options))
+    /// The image of the given page of the atlas.
+    pub fn page_image(&self, page: usize) -> Option<&FontImage> {
+        self.pages.get(page).map(|page| &page.image)
+    }
+
+    /// Does the given page contain signed distance fields instead of coverage?
+    ///
+    /// Such pages are uploaded with [`Self::sdf_texture_options`].
+    pub fn is_sdf_page(&self, page: usize) -> bool {
+        self.pages.get(page).is_some_and(|page| page.sdf)
+    }
+
+    /// The texture used to paint the given page.
+    ///
+    /// The first page is always painted with [`TextureId::default`].
+    /// The others need to be assigned by the integration with [`Self::set_page_texture_id`].
+    pub fn page_texture_id(&self, page: usize) -> Option<TextureId> {
+        if page == 0 {
+            Some(TextureId::default())
         } else {
-            let pos = [dirty.min_x, dirty.min_y];
-            let size = [dirty.max_x - dirty.min_x, dirty.max_y - dirty.min_y];
-            let region = self.image.region(pos, size);
-            Some(ImageDelta::partial(pos, region, texture_options))
+            self.pages.get(page)?.texture_id
+        }
+    }
+
+    /// Assign the texture that a page (other than the first) is painted with.
+    pub fn set_page_texture_id(&mut self, page: usize, texture_id: TextureId) {
+        if let Some(page) = self.pages.get_mut(page) {
+            page.texture_id = Some(texture_id);
+        }
+    }
+
+    /// All pages but the first, that have been assigned a texture.
+    ///
+    /// Pass this to [`crate::Tessellator::with_font_pages`].
+    pub fn font_pages(&self) -> Vec<Option<FontPage>> {
+        self.pages
+            .iter()
+            .skip(1)
+            .map(|page| {
+                Some(FontPage {
+                    texture_id: page.texture_id?,
+                    size: page.image.size,
+                })
+            })
+            .collect()
+    }
+
+    /// The textures assigned to the pages, so they can be reused or freed when the atlas is replaced.
+    pub fn page_texture_ids(&self) -> Vec<TextureId> {
+        self.pages
+            .iter()
+            .filter_map(|page| page.texture_id)
+            .collect()
+    }
+
+    /// Call to get the change to the first page since last call.
+    ///
+    /// Use [`Self::take_deltas`] if you support more than one page.
+    pub fn take_delta(&mut self) -> Option<ImageDelta> {
+        take_page_delta(&mut self.pages[0])
+    }
+
+    /// Call to get the changes to all pages since last call.
+    pub fn take_deltas(&mut self) -> Vec<(usize, ImageDelta)> {
+        self.pages
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, page)| Some((index, take_page_delta(page)?)))
+            .collect()
+    }
+
+    /// Call once at the start of each frame.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Mark something allocated at this position as used this frame.
+    ///
+    /// Things that haven't been used in a while may be evicted by [`Self::evict_unused`].
+    pub fn mark_used(&mut self, pos: AtlasPos) {
+        let frame = self.frame;
+        if let Some(slot) = self
+            .pages
+            .get_mut(pos.page)
+            .and_then(|page| page.slot_mut(pos.x, pos.y))
+        {
+            if let Some(last_used) = &mut slot.last_used {
+                *last_used = (*last_used).max(frame);
+            }
         }
     }
 
-    /// Returns the coordinates of where the rect ended up,
+    /// Free up space by evicting the least recently used allocations
+    /// until the atlas is at most `target_fill_ratio` full.
+    ///
+    /// Only things that haven't been used in the last couple of frames are evicted.
+    /// Returns the positions of everything that was evicted.
+    /// Anything referring to those positions (e.g. cached glyphs) must be forgotten.
+    pub fn evict_unused(&mut self, target_fill_ratio: f32) -> Vec<AtlasPos> {
+        crate::profile_function!();
+
+        let max_area = MAX_PAGES * self.size()[0] * self.pages[0].max_height();
+        let mut used_area: usize = self.pages.iter().map(Page::used_area).sum();
+        let target_area = (target_fill_ratio * max_area as f32) as usize;
+
+        // (last_used, page, shelf, slot):
+        let mut candidates: Vec<(u64, usize, usize, usize)> = vec![];
+        for (page_index, page) in self.pages.iter().enumerate() {
+            for (shelf_index, shelf) in page.shelves.iter().enumerate() {
+                for (slot_index, slot) in shelf.slots.iter().enumerate() {
+                    if let Some(last_used) = slot.last_used {
+                        if last_used.saturating_add(MIN_FRAMES_UNUSED) <= self.frame {
+                            candidates.push((last_used, page_index, shelf_index, slot_index));
+                        }
+                    }
+                }
+            }
+        }
+        candidates.sort_unstable();
+
+        let mut evicted = vec![];
+        for (_, page_index, shelf_index, slot_index) in candidates {
+            if used_area <= target_area {
+                break;
+            }
+            let page = &mut self.pages[page_index];
+            let shelf = &mut page.shelves[shelf_index];
+            let slot = &mut shelf.slots[slot_index];
+            slot.last_used = None;
+            used_area -= slot.width * shelf.height;
+
+            let (x, y, w, h) = (slot.x, shelf.y, slot.width, shelf.height);
+            clear_region(page, (x, y), (w, h));
+            page.mark_dirty((x, y), (w, h));
+            evicted.push(AtlasPos {
+                page: page_index,
+                x,
+                y,
+            });
+        }
+
+        // Merge neighboring free slots, so they can fit bigger things:
+        for page in &mut self.pages {
+            for shelf in &mut page.shelves {
+                shelf.slots.dedup_by(|next, slot| {
+                    let both_free = slot.last_used.is_none() && next.last_used.is_none();
+                    if both_free {
+                        slot.width += next.width;
+                    }
+                    both_free
+                });
+                if shelf
+                    .slots
+                    .last()
+                    .is_some_and(|slot| slot.last_used.is_none())
+                {
+                    shelf.slots.pop();
+                }
+            }
+        }
+
+        evicted
+    }
+
+    /// Returns the position of where the rect ended up,
     /// and invalidates the region.
-    pub fn allocate(&mut self, (w, h): (usize, usize)) -> ((usize, usize), &mut FontImage) {
-        /// On some low-precision GPUs (my old iPad) characters get muddled up
-        /// if we don't add some empty pixels between the characters.
-        /// On modern high-precision GPUs this is not needed.
-        const PADDING: usize = 1;
+    ///
+    /// Uses the first page with room, and adds a new page if none has room.
+    pub fn allocate(&mut self, size: (usize, usize)) -> (AtlasPos, &mut FontImage) {
+        self.allocate_on_page_kind(size, false)
+    }
 
-        assert!(
-            w <= self.image.width(),
-            "Tried to allocate a {} wide glyph in a {} wide texture atlas",
-            w,
-            self.image.width()
-        );
-        if self.cursor.0 + w > self.image.width() {
-            // New row:
-            self.cursor.0 = 0;
-            self.cursor.1 += self.row_height + PADDING;
-            self.row_height = 0;
+    /// Allocate room for a color glyph (e.g. an emoji) and copy its premultiplied `pixels` there.
+    ///
+    /// Pages with color glyphs are uploaded as [`ColorImage`]s,
+    /// where the colors are used instead of the coverage.
+    pub fn allocate_color(&mut self, (w, h): (usize, usize), pixels: &[Color32]) -> AtlasPos {
+        assert_eq!(pixels.len(), w * h);
+        let (pos, _) = self.allocate((w, h));
+        let page = &mut self.pages[pos.page];
+        if page.colors.is_empty() {
+            page.colors = vec![Color32::TRANSPARENT; page.image.pixels.len()];
         }
+        let page_width = page.image.width();
+        for (y, row) in pixels.chunks_exact(w).enumerate() {
+            let start = (pos.y + y) * page_width + pos.x;
+            page.colors[start..start + w].copy_from_slice(row);
+        }
+        pos
+    }
+
+    /// Like [`Self::allocate`], but on a page that only contains signed distance fields.
+    ///
+    /// The values written to the image are distances remapped so that `0.5` is the edge,
+    /// and the page is uploaded with [`Self::sdf_texture_options`].
+    /// The first page is never such a page.
+    pub fn allocate_sdf(&mut self, size: (usize, usize)) -> (AtlasPos, &mut FontImage) {
+        self.allocate_on_page_kind(size, true)
+    }
 
-        self.row_height = self.row_height.max(h);
+    fn allocate_on_page_kind(
+        &mut self,
+        (w, h): (usize, usize),
+        sdf: bool,
+    ) -> (AtlasPos, &mut FontImage) {
+        let page_width = self.size()[0];
+        assert!(
+            w <= page_width,
+            "Tried to allocate a {w} wide glyph in a {page_width} wide texture atlas"
+        );
 
-        let required_height = self.cursor.1 + self.row_height;
+        let frame = self.frame;
+        let padded_size = (w + PADDING, h + PADDING);
+
+        let mut allocation = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, page)| page.sdf == sdf)
+            .find_map(|(index, page)| Some((index, page.allocate(padded_size, frame)?)));
+
+        if allocation.is_none() && self.pages.len() < MAX_PAGES {
+            // Start small, just like the first page:
+            let mut page = Page::new([page_width, 32], sdf);
+            allocation = page
+                .allocate(padded_size, frame)
+                .map(|pos| (self.pages.len(), pos));
+            self.pages.push(page);
+        }
 
-        if required_height > self.max_height() {
+        let (page_index, (x, y)) = allocation.unwrap_or_else(|| {
             // This is a bad place to be - we need to start reusing space :/
 
             #[cfg(feature = "log")]
             log::warn!("epaint texture atlas overflowed!");
 
-            self.cursor = (0, self.image.height() / 3); // Restart a bit down - the top of the atlas has too many important things in it
             self.overflowed = true; // this will signal the user that we need to recreate the texture atlas next frame.
-        } else if resize_to_min_height(&mut self.image, required_height) {
-            self.dirty = Rectu::EVERYTHING;
-        }
 
-        let pos = self.cursor;
-        self.cursor.0 += w + PADDING;
+            // Draw over the last page of the right kind, a bit down - the top of the first page has too many important things in it:
+            let page_index = self
+                .pages
+                .iter()
+                .rposition(|page| page.sdf == sdf)
+                .unwrap_or(self.pages.len() - 1);
+            let image = &self.pages[page_index].image;
+            (page_index, (0, image.height() / 3))
+        });
+
+        let page = &mut self.pages[page_index];
+        page.mark_dirty((x, y), (w, h));
+
+        (
+            AtlasPos {
+                page: page_index,
+                x,
+                y,
+            },
+            &mut page.image,
+        )
+    }
+}
 
-        self.dirty.min_x = self.dirty.min_x.min(pos.0);
-        self.dirty.min_y = self.dirty.min_y.min(pos.1);
-        self.dirty.max_x = self.dirty.max_x.max(pos.0 + w);
-        self.dirty.max_y = self.dirty.max_y.max(pos.1 + h);
+fn take_page_delta(page: &mut Page) -> Option<ImageDelta> {
+    let dirty = std::mem::replace(&mut page.dirty, Rectu::NOTHING);
+    if dirty == Rectu::NOTHING {
+        return None;
+    }
 
-        (pos, &mut self.image)
+    let (pos, size) = if dirty == Rectu::EVERYTHING {
+        ([0, 0], page.image.size)
+    } else {
+        let pos = [dirty.min_x, dirty.min_y];
+        let size = [dirty.max_x - dirty.min_x, dirty.max_y - dirty.min_y];
+        (pos, size)
+    };
+    let image = page.image.region(pos, size);
+
+    let (image, options): (ImageData, _) = if page.sdf {
+        // The distances must reach the shader unchanged, so they can't go through
+        // the gamma-corrected coverage path of `FontImage::srgba_pixels`:
+        (
+            sdf_color_image(&image).into(),
+            TextureAtlas::sdf_texture_options(),
+        )
+    } else if !page.colors.is_empty() {
+        (
+            colored_image(&image, &page.colors, page.image.width(), pos).into(),
+            TextureAtlas::texture_options(),
+        )
+    } else {
+        (image.into(), TextureAtlas::texture_options())
+    };
+
+    let delta = if dirty == Rectu::EVERYTHING {
+        ImageDelta::full(image, options)
+    } else {
+        ImageDelta::partial(pos, image, options)
+    };
+    Some(delta)
+}
+
+/// Combine a region of a page at `pos` with the colors of the color glyphs on that page.
+fn colored_image(
+    region: &FontImage,
+    page_colors: &[Color32],
+    page_width: usize,
+    [x, y]: [usize; 2],
+) -> ColorImage {
+    let [w, h] = region.size;
+    let colors = (y..y + h).flat_map(|y| &page_colors[y * page_width + x..y * page_width + x + w]);
+    let pixels = region
+        .srgba_pixels(None)
+        .zip(colors)
+        .map(|(coverage, &color)| {
+            if color == Color32::TRANSPARENT {
+                coverage
+            } else {
+                color
+            }
+        })
+        .collect();
+    ColorImage {
+        size: region.size,
+        pixels,
+    }
+}
+
+/// Store the distances of an SDF page in all four channels.
+fn sdf_color_image(image: &FontImage) -> ColorImage {
+    let pixels = image
+        .pixels
+        .iter()
+        .map(|&distance| {
+            let d = (distance.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+            Color32::from_rgba_premultiplied(d, d, d, d)
+        })
+        .collect();
+    ColorImage {
+        size: image.size,
+        pixels,
+    }
+}
+
+fn clear_region(page: &mut Page, (x, y): (usize, usize), (w, h): (usize, usize)) {
+    let image = &mut page.image;
+    let (max_x, max_y) = ((x + w).min(image.width()), (y + h).min(image.height()));
+    for y in y..max_y {
+        for x in x..max_x {
+            image[(x, y)] = 0.0;
+        }
+    }
+    if !page.colors.is_empty() {
+        for y in y..max_y {
+            let row = y * image.width();
+            page.colors[row + x..row + max_x].fill(Color32::TRANSPARENT);
+        }
     }
 }
 
 fn resize_to_min_height(image: &mut FontImage, required_height: usize) -> bool {
-    while required_height >= image.height() {
-        image.size[1] *= 2; // double the height
+    let mut height = image.height();
+    while required_height >= height {
+        height *= 2; // double the height
     }
 
-    if image.width() * image.height() > image.pixels.len() {
-        image.pixels.resize(image.width() * image.height(), 0.0);
+    if height > image.height() {
+        image.resize_height(height);
         true
     } else {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_pages_when_full() {
+        let mut atlas = TextureAtlas::new([1024, 32]);
+        assert_eq!(atlas.num_pages(), 1);
+
+        let (first, _) = atlas.allocate((1010, 1010));
+        assert_eq!(
+            first.page, 1,
+            "Doesn't fit below the discs on the first page"
+        );
+        let (second, _) = atlas.allocate((1010, 1010));
+        assert_eq!(second.page, 2);
+        assert_eq!(atlas.num_pages(), 3);
+        assert!(!atlas.overflowed);
+
+        // Small things still fit on the first page:
+        let (small, _) = atlas.allocate((10, 10));
+        assert_eq!(small.page, 0);
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let mut atlas = TextureAtlas::new([1024, 32]);
+
+        let (old, image) = atlas.allocate((10, 10));
+        image[(old.x, old.y)] = 1.0;
+        atlas.begin_frame();
+        let (recent, _) = atlas.allocate((10, 10));
+        for _ in 0..MIN_FRAMES_UNUSED {
+            atlas.begin_frame();
+        }
+        atlas.mark_used(recent);
+
+        let evicted = atlas.evict_unused(0.0);
+        assert_eq!(
+            evicted,
+            vec![old],
+            "Only the unused glyph is evicted, not the discs"
+        );
+        assert_eq!(atlas.image()[(old.x, old.y)], 0.0, "The slot is cleared");
+
+        // The free slot is reused:
+        let (reused, _) = atlas.allocate((8, 8));
+        assert_eq!(reused, old);
+
+        assert!(atlas.evict_unused(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_color_glyphs() {
+        let mut atlas = TextureAtlas::new([1024, 32]);
+        assert!(matches!(
+            atlas.take_delta().unwrap().image,
+            ImageData::Font(_)
+        ));
+
+        let pos = atlas.allocate_color((2, 1), &[Color32::RED, Color32::TRANSPARENT]);
+        let Some(ImageDelta {
+            image: ImageData::Color(image),
+            pos: Some(region_pos),
+            ..
+        }) = atlas.take_delta()
+        else {
+            panic!("A page with color glyphs should be uploaded in color");
+        };
+        let index = (pos.y - region_pos[1]) * image.width() + (pos.x - region_pos[0]);
+        assert_eq!(image.pixels[index], Color32::RED);
+        assert_eq!(
+            image.pixels[index + 1],
+            Color32::TRANSPARENT,
+            "No coverage, no color"
+        );
+    }
+
+    #[test]
+    fn test_sdf_pages() {
+        let mut atlas = TextureAtlas::new([1024, 32]);
+        let (sdf, _) = atlas.allocate_sdf((10, 10));
+        assert_eq!(sdf.page, 1, "Distance fields never go on the first page");
+        assert!(atlas.is_sdf_page(1));
+
+        let (coverage, _) = atlas.allocate((1010, 1010));
+        assert_eq!(
+            coverage.page, 2,
+            "Coverage never goes on a page of distance fields"
+        );
+
+        let (sdf, _) = atlas.allocate_sdf((10, 10));
+        assert_eq!(sdf.page, 1);
+
+        for (page, delta) in atlas.take_deltas() {
+            assert_eq!(
+                delta.options.signed_distance_field,
+                page == 1,
+                "page {page}"
+            );
+        }
+    }
+}
@@ -139,9 +139,14 @@ pub struct TextureMeta {
 
 impl TextureMeta {
     /// Size in bytes.
-    /// width x height x [`Self::bytes_per_pixel`].
+    /// width x height x [`Self::bytes_per_pixel`], plus the mip levels if any.
     pub fn bytes_used(&self) -> usize {
-        self.size[0] * self.size[1] * self.bytes_per_pixel
+        let level_0 = self.size[0] * self.size[1] * self.bytes_per_pixel;
+        if self.options.mipmap_mode.is_some() {
+            level_0 + level_0 / 3
+        } else {
+            level_0
+        }
     }
 }
 
@@ -159,6 +164,41 @@ pub struct TextureOptions {
 
     /// How to wrap the texture when the texture coordinates are outside the [0, 1] range.
     pub wrap_mode: TextureWrapMode,
+
+    /// The alpha channel of the texture is a signed distance field rather than coverage.
+    ///
+    /// `0.5` is the edge of the shape, and the renderer should produce a sharp,
+    /// anti-aliased edge there at any scale.
+    /// Used for glyphs rasterized with [`crate::text::FontTweak::signed_distance_field`].
+    ///
+    /// Prefer [`Self::with_signed_distance_field`] over setting this directly.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub signed_distance_field: bool,
+
+    /// How to filter between mip levels, or `None` for no mipmaps.
+    ///
+    /// Mipmaps are pre-shrunk copies of the texture (half the size each)
+    /// that prevent shimmering and aliasing when a large texture is painted small,
+    /// e.g. a photo shown as a thumbnail.
+    /// They use a third more memory.
+    ///
+    /// Renderers that can't generate mipmaps on the GPU can use [`crate::ColorImage::mipmaps`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mipmap_mode: Option<TextureFilter>,
+
+    /// The maximum number of samples to use for anisotropic filtering, in `1..=16`.
+    ///
+    /// Anisotropic filtering keeps textures sharp when they are squashed more
+    /// in one direction than the other. `1` turns it off.
+    /// Only used when [`Self::mipmap_mode`] is set and all filters are [`TextureFilter::Linear`].
+    #[cfg_attr(feature = "serde", serde(default = "no_anisotropy"))]
+    pub anisotropy: u8,
+}
+
+/// The default of [`TextureOptions::anisotropy`] for options serialized before it existed.
+#[cfg(feature = "serde")]
+fn no_anisotropy() -> u8 {
+    1
 }
 
 impl TextureOptions {
@@ -167,6 +207,9 @@ impl TextureOptions {
         magnification: TextureFilter::Linear,
         minification: TextureFilter::Linear,
         wrap_mode: TextureWrapMode::ClampToEdge,
+        signed_distance_field: false,
+        mipmap_mode: None,
+        anisotropy: 1,
     };
 
     /// Nearest magnification and minification.
@@ -174,6 +217,9 @@ impl TextureOptions {
         magnification: TextureFilter::Nearest,
         minification: TextureFilter::Nearest,
         wrap_mode: TextureWrapMode::ClampToEdge,
+        signed_distance_field: false,
+        mipmap_mode: None,
+        anisotropy: 1,
     };
 
     /// Linear magnification and minification, but with the texture repeated.
@@ -181,6 +227,9 @@ impl TextureOptions {
         magnification: TextureFilter::Linear,
         minification: TextureFilter::Linear,
         wrap_mode: TextureWrapMode::Repeat,
+        signed_distance_field: false,
+        mipmap_mode: None,
+        anisotropy: 1,
     };
 
     /// Linear magnification and minification, but with the texture mirrored and repeated.
@@ -188,6 +237,9 @@ impl TextureOptions {
         magnification: TextureFilter::Linear,
         minification: TextureFilter::Linear,
         wrap_mode: TextureWrapMode::MirroredRepeat,
+        signed_distance_field: false,
+        mipmap_mode: None,
+        anisotropy: 1,
     };
 
     /// Nearest magnification and minification, but with the texture repeated.
@@ -195,6 +247,9 @@ impl TextureOptions {
         magnification: TextureFilter::Nearest,
         minification: TextureFilter::Nearest,
         wrap_mode: TextureWrapMode::Repeat,
+        signed_distance_field: false,
+        mipmap_mode: None,
+        anisotropy: 1,
     };
 
     /// Nearest magnification and minification, but with the texture mirrored and repeated.
@@ -202,7 +257,54 @@ impl TextureOptions {
         magnification: TextureFilter::Nearest,
         minification: TextureFilter::Nearest,
         wrap_mode: TextureWrapMode::MirroredRepeat,
+        signed_distance_field: false,
+        mipmap_mode: None,
+        anisotropy: 1,
     };
+
+    /// Set whether the alpha channel is a signed distance field, see [`Self::signed_distance_field`].
+    #[inline]
+    pub const fn with_signed_distance_field(self, signed_distance_field: bool) -> Self {
+        Self {
+            signed_distance_field,
+            ..self
+        }
+    }
+
+    /// Set how to filter between mip levels, or `None` for no mipmaps.
+    #[inline]
+    pub const fn with_mipmap_mode(self, mipmap_mode: Option<TextureFilter>) -> Self {
+        Self {
+            mipmap_mode,
+            ..self
+        }
+    }
+
+    /// Set the maximum anisotropy, see [`Self::anisotropy`].
+    #[inline]
+    pub const fn with_anisotropy(self, anisotropy: u8) -> Self {
+        Self { anisotropy, ..self }
+    }
+
+    /// [`Self::anisotropy`] clamped to `1..=16`,
+    /// or `1` unless mipmaps are on and all filters are linear.
+    pub fn effective_anisotropy(&self) -> u8 {
+        let all_linear = self.magnification == TextureFilter::Linear
+            && self.minification == TextureFilter::Linear
+            && self.mipmap_mode == Some(TextureFilter::Linear);
+        if all_linear {
+            self.anisotropy.clamp(1, 16)
+        } else {
+            1
+        }
+    }
+}
+
+/// How many mip levels a texture of the given size has, including the full-size one.
+///
+/// Each level is half the size of the previous one (rounded down), down to 1x1.
+pub fn mip_level_count([width, height]: [usize; 2]) -> u32 {
+    usize::BITS - width.max(height).max(1).leading_zeros()
 }
 
 impl Default for TextureOptions {